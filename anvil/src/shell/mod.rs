@@ -9,7 +9,9 @@ use smithay::wayland::drm_syncobj::DrmSyncobjCachedState;
 use smithay::{
     backend::renderer::utils::on_commit_buffer_handler,
     desktop::{
-        LayerSurface, PopupKind, PopupManager, Space, WindowSurfaceType, layer_map_for_output,
+        LayerSurface, PopupKind, PopupManager, Space, Window, WindowSurfaceType,
+        grabs::{self, MoveResizeHandler},
+        layer_map_for_output,
         space::SpaceElement,
     },
     input::pointer::{CursorImageStatus, CursorImageSurfaceData},
@@ -46,14 +48,12 @@ use crate::{
 };
 
 mod element;
-mod grabs;
 pub(crate) mod ssd;
 #[cfg(feature = "xwayland")]
 mod x11;
 mod xdg;
 
 pub use self::element::*;
-pub use self::grabs::*;
 
 fn fullscreen_output_geometry(
     wl_surface: &WlSurface,
@@ -194,8 +194,12 @@ impl<BackendData: Backend> CompositorHandler for AnvilState<BackendData> {
 
                     if let Some(buffer_offset) = buffer_offset {
                         let current_loc = self.space.element_location(&window).unwrap();
-                        self.space.map_element(window, current_loc + buffer_offset, false);
+                        self.space
+                            .map_element(window.clone(), current_loc + buffer_offset, false);
                     }
+
+                    // Windows resized by their top or left edge need to be moved
+                    grabs::handle_commit(self, &window.0);
                 }
             }
         }
@@ -242,6 +246,24 @@ impl<BackendData: Backend> CompositorHandler for AnvilState<BackendData> {
     }
 }
 
+impl<BackendData: Backend> MoveResizeHandler for AnvilState<BackendData> {
+    fn window_location(&self, window: &Window) -> Option<Point<i32, Logical>> {
+        let element = self.space.elements().find(|element| element.0 == *window)?;
+        self.space.element_location(element)
+    }
+
+    fn set_window_location(&mut self, window: &Window, location: Point<i32, Logical>) {
+        let element = self
+            .space
+            .elements()
+            .find(|element| element.0 == *window)
+            .cloned();
+        if let Some(element) = element {
+            self.space.map_element(element, location, true);
+        }
+    }
+}
+
 impl<BackendData: Backend> WlrLayerShellHandler for AnvilState<BackendData> {
     fn shell_state(&mut self) -> &mut WlrLayerShellState {
         &mut self.layer_shell_state
@@ -288,7 +310,6 @@ impl<BackendData: Backend> AnvilState<BackendData> {
 #[derive(Default)]
 pub struct SurfaceData {
     pub geometry: Option<Rectangle<i32, Logical>>,
-    pub resize_state: ResizeState,
 }
 
 fn ensure_initial_configure(surface: &WlSurface, space: &Space<WindowElement>, popups: &mut PopupManager) {
@@ -326,19 +347,6 @@ fn ensure_initial_configure(surface: &WlSurface, space: &Space<WindowElement>, p
             }
         }
 
-        with_states(surface, |states| {
-            let mut data = states
-                .data_map
-                .get::<RefCell<SurfaceData>>()
-                .unwrap()
-                .borrow_mut();
-
-            // Finish resizing.
-            if let ResizeState::WaitingForCommit(_) = data.resize_state {
-                data.resize_state = ResizeState::NotResizing;
-            }
-        });
-
         return;
     }
 
@@ -1,11 +1,13 @@
 use std::{cell::RefCell, os::unix::io::OwnedFd};
 
 use smithay::{
-    desktop::{Window, space::SpaceElement},
+    desktop::{
+        Window,
+        grabs::{PointerMoveResizeGrab, TouchMoveResizeGrab},
+    },
     input::pointer::Focus,
     utils::{Logical, Rectangle, SERIAL_COUNTER},
     wayland::{
-        compositor::with_states,
         selection::{
             SelectionTarget,
             data_device::{
@@ -17,21 +19,19 @@ use smithay::{
                 request_primary_client_selection, set_primary_selection,
             },
         },
+        xdg_activation::{XdgActivationState, XdgActivationToken, XdgActivationTokenData},
         xwayland_shell::{XWaylandShellHandler, XWaylandShellState},
     },
     xwayland::{
         X11Surface, X11Wm, XwmHandler,
-        xwm::{Reorder, ResizeEdge as X11ResizeEdge, XwmId},
+        xwm::{Reorder, ResizeEdge as X11ResizeEdge, WmWindowProperty, XwmId},
     },
 };
 use tracing::{error, trace};
 
 use crate::{AnvilState, focus::KeyboardFocusTarget, state::Backend};
 
-use super::{
-    FullscreenSurface, PointerMoveSurfaceGrab, PointerResizeSurfaceGrab, ResizeData, ResizeState,
-    SurfaceData, TouchMoveSurfaceGrab, WindowElement, place_new_window,
-};
+use super::{FullscreenSurface, WindowElement, place_new_window};
 
 #[derive(Debug, Default)]
 struct OldGeometry(RefCell<Option<Rectangle<i32, Logical>>>);
@@ -173,6 +173,8 @@ impl<BackendData: Backend> XwmHandler for AnvilState<BackendData> {
                 // Assumes that at least one output exists
                 .expect("No outputs found");
             let geometry = self.space.output_geometry(output).unwrap();
+            // Games changing the resolution through RandR are scaled up by Xwayland
+            let geometry = window.randr_emulated_geometry(geometry).unwrap_or(geometry);
 
             window.set_fullscreen(true).unwrap();
             elem.set_ssd(false);
@@ -187,6 +189,22 @@ impl<BackendData: Backend> XwmHandler for AnvilState<BackendData> {
         }
     }
 
+    fn property_notify(&mut self, _xwm: XwmId, window: X11Surface, property: WmWindowProperty) {
+        if property != WmWindowProperty::RandrEmulation || !window.is_fullscreen() {
+            return;
+        }
+        let output = self.space.outputs().find(|o| {
+            o.user_data()
+                .get::<FullscreenSurface>()
+                .and_then(|f| f.get())
+                .is_some_and(|w| matches!(w.0.x11_surface(), Some(w) if w == &window))
+        });
+        if let Some(geometry) = output.and_then(|o| self.space.output_geometry(o)) {
+            let geometry = window.randr_emulated_geometry(geometry).unwrap_or(geometry);
+            window.configure(geometry).unwrap();
+        }
+    }
+
     fn unfullscreen_request(&mut self, _xwm: XwmId, window: X11Surface) {
         if let Some(elem) = self
             .space
@@ -222,31 +240,13 @@ impl<BackendData: Backend> XwmHandler for AnvilState<BackendData> {
             return;
         };
 
-        let geometry = element.geometry();
-        let loc = self.space.element_location(element).unwrap();
-        let (initial_window_location, initial_window_size) = (loc, geometry.size);
-
-        with_states(&element.wl_surface().unwrap(), move |states| {
-            states
-                .data_map
-                .get::<RefCell<SurfaceData>>()
-                .unwrap()
-                .borrow_mut()
-                .resize_state = ResizeState::Resizing(ResizeData {
-                edges: edges.into(),
-                initial_window_location,
-                initial_window_size,
-            });
-        });
-
-        let grab = PointerResizeSurfaceGrab {
+        let initial_window_location = self.space.element_location(element).unwrap();
+        let grab = PointerMoveResizeGrab::new_resize(
             start_data,
-            window: element.clone(),
-            edges: edges.into(),
+            element.0.clone(),
+            edges.into(),
             initial_window_location,
-            initial_window_size,
-            last_window_size: initial_window_size,
-        };
+        );
 
         let pointer = self.pointer.clone();
         pointer.set_grab(self, grab, SERIAL_COUNTER.next_serial(), Focus::Clear);
@@ -256,6 +256,30 @@ impl<BackendData: Backend> XwmHandler for AnvilState<BackendData> {
         self.move_request_x11(&window)
     }
 
+    fn xdg_activation_state(&mut self) -> Option<&mut XdgActivationState> {
+        Some(&mut self.xdg_activation_state)
+    }
+
+    fn activation_request(
+        &mut self,
+        _xwm: XwmId,
+        window: X11Surface,
+        _token: XdgActivationToken,
+        token_data: XdgActivationTokenData,
+    ) {
+        // Same policy as for wayland clients
+        if token_data.timestamp.elapsed().as_secs() < 10 {
+            let w = self
+                .space
+                .elements()
+                .find(|e| matches!(e.0.x11_surface(), Some(w) if w == &window))
+                .cloned();
+            if let Some(window) = w {
+                self.space.raise_element(&window, true);
+            }
+        }
+    }
+
     fn allow_selection_access(&mut self, xwm: XwmId, _selection: SelectionTarget) -> bool {
         if let Some(keyboard) = self.seat.get_keyboard() {
             // check that an X11 window is focused
@@ -376,11 +400,8 @@ impl<BackendData: Backend> AnvilState<BackendData> {
                         }
                     }
 
-                    let grab = TouchMoveSurfaceGrab {
-                        start_data,
-                        window: element.clone(),
-                        initial_window_location,
-                    };
+                    let grab =
+                        TouchMoveResizeGrab::new_move(start_data, element.0.clone(), initial_window_location);
 
                     touch.set_grab(self, grab, SERIAL_COUNTER.next_serial());
                     return;
@@ -419,11 +440,7 @@ impl<BackendData: Backend> AnvilState<BackendData> {
             }
         }
 
-        let grab = PointerMoveSurfaceGrab {
-            start_data,
-            window: element.clone(),
-            initial_window_location,
-        };
+        let grab = PointerMoveResizeGrab::new_move(start_data, element.0.clone(), initial_window_location);
 
         let pointer = self.pointer.clone();
         pointer.set_grab(self, grab, SERIAL_COUNTER.next_serial(), Focus::Clear);
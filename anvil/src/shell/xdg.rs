@@ -1,10 +1,9 @@
-use std::cell::RefCell;
-
 use smithay::{
     desktop::{
-        PopupKeyboardGrab, PopupKind, PopupPointerGrab, PopupUngrabStrategy, Space, Window,
-        WindowSurfaceType, find_popup_root_surface, get_popup_toplevel_coords, layer_map_for_output,
-        space::SpaceElement,
+        PopupKeyboardGrab, PopupKind, PopupPointerGrab, PopupUngrabStrategy, Window, WindowSurfaceType,
+        find_popup_root_surface, get_popup_toplevel_coords,
+        grabs::{self, PointerMoveResizeGrab, TouchMoveResizeGrab},
+        layer_map_for_output,
     },
     input::{Seat, pointer::Focus},
     output::Output,
@@ -15,13 +14,11 @@ use smithay::{
             protocol::{wl_output, wl_seat, wl_surface::WlSurface},
         },
     },
-    utils::{Logical, Point, Serial},
+    utils::Serial,
     wayland::{
-        compositor::{self, with_states},
         seat::WaylandFocus,
         shell::xdg::{
-            Configure, PopupSurface, PositionerState, ToplevelCachedState, ToplevelSurface, XdgShellHandler,
-            XdgShellState,
+            Configure, PopupSurface, PositionerState, ToplevelSurface, XdgShellHandler, XdgShellState,
         },
     },
 };
@@ -29,14 +26,10 @@ use tracing::{trace, warn};
 
 use crate::{
     focus::KeyboardFocusTarget,
-    shell::{TouchMoveSurfaceGrab, TouchResizeSurfaceGrab},
     state::{AnvilState, Backend},
 };
 
-use super::{
-    FullscreenSurface, PointerMoveSurfaceGrab, PointerResizeSurfaceGrab, ResizeData, ResizeEdge, ResizeState,
-    SurfaceData, WindowElement, fullscreen_output_geometry, place_new_window,
-};
+use super::{FullscreenSurface, WindowElement, fullscreen_output_geometry, place_new_window};
 
 impl<BackendData: Backend> XdgShellHandler for AnvilState<BackendData> {
     fn xdg_shell_state(&mut self) -> &mut XdgShellState {
@@ -49,10 +42,6 @@ impl<BackendData: Backend> XdgShellHandler for AnvilState<BackendData> {
         // the surface is not already configured
         let window = WindowElement(Window::new_wayland_window(surface.clone()));
         place_new_window(&mut self.space, self.pointer.current_location(), &window, true);
-
-        compositor::add_post_commit_hook(surface.wl_surface(), |state: &mut Self, _, surface| {
-            handle_toplevel_commit(&mut state.space, surface);
-        });
     }
 
     fn new_popup(&mut self, surface: PopupSurface, _positioner: PositionerState) {
@@ -115,31 +104,13 @@ impl<BackendData: Backend> XdgShellHandler for AnvilState<BackendData> {
                     tracing::info!("different surface");
                     return;
                 }
-                let geometry = window.geometry();
-                let loc = self.space.element_location(&window).unwrap();
-                let (initial_window_location, initial_window_size) = (loc, geometry.size);
-
-                with_states(surface.wl_surface(), move |states| {
-                    states
-                        .data_map
-                        .get::<RefCell<SurfaceData>>()
-                        .unwrap()
-                        .borrow_mut()
-                        .resize_state = ResizeState::Resizing(ResizeData {
-                        edges: edges.into(),
-                        initial_window_location,
-                        initial_window_size,
-                    });
-                });
-
-                let grab = TouchResizeSurfaceGrab {
+                let initial_window_location = self.space.element_location(&window).unwrap();
+                let grab = TouchMoveResizeGrab::new_resize(
                     start_data,
-                    window,
-                    edges: edges.into(),
+                    window.0,
+                    edges.into(),
                     initial_window_location,
-                    initial_window_size,
-                    last_window_size: initial_window_size,
-                };
+                );
 
                 touch.set_grab(self, grab, serial);
                 return;
@@ -169,87 +140,22 @@ impl<BackendData: Backend> XdgShellHandler for AnvilState<BackendData> {
             return;
         }
 
-        let geometry = window.geometry();
-        let loc = self.space.element_location(&window).unwrap();
-        let (initial_window_location, initial_window_size) = (loc, geometry.size);
-
-        with_states(surface.wl_surface(), move |states| {
-            states
-                .data_map
-                .get::<RefCell<SurfaceData>>()
-                .unwrap()
-                .borrow_mut()
-                .resize_state = ResizeState::Resizing(ResizeData {
-                edges: edges.into(),
-                initial_window_location,
-                initial_window_size,
-            });
-        });
-
-        let grab = PointerResizeSurfaceGrab {
-            start_data,
-            window,
-            edges: edges.into(),
-            initial_window_location,
-            initial_window_size,
-            last_window_size: initial_window_size,
-        };
+        let initial_window_location = self.space.element_location(&window).unwrap();
+        let grab =
+            PointerMoveResizeGrab::new_resize(start_data, window.0, edges.into(), initial_window_location);
 
         pointer.set_grab(self, grab, serial, Focus::Clear);
     }
 
     fn ack_configure(&mut self, surface: WlSurface, configure: Configure) {
         if let Configure::Toplevel(configure) = configure {
-            if let Some(serial) = with_states(&surface, |states| {
-                if let Some(data) = states.data_map.get::<RefCell<SurfaceData>>() {
-                    if let ResizeState::WaitingForFinalAck(_, serial) = data.borrow().resize_state {
-                        return Some(serial);
-                    }
-                }
-
-                None
-            }) {
-                // When the resize grab is released the surface
-                // resize state will be set to WaitingForFinalAck
-                // and the client will receive a configure request
-                // without the resize state to inform the client
-                // resizing has finished. Here we will wait for
-                // the client to acknowledge the end of the
-                // resizing. To check if the surface was resizing
-                // before sending the configure we need to use
-                // the current state as the received acknowledge
-                // will no longer have the resize state set
-                let is_resizing = with_states(&surface, |states| {
-                    states
-                        .cached_state
-                        .get::<ToplevelCachedState>()
-                        .current()
-                        .last_acked
-                        .as_ref()
-                        .is_some_and(|c| c.state.states.contains(xdg_toplevel::State::Resizing))
-                });
-
-                if configure.serial >= serial && is_resizing {
-                    with_states(&surface, |states| {
-                        let mut data = states
-                            .data_map
-                            .get::<RefCell<SurfaceData>>()
-                            .unwrap()
-                            .borrow_mut();
-                        if let ResizeState::WaitingForFinalAck(resize_data, _) = data.resize_state {
-                            data.resize_state = ResizeState::WaitingForCommit(resize_data);
-                        } else {
-                            unreachable!()
-                        }
-                    });
-                }
-            }
-
             let window = self
                 .space
                 .elements()
                 .find(|element| element.wl_surface().as_deref() == Some(&surface));
             if let Some(window) = window {
+                grabs::ack_configure(&window.0, &configure);
+
                 use xdg_decoration::zv1::server::zxdg_toplevel_decoration_v1::Mode;
                 let is_ssd = configure
                     .state
@@ -477,11 +383,7 @@ impl<BackendData: Backend> AnvilState<BackendData> {
                     initial_window_location = start_data.location.to_i32_round();
                 }
 
-                let grab = TouchMoveSurfaceGrab {
-                    start_data,
-                    window,
-                    initial_window_location,
-                };
+                let grab = TouchMoveResizeGrab::new_move(start_data, window.0, initial_window_location);
 
                 touch.set_grab(self, grab, serial);
                 return;
@@ -544,11 +446,7 @@ impl<BackendData: Backend> AnvilState<BackendData> {
             initial_window_location = (pos.x as i32, pos.y as i32).into();
         }
 
-        let grab = PointerMoveSurfaceGrab {
-            start_data,
-            window,
-            initial_window_location,
-        };
+        let grab = PointerMoveResizeGrab::new_move(start_data, window.0, initial_window_location);
 
         pointer.set_grab(self, grab, serial, Focus::Clear);
     }
@@ -588,54 +486,3 @@ impl<BackendData: Backend> AnvilState<BackendData> {
         });
     }
 }
-
-/// Should be called on `WlSurface::commit` of xdg toplevel
-fn handle_toplevel_commit(space: &mut Space<WindowElement>, surface: &WlSurface) -> Option<()> {
-    let window = space
-        .elements()
-        .find(|w| w.wl_surface().as_deref() == Some(surface))
-        .cloned()?;
-
-    let mut window_loc = space.element_location(&window)?;
-    let geometry = window.geometry();
-
-    let new_loc: Point<Option<i32>, Logical> = with_states(window.wl_surface().as_deref()?, |states| {
-        let data = states.data_map.get::<RefCell<SurfaceData>>()?.borrow_mut();
-
-        if let ResizeState::Resizing(resize_data) = data.resize_state {
-            let edges = resize_data.edges;
-            let loc = resize_data.initial_window_location;
-            let size = resize_data.initial_window_size;
-
-            // If the window is being resized by top or left, its location must be adjusted
-            // accordingly.
-            edges.intersects(ResizeEdge::TOP_LEFT).then(|| {
-                let new_x = edges
-                    .intersects(ResizeEdge::LEFT)
-                    .then_some(loc.x + (size.w - geometry.size.w));
-
-                let new_y = edges
-                    .intersects(ResizeEdge::TOP)
-                    .then_some(loc.y + (size.h - geometry.size.h));
-
-                (new_x, new_y).into()
-            })
-        } else {
-            None
-        }
-    })?;
-
-    if let Some(new_x) = new_loc.x {
-        window_loc.x = new_x;
-    }
-    if let Some(new_y) = new_loc.y {
-        window_loc.y = new_y;
-    }
-
-    if new_loc.x.is_some() || new_loc.y.is_some() {
-        // If TOP or LEFT side of the window got resized, we have to move it
-        space.map_element(window, window_loc, false);
-    }
-
-    Some(())
-}
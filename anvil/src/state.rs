@@ -27,10 +27,10 @@ use smithay::{
     },
     input::{
         Seat, SeatHandler, SeatState,
-        dnd::{DnDGrab, DndGrabHandler, DndTarget, GrabType, Source},
+        dnd::{DnDGrab, DndGrabHandler, DndTarget, GrabType, Source, TabletToolDnDGrab},
         keyboard::{Keysym, LedState, XkbConfig},
         pointer::{CursorImageStatus, Focus, PointerHandle},
-        tablet::TabletSeatHandler,
+        tablet::{TabletSeatHandler, TabletSeatTrait},
     },
     output::Output,
     reexports::{
@@ -113,7 +113,7 @@ use smithay::{
     wayland::selection::{SelectionSource, SelectionTarget},
     wayland::xwayland_keyboard_grab::{XWaylandKeyboardGrabHandler, XWaylandKeyboardGrabState},
     wayland::xwayland_shell,
-    xwayland::{X11Wm, XWayland, XWaylandEvent},
+    xwayland::{X11Wm, XWayland, XWaylandEvent, xwm::X11StartupToken},
 };
 
 #[derive(Debug, Default)]
@@ -234,6 +234,21 @@ impl<BackendData: Backend> WaylandDndGrabHandler for AnvilState<BackendData> {
                     serial,
                 );
             }
+            GrabType::TabletTool => {
+                let Some(tool) = seat.tablet_seat().tool_with_grab(serial) else {
+                    source.cancel();
+                    return;
+                };
+                let start_data = tool.grab_start_data().unwrap();
+                let time = self.clock.now().as_millis();
+                tool.set_grab(
+                    self,
+                    TabletToolDnDGrab::new(&self.display_handle, start_data, source, seat),
+                    time,
+                    serial,
+                    Focus::Keep,
+                );
+            }
         }
     }
 }
@@ -453,6 +468,12 @@ impl<BackendData: Backend> XdgActivationHandler for AnvilState<BackendData> {
         token_data: XdgActivationTokenData,
         surface: WlSurface,
     ) {
+        // Launches announced by X11 clients were not validated by us
+        #[cfg(feature = "xwayland")]
+        if X11StartupToken::is_set(&token_data) {
+            return;
+        }
+
         if token_data.timestamp.elapsed().as_secs() < 10 {
             // Just grant the wish
             let w = self
@@ -73,8 +73,8 @@ impl WaylandDndGrabHandler for Smallvil {
                 let grab = DnDGrab::new_pointer(&self.display_handle, start_data, source, seat);
                 ptr.set_grab(self, grab, serial, Focus::Keep);
             }
-            GrabType::Touch => {
-                // smallvil lacks touch handling
+            GrabType::Touch | GrabType::TabletTool => {
+                // smallvil lacks touch and tablet handling
                 source.cancel();
             }
         }
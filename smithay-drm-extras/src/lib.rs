@@ -5,6 +5,8 @@
 //! - [`display_info`] is responsible for extraction of information from DRM connectors
 //! - [`drm_scanner`] is responsible for detecting connector connected and
 //!   disconnected events, as well as mapping CRTC to them.
+//! - [`output_profile`] is responsible for selecting output configuration profiles
+//!   for the connected displays.
 //!
 //! ### Features
 //! - `display_info` - If enabled `display_info` functionality is enabled through `libdisplay-info` integration
@@ -14,3 +16,4 @@
 #[cfg(feature = "display-info")]
 pub mod display_info;
 pub mod drm_scanner;
+pub mod output_profile;
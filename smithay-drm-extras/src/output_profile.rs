@@ -0,0 +1,546 @@
+//! # Output profiles
+//!
+//! Kanshi-style output configuration profiles.
+//!
+//! - [`OutputIdentity`] describes a connected display by connector name and EDID make/model/serial.
+//! - [`OutputProfile`] is a set of [`OutputConfig`]s, each describing how one display should be configured.
+//! - [`ProfileSet`] selects the best matching profile for the currently connected displays
+//!   and can be serialized to, and parsed from, a stable text format.
+//!
+//! A profile matches if every connected display can be assigned to exactly one of its outputs.
+//! If multiple profiles match, the one with the most specific matchers wins,
+//! ties are resolved in favor of the profile defined first.
+//!
+//! ### Text format
+//!
+//! ```text
+//! # Comments start with a '#'
+//! profile "docked" {
+//!     output edid "Dell Inc." "DELL U2720Q" "8H3JX13" mode 3840x2160@59.997 scale 1.5 position 0,0
+//!     output connector eDP-1 disable
+//! }
+//!
+//! profile "undocked" {
+//!     output connector eDP-1 enable scale 2 transform normal
+//! }
+//! ```
+//!
+//! `edid` matchers take make, model and serial, any of which may be `*` to match every value.
+//! A lone `*` matches any display.
+//!
+//! ### Example
+//! ```no_run
+//! # mod helpers { include!("./docs/doctest_helpers.rs"); };
+//! # let drm_device: helpers::FakeDevice = todo!();
+//! use smithay_drm_extras::{drm_scanner::DrmScanner, output_profile::{self, ProfileSet}};
+//!
+//! let profiles: ProfileSet = std::fs::read_to_string("outputs.conf").unwrap().parse().unwrap();
+//!
+//! let mut scanner: DrmScanner = DrmScanner::new();
+//! scanner.scan_connectors(&drm_device).expect("failed to scan connectors");
+//!
+//! let (handles, outputs): (Vec<_>, Vec<_>) =
+//!     output_profile::connected_outputs(&drm_device, scanner.connectors().values())
+//!         .into_iter()
+//!         .unzip();
+//!
+//! if let Some(matched) = profiles.select(&outputs) {
+//!     for (handle, config) in handles.iter().zip(matched.configs()) {
+//!         println!("{:?}: enabled={} scale={:?}", handle, config.enabled, config.scale);
+//!     }
+//! }
+//! ```
+
+use std::fmt;
+
+use drm::control::{Mode, ModeTypeFlags, connector};
+
+mod format;
+pub use format::ParseError;
+
+/// Identity of a connected display
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct OutputIdentity {
+    /// Name of the connector the display is attached to (e.g. `eDP-1`)
+    pub connector: String,
+    /// Manufacturer as reported by the EDID
+    pub make: Option<String>,
+    /// Model as reported by the EDID
+    pub model: Option<String>,
+    /// Serial number as reported by the EDID
+    pub serial: Option<String>,
+}
+
+impl OutputIdentity {
+    /// Create an identity for the connector without any EDID information
+    pub fn new(connector: &connector::Info) -> Self {
+        OutputIdentity {
+            connector: connector_name(connector),
+            make: None,
+            model: None,
+            serial: None,
+        }
+    }
+
+    /// Create an identity for the connector from already parsed EDID information
+    #[cfg(feature = "display-info")]
+    pub fn from_info(connector: &connector::Info, info: &libdisplay_info::info::Info) -> Self {
+        OutputIdentity {
+            connector: connector_name(connector),
+            make: info.make(),
+            model: info.model(),
+            serial: info.serial(),
+        }
+    }
+
+    /// Create an identity for the connector reading its EDID
+    ///
+    /// Falls back to [`OutputIdentity::new`] if the EDID cannot be read.
+    #[cfg(feature = "display-info")]
+    pub fn for_connector(device: &impl drm::control::Device, connector: &connector::Info) -> Self {
+        match crate::display_info::for_connector(device, connector.handle()) {
+            Some(info) => Self::from_info(connector, &info),
+            None => Self::new(connector),
+        }
+    }
+}
+
+/// Build identities for all connected connectors
+///
+/// Meant to be used with [`DrmScanner::connectors`](crate::drm_scanner::DrmScanner::connectors).
+#[cfg(feature = "display-info")]
+pub fn connected_outputs<'a>(
+    device: &impl drm::control::Device,
+    connectors: impl IntoIterator<Item = &'a connector::Info>,
+) -> Vec<(connector::Handle, OutputIdentity)> {
+    connectors
+        .into_iter()
+        .filter(|info| info.state() == connector::State::Connected)
+        .map(|info| (info.handle(), OutputIdentity::for_connector(device, info)))
+        .collect()
+}
+
+/// Name of a connector as used by the kernel (e.g. `HDMI-A-1`)
+pub fn connector_name(connector: &connector::Info) -> String {
+    format!("{}-{}", connector.interface().as_str(), connector.interface_id())
+}
+
+/// Selects which display an [`OutputConfig`] applies to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OutputMatcher {
+    /// Matches any display
+    Any,
+    /// Matches a display by connector name
+    Connector(String),
+    /// Matches a display by its EDID, `None` fields match any value
+    Edid {
+        /// Manufacturer to match
+        make: Option<String>,
+        /// Model to match
+        model: Option<String>,
+        /// Serial number to match
+        serial: Option<String>,
+    },
+}
+
+impl OutputMatcher {
+    /// Returns if this matcher matches the given display
+    pub fn matches(&self, output: &OutputIdentity) -> bool {
+        fn field(expected: &Option<String>, actual: &Option<String>) -> bool {
+            match expected {
+                None => true,
+                Some(expected) => actual.as_ref() == Some(expected),
+            }
+        }
+
+        match self {
+            OutputMatcher::Any => true,
+            OutputMatcher::Connector(name) => &output.connector == name,
+            OutputMatcher::Edid { make, model, serial } => {
+                field(make, &output.make) && field(model, &output.model) && field(serial, &output.serial)
+            }
+        }
+    }
+
+    /// How specific this matcher is, used to rank matching profiles
+    ///
+    /// Wildcards rank lowest, connector names above them and EDID matchers
+    /// above connector names, gaining more weight for every non-wildcard field.
+    pub fn specificity(&self) -> u32 {
+        match self {
+            OutputMatcher::Any => 0,
+            OutputMatcher::Connector(_) => 1,
+            OutputMatcher::Edid { make, model, serial } => {
+                1 + [make, model, serial].iter().filter(|f| f.is_some()).count() as u32
+            }
+        }
+    }
+}
+
+/// Display mode requested by a profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProfileMode {
+    /// Width in pixels
+    pub width: u16,
+    /// Height in pixels
+    pub height: u16,
+    /// Refresh rate in millihertz, `None` picks the best available refresh rate
+    pub refresh: Option<u32>,
+}
+
+impl ProfileMode {
+    /// Find the best fitting mode out of the modes provided by a connector
+    ///
+    /// Only modes of the requested size are considered. If a refresh rate is requested
+    /// the mode with the closest refresh rate is returned, otherwise a preferred mode
+    /// or the mode with the highest refresh rate.
+    pub fn find(&self, modes: &[Mode]) -> Option<Mode> {
+        let candidates = modes
+            .iter()
+            .copied()
+            .filter(|mode| mode.size() == (self.width, self.height));
+
+        match self.refresh {
+            Some(refresh) => candidates.min_by_key(|mode| mode_refresh(mode).abs_diff(refresh)),
+            None => candidates.max_by_key(|mode| {
+                (
+                    mode.mode_type().contains(ModeTypeFlags::PREFERRED),
+                    mode_refresh(mode),
+                )
+            }),
+        }
+    }
+}
+
+/// Refresh rate of a drm mode in millihertz
+pub fn mode_refresh(mode: &Mode) -> u32 {
+    let clock = mode.clock() as u64;
+    let htotal = mode.hsync().2 as u64;
+    let vtotal = mode.vsync().2 as u64;
+    if htotal == 0 || vtotal == 0 {
+        return 0;
+    }
+
+    let mut refresh = (clock * 1_000_000 / htotal + vtotal / 2) / vtotal;
+
+    if mode.flags().contains(drm::control::ModeFlags::INTERLACE) {
+        refresh *= 2;
+    }
+
+    if mode.flags().contains(drm::control::ModeFlags::DBLSCAN) {
+        refresh /= 2;
+    }
+
+    if mode.vscan() > 1 {
+        refresh /= mode.vscan() as u64;
+    }
+
+    refresh as u32
+}
+
+/// Output transformation
+///
+/// Mirrors the `wl_output` transform values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Transform {
+    /// No transformation
+    #[default]
+    Normal,
+    /// Rotated by 90 degrees
+    _90,
+    /// Rotated by 180 degrees
+    _180,
+    /// Rotated by 270 degrees
+    _270,
+    /// Flipped vertically
+    Flipped,
+    /// Flipped vertically and rotated by 90 degrees
+    Flipped90,
+    /// Flipped vertically and rotated by 180 degrees
+    Flipped180,
+    /// Flipped vertically and rotated by 270 degrees
+    Flipped270,
+}
+
+impl Transform {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Transform::Normal => "normal",
+            Transform::_90 => "90",
+            Transform::_180 => "180",
+            Transform::_270 => "270",
+            Transform::Flipped => "flipped",
+            Transform::Flipped90 => "flipped-90",
+            Transform::Flipped180 => "flipped-180",
+            Transform::Flipped270 => "flipped-270",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "normal" => Transform::Normal,
+            "90" => Transform::_90,
+            "180" => Transform::_180,
+            "270" => Transform::_270,
+            "flipped" => Transform::Flipped,
+            "flipped-90" => Transform::Flipped90,
+            "flipped-180" => Transform::Flipped180,
+            "flipped-270" => Transform::Flipped270,
+            _ => return None,
+        })
+    }
+}
+
+/// Configuration of a single display inside an [`OutputProfile`]
+///
+/// `None` values leave the respective property up to the compositor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputConfig {
+    /// Display this configuration applies to
+    pub matcher: OutputMatcher,
+    /// Whether the display should be enabled
+    pub enabled: bool,
+    /// Requested mode
+    pub mode: Option<ProfileMode>,
+    /// Requested scale
+    pub scale: Option<f64>,
+    /// Requested transform
+    pub transform: Option<Transform>,
+    /// Requested position in the global compositor space
+    pub position: Option<(i32, i32)>,
+}
+
+impl OutputConfig {
+    /// Create an enabled configuration without any requested properties
+    pub fn new(matcher: OutputMatcher) -> Self {
+        OutputConfig {
+            matcher,
+            enabled: true,
+            mode: None,
+            scale: None,
+            transform: None,
+            position: None,
+        }
+    }
+}
+
+/// A named set of display configurations
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutputProfile {
+    /// Optional name of this profile
+    pub name: Option<String>,
+    /// Configurations of all displays that are part of this profile
+    pub outputs: Vec<OutputConfig>,
+}
+
+impl OutputProfile {
+    /// Try to assign every display to exactly one of the profiles outputs
+    ///
+    /// Returns the index into [`OutputProfile::outputs`] for every display or `None`,
+    /// if the profile doesn't match the given set of displays.
+    pub fn assign(&self, outputs: &[OutputIdentity]) -> Option<Vec<usize>> {
+        if outputs.len() != self.outputs.len() {
+            return None;
+        }
+
+        // try more specific matchers first, so wildcards don't steal displays
+        let mut order = (0..self.outputs.len()).collect::<Vec<_>>();
+        order.sort_by_key(|idx| std::cmp::Reverse(self.outputs[*idx].matcher.specificity()));
+
+        let mut used = vec![false; self.outputs.len()];
+        let mut assignment = Vec::with_capacity(outputs.len());
+        if self.assign_from(outputs, &order, &mut used, &mut assignment) {
+            Some(assignment)
+        } else {
+            None
+        }
+    }
+
+    fn assign_from(
+        &self,
+        outputs: &[OutputIdentity],
+        order: &[usize],
+        used: &mut [bool],
+        assignment: &mut Vec<usize>,
+    ) -> bool {
+        let Some(output) = outputs.get(assignment.len()) else {
+            return true;
+        };
+
+        for &idx in order {
+            if used[idx] || !self.outputs[idx].matcher.matches(output) {
+                continue;
+            }
+
+            used[idx] = true;
+            assignment.push(idx);
+            if self.assign_from(outputs, order, used, assignment) {
+                return true;
+            }
+            assignment.pop();
+            used[idx] = false;
+        }
+
+        false
+    }
+
+    /// Sum of the specificity of all matchers of this profile
+    pub fn specificity(&self) -> u32 {
+        self.outputs
+            .iter()
+            .map(|config| config.matcher.specificity())
+            .sum()
+    }
+}
+
+/// Result of [`ProfileSet::select`]
+#[derive(Debug, Clone)]
+pub struct ProfileMatch<'a> {
+    profile: &'a OutputProfile,
+    assignment: Vec<usize>,
+}
+
+impl<'a> ProfileMatch<'a> {
+    /// The profile that was selected
+    pub fn profile(&self) -> &'a OutputProfile {
+        self.profile
+    }
+
+    /// Configuration for the display at `index` of the slice passed to [`ProfileSet::select`]
+    pub fn config(&self, index: usize) -> Option<&'a OutputConfig> {
+        let profile = self.profile;
+        self.assignment.get(index).map(|idx| &profile.outputs[*idx])
+    }
+
+    /// Iterator over the configurations in the order of the displays passed to [`ProfileSet::select`]
+    pub fn configs(&self) -> impl Iterator<Item = &'a OutputConfig> + '_ {
+        let profile = self.profile;
+        self.assignment.iter().map(move |idx| &profile.outputs[*idx])
+    }
+}
+
+/// A list of profiles to choose from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProfileSet {
+    /// All known profiles in order of definition
+    pub profiles: Vec<OutputProfile>,
+}
+
+impl ProfileSet {
+    /// Create an empty profile set
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Select the best matching profile for the given connected displays
+    ///
+    /// Should be called whenever the set of connected displays changed,
+    /// e.g. after every [`DrmScanner::scan_connectors`](crate::drm_scanner::DrmScanner::scan_connectors).
+    pub fn select(&self, outputs: &[OutputIdentity]) -> Option<ProfileMatch<'_>> {
+        let mut best: Option<(u32, ProfileMatch<'_>)> = None;
+
+        for profile in &self.profiles {
+            let Some(assignment) = profile.assign(outputs) else {
+                continue;
+            };
+
+            let score = profile.specificity();
+            if best.as_ref().is_none_or(|(best_score, _)| score > *best_score) {
+                best = Some((score, ProfileMatch { profile, assignment }));
+            }
+        }
+
+        best.map(|(_, matched)| matched)
+    }
+}
+
+impl fmt::Display for ProfileSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, profile) in self.profiles.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{profile}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for ProfileSet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        format::parse(s).map(|profiles| ProfileSet { profiles })
+    }
+}
+
+impl fmt::Display for OutputProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "profile ")?;
+        if let Some(name) = &self.name {
+            write!(f, "{} ", format::Quoted(name))?;
+        }
+        writeln!(f, "{{")?;
+        for output in &self.outputs {
+            writeln!(f, "    {output}")?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+impl std::str::FromStr for OutputProfile {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut profiles = format::parse(s)?;
+        if profiles.len() != 1 {
+            return Err(ParseError::new(1, "expected exactly one profile"));
+        }
+        Ok(profiles.remove(0))
+    }
+}
+
+impl fmt::Display for OutputConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "output {}", self.matcher)?;
+        write!(f, " {}", if self.enabled { "enable" } else { "disable" })?;
+        if let Some(mode) = &self.mode {
+            write!(f, " mode {}x{}", mode.width, mode.height)?;
+            if let Some(refresh) = mode.refresh {
+                write!(f, "@{}.{:03}", refresh / 1000, refresh % 1000)?;
+            }
+        }
+        if let Some(scale) = self.scale {
+            write!(f, " scale {scale}")?;
+        }
+        if let Some(transform) = self.transform {
+            write!(f, " transform {}", transform.as_str())?;
+        }
+        if let Some((x, y)) = self.position {
+            write!(f, " position {x},{y}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for OutputMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn field(f: &mut fmt::Formatter<'_>, value: &Option<String>) -> fmt::Result {
+            match value {
+                Some(value) => write!(f, " {}", format::Quoted(value)),
+                None => write!(f, " *"),
+            }
+        }
+
+        match self {
+            OutputMatcher::Any => write!(f, "*"),
+            OutputMatcher::Connector(name) => write!(f, "connector {}", format::Quoted(name)),
+            OutputMatcher::Edid { make, model, serial } => {
+                write!(f, "edid")?;
+                field(f, make)?;
+                field(f, model)?;
+                field(f, serial)
+            }
+        }
+    }
+}
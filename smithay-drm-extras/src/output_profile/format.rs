@@ -0,0 +1,433 @@
+use std::{fmt, iter::Peekable};
+
+use super::{OutputConfig, OutputMatcher, OutputProfile, ProfileMode, Transform};
+
+/// Error returned when parsing profiles from text fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    line: usize,
+    message: String,
+}
+
+impl ParseError {
+    pub(super) fn new(line: usize, message: impl Into<String>) -> Self {
+        ParseError {
+            line,
+            message: message.into(),
+        }
+    }
+
+    /// Line (starting at 1) the error occurred at
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Description of the error
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Writes a string as a quoted and escaped token
+pub(super) struct Quoted<'a>(pub &'a str);
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                '\n' => write!(f, "\\n")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Open,
+    Close,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(word) => format!("`{word}`"),
+            Token::Quoted(string) => format!("{}", Quoted(string)),
+            Token::Open => "`{`".into(),
+            Token::Close => "`}`".into(),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+
+    for (idx, line) in input.lines().enumerate() {
+        let line_nr = idx + 1;
+        let mut chars = line.chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            match c {
+                '#' => break,
+                c if c.is_whitespace() => {
+                    chars.next();
+                }
+                '{' => {
+                    chars.next();
+                    tokens.push((line_nr, Token::Open));
+                }
+                '}' => {
+                    chars.next();
+                    tokens.push((line_nr, Token::Close));
+                }
+                '"' => {
+                    chars.next();
+                    let mut string = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some('\\') => match chars.next() {
+                                Some('n') => string.push('\n'),
+                                Some(c @ ('"' | '\\')) => string.push(c),
+                                Some(c) => {
+                                    return Err(ParseError::new(line_nr, format!("invalid escape `\\{c}`")));
+                                }
+                                None => return Err(ParseError::new(line_nr, "unterminated string")),
+                            },
+                            Some(c) => string.push(c),
+                            None => return Err(ParseError::new(line_nr, "unterminated string")),
+                        }
+                    }
+                    tokens.push((line_nr, Token::Quoted(string)));
+                }
+                _ => {
+                    let mut word = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if c.is_whitespace() || matches!(c, '{' | '}' | '"' | '#') {
+                            break;
+                        }
+                        word.push(c);
+                        chars.next();
+                    }
+                    tokens.push((line_nr, Token::Word(word)));
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<I: Iterator<Item = (usize, Token)>> {
+    tokens: Peekable<I>,
+    line: usize,
+}
+
+impl<I: Iterator<Item = (usize, Token)>> Parser<I> {
+    fn next(&mut self) -> Result<Token, ParseError> {
+        match self.tokens.next() {
+            Some((line, token)) => {
+                self.line = line;
+                Ok(token)
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn peek(&mut self) -> Option<&Token> {
+        self.tokens.peek().map(|(_, token)| token)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.line, message)
+    }
+
+    fn unexpected(&self, token: &Token, expected: &str) -> ParseError {
+        self.error(format!("expected {expected}, found {}", token.describe()))
+    }
+
+    fn string(&mut self, expected: &str) -> Result<String, ParseError> {
+        match self.next()? {
+            Token::Word(string) | Token::Quoted(string) => Ok(string),
+            token => Err(self.unexpected(&token, expected)),
+        }
+    }
+
+    fn word(&mut self, expected: &str) -> Result<String, ParseError> {
+        match self.next()? {
+            Token::Word(word) => Ok(word),
+            token => Err(self.unexpected(&token, expected)),
+        }
+    }
+
+    fn profile(&mut self) -> Result<OutputProfile, ParseError> {
+        let name = match self.next()? {
+            Token::Open => None,
+            Token::Word(name) | Token::Quoted(name) => {
+                match self.next()? {
+                    Token::Open => {}
+                    token => return Err(self.unexpected(&token, "`{`")),
+                }
+                Some(name)
+            }
+            token => return Err(self.unexpected(&token, "profile name or `{`")),
+        };
+
+        let mut outputs = Vec::new();
+        loop {
+            match self.next()? {
+                Token::Close => break,
+                Token::Word(word) if word == "output" => outputs.push(self.output()?),
+                token => return Err(self.unexpected(&token, "`output` or `}`")),
+            }
+        }
+
+        Ok(OutputProfile { name, outputs })
+    }
+
+    fn edid_field(&mut self) -> Result<Option<String>, ParseError> {
+        match self.next()? {
+            Token::Word(word) if word == "*" => Ok(None),
+            Token::Word(string) | Token::Quoted(string) => Ok(Some(string)),
+            token => Err(self.unexpected(&token, "edid field")),
+        }
+    }
+
+    fn output(&mut self) -> Result<OutputConfig, ParseError> {
+        let matcher = match self.word("output matcher")?.as_str() {
+            "*" => OutputMatcher::Any,
+            "connector" => OutputMatcher::Connector(self.string("connector name")?),
+            "edid" => OutputMatcher::Edid {
+                make: self.edid_field()?,
+                model: self.edid_field()?,
+                serial: self.edid_field()?,
+            },
+            word => return Err(self.error(format!("unknown output matcher `{word}`"))),
+        };
+
+        let mut config = OutputConfig::new(matcher);
+        while let Some(Token::Word(word)) = self.peek() {
+            if word == "output" {
+                break;
+            }
+
+            match self.word("output option")?.as_str() {
+                "enable" => config.enabled = true,
+                "disable" => config.enabled = false,
+                "mode" => {
+                    let value = self.word("mode")?;
+                    config.mode = Some(
+                        parse_mode(&value).ok_or_else(|| self.error(format!("invalid mode `{value}`")))?,
+                    );
+                }
+                "scale" => {
+                    let value = self.word("scale")?;
+                    let scale = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|scale| scale.is_finite() && *scale > 0.0)
+                        .ok_or_else(|| self.error(format!("invalid scale `{value}`")))?;
+                    config.scale = Some(scale);
+                }
+                "transform" => {
+                    let value = self.word("transform")?;
+                    config.transform = Some(
+                        Transform::parse(&value)
+                            .ok_or_else(|| self.error(format!("invalid transform `{value}`")))?,
+                    );
+                }
+                "position" => {
+                    let value = self.word("position")?;
+                    config.position = Some(
+                        parse_position(&value)
+                            .ok_or_else(|| self.error(format!("invalid position `{value}`")))?,
+                    );
+                }
+                option => return Err(self.error(format!("unknown output option `{option}`"))),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Parses `WIDTHxHEIGHT` with an optional `@REFRESH` in Hz (with up to three decimal places)
+fn parse_mode(value: &str) -> Option<ProfileMode> {
+    let (size, refresh) = match value.split_once('@') {
+        Some((size, refresh)) => (size, Some(refresh.strip_suffix("Hz").unwrap_or(refresh))),
+        None => (value, None),
+    };
+    let (width, height) = size.split_once('x')?;
+
+    let refresh = match refresh {
+        Some(refresh) => {
+            let (int, frac) = refresh.split_once('.').unwrap_or((refresh, ""));
+            if frac.len() > 3 || !frac.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let int = int.parse::<u32>().ok()?;
+            let frac = format!("{frac:0<3}").parse::<u32>().ok()?;
+            Some(int.checked_mul(1000)?.checked_add(frac)?)
+        }
+        None => None,
+    };
+
+    Some(ProfileMode {
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+        refresh,
+    })
+}
+
+fn parse_position(value: &str) -> Option<(i32, i32)> {
+    let (x, y) = value.split_once(',')?;
+    Some((x.parse().ok()?, y.parse().ok()?))
+}
+
+pub(super) fn parse(input: &str) -> Result<Vec<OutputProfile>, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?.into_iter().peekable(),
+        line: 1,
+    };
+
+    let mut profiles = Vec::new();
+    while parser.peek().is_some() {
+        match parser.next()? {
+            Token::Word(word) if word == "profile" => profiles.push(parser.profile()?),
+            token => return Err(parser.unexpected(&token, "`profile`")),
+        }
+    }
+
+    Ok(profiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{OutputIdentity, ProfileSet};
+    use super::*;
+
+    const PROFILES: &str = r#"
+# laptop on its own
+profile "undocked" {
+    output connector eDP-1 enable scale 2
+}
+
+profile "docked" {
+    output edid "Dell Inc." "DELL U2720Q" * mode 3840x2160@59.997 scale 1.5 position 0,0
+    output connector eDP-1 disable
+}
+
+profile {
+    output * transform flipped-90
+    output connector "eDP-1" mode 1920x1080 position -1920,0
+}
+"#;
+
+    fn identity(connector: &str, make: Option<&str>, model: Option<&str>) -> OutputIdentity {
+        OutputIdentity {
+            connector: connector.into(),
+            make: make.map(String::from),
+            model: model.map(String::from),
+            serial: None,
+        }
+    }
+
+    #[test]
+    fn parse_profiles() {
+        let set: ProfileSet = PROFILES.parse().unwrap();
+        assert_eq!(set.profiles.len(), 3);
+
+        let docked = &set.profiles[1];
+        assert_eq!(docked.name.as_deref(), Some("docked"));
+        assert_eq!(
+            docked.outputs[0].matcher,
+            OutputMatcher::Edid {
+                make: Some("Dell Inc.".into()),
+                model: Some("DELL U2720Q".into()),
+                serial: None,
+            }
+        );
+        assert_eq!(
+            docked.outputs[0].mode,
+            Some(ProfileMode {
+                width: 3840,
+                height: 2160,
+                refresh: Some(59997),
+            })
+        );
+        assert_eq!(docked.outputs[0].scale, Some(1.5));
+        assert!(!docked.outputs[1].enabled);
+
+        let unnamed = &set.profiles[2];
+        assert_eq!(unnamed.name, None);
+        assert_eq!(unnamed.outputs[0].matcher, OutputMatcher::Any);
+        assert_eq!(unnamed.outputs[0].transform, Some(Transform::Flipped90));
+        assert_eq!(unnamed.outputs[1].position, Some((-1920, 0)));
+    }
+
+    #[test]
+    fn roundtrip() {
+        let set: ProfileSet = PROFILES.parse().unwrap();
+        let serialized = set.to_string();
+        let reparsed: ProfileSet = serialized.parse().unwrap();
+        assert_eq!(set, reparsed);
+        assert_eq!(serialized, reparsed.to_string());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = "profile {\n    output connector eDP-1 scale nope\n}"
+            .parse::<ProfileSet>()
+            .unwrap_err();
+        assert_eq!(err.line(), 2);
+
+        assert!("profile { output".parse::<ProfileSet>().is_err());
+        assert!("profile { output foo }".parse::<ProfileSet>().is_err());
+        assert!("profile \"unterminated {".parse::<ProfileSet>().is_err());
+        assert!(
+            "profile { output * mode 1920x1080@60.0001 }"
+                .parse::<ProfileSet>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn select_profile() {
+        let set: ProfileSet = PROFILES.parse().unwrap();
+
+        let laptop = identity("eDP-1", Some("BOE"), None);
+        let dell = identity("DP-3", Some("Dell Inc."), Some("DELL U2720Q"));
+        let other = identity("HDMI-A-1", Some("LG"), None);
+
+        let matched = set.select(std::slice::from_ref(&laptop)).unwrap();
+        assert_eq!(matched.profile().name.as_deref(), Some("undocked"));
+
+        // the edid matcher is more specific than the wildcard
+        let outputs = [laptop.clone(), dell];
+        let matched = set.select(&outputs).unwrap();
+        assert_eq!(matched.profile().name.as_deref(), Some("docked"));
+        assert!(!matched.config(0).unwrap().enabled);
+        assert_eq!(matched.config(1).unwrap().scale, Some(1.5));
+
+        let outputs = [other, laptop];
+        let matched = set.select(&outputs).unwrap();
+        assert_eq!(matched.profile().name, None);
+        assert_eq!(matched.config(0).unwrap().matcher, OutputMatcher::Any);
+
+        assert!(set.select(&[]).is_none());
+    }
+}
@@ -1,4 +1,9 @@
 //! Device-wide synchronization helpers
+//!
+//! The [`DrmOutputManager`] hands out [`DrmOutput`]s for individual crtcs of a [`DrmDevice`].
+//! Outputs can be mirrored either by driving additional connectors from the same crtc
+//! ([`LockedDrmOutputManager::add_mirror_connector`]) or by copying rendered frames onto other
+//! outputs with a [`DrmOutputMirror`].
 
 use std::{
     collections::HashMap,
@@ -14,20 +19,29 @@ use drm_fourcc::{DrmFormat, DrmFourcc, DrmModifier};
 use crate::{
     backend::{
         allocator::{
-            Allocator,
+            Allocator, Buffer,
             dmabuf::{AsDmabuf, Dmabuf},
+            format::has_alpha,
             gbm::GbmDevice,
         },
-        renderer::{Bind, Color32F, DebugFlags, Renderer, RendererSuper, Texture, element::RenderElement},
+        renderer::{
+            Bind, Blit, Color32F, ContextId, DebugFlags, Frame, Offscreen, Renderer, RendererSuper, Texture,
+            damage::OutputDamageTracker,
+            element::{Element, Id, RenderElement},
+            utils::{CommitCounter, DamageBag, DamageSet, DamageSnapshot, OpaqueRegions},
+        },
     },
     output::OutputModeSource,
+    utils::{
+        Buffer as BufferCoords, Physical, Point, Rectangle, Scale, Size, Transform, user_data::UserDataMap,
+    },
 };
 
 use super::{
-    DrmDevice, DrmError, Planes,
+    DrmDevice, DrmError, Framebuffer, Planes,
     compositor::{
-        DrmCompositor, FrameError, FrameFlags, FrameResult, PrimaryPlaneElement, RenderFrameError,
-        RenderFrameErrorType, RenderFrameResult,
+        BlitFrameResultError, DrmCompositor, FrameError, FrameFlags, FrameResult, PrimaryPlaneElement,
+        RenderFrameError, RenderFrameErrorType, RenderFrameResult,
     },
     exporter::ExportFramebuffer,
 };
@@ -190,6 +204,12 @@ where
     /// The specified CRTC is already in use
     #[error("The specified CRTC {0:?} is already in use.")]
     DuplicateCrtc(crtc::Handle),
+    /// The specified CRTC is not driven by any output of this manager
+    #[error("The specified CRTC {0:?} is not driven by any output.")]
+    UnknownCrtc(crtc::Handle),
+    /// The specified connector is already driven by another CRTC
+    #[error("The specified connector {0:?} is already in use by CRTC {1:?}.")]
+    ConnectorInUse(connector::Handle, crtc::Handle),
     /// The underlying drm device returned an error
     #[error(transparent)]
     Drm(#[from] DrmError),
@@ -591,6 +611,101 @@ where
         Ok(())
     }
 
+    /// Mirrors the output driven by `crtc` onto an additional `connector`.
+    ///
+    /// This is the cheapest form of mirroring as the same framebuffers are scanned out
+    /// to all connectors by a single crtc, but it requires the `connector` to support
+    /// the current mode of the crtc and an encoder able to drive it from the given crtc.
+    /// If that is not the case this will fail with [`DrmError::ModeNotSuitable`] or
+    /// another [`DrmError`] and you may fall back to [`DrmOutputMirror`] to mirror the
+    /// output to a separately initialized [`DrmOutput`] instead.
+    ///
+    /// The connector is enabled by committing a composited frame to the crtc.
+    pub fn add_mirror_connector<R, E>(
+        &mut self,
+        crtc: &crtc::Handle,
+        connector: connector::Handle,
+        renderer: &mut R,
+        render_elements: &DrmOutputRenderElements<R, E>,
+    ) -> DrmOutputManagerResult<(), A, F, R>
+    where
+        E: RenderElement<R>,
+        R: Renderer + Bind<Dmabuf>,
+        R::TextureId: Texture + 'static,
+        R::Error: Send + Sync + 'static,
+    {
+        if !self.compositor.contains_key(crtc) {
+            return Err(DrmOutputManagerError::UnknownCrtc(*crtc));
+        }
+
+        if let Some(other) = self.compositor.iter_mut().find_map(|(other, compositor)| {
+            compositor
+                .get_mut()
+                .unwrap()
+                .pending_connectors()
+                .into_iter()
+                .any(|conn| conn == connector)
+                .then_some(*other)
+        }) {
+            if other == *crtc {
+                return Ok(());
+            }
+            return Err(DrmOutputManagerError::ConnectorInUse(connector, other));
+        }
+
+        let compositor = self.compositor.get_mut(crtc).unwrap().get_mut().unwrap();
+        compositor
+            .add_connector(connector)
+            .map_err(DrmOutputManagerError::Frame)?;
+
+        if let Err(err) = render_elements.submit_composited_frame(&mut *compositor, renderer) {
+            if !matches!(err, DrmOutputManagerError::Frame(FrameError::EmptyFrame)) {
+                tracing::warn!(?crtc, ?connector, "failed to enable mirror connector");
+                let _ = compositor.remove_connector(connector);
+                return Err(err);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stops mirroring the output driven by `crtc` to `connector`.
+    ///
+    /// The connector has to be previously added by [`LockedDrmOutputManager::add_mirror_connector`]
+    /// or initially passed to [`LockedDrmOutputManager::initialize_output`], but it can't be the
+    /// last connector of the crtc.
+    pub fn remove_mirror_connector<R, E>(
+        &mut self,
+        crtc: &crtc::Handle,
+        connector: connector::Handle,
+        renderer: &mut R,
+        render_elements: &DrmOutputRenderElements<R, E>,
+    ) -> DrmOutputManagerResult<(), A, F, R>
+    where
+        E: RenderElement<R>,
+        R: Renderer + Bind<Dmabuf>,
+        R::TextureId: Texture + 'static,
+        R::Error: Send + Sync + 'static,
+    {
+        let compositor = self
+            .compositor
+            .get_mut(crtc)
+            .ok_or(DrmOutputManagerError::UnknownCrtc(*crtc))?
+            .get_mut()
+            .unwrap();
+        compositor
+            .remove_connector(connector)
+            .map_err(DrmOutputManagerError::Frame)?;
+
+        if let Err(err) = render_elements.submit_composited_frame(&mut *compositor, renderer) {
+            if !matches!(err, DrmOutputManagerError::Frame(FrameError::EmptyFrame)) {
+                return Err(err);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Activates a previously paused device.
     ///
     /// Specifying `true` for `disable_connectors` will call [`DrmDevice::reset_state`] if
@@ -1028,3 +1143,396 @@ where
         Ok(())
     }
 }
+
+/// Software mirroring of the frames of a [`DrmOutput`] onto other outputs.
+///
+/// If an output can't be mirrored by driving an additional connector from the same crtc
+/// (see [`LockedDrmOutputManager::add_mirror_connector`]), e.g. because the displays don't
+/// share a common mode, the `DrmOutputMirror` can be used to present the frame of the source
+/// output on other [`DrmOutput`]s without rendering the scene again.
+///
+/// After rendering the source output with [`DrmOutput::render_frame`] call
+/// [`DrmOutputMirror::update`] with the returned [`RenderFrameResult`]. This copies the damaged
+/// regions of the frame into an offscreen texture. The texture can then be presented on any number
+/// of outputs using the element returned by [`DrmOutputMirror::render_element`], which scales the
+/// frame to the target output preserving its aspect ratio (letterboxing) and handles damage tracking.
+#[derive(Debug)]
+pub struct DrmOutputMirror<T: Texture> {
+    id: Id,
+    format: DrmFourcc,
+    texture: Option<(ContextId<T>, T)>,
+    size: Size<i32, Physical>,
+    transform: Transform,
+    damage_tracker: Option<OutputDamageTracker>,
+    damage: DamageBag<i32, BufferCoords>,
+}
+
+impl<T: Texture + Clone + 'static> DrmOutputMirror<T> {
+    /// Create a new mirror using an offscreen texture of the given `format`
+    pub fn new(format: DrmFourcc) -> Self {
+        DrmOutputMirror {
+            id: Id::new(),
+            format,
+            texture: None,
+            size: Size::default(),
+            transform: Transform::Normal,
+            damage_tracker: None,
+            damage: DamageBag::default(),
+        }
+    }
+
+    /// Update the mirrored contents from a frame of the source output.
+    ///
+    /// - `frame_result` as returned by [`DrmOutput::render_frame`] of the source output
+    /// - `size`, `scale` and `transform` of the current mode of the source output
+    ///
+    /// Only the damaged parts of the frame are copied.
+    pub fn update<R, B, Fb, E>(
+        &mut self,
+        renderer: &mut R,
+        frame_result: &RenderFrameResult<'_, B, Fb, E>,
+        size: impl Into<Size<i32, Physical>>,
+        scale: impl Into<Scale<f64>>,
+        transform: Transform,
+    ) -> Result<(), BlitFrameResultError<R::Error, <B as AsDmabuf>::Error>>
+    where
+        R: Renderer<TextureId = T> + Offscreen<T> + Bind<Dmabuf> + Blit,
+        B: Buffer + AsDmabuf,
+        <B as AsDmabuf>::Error: fmt::Debug,
+        Fb: Framebuffer,
+        E: Element + RenderElement<R>,
+    {
+        let size = size.into();
+        let scale = scale.into();
+
+        let context_id = renderer.context_id();
+        let needs_realloc = self.size != size
+            || self
+                .texture
+                .as_ref()
+                .is_none_or(|(texture_context, _)| *texture_context != context_id);
+        if needs_realloc {
+            let texture = renderer
+                .create_buffer(self.format, size.to_logical(1).to_buffer(1, Transform::Normal))
+                .map_err(BlitFrameResultError::Rendering)?;
+            self.texture = Some((context_id, texture));
+            self.damage_tracker = None;
+            self.damage.reset();
+            self.size = size;
+        }
+
+        let mode = OutputModeSource::Static {
+            size,
+            scale,
+            transform,
+        };
+        if self
+            .damage_tracker
+            .as_ref()
+            .is_none_or(|tracker| *tracker.mode() != mode)
+        {
+            self.damage_tracker = Some(OutputDamageTracker::new(size, scale, transform));
+            self.transform = transform;
+        }
+        let damage_tracker = self.damage_tracker.as_mut().unwrap();
+
+        // the texture is reused for every frame, so everything not damaged since the last frame is still valid
+        let age = if needs_realloc { 0 } else { 1 };
+        let damage = match frame_result.damage_from_age(damage_tracker, age, []) {
+            Ok((Some(damage), _)) => damage.clone(),
+            Ok((None, _)) => return Ok(()),
+            // can't happen for a static mode
+            Err(_) => vec![Rectangle::from_size(transform.transform_size(size))],
+        };
+
+        let (_, texture) = self.texture.as_mut().unwrap();
+        let mut framebuffer = renderer.bind(texture).map_err(BlitFrameResultError::Rendering)?;
+        let sync = frame_result.blit_frame_result(
+            size,
+            transform,
+            scale,
+            renderer,
+            &mut framebuffer,
+            damage.iter().copied(),
+            [],
+        )?;
+        std::mem::drop(framebuffer);
+        renderer.wait(&sync).map_err(BlitFrameResultError::Rendering)?;
+
+        let area = transform.transform_size(size).to_logical(1);
+        self.damage.add(
+            damage
+                .into_iter()
+                .map(|rect| rect.to_logical(1).to_buffer(1, transform, &area)),
+        );
+
+        Ok(())
+    }
+
+    /// Create an element presenting the mirrored frame on an output of the given physical size.
+    ///
+    /// `output_size` is the size of the target output with its transform applied, as e.g. returned by
+    /// [`Output::current_mode`](crate::output::Output::current_mode) and
+    /// [`Transform::transform_size`](crate::utils::Transform::transform_size).
+    /// The frame is scaled to fit the output and centered, the remaining area is left to the
+    /// clear color of the target output.
+    ///
+    /// Returns `None` until [`DrmOutputMirror::update`] was called at least once.
+    pub fn render_element(
+        &self,
+        output_size: impl Into<Size<i32, Physical>>,
+    ) -> Option<MirrorRenderElement<T>> {
+        let (context_id, texture) = self.texture.as_ref()?;
+        let output_size = output_size.into();
+
+        let source_size = self.transform.transform_size(self.size);
+        if source_size.is_empty() || output_size.is_empty() {
+            return None;
+        }
+
+        let factor = f64::min(
+            output_size.w as f64 / source_size.w as f64,
+            output_size.h as f64 / source_size.h as f64,
+        );
+        let dst_size = Size::<i32, Physical>::from((
+            (source_size.w as f64 * factor).round() as i32,
+            (source_size.h as f64 * factor).round() as i32,
+        ));
+        let dst_loc = Point::<i32, Physical>::from((
+            (output_size.w - dst_size.w) / 2,
+            (output_size.h - dst_size.h) / 2,
+        ));
+
+        Some(MirrorRenderElement {
+            id: self.id.clone(),
+            context_id: context_id.clone(),
+            texture: texture.clone(),
+            size: self.size,
+            transform: self.transform,
+            dst: Rectangle::new(dst_loc, dst_size),
+            snapshot: self.damage.snapshot(),
+        })
+    }
+}
+
+/// Element presenting the contents of a [`DrmOutputMirror`]
+#[derive(Debug)]
+pub struct MirrorRenderElement<T: Texture> {
+    id: Id,
+    context_id: ContextId<T>,
+    texture: T,
+    size: Size<i32, Physical>,
+    transform: Transform,
+    dst: Rectangle<i32, Physical>,
+    snapshot: DamageSnapshot<i32, BufferCoords>,
+}
+
+impl<T: Texture> MirrorRenderElement<T> {
+    fn buffer_size(&self) -> Size<i32, BufferCoords> {
+        self.size.to_logical(1).to_buffer(1, Transform::Normal)
+    }
+}
+
+impl<T: Texture> Element for MirrorRenderElement<T> {
+    fn id(&self) -> &Id {
+        &self.id
+    }
+
+    fn current_commit(&self) -> CommitCounter {
+        self.snapshot.current_commit()
+    }
+
+    fn src(&self) -> Rectangle<f64, BufferCoords> {
+        Rectangle::from_size(self.buffer_size()).to_f64()
+    }
+
+    fn geometry(&self, _scale: Scale<f64>) -> Rectangle<i32, Physical> {
+        self.dst
+    }
+
+    fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    fn damage_since(&self, _scale: Scale<f64>, commit: Option<CommitCounter>) -> DamageSet<i32, Physical> {
+        let buffer_size = self.buffer_size();
+        let source_size = self.transform.transform_size(self.size).to_f64();
+        let factor = Scale::from((
+            self.dst.size.w as f64 / source_size.w,
+            self.dst.size.h as f64 / source_size.h,
+        ));
+
+        self.snapshot
+            .damage_since(commit)
+            .map(|damage| {
+                damage
+                    .into_iter()
+                    .map(|rect| {
+                        let rect = rect.to_logical(1, self.transform, &buffer_size).to_f64();
+                        let rect = Rectangle::<f64, Physical>::new(
+                            (rect.loc.x * factor.x, rect.loc.y * factor.y).into(),
+                            (rect.size.w * factor.x, rect.size.h * factor.y).into(),
+                        )
+                        .to_i32_up::<i32>();
+                        Rectangle::new(rect.loc + self.dst.loc, rect.size)
+                    })
+                    .collect()
+            })
+            .unwrap_or_else(|| DamageSet::from_slice(&[self.dst]))
+    }
+
+    fn opaque_regions(&self, _scale: Scale<f64>) -> OpaqueRegions<i32, Physical> {
+        // the copied frame can only contain transparency if the offscreen texture has an alpha channel
+        if self.texture.format().is_some_and(|format| !has_alpha(format)) {
+            OpaqueRegions::from_slice(&[self.dst])
+        } else {
+            OpaqueRegions::default()
+        }
+    }
+}
+
+impl<R, T> RenderElement<R> for MirrorRenderElement<T>
+where
+    R: Renderer<TextureId = T>,
+    T: Texture,
+{
+    fn draw(
+        &self,
+        frame: &mut R::Frame<'_, '_>,
+        src: Rectangle<f64, BufferCoords>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        opaque_regions: &[Rectangle<i32, Physical>],
+        _cache: Option<&UserDataMap>,
+    ) -> Result<(), R::Error> {
+        if frame.context_id() != self.context_id {
+            tracing::warn!("trying to render mirrored output from different renderer context");
+            return Ok(());
+        }
+
+        frame.render_texture_from_to(
+            &self.texture,
+            src,
+            dst,
+            damage,
+            opaque_regions,
+            self.transform,
+            1.0,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use drm_fourcc::DrmFourcc;
+
+    use crate::{
+        backend::renderer::{ContextId, Texture, element::Element},
+        utils::{Rectangle, Scale, Size, Transform},
+    };
+
+    use super::DrmOutputMirror;
+
+    #[derive(Debug, Clone)]
+    struct TestTexture {
+        format: DrmFourcc,
+    }
+
+    impl Texture for TestTexture {
+        fn width(&self) -> u32 {
+            0
+        }
+
+        fn height(&self) -> u32 {
+            0
+        }
+
+        fn format(&self) -> Option<DrmFourcc> {
+            Some(self.format)
+        }
+    }
+
+    fn mirror(format: DrmFourcc, size: (i32, i32), transform: Transform) -> DrmOutputMirror<TestTexture> {
+        let mut mirror = DrmOutputMirror::new(format);
+        mirror.texture = Some((ContextId::new(), TestTexture { format }));
+        mirror.size = size.into();
+        mirror.transform = transform;
+        mirror
+    }
+
+    #[test]
+    fn no_element_before_update() {
+        let mirror = DrmOutputMirror::<TestTexture>::new(DrmFourcc::Xrgb8888);
+        assert!(mirror.render_element((1920, 1080)).is_none());
+    }
+
+    #[test]
+    fn letterbox() {
+        let mirror = mirror(DrmFourcc::Xrgb8888, (1920, 1080), Transform::Normal);
+
+        let element = mirror.render_element((1280, 1024)).unwrap();
+        assert_eq!(
+            element.geometry(Scale::from(1.0)),
+            Rectangle::new((0, 152).into(), (1280, 720).into())
+        );
+
+        let element = mirror.render_element((3840, 1080)).unwrap();
+        assert_eq!(
+            element.geometry(Scale::from(1.0)),
+            Rectangle::new((960, 0).into(), (1920, 1080).into())
+        );
+
+        assert!(mirror.render_element(Size::default()).is_none());
+    }
+
+    #[test]
+    fn letterbox_transformed() {
+        let mirror = mirror(DrmFourcc::Xrgb8888, (1920, 1080), Transform::_90);
+
+        let element = mirror.render_element((1920, 1080)).unwrap();
+        assert_eq!(
+            element.geometry(Scale::from(1.0)),
+            Rectangle::new((656, 0).into(), (608, 1080).into())
+        );
+    }
+
+    #[test]
+    fn opaque_regions() {
+        let opaque = mirror(DrmFourcc::Xrgb8888, (1920, 1080), Transform::Normal);
+        let element = opaque.render_element((1920, 1080)).unwrap();
+        assert_eq!(
+            &*element.opaque_regions(Scale::from(1.0)),
+            &[Rectangle::from_size((1920, 1080).into())]
+        );
+
+        let translucent = mirror(DrmFourcc::Argb8888, (1920, 1080), Transform::Normal);
+        let element = translucent.render_element((1920, 1080)).unwrap();
+        assert!(element.opaque_regions(Scale::from(1.0)).is_empty());
+    }
+
+    #[test]
+    fn damage_is_scaled() {
+        let mut mirror = mirror(DrmFourcc::Xrgb8888, (200, 100), Transform::Normal);
+        let commit = mirror.render_element((100, 100)).unwrap().current_commit();
+
+        mirror
+            .damage
+            .add([Rectangle::new((20, 20).into(), (40, 40).into())]);
+        let element = mirror.render_element((100, 100)).unwrap();
+
+        // scaled by 0.5 and offset by the letterboxing
+        assert_eq!(
+            &*element.damage_since(Scale::from(1.0), Some(commit)),
+            &[Rectangle::new((10, 35).into(), (20, 20).into())]
+        );
+        assert!(
+            element
+                .damage_since(Scale::from(1.0), Some(element.current_commit()))
+                .is_empty()
+        );
+        assert_eq!(
+            &*element.damage_since(Scale::from(1.0), None),
+            &[Rectangle::new((0, 25).into(), (100, 50).into())]
+        );
+    }
+}
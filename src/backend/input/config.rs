@@ -0,0 +1,143 @@
+use super::Device;
+
+/// Errors returned when changing the configuration of a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum DeviceConfigError {
+    /// The option is not supported by the device
+    #[error("The configuration option is not supported by the device")]
+    Unsupported,
+    /// The value is not valid for the option
+    #[error("Invalid value for the configuration option")]
+    Invalid,
+}
+
+/// Pointer acceleration profile
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccelProfile {
+    /// Motion is accelerated by a constant factor
+    Flat,
+    /// Acceleration depends on the speed of the motion
+    Adaptive,
+}
+
+/// Method to generate scroll events from pointer motion
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScrollMethod {
+    /// Never generate scroll events from pointer motion
+    NoScroll,
+    /// Scroll when two fingers are down on the device
+    TwoFinger,
+    /// Scroll when a finger moves along the bottom or right edge of the device
+    Edge,
+    /// Scroll when a button is held down while moving the device
+    OnButtonDown,
+}
+
+/// Typed configuration of an input device
+///
+/// Every option comes with a method to query if the device supports it. Setting an
+/// unsupported option returns [`DeviceConfigError::Unsupported`], the default implementations
+/// report every option as unsupported.
+///
+/// Devices of nested backends, like winit or x11, receive input already processed by the host system.
+/// These emulate some options (natural scroll, left-handed buttons and the calibration matrix)
+/// by transforming the generated events.
+pub trait DeviceConfig: Device {
+    /// Number of fingers supported for tapping, `0` if the device doesn't support tap-to-click
+    fn tap_finger_count(&self) -> u32 {
+        0
+    }
+    /// Returns if tap-to-click is enabled
+    fn tap_enabled(&self) -> bool {
+        false
+    }
+    /// Enable or disable tap-to-click
+    fn set_tap_enabled(&mut self, enabled: bool) -> Result<(), DeviceConfigError> {
+        let _ = enabled;
+        Err(DeviceConfigError::Unsupported)
+    }
+
+    /// Acceleration profiles supported by the device, empty if the device doesn't support acceleration
+    fn accel_profiles(&self) -> Vec<AccelProfile> {
+        Vec::new()
+    }
+    /// Returns the current acceleration profile
+    fn accel_profile(&self) -> Option<AccelProfile> {
+        None
+    }
+    /// Set the acceleration profile
+    fn set_accel_profile(&mut self, profile: AccelProfile) -> Result<(), DeviceConfigError> {
+        let _ = profile;
+        Err(DeviceConfigError::Unsupported)
+    }
+    /// Returns the current acceleration speed in the range `[-1, 1]`
+    fn accel_speed(&self) -> Option<f64> {
+        None
+    }
+    /// Set the acceleration speed in the range `[-1, 1]`
+    fn set_accel_speed(&mut self, speed: f64) -> Result<(), DeviceConfigError> {
+        let _ = speed;
+        Err(DeviceConfigError::Unsupported)
+    }
+
+    /// Returns if the device supports natural scrolling
+    fn has_natural_scroll(&self) -> bool {
+        false
+    }
+    /// Returns if natural scrolling is enabled
+    fn natural_scroll(&self) -> bool {
+        false
+    }
+    /// Enable or disable natural scrolling
+    fn set_natural_scroll(&mut self, enabled: bool) -> Result<(), DeviceConfigError> {
+        let _ = enabled;
+        Err(DeviceConfigError::Unsupported)
+    }
+
+    /// Returns if the device supports a left-handed button configuration
+    fn has_left_handed(&self) -> bool {
+        false
+    }
+    /// Returns if the left-handed button configuration is enabled
+    fn left_handed(&self) -> bool {
+        false
+    }
+    /// Enable or disable the left-handed button configuration
+    fn set_left_handed(&mut self, enabled: bool) -> Result<(), DeviceConfigError> {
+        let _ = enabled;
+        Err(DeviceConfigError::Unsupported)
+    }
+
+    /// Scroll methods supported by the device
+    fn scroll_methods(&self) -> Vec<ScrollMethod> {
+        Vec::new()
+    }
+    /// Returns the current scroll method
+    fn scroll_method(&self) -> Option<ScrollMethod> {
+        None
+    }
+    /// Set the scroll method
+    fn set_scroll_method(&mut self, method: ScrollMethod) -> Result<(), DeviceConfigError> {
+        let _ = method;
+        Err(DeviceConfigError::Unsupported)
+    }
+
+    /// Returns if the device supports a calibration matrix
+    fn has_calibration_matrix(&self) -> bool {
+        false
+    }
+    /// Returns the current calibration matrix
+    ///
+    /// The matrix is given as the first two rows of a 3x3 matrix in row-major order,
+    /// applied to absolute coordinates normalized to the `[0, 1]` range.
+    fn calibration_matrix(&self) -> Option<[f32; 6]> {
+        None
+    }
+    /// Set the calibration matrix, see [`DeviceConfig::calibration_matrix`]
+    fn set_calibration_matrix(&mut self, matrix: [f32; 6]) -> Result<(), DeviceConfigError> {
+        let _ = matrix;
+        Err(DeviceConfigError::Unsupported)
+    }
+}
@@ -0,0 +1,166 @@
+//! Emulated device configuration for the virtual devices of nested backends
+
+use std::{
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+use super::DeviceConfigError;
+
+const IDENTITY_MATRIX: [f32; 6] = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+
+#[derive(Debug, Clone, Copy)]
+struct EmulatedConfigState {
+    natural_scroll: bool,
+    left_handed: bool,
+    calibration_matrix: [f32; 6],
+}
+
+/// Configuration of a virtual device, applied by the backend to generated events
+#[derive(Debug, Clone)]
+pub(crate) struct EmulatedDeviceConfig(Arc<Mutex<EmulatedConfigState>>);
+
+impl Default for EmulatedDeviceConfig {
+    fn default() -> Self {
+        EmulatedDeviceConfig(Arc::new(Mutex::new(EmulatedConfigState {
+            natural_scroll: false,
+            left_handed: false,
+            calibration_matrix: IDENTITY_MATRIX,
+        })))
+    }
+}
+
+impl PartialEq for EmulatedDeviceConfig {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for EmulatedDeviceConfig {}
+
+impl Hash for EmulatedDeviceConfig {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state)
+    }
+}
+
+impl EmulatedDeviceConfig {
+    pub(crate) fn natural_scroll(&self) -> bool {
+        self.0.lock().unwrap().natural_scroll
+    }
+
+    pub(crate) fn set_natural_scroll(&self, enabled: bool) {
+        self.0.lock().unwrap().natural_scroll = enabled;
+    }
+
+    pub(crate) fn left_handed(&self) -> bool {
+        self.0.lock().unwrap().left_handed
+    }
+
+    pub(crate) fn set_left_handed(&self, enabled: bool) {
+        self.0.lock().unwrap().left_handed = enabled;
+    }
+
+    pub(crate) fn calibration_matrix(&self) -> [f32; 6] {
+        self.0.lock().unwrap().calibration_matrix
+    }
+
+    pub(crate) fn set_calibration_matrix(&self, matrix: [f32; 6]) -> Result<(), DeviceConfigError> {
+        if matrix.iter().any(|v| !v.is_finite()) {
+            return Err(DeviceConfigError::Invalid);
+        }
+        self.0.lock().unwrap().calibration_matrix = matrix;
+        Ok(())
+    }
+
+    /// Applies the natural scroll setting to a scroll amount
+    pub(crate) fn scroll(&self, amount: f64) -> f64 {
+        if self.natural_scroll() { -amount } else { amount }
+    }
+
+    /// Applies the calibration matrix to a position normalized to the `[0, 1]` range
+    pub(crate) fn calibrate(&self, x: f64, y: f64) -> (f64, f64) {
+        let [a, b, c, d, e, f] = self.calibration_matrix().map(f64::from);
+        (a * x + b * y + c, d * x + e * y + f)
+    }
+}
+
+/// Implements [`DeviceConfig`] for a virtual device with an `config: EmulatedDeviceConfig` field
+macro_rules! emulated_device_config {
+    ($device:ty) => {
+        impl $crate::backend::input::DeviceConfig for $device {
+            fn has_natural_scroll(&self) -> bool {
+                true
+            }
+            fn natural_scroll(&self) -> bool {
+                self.config.natural_scroll()
+            }
+            fn set_natural_scroll(
+                &mut self,
+                enabled: bool,
+            ) -> Result<(), $crate::backend::input::DeviceConfigError> {
+                self.config.set_natural_scroll(enabled);
+                Ok(())
+            }
+
+            fn has_left_handed(&self) -> bool {
+                true
+            }
+            fn left_handed(&self) -> bool {
+                self.config.left_handed()
+            }
+            fn set_left_handed(
+                &mut self,
+                enabled: bool,
+            ) -> Result<(), $crate::backend::input::DeviceConfigError> {
+                self.config.set_left_handed(enabled);
+                Ok(())
+            }
+
+            fn has_calibration_matrix(&self) -> bool {
+                true
+            }
+            fn calibration_matrix(&self) -> Option<[f32; 6]> {
+                Some(self.config.calibration_matrix())
+            }
+            fn set_calibration_matrix(
+                &mut self,
+                matrix: [f32; 6],
+            ) -> Result<(), $crate::backend::input::DeviceConfigError> {
+                self.config.set_calibration_matrix(matrix)
+            }
+        }
+    };
+}
+pub(crate) use emulated_device_config;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emulated_config() {
+        let config = EmulatedDeviceConfig::default();
+        assert_eq!(config.scroll(2.0), 2.0);
+        assert_eq!(config.calibrate(0.25, 0.75), (0.25, 0.75));
+
+        config.set_natural_scroll(true);
+        assert_eq!(config.scroll(2.0), -2.0);
+
+        // rotate by 90 degrees
+        config
+            .set_calibration_matrix([0.0, -1.0, 1.0, 1.0, 0.0, 0.0])
+            .unwrap();
+        assert_eq!(config.calibrate(0.25, 0.75), (0.25, 0.25));
+        assert_eq!(
+            config.set_calibration_matrix([f32::NAN, 0.0, 0.0, 0.0, 0.0, 0.0]),
+            Err(DeviceConfigError::Invalid)
+        );
+
+        // clones share the configuration
+        let other = config.clone();
+        assert!(other.natural_scroll());
+        assert_eq!(other, config);
+        assert_ne!(EmulatedDeviceConfig::default(), config);
+    }
+}
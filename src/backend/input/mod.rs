@@ -4,8 +4,14 @@ use std::path::PathBuf;
 
 pub use xkbcommon::xkb::Keycode;
 
+mod config;
+#[cfg(any(feature = "backend_winit", feature = "backend_x11"))]
+mod emulated;
 mod tablet;
 
+pub use config::{AccelProfile, DeviceConfig, DeviceConfigError, ScrollMethod};
+#[cfg(any(feature = "backend_winit", feature = "backend_x11"))]
+pub(crate) use emulated::{EmulatedDeviceConfig, emulated_device_config};
 pub use tablet::{
     ProximityState, TabletToolAxisEvent, TabletToolButtonEvent, TabletToolCapabilities, TabletToolDescriptor,
     TabletToolEvent, TabletToolProximityEvent, TabletToolTipEvent, TabletToolTipState, TabletToolType,
@@ -627,7 +633,9 @@ impl<B: InputBackend> SwitchToggleEvent<B> for UnusedEvent {
 /// given events.
 pub trait InputBackend: Sized {
     /// Type representing input devices
-    type Device: Device;
+    ///
+    /// Devices of all backends can be configured through [`DeviceConfig`].
+    type Device: Device + DeviceConfig;
     /// Type representing keyboard events
     type KeyboardKeyEvent: KeyboardKeyEvent<Self>;
     /// Type representing axis events on pointer devices
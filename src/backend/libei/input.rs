@@ -92,6 +92,9 @@ impl input::Device for request::Device {
     }
 }
 
+// Input of ei clients is emulated by the client itself, there is nothing to configure.
+impl input::DeviceConfig for request::Device {}
+
 impl<T: request::DeviceEvent + request::EventTime> input::Event<EiInput> for T {
     fn time(&self) -> u64 {
         request::EventTime::time(self)
@@ -56,6 +56,23 @@ impl LibinputInputBackend {
     }
 }
 
+/// Returns the name of the seat a libinput device is assigned to
+///
+/// Devices are grouped into physical seats by the `ID_SEAT` udev property and into logical seats
+/// within a physical seat by the `WL_SEAT` udev property. This returns the logical seat name if
+/// one is set, and the physical seat name otherwise. Useful together with
+/// [`SeatAssignment`](crate::input::SeatAssignment) to drive multiple seats in one compositor.
+///
+/// A libinput context assigned to a udev seat only contains devices of a single physical seat,
+/// use one context per seat returned by `backend::udev::seats` to drive multiple physical seats.
+pub fn seat_name(device: &libinput::Device) -> String {
+    let seat = device.seat();
+    match seat.logical_name() {
+        name if name == "default" => seat.physical_name().into_owned(),
+        name => name.into_owned(),
+    }
+}
+
 impl backend::Device for libinput::Device {
     fn id(&self) -> String {
         self.sysname().into()
@@ -85,6 +102,161 @@ impl backend::Device for libinput::Device {
     }
 }
 
+impl backend::DeviceConfig for libinput::Device {
+    fn tap_finger_count(&self) -> u32 {
+        self.config_tap_finger_count()
+    }
+
+    fn tap_enabled(&self) -> bool {
+        self.config_tap_enabled()
+    }
+
+    fn set_tap_enabled(&mut self, enabled: bool) -> Result<(), backend::DeviceConfigError> {
+        self.config_tap_set_enabled(enabled).map_err(Into::into)
+    }
+
+    fn accel_profiles(&self) -> Vec<backend::AccelProfile> {
+        self.config_accel_profiles()
+            .into_iter()
+            .filter_map(|profile| profile.try_into().ok())
+            .collect()
+    }
+
+    fn accel_profile(&self) -> Option<backend::AccelProfile> {
+        self.config_accel_profile()
+            .and_then(|profile| profile.try_into().ok())
+    }
+
+    fn set_accel_profile(
+        &mut self,
+        profile: backend::AccelProfile,
+    ) -> Result<(), backend::DeviceConfigError> {
+        self.config_accel_set_profile(profile.into()).map_err(Into::into)
+    }
+
+    fn accel_speed(&self) -> Option<f64> {
+        self.config_accel_is_available()
+            .then(|| self.config_accel_speed())
+    }
+
+    fn set_accel_speed(&mut self, speed: f64) -> Result<(), backend::DeviceConfigError> {
+        self.config_accel_set_speed(speed).map_err(Into::into)
+    }
+
+    fn has_natural_scroll(&self) -> bool {
+        self.config_scroll_has_natural_scroll()
+    }
+
+    fn natural_scroll(&self) -> bool {
+        self.config_scroll_natural_scroll_enabled()
+    }
+
+    fn set_natural_scroll(&mut self, enabled: bool) -> Result<(), backend::DeviceConfigError> {
+        self.config_scroll_set_natural_scroll_enabled(enabled)
+            .map_err(Into::into)
+    }
+
+    fn has_left_handed(&self) -> bool {
+        self.config_left_handed_is_available()
+    }
+
+    fn left_handed(&self) -> bool {
+        self.config_left_handed()
+    }
+
+    fn set_left_handed(&mut self, enabled: bool) -> Result<(), backend::DeviceConfigError> {
+        self.config_left_handed_set(enabled).map_err(Into::into)
+    }
+
+    fn scroll_methods(&self) -> Vec<backend::ScrollMethod> {
+        self.config_scroll_methods()
+            .into_iter()
+            .filter_map(|method| method.try_into().ok())
+            .collect()
+    }
+
+    fn scroll_method(&self) -> Option<backend::ScrollMethod> {
+        self.config_scroll_method()
+            .and_then(|method| method.try_into().ok())
+    }
+
+    fn set_scroll_method(&mut self, method: backend::ScrollMethod) -> Result<(), backend::DeviceConfigError> {
+        self.config_scroll_set_method(method.into()).map_err(Into::into)
+    }
+
+    fn has_calibration_matrix(&self) -> bool {
+        self.config_calibration_has_matrix()
+    }
+
+    fn calibration_matrix(&self) -> Option<[f32; 6]> {
+        self.config_calibration_matrix()
+    }
+
+    fn set_calibration_matrix(&mut self, matrix: [f32; 6]) -> Result<(), backend::DeviceConfigError> {
+        self.config_calibration_set_matrix(matrix).map_err(Into::into)
+    }
+}
+
+impl From<libinput::DeviceConfigError> for backend::DeviceConfigError {
+    #[inline]
+    fn from(err: libinput::DeviceConfigError) -> Self {
+        match err {
+            libinput::DeviceConfigError::Unsupported => backend::DeviceConfigError::Unsupported,
+            libinput::DeviceConfigError::Invalid => backend::DeviceConfigError::Invalid,
+        }
+    }
+}
+
+impl TryFrom<libinput::AccelProfile> for backend::AccelProfile {
+    type Error = backend::DeviceConfigError;
+
+    #[inline]
+    fn try_from(profile: libinput::AccelProfile) -> Result<Self, Self::Error> {
+        match profile {
+            libinput::AccelProfile::Flat => Ok(backend::AccelProfile::Flat),
+            libinput::AccelProfile::Adaptive => Ok(backend::AccelProfile::Adaptive),
+            _ => Err(backend::DeviceConfigError::Unsupported),
+        }
+    }
+}
+
+impl From<backend::AccelProfile> for libinput::AccelProfile {
+    #[inline]
+    fn from(profile: backend::AccelProfile) -> Self {
+        match profile {
+            backend::AccelProfile::Flat => libinput::AccelProfile::Flat,
+            backend::AccelProfile::Adaptive => libinput::AccelProfile::Adaptive,
+        }
+    }
+}
+
+impl TryFrom<libinput::ScrollMethod> for backend::ScrollMethod {
+    type Error = backend::DeviceConfigError;
+
+    #[inline]
+    fn try_from(method: libinput::ScrollMethod) -> Result<Self, Self::Error> {
+        match method {
+            libinput::ScrollMethod::NoScroll => Ok(backend::ScrollMethod::NoScroll),
+            libinput::ScrollMethod::TwoFinger => Ok(backend::ScrollMethod::TwoFinger),
+            libinput::ScrollMethod::Edge => Ok(backend::ScrollMethod::Edge),
+            libinput::ScrollMethod::OnButtonDown => Ok(backend::ScrollMethod::OnButtonDown),
+            _ => Err(backend::DeviceConfigError::Unsupported),
+        }
+    }
+}
+
+impl From<backend::ScrollMethod> for libinput::ScrollMethod {
+    #[inline]
+    fn from(method: backend::ScrollMethod) -> Self {
+        match method {
+            backend::ScrollMethod::NoScroll => libinput::ScrollMethod::NoScroll,
+            backend::ScrollMethod::TwoFinger => libinput::ScrollMethod::TwoFinger,
+            backend::ScrollMethod::Edge => libinput::ScrollMethod::Edge,
+            backend::ScrollMethod::OnButtonDown => libinput::ScrollMethod::OnButtonDown,
+        }
+    }
+}
+
 impl From<backend::DeviceCapability> for libinput::DeviceCapability {
     #[inline]
     fn from(other: backend::DeviceCapability) -> libinput::DeviceCapability {
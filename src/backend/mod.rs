@@ -84,6 +84,7 @@
 pub mod allocator;
 pub mod input;
 pub mod renderer;
+pub mod replay;
 
 #[cfg(feature = "backend_drm")]
 pub mod drm;
@@ -106,7 +106,9 @@ use tracing::{info_span, instrument, trace, warn};
 use crate::{
     backend::renderer::{Frame, element::RenderElementPresentationState},
     output::{Output, OutputModeSource, OutputNoMode},
-    utils::{Buffer as BufferCoords, Physical, Rectangle, Scale, Size, Transform, user_data::UserDataMap},
+    utils::{
+        Buffer as BufferCoords, Physical, Point, Rectangle, Scale, Size, Transform, user_data::UserDataMap,
+    },
 };
 
 use super::{
@@ -703,8 +705,14 @@ impl OutputDamageTracker {
                 self.opaque_regions_index[z_index].start
             };
             let element_geometry = element.geometry(output_scale);
+            // include the surroundings the effect samples from
+            let margin = element.framebuffer_effect_margin(output_scale).max(0);
+            let sample_area = Rectangle::new(
+                element_geometry.loc - Point::from((margin, margin)),
+                element_geometry.size + Size::from((margin * 2, margin * 2)),
+            );
             // SAFETY: render_elements only contains elements overlapping with the output geometry
-            let intersection = element_geometry.intersection(output_geo).unwrap();
+            let intersection = sample_area.intersection(output_geo).unwrap();
             let element_state = element_render_states.states.get_mut(element.id()).unwrap();
             let with_element_state = with_states
                 .as_ref()
@@ -0,0 +1,353 @@
+//! Element to blur the contents behind it
+//!
+//! [`BlurElement`] is a framebuffer effect (see [`Element::is_framebuffer_effect`]).
+//! Whenever the contents behind the element change, they are captured into an offscreen buffer,
+//! blurred and composited back in place of the element. This can be used to implement blurred
+//! backgrounds of translucent windows, e.g. as requested through the
+//! [`background_effect`](crate::wayland::background_effect) protocol.
+//!
+//! The blur is implemented for the following renderers:
+//! - [`GlesRenderer`](crate::backend::renderer::gles::GlesRenderer) and
+//!   [`GlowRenderer`](crate::backend::renderer::glow::GlowRenderer) use a dual kawase blur
+//! - [`PixmanRenderer`](crate::backend::renderer::pixman::PixmanRenderer) approximates a gaussian blur
+//!   with multiple box blurs, which is considerably slower
+//!
+//! Blurring samples the contents around the blurred area. The [`OutputDamageTracker`] takes this into
+//! account and re-renders the surroundings of the element as well, whenever something behind it changed.
+//!
+//! # How to use it
+//!
+//! The element has to be kept around between frames, as its [`Id`] is used to find the
+//! previously captured contents.
+//!
+//! ```no_run
+//! use smithay::{
+//!     backend::renderer::element::blur::{BlurElement, BlurParameters},
+//!     utils::{Logical, Rectangle},
+//! };
+//!
+//! // Create the element once, e.g. when the client requests a blurred background
+//! let mut blur = BlurElement::new(Rectangle::from_size((400, 300).into()), BlurParameters::default(), 1.0);
+//!
+//! # let window_geometry: Rectangle<i32, Logical> = Rectangle::default();
+//! // Update the blurred area every frame
+//! blur.resize(window_geometry, None);
+//!
+//! // and render a clone of it right below the elements of the window
+//! let element = blur.clone();
+//! ```
+//!
+//! [`OutputDamageTracker`]: crate::backend::renderer::damage::OutputDamageTracker
+
+use crate::{
+    backend::renderer::utils::CommitCounter,
+    utils::{Buffer, Logical, Physical, Rectangle, Scale, Transform},
+};
+
+use super::{Element, Id, Kind};
+
+/// Maximum number of passes of a [`BlurElement`]
+pub const MAX_BLUR_PASSES: u32 = 6;
+
+/// Parameters controlling the strength of a [`BlurElement`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlurParameters {
+    /// Number of passes
+    ///
+    /// Every pass halves the resolution of the blurred contents, which increases the strength
+    /// of the blur considerably. Clamped to `1..=`[`MAX_BLUR_PASSES`].
+    pub passes: u32,
+    /// Distance of the samples taken in each pass in physical pixels
+    pub offset: f32,
+}
+
+impl Default for BlurParameters {
+    fn default() -> Self {
+        Self {
+            passes: 2,
+            offset: 3.0,
+        }
+    }
+}
+
+impl BlurParameters {
+    /// Returns the effective number of passes
+    pub fn passes(&self) -> u32 {
+        self.passes.clamp(1, MAX_BLUR_PASSES)
+    }
+
+    /// Returns how far the blur samples beyond the blurred area in physical pixels
+    pub fn radius(&self) -> i32 {
+        (self.offset.max(0.0) * (1u32 << (self.passes() + 1)) as f32).ceil() as i32
+    }
+}
+
+/// Render element blurring the contents behind it
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct BlurElement {
+    id: Id,
+    commit_counter: CommitCounter,
+    area: Rectangle<i32, Logical>,
+    region: Option<Vec<Rectangle<i32, Logical>>>,
+    parameters: BlurParameters,
+    alpha: f32,
+}
+
+impl BlurElement {
+    /// Create a new [`BlurElement`] blurring the given area
+    pub fn new(area: Rectangle<i32, Logical>, parameters: BlurParameters, alpha: f32) -> Self {
+        BlurElement {
+            id: Id::new(),
+            commit_counter: CommitCounter::default(),
+            area,
+            region: None,
+            parameters,
+            alpha,
+        }
+    }
+
+    /// Resize the blurred area
+    ///
+    /// The optional region is relative to the area and limits the blur to the contained
+    /// rectangles, e.g. to the blur region of a surface.
+    pub fn resize(&mut self, area: Rectangle<i32, Logical>, region: Option<Vec<Rectangle<i32, Logical>>>) {
+        if self.area != area || self.region != region {
+            self.area = area;
+            self.region = region;
+            self.commit_counter.increment();
+        }
+    }
+
+    /// Update the parameters of the blur
+    pub fn set_parameters(&mut self, parameters: BlurParameters) {
+        if self.parameters != parameters {
+            self.parameters = parameters;
+            self.commit_counter.increment();
+        }
+    }
+
+    /// Returns the current parameters of the blur
+    pub fn parameters(&self) -> BlurParameters {
+        self.parameters
+    }
+
+    /// Set the alpha value the blurred contents are drawn with
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.alpha = alpha;
+    }
+
+    /// Returns the area to capture for an element drawn at `dst`
+    #[cfg_attr(
+        not(any(feature = "renderer_gl", feature = "renderer_pixman")),
+        allow(dead_code)
+    )]
+    pub(crate) fn sample_area(&self, dst: Rectangle<i32, Physical>) -> Rectangle<i32, Physical> {
+        let radius = self.parameters.radius();
+        Rectangle::new(
+            (dst.loc.x - radius, dst.loc.y - radius).into(),
+            (dst.size.w + radius * 2, dst.size.h + radius * 2).into(),
+        )
+    }
+
+    /// Maps the part of `dst` covered by the captured `area` into the captured buffer
+    ///
+    /// Returns the src in the buffer, the clipped destination and the damage relative to it.
+    /// The captured buffer has to be stored with the `transform` of the frame.
+    #[cfg_attr(
+        not(any(feature = "renderer_gl", feature = "renderer_pixman")),
+        allow(dead_code)
+    )]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn captured_src_dst(
+        &self,
+        area: Rectangle<i32, Physical>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        transform: Transform,
+    ) -> Option<(
+        Rectangle<f64, Buffer>,
+        Rectangle<i32, Physical>,
+        Vec<Rectangle<i32, Physical>>,
+    )> {
+        let draw_dst = dst.intersection(area)?;
+
+        let relative = Rectangle::<i32, Physical>::new(draw_dst.loc - area.loc, draw_dst.size);
+        let src = transform.transform_rect_in(relative, &area.size);
+        let src =
+            Rectangle::<i32, Buffer>::new((src.loc.x, src.loc.y).into(), (src.size.w, src.size.h).into());
+
+        let offset = dst.loc - draw_dst.loc;
+        let clip = Rectangle::from_size(draw_dst.size);
+        let region = self.region.as_ref().map(|region| {
+            let scale: Scale<f64> = dst.size.to_f64() / self.area.size.to_f64();
+            region
+                .iter()
+                .map(|rect| {
+                    let mut rect = rect.to_f64().to_physical(scale).to_i32_round();
+                    rect.loc += offset;
+                    rect
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let damage = damage
+            .iter()
+            .filter_map(|rect| {
+                let mut rect = *rect;
+                rect.loc += offset;
+                rect.intersection(clip)
+            })
+            .flat_map(|rect| match region.as_ref() {
+                Some(region) => region.iter().filter_map(|r| r.intersection(rect)).collect(),
+                None => vec![rect],
+            })
+            .collect::<Vec<_>>();
+
+        Some((src.to_f64(), draw_dst, damage))
+    }
+}
+
+impl Element for BlurElement {
+    fn id(&self) -> &Id {
+        &self.id
+    }
+
+    fn current_commit(&self) -> CommitCounter {
+        self.commit_counter
+    }
+
+    fn src(&self) -> Rectangle<f64, Buffer> {
+        Rectangle::from_size(self.area.size.to_f64().to_buffer(1.0, Transform::Normal))
+    }
+
+    fn geometry(&self, scale: Scale<f64>) -> Rectangle<i32, Physical> {
+        self.area.to_physical_precise_round(scale)
+    }
+
+    fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    fn kind(&self) -> Kind {
+        Kind::Unspecified
+    }
+
+    fn is_framebuffer_effect(&self) -> bool {
+        true
+    }
+
+    fn framebuffer_effect_margin(&self, _scale: Scale<f64>) -> i32 {
+        self.parameters.radius()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(area: Rectangle<i32, Logical>, region: Option<Vec<Rectangle<i32, Logical>>>) -> BlurElement {
+        let mut element = BlurElement::new(area, BlurParameters::default(), 1.0);
+        element.resize(area, region);
+        element
+    }
+
+    #[test]
+    fn radius() {
+        let radius = |passes, offset| BlurParameters { passes, offset }.radius();
+        assert_eq!(BlurParameters::default().radius(), 24);
+        assert_eq!(radius(1, 1.0), 4);
+        assert_eq!(radius(1, 1.1), 5);
+        assert_eq!(radius(MAX_BLUR_PASSES, 1.0), 128);
+        // passes are clamped
+        assert_eq!(radius(0, 1.0), radius(1, 1.0));
+        assert_eq!(radius(100, 1.0), radius(MAX_BLUR_PASSES, 1.0));
+        // negative offsets don't sample anything
+        assert_eq!(radius(2, -3.0), 0);
+        assert_eq!(radius(2, 0.0), 0);
+    }
+
+    #[test]
+    fn sample_area() {
+        let element = element(Rectangle::from_size((100, 100).into()), None);
+        assert_eq!(
+            element.sample_area(Rectangle::new((10, 20).into(), (100, 100).into())),
+            Rectangle::new((-14, -4).into(), (148, 148).into())
+        );
+    }
+
+    #[test]
+    fn captured_src_dst_inside() {
+        let element = element(Rectangle::from_size((100, 100).into()), None);
+        let area = Rectangle::new((0, 0).into(), (200, 200).into());
+        let dst = Rectangle::new((50, 50).into(), (100, 100).into());
+        let damage = [Rectangle::new((0, 0).into(), (10, 10).into())];
+
+        let (src, draw_dst, damage) = element
+            .captured_src_dst(area, dst, &damage, Transform::Normal)
+            .unwrap();
+        assert_eq!(src, Rectangle::new((50., 50.).into(), (100., 100.).into()));
+        assert_eq!(draw_dst, dst);
+        assert_eq!(damage, vec![Rectangle::new((0, 0).into(), (10, 10).into())]);
+    }
+
+    #[test]
+    fn captured_src_dst_clipped() {
+        let element = element(Rectangle::from_size((100, 100).into()), None);
+        // the captured area only covers the bottom right quarter of the element
+        let area = Rectangle::new((50, 50).into(), (100, 100).into());
+        let dst = Rectangle::new((0, 0).into(), (100, 100).into());
+        let damage = [
+            Rectangle::new((0, 0).into(), (100, 100).into()),
+            Rectangle::new((0, 0).into(), (10, 10).into()),
+        ];
+
+        let (src, draw_dst, damage) = element
+            .captured_src_dst(area, dst, &damage, Transform::Normal)
+            .unwrap();
+        assert_eq!(src, Rectangle::new((0., 0.).into(), (50., 50.).into()));
+        assert_eq!(draw_dst, Rectangle::new((50, 50).into(), (50, 50).into()));
+        // damage is made relative to the clipped destination
+        assert_eq!(damage, vec![Rectangle::new((0, 0).into(), (50, 50).into())]);
+
+        let outside = Rectangle::new((200, 200).into(), (100, 100).into());
+        assert!(
+            element
+                .captured_src_dst(outside, dst, &damage, Transform::Normal)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn captured_src_dst_transformed() {
+        let element = element(Rectangle::from_size((100, 100).into()), None);
+        let area = Rectangle::new((0, 0).into(), (200, 100).into());
+        let dst = Rectangle::new((0, 0).into(), (50, 100).into());
+
+        let (src, _, _) = element.captured_src_dst(area, dst, &[], Transform::_90).unwrap();
+        // the captured buffer is stored rotated
+        assert_eq!(src.size, (100., 50.).into());
+        let (src, _, _) = element
+            .captured_src_dst(area, dst, &[], Transform::Normal)
+            .unwrap();
+        assert_eq!(src, Rectangle::new((0., 0.).into(), (50., 100.).into()));
+    }
+
+    #[test]
+    fn captured_src_dst_region() {
+        // the element is drawn at twice its logical size
+        let element = element(
+            Rectangle::from_size((100, 100).into()),
+            Some(vec![Rectangle::new((0, 0).into(), (10, 10).into())]),
+        );
+        let area = Rectangle::new((0, 0).into(), (400, 400).into());
+        let dst = Rectangle::new((100, 100).into(), (200, 200).into());
+        let damage = [Rectangle::from_size((200, 200).into())];
+
+        let (_, _, damage) = element
+            .captured_src_dst(area, dst, &damage, Transform::Normal)
+            .unwrap();
+        assert_eq!(damage, vec![Rectangle::from_size((20, 20).into())]);
+    }
+}
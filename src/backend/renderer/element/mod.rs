@@ -12,6 +12,7 @@
 //! - [`texture`] - Texture based render element
 //! - [`surface`] - Wayland surface render element
 //! - [`solid`] - Solid color render element
+//! - [`blur`] - Render element blurring the contents behind it
 //!
 //! The [`render_elements!`] macro provides an easy way to aggregate multiple different [RenderElement]s
 //! into a single enum.
@@ -39,6 +40,7 @@ use super::{
     utils::{CommitCounter, DamageSet, OpaqueRegions},
 };
 
+pub mod blur;
 pub mod memory;
 pub mod solid;
 #[cfg(feature = "wayland_frontend")]
@@ -566,6 +568,19 @@ pub trait Element {
     fn is_framebuffer_effect(&self) -> bool {
         false
     }
+    /// Returns how far a framebuffer effect samples beyond its geometry.
+    ///
+    /// Effects like blurs read the contents surrounding the area they are drawn in.
+    /// The margin is added to all sides of the geometry when checking whether the contents behind
+    /// the element have changed and when damaging the area to be captured.
+    /// `RenderElement::capture_framebuffer` is still called with the geometry of the element,
+    /// so implementations need to add the margin themselves.
+    ///
+    /// Only used if [`Element::is_framebuffer_effect`] returns `true`.
+    fn framebuffer_effect_margin(&self, scale: Scale<f64>) -> i32 {
+        let _ = scale;
+        0
+    }
 }
 
 /// A single render element
@@ -670,6 +685,10 @@ where
     fn is_framebuffer_effect(&self) -> bool {
         (*self).is_framebuffer_effect()
     }
+
+    fn framebuffer_effect_margin(&self, scale: Scale<f64>) -> i32 {
+        (*self).framebuffer_effect_margin(scale)
+    }
 }
 
 impl<R, E> RenderElement<R> for &E
@@ -787,6 +806,10 @@ impl<E: Element> Element for NamespacedElement<E> {
     fn is_framebuffer_effect(&self) -> bool {
         self.inner.is_framebuffer_effect()
     }
+
+    fn framebuffer_effect_margin(&self, scale: Scale<f64>) -> i32 {
+        self.inner.framebuffer_effect_margin(scale)
+    }
 }
 
 impl<R: Renderer, E: Element + RenderElement<R>> RenderElement<R> for NamespacedElement<E> {
@@ -1111,6 +1134,19 @@ macro_rules! render_elements_internal {
                 Self::_GenericCatcher(_) => unreachable!(),
             }
         }
+
+        fn framebuffer_effect_margin(&self, scale: $crate::utils::Scale<f64>) -> i32 {
+            match self {
+                $(
+                    #[allow(unused_doc_comments)]
+                    $(
+                        #[$meta]
+                    )*
+                    Self::$body(x) => $crate::render_elements_internal!(@call framebuffer_effect_margin; x, scale)
+                ),*,
+                Self::_GenericCatcher(_) => unreachable!(),
+            }
+        }
     };
     (@draw <$renderer:ty>; $($(#[$meta:meta])* $body:ident=$field:ty $(as <$other_renderer:ty>)?),* $(,)?) => {
         fn draw(
@@ -1800,6 +1836,10 @@ where
     fn is_framebuffer_effect(&self) -> bool {
         self.0.is_framebuffer_effect()
     }
+
+    fn framebuffer_effect_margin(&self, scale: Scale<f64>) -> i32 {
+        self.0.framebuffer_effect_margin(scale)
+    }
 }
 
 impl<R, C> RenderElement<R> for Wrap<C>
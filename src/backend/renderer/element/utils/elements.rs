@@ -91,6 +91,11 @@ impl<E: Element> Element for RescaleRenderElement<E> {
     fn is_framebuffer_effect(&self) -> bool {
         self.element.is_framebuffer_effect()
     }
+
+    fn framebuffer_effect_margin(&self, scale: Scale<f64>) -> i32 {
+        let margin = self.element.framebuffer_effect_margin(scale) as f64;
+        (margin * self.scale.x.max(self.scale.y)).ceil() as i32
+    }
 }
 
 impl<R: Renderer, E: RenderElement<R>> RenderElement<R> for RescaleRenderElement<E> {
@@ -290,6 +295,10 @@ impl<E: Element> Element for CropRenderElement<E> {
     fn is_framebuffer_effect(&self) -> bool {
         self.element.is_framebuffer_effect()
     }
+
+    fn framebuffer_effect_margin(&self, scale: Scale<f64>) -> i32 {
+        self.element.framebuffer_effect_margin(scale)
+    }
 }
 
 impl<R: Renderer, E: RenderElement<R>> RenderElement<R> for CropRenderElement<E> {
@@ -407,6 +416,10 @@ impl<E: Element> Element for RelocateRenderElement<E> {
     fn is_framebuffer_effect(&self) -> bool {
         self.element.is_framebuffer_effect()
     }
+
+    fn framebuffer_effect_margin(&self, scale: Scale<f64>) -> i32 {
+        self.element.framebuffer_effect_margin(scale)
+    }
 }
 
 impl<R: Renderer, E: RenderElement<R>> RenderElement<R> for RelocateRenderElement<E> {
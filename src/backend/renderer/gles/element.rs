@@ -1,14 +1,26 @@
 //! RenderElements specific to using a `GlesRenderer`
 
+use std::{borrow::BorrowMut, cell::RefCell};
+
 use crate::{
-    backend::renderer::{
-        element::{Element, Id, Kind, RenderElement, UnderlyingStorage, texture::TextureRenderElement},
-        utils::{CommitCounter, DamageSet, OpaqueRegions},
+    backend::{
+        allocator::Fourcc,
+        renderer::{
+            Bind, Color32F, Frame, FrameContext, Offscreen, Renderer, Texture,
+            element::{
+                Element, Id, Kind, RenderElement, UnderlyingStorage, blur::BlurElement,
+                texture::TextureRenderElement,
+            },
+            utils::{CommitCounter, DamageSet, OpaqueRegions},
+        },
     },
-    utils::{Buffer, Logical, Physical, Point, Rectangle, Scale, Transform, user_data::UserDataMap},
+    utils::{Buffer, Logical, Physical, Point, Rectangle, Scale, Size, Transform, user_data::UserDataMap},
 };
 
-use super::{GlesError, GlesFrame, GlesPixelProgram, GlesRenderer, GlesTexProgram, GlesTexture, Uniform};
+use super::{
+    GlesError, GlesFrame, GlesPixelProgram, GlesRenderer, GlesTexProgram, GlesTexture, Uniform, UniformName,
+    UniformType, ffi, shaders,
+};
 
 /// Render element for drawing with a gles2 pixel shader
 #[derive(Debug, Clone)]
@@ -225,3 +237,1098 @@ impl RenderElement<GlesRenderer> for TextureShaderElement {
         )
     }
 }
+
+// Programs used by `BlurElement`, stored in the user data of the `EGLContext`
+#[derive(Debug, Clone)]
+struct BlurPrograms {
+    down: GlesTexProgram,
+    up: GlesTexProgram,
+}
+
+impl BlurPrograms {
+    fn get(renderer: &mut GlesRenderer) -> Result<BlurPrograms, GlesError> {
+        if let Some(programs) = renderer.egl_context().user_data().get::<BlurPrograms>() {
+            return Ok(programs.clone());
+        }
+
+        let uniforms = [
+            UniformName::new("half_pixel", UniformType::_2f),
+            UniformName::new("offset", UniformType::_1f),
+        ];
+        let programs = BlurPrograms {
+            down: renderer.compile_custom_texture_shader(shaders::FRAGMENT_SHADER_BLUR_DOWN, &uniforms)?,
+            up: renderer.compile_custom_texture_shader(shaders::FRAGMENT_SHADER_BLUR_UP, &uniforms)?,
+        };
+        renderer
+            .egl_context()
+            .user_data()
+            .insert_if_missing(|| programs.clone());
+        Ok(programs)
+    }
+}
+
+// Captured contents of a `BlurElement`, stored in its effects cache
+#[derive(Debug, Default)]
+struct BlurState {
+    area: Rectangle<i32, Physical>,
+    // the captured contents followed by one texture per pass, each half the size of the previous one
+    textures: Vec<GlesTexture>,
+}
+
+impl BlurState {
+    fn ensure_textures(
+        &mut self,
+        renderer: &mut GlesRenderer,
+        size: Size<i32, Buffer>,
+        passes: u32,
+    ) -> Result<(), GlesError> {
+        let sizes = (0..=passes)
+            .map(|pass| Size::<i32, Buffer>::from(((size.w >> pass).max(1), (size.h >> pass).max(1))));
+        if self
+            .textures
+            .iter()
+            .map(|texture| texture.size())
+            .eq(sizes.clone())
+        {
+            return Ok(());
+        }
+
+        self.textures = sizes
+            .map(|size| {
+                let texture: GlesTexture = renderer.create_buffer(Fourcc::Abgr8888, size)?;
+                // samples outside of the captured area should not wrap around
+                renderer.with_context(|gl| unsafe {
+                    gl.BindTexture(ffi::TEXTURE_2D, texture.tex_id());
+                    gl.TexParameteri(ffi::TEXTURE_2D, ffi::TEXTURE_WRAP_S, ffi::CLAMP_TO_EDGE as i32);
+                    gl.TexParameteri(ffi::TEXTURE_2D, ffi::TEXTURE_WRAP_T, ffi::CLAMP_TO_EDGE as i32);
+                    gl.BindTexture(ffi::TEXTURE_2D, 0);
+                })?;
+                Ok(texture)
+            })
+            .collect::<Result<Vec<_>, GlesError>>()?;
+        Ok(())
+    }
+}
+
+fn blur_pass(
+    renderer: &mut GlesRenderer,
+    src: &GlesTexture,
+    dst: &mut GlesTexture,
+    program: &GlesTexProgram,
+    offset: f32,
+) -> Result<(), GlesError> {
+    let src_size = src.size();
+    let half_pixel = [0.5 / src_size.w as f32, 0.5 / src_size.h as f32];
+    let dst_size = dst.size();
+    let dst_rect = Rectangle::<i32, Physical>::from_size((dst_size.w, dst_size.h).into());
+
+    let mut target = renderer.bind(dst)?;
+    let mut frame = renderer.render(&mut target, dst_rect.size, Transform::Normal)?;
+    frame.clear(Color32F::TRANSPARENT, &[dst_rect])?;
+    frame.render_texture_from_to(
+        src,
+        Rectangle::from_size(src_size.to_f64()),
+        dst_rect,
+        &[dst_rect],
+        &[],
+        Transform::Normal,
+        1.0,
+        Some(program),
+        &[
+            Uniform::new("half_pixel", half_pixel),
+            Uniform::new("offset", offset),
+        ],
+    )?;
+    // all passes are executed in order on the same context, no need to wait
+    let _ = frame.finish()?;
+    Ok(())
+}
+
+impl RenderElement<GlesRenderer> for BlurElement {
+    #[profiling::function]
+    fn draw(
+        &self,
+        frame: &mut GlesFrame<'_, '_>,
+        _src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        _opaque_regions: &[Rectangle<i32, Physical>],
+        cache: Option<&UserDataMap>,
+    ) -> Result<(), GlesError> {
+        let Some(state) = cache.and_then(|cache| cache.get::<RefCell<BlurState>>()) else {
+            return Ok(());
+        };
+        let state = state.borrow();
+        let Some(texture) = state.textures.first() else {
+            return Ok(());
+        };
+        let Some((src, dst, damage)) = self.captured_src_dst(state.area, dst, damage, frame.transform) else {
+            return Ok(());
+        };
+
+        frame.render_texture_from_to(
+            texture,
+            src,
+            dst,
+            &damage,
+            &[],
+            frame.transform,
+            self.alpha(),
+            None,
+            &[],
+        )
+    }
+
+    #[inline]
+    fn underlying_storage(&self, _renderer: &mut GlesRenderer) -> Option<UnderlyingStorage<'_>> {
+        None
+    }
+
+    #[profiling::function]
+    fn capture_framebuffer(
+        &self,
+        frame: &mut GlesFrame<'_, '_>,
+        _src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        cache: &UserDataMap,
+    ) -> Result<(), GlesError> {
+        let mut state = cache
+            .get_or_insert(|| RefCell::new(BlurState::default()))
+            .borrow_mut();
+        let Some(area) = self
+            .sample_area(dst)
+            .intersection(Rectangle::from_size(frame.size))
+        else {
+            state.textures.clear();
+            return Ok(());
+        };
+        let fb_area = frame.transform.transform_rect_in(area, &frame.size);
+        let parameters = self.parameters();
+        let passes = parameters.passes();
+
+        let programs = {
+            let mut guard = frame.renderer();
+            let renderer = guard.as_mut();
+            state.ensure_textures(renderer, (fb_area.size.w, fb_area.size.h).into(), passes)?;
+            BlurPrograms::get(renderer)?
+        };
+
+        // the frame's target is bound again after the guard was dropped
+        frame.with_context(|gl| unsafe {
+            gl.BindTexture(ffi::TEXTURE_2D, state.textures[0].tex_id());
+            gl.CopyTexSubImage2D(
+                ffi::TEXTURE_2D,
+                0,
+                0,
+                0,
+                fb_area.loc.x,
+                fb_area.loc.y,
+                fb_area.size.w,
+                fb_area.size.h,
+            );
+            gl.BindTexture(ffi::TEXTURE_2D, 0);
+        })?;
+
+        let mut guard = frame.renderer();
+        let renderer = guard.as_mut();
+        for pass in 0..passes as usize {
+            let (src, dst) = state.textures.split_at_mut(pass + 1);
+            blur_pass(
+                renderer,
+                &src[pass],
+                &mut dst[0],
+                &programs.down,
+                parameters.offset,
+            )?;
+        }
+        for pass in (0..passes as usize).rev() {
+            let (dst, src) = state.textures.split_at_mut(pass + 1);
+            blur_pass(renderer, &src[0], &mut dst[pass], &programs.up, parameters.offset)?;
+        }
+        state.area = area;
+
+        Ok(())
+    }
+}
+
+/// Radii of the corners of a rounded rectangle
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CornerRadius {
+    /// Radius of the top-left corner
+    pub top_left: f32,
+    /// Radius of the top-right corner
+    pub top_right: f32,
+    /// Radius of the bottom-right corner
+    pub bottom_right: f32,
+    /// Radius of the bottom-left corner
+    pub bottom_left: f32,
+}
+
+impl From<f32> for CornerRadius {
+    #[inline]
+    fn from(radius: f32) -> Self {
+        CornerRadius::uniform(radius)
+    }
+}
+
+impl CornerRadius {
+    /// Create a [`CornerRadius`] using the same radius for all corners
+    pub const fn uniform(radius: f32) -> Self {
+        CornerRadius {
+            top_left: radius,
+            top_right: radius,
+            bottom_right: radius,
+            bottom_left: radius,
+        }
+    }
+
+    /// Returns the radii multiplied by `scale`, e.g. to convert them into physical coordinates
+    pub fn scaled_by(self, scale: f32) -> Self {
+        CornerRadius {
+            top_left: self.top_left * scale,
+            top_right: self.top_right * scale,
+            bottom_right: self.bottom_right * scale,
+            bottom_left: self.bottom_left * scale,
+        }
+    }
+
+    /// Returns the radii of the rectangle grown by `width` on all sides
+    ///
+    /// Square corners stay square, negative values shrink the radii.
+    pub fn expanded_by(self, width: f32) -> Self {
+        let expand = |radius: f32| {
+            if radius > 0.0 {
+                (radius + width).max(0.0)
+            } else {
+                0.0
+            }
+        };
+        CornerRadius {
+            top_left: expand(self.top_left),
+            top_right: expand(self.top_right),
+            bottom_right: expand(self.bottom_right),
+            bottom_left: expand(self.bottom_left),
+        }
+    }
+
+    /// Returns `true` if all corners are square
+    pub fn is_zero(&self) -> bool {
+        self.to_array().iter().all(|radius| *radius <= 0.0)
+    }
+
+    fn to_array(self) -> [f32; 4] {
+        [self.top_left, self.top_right, self.bottom_right, self.bottom_left]
+    }
+
+    // Scales down all radii, so that adjacent corners do not overlap
+    fn fit_to<Kind>(self, size: Size<f64, Kind>) -> Self {
+        let [top_left, top_right, bottom_right, bottom_left] = self.to_array().map(|radius| radius.max(0.0));
+        let factor = [
+            (size.w as f32, top_left + top_right),
+            (size.w as f32, bottom_left + bottom_right),
+            (size.h as f32, top_left + bottom_left),
+            (size.h as f32, top_right + bottom_right),
+        ]
+        .into_iter()
+        .filter(|(_, sum)| *sum > 0.0)
+        .fold(1.0f32, |factor, (length, sum)| factor.min(length.max(0.0) / sum));
+
+        CornerRadius {
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+        }
+        .scaled_by(factor)
+    }
+
+    // Moves the radii to the corners they end up at after applying `transform`
+    fn transformed(self, transform: Transform) -> Self {
+        let unit = Size::<f64, Physical>::from((1.0, 1.0));
+        let mut result = CornerRadius::default();
+        for (corner, radius) in [
+            ((0.0, 0.0), self.top_left),
+            ((1.0, 0.0), self.top_right),
+            ((1.0, 1.0), self.bottom_right),
+            ((0.0, 1.0), self.bottom_left),
+        ] {
+            let corner = transform.transform_point_in(Point::from(corner), &unit);
+            match (corner.x < 0.5, corner.y < 0.5) {
+                (true, true) => result.top_left = radius,
+                (false, true) => result.top_right = radius,
+                (false, false) => result.bottom_right = radius,
+                (true, false) => result.bottom_left = radius,
+            }
+        }
+        result
+    }
+
+    // Squares covering the rounded corners of `rect`
+    fn corner_rects(self, rect: Rectangle<i32, Physical>) -> [Rectangle<i32, Physical>; 4] {
+        let square = |x: i32, y: i32, radius: f32, right: bool, bottom: bool| {
+            let radius = radius.ceil() as i32;
+            let x = if right { x + rect.size.w - radius } else { x };
+            let y = if bottom { y + rect.size.h - radius } else { y };
+            Rectangle::new((x, y).into(), (radius, radius).into())
+        };
+        let (x, y) = (rect.loc.x, rect.loc.y);
+        [
+            square(x, y, self.top_left, false, false),
+            square(x, y, self.top_right, true, false),
+            square(x, y, self.bottom_right, true, true),
+            square(x, y, self.bottom_left, false, true),
+        ]
+    }
+}
+
+// Program used by `RoundedCornerElement`, stored in the user data of the `EGLContext`
+#[derive(Debug, Clone)]
+struct RoundedCornerProgram(GlesTexProgram);
+
+impl RoundedCornerProgram {
+    fn get(frame: &mut GlesFrame<'_, '_>) -> Result<GlesTexProgram, GlesError> {
+        if let Some(program) = frame.egl_context().user_data().get::<RoundedCornerProgram>() {
+            return Ok(program.0.clone());
+        }
+
+        let mut guard = frame.renderer();
+        let renderer = guard.as_mut();
+        let program = renderer.compile_custom_texture_shader(
+            shaders::FRAGMENT_SHADER_ROUNDED_CORNERS,
+            &[
+                UniformName::new("clip_rect", UniformType::_4f),
+                UniformName::new("corner_radius", UniformType::_4f),
+            ],
+        )?;
+        renderer
+            .egl_context()
+            .user_data()
+            .insert_if_missing(|| RoundedCornerProgram(program.clone()));
+        Ok(program)
+    }
+}
+
+// Program used by `RoundedCornerElement` for solid colors, stored in the user data of the `EGLContext`
+#[derive(Debug, Clone)]
+struct RoundedCornerSolidProgram(GlesPixelProgram);
+
+impl RoundedCornerSolidProgram {
+    fn get(frame: &mut GlesFrame<'_, '_>) -> Result<GlesPixelProgram, GlesError> {
+        if let Some(program) = frame.egl_context().user_data().get::<RoundedCornerSolidProgram>() {
+            return Ok(program.0.clone());
+        }
+
+        let mut guard = frame.renderer();
+        let renderer = guard.as_mut();
+        let program = renderer.compile_custom_pixel_shader(
+            shaders::FRAGMENT_SHADER_ROUNDED_CORNERS_SOLID,
+            &[
+                UniformName::new("color", UniformType::_4f),
+                UniformName::new("clip_rect", UniformType::_4f),
+                UniformName::new("corner_radius", UniformType::_4f),
+            ],
+        )?;
+        renderer
+            .egl_context()
+            .user_data()
+            .insert_if_missing(|| RoundedCornerSolidProgram(program.clone()));
+        Ok(program)
+    }
+}
+
+/// State of a [`RoundedCornerElement`] kept across frames
+///
+/// Changes of the clipping rectangle or the corner radii don't change the wrapped element.
+/// The state counts them, so the damage tracker can notice them, and therefore has to be
+/// passed to every [`RoundedCornerElement`] created for the same element.
+#[derive(Debug, Default)]
+pub struct RoundedCornerState {
+    // clipping rectangle and corner radii of the last element
+    clip: Option<(Rectangle<i32, Physical>, CornerRadius)>,
+    commit: CommitCounter,
+    // combined commit of the first element with the current clipping
+    changed_at: CommitCounter,
+}
+
+/// Render element clipping another element to a rounded rectangle
+///
+/// Textures and solid colors drawn by the element are clipped by shaders, which anti-alias the rounded
+/// corners. Elements drawing with custom shaders, e.g. a [`PixelShaderElement`], are not clipped.
+///
+/// The opaque regions of the element are reduced by the rounded corners and everything outside of the
+/// rectangle. If the element neither touches a rounded corner nor extends outside of the rectangle,
+/// it is drawn unmodified and can still be scanned out directly.
+#[derive(Debug)]
+pub struct RoundedCornerElement<E> {
+    element: E,
+    // clipping rectangle relative to the geometry of the element
+    clip_rect: Rectangle<i32, Physical>,
+    // size of the element the clipping rectangle is relative to
+    element_size: Size<i32, Physical>,
+    corner_radius: CornerRadius,
+    clipped: bool,
+    commit: CommitCounter,
+    changed_at: CommitCounter,
+}
+
+impl<E: Element> RoundedCornerElement<E> {
+    /// Create a rounded corner element clipping an existing element
+    ///
+    /// The geometry is expected to be relative to the same origin the element is relative to,
+    /// the corner radius is expected in physical coordinates. Radii too large for the geometry
+    /// are reduced.
+    ///
+    /// The scale is used to calculate the position of the element relative to the geometry
+    /// and should therefore equal the scale that was used to calculate the geometry.
+    ///
+    /// The `state` has to be kept across frames for the element, see [`RoundedCornerState`].
+    pub fn from_element(
+        element: E,
+        scale: impl Into<Scale<f64>>,
+        geometry: Rectangle<i32, Physical>,
+        corner_radius: impl Into<CornerRadius>,
+        state: &mut RoundedCornerState,
+    ) -> Self {
+        let element_geometry = element.geometry(scale.into());
+        let clip_rect = Rectangle::new(geometry.loc - element_geometry.loc, geometry.size);
+        let corner_radius = corner_radius.into().fit_to(geometry.size.to_f64());
+
+        let element_rect = Rectangle::from_size(element_geometry.size);
+        let clipped = !clip_rect.contains_rect(element_rect)
+            || corner_radius
+                .corner_rects(clip_rect)
+                .iter()
+                .any(|corner| corner.overlaps(element_rect));
+
+        if state.clip != Some((clip_rect, corner_radius)) {
+            state.clip = Some((clip_rect, corner_radius));
+            state.commit.increment();
+            state.changed_at = element.current_commit().wrapping_add(state.commit);
+        }
+
+        RoundedCornerElement {
+            element,
+            clip_rect,
+            element_size: element_geometry.size,
+            corner_radius,
+            clipped,
+            commit: state.commit,
+            changed_at: state.changed_at,
+        }
+    }
+
+    /// Returns a reference to the clipped element
+    pub fn element(&self) -> &E {
+        &self.element
+    }
+
+    /// Returns `true` if the element is actually clipped
+    ///
+    /// Unclipped elements are drawn unmodified.
+    pub fn is_clipped(&self) -> bool {
+        self.clipped
+    }
+
+    // Clipping rectangle and corner radii relative to the element drawn with the given size
+    fn clip(&self, size: Size<i32, Physical>) -> (Rectangle<f64, Physical>, CornerRadius) {
+        let clip_rect = self.clip_rect.to_f64();
+        if size == self.element_size || self.element_size.is_empty() {
+            return (clip_rect, self.corner_radius);
+        }
+
+        let scale = size.to_f64() / self.element_size.to_f64();
+        let clip_rect = Rectangle::new(
+            (clip_rect.loc.x * scale.x, clip_rect.loc.y * scale.y).into(),
+            (clip_rect.size.w * scale.x, clip_rect.size.h * scale.y).into(),
+        );
+        (
+            clip_rect,
+            self.corner_radius.scaled_by(scale.x.min(scale.y) as f32),
+        )
+    }
+
+    // Uniforms of the clipping shaders for an element drawn at `dst`
+    fn clip_uniforms(
+        &self,
+        frame: &GlesFrame<'_, '_>,
+        dst: Rectangle<i32, Physical>,
+    ) -> Vec<Uniform<'static>> {
+        let (mut clip_rect, corner_radius) = self.clip(dst.size);
+        clip_rect.loc += dst.loc.to_f64();
+        let clip_rect = frame.transform.transform_rect_in(clip_rect, &frame.size.to_f64());
+        let corner_radius = corner_radius.transformed(frame.transform);
+
+        vec![
+            Uniform::new(
+                "clip_rect",
+                [
+                    clip_rect.loc.x as f32,
+                    clip_rect.loc.y as f32,
+                    clip_rect.size.w as f32,
+                    clip_rect.size.h as f32,
+                ],
+            ),
+            Uniform::new("corner_radius", corner_radius.to_array()),
+        ]
+    }
+
+    pub(crate) fn draw_clipped<'frame, 'buffer, F>(
+        &self,
+        frame: &mut F,
+        dst: Rectangle<i32, Physical>,
+        draw: impl FnOnce(&mut F) -> Result<(), GlesError>,
+    ) -> Result<(), GlesError>
+    where
+        'buffer: 'frame,
+        F: BorrowMut<GlesFrame<'frame, 'buffer>>,
+    {
+        if !self.clipped {
+            return draw(frame);
+        }
+
+        let gles_frame = frame.borrow_mut();
+        let tex_program = RoundedCornerProgram::get(gles_frame)?;
+        let solid_program = RoundedCornerSolidProgram::get(gles_frame)?;
+        let uniforms = self.clip_uniforms(gles_frame, dst);
+        let previous_tex = gles_frame
+            .tex_program_override
+            .replace((tex_program, uniforms.clone()));
+        let previous_solid = gles_frame
+            .solid_program_override
+            .replace((solid_program, uniforms));
+
+        let result = draw(frame);
+
+        let gles_frame = frame.borrow_mut();
+        gles_frame.tex_program_override = previous_tex;
+        gles_frame.solid_program_override = previous_solid;
+        result
+    }
+}
+
+impl<E: Element> Element for RoundedCornerElement<E> {
+    fn id(&self) -> &Id {
+        self.element.id()
+    }
+
+    fn current_commit(&self) -> CommitCounter {
+        self.element.current_commit().wrapping_add(self.commit)
+    }
+
+    fn location(&self, scale: Scale<f64>) -> Point<i32, Physical> {
+        self.element.location(scale)
+    }
+
+    fn src(&self) -> Rectangle<f64, Buffer> {
+        self.element.src()
+    }
+
+    fn transform(&self) -> Transform {
+        self.element.transform()
+    }
+
+    fn geometry(&self, scale: Scale<f64>) -> Rectangle<i32, Physical> {
+        self.element.geometry(scale)
+    }
+
+    fn damage_since(&self, scale: Scale<f64>, commit: Option<CommitCounter>) -> DamageSet<i32, Physical> {
+        match commit {
+            // the clipping didn't change since, so the commit of the element is known
+            Some(commit) if commit >= self.changed_at => self
+                .element
+                .damage_since(scale, Some(commit.wrapping_sub(self.commit))),
+            _ => DamageSet::from_slice(&[Rectangle::from_size(self.geometry(scale).size)]),
+        }
+    }
+
+    fn opaque_regions(&self, scale: Scale<f64>) -> OpaqueRegions<i32, Physical> {
+        let opaque_regions = self.element.opaque_regions(scale);
+        if !self.clipped {
+            return opaque_regions;
+        }
+
+        let (clip_rect, corner_radius) = self.clip(self.element.geometry(scale).size);
+        let clip_rect = clip_rect.to_i32_down();
+        let opaque_regions = opaque_regions
+            .into_iter()
+            .filter_map(|rect| rect.intersection(clip_rect))
+            .collect::<Vec<_>>();
+        Rectangle::subtract_rects_many_in_place(opaque_regions, corner_radius.corner_rects(clip_rect))
+            .into_iter()
+            .collect()
+    }
+
+    fn alpha(&self) -> f32 {
+        self.element.alpha()
+    }
+
+    fn kind(&self) -> Kind {
+        self.element.kind()
+    }
+
+    fn is_framebuffer_effect(&self) -> bool {
+        self.element.is_framebuffer_effect()
+    }
+
+    fn framebuffer_effect_margin(&self, scale: Scale<f64>) -> i32 {
+        self.element.framebuffer_effect_margin(scale)
+    }
+}
+
+impl<E: RenderElement<GlesRenderer>> RenderElement<GlesRenderer> for RoundedCornerElement<E> {
+    #[profiling::function]
+    fn draw(
+        &self,
+        frame: &mut GlesFrame<'_, '_>,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        opaque_regions: &[Rectangle<i32, Physical>],
+        cache: Option<&UserDataMap>,
+    ) -> Result<(), GlesError> {
+        self.draw_clipped(frame, dst, |frame| {
+            self.element.draw(frame, src, dst, damage, opaque_regions, cache)
+        })
+    }
+
+    #[inline]
+    fn underlying_storage(&self, renderer: &mut GlesRenderer) -> Option<UnderlyingStorage<'_>> {
+        if self.clipped {
+            None
+        } else {
+            self.element.underlying_storage(renderer)
+        }
+    }
+
+    fn capture_framebuffer(
+        &self,
+        frame: &mut GlesFrame<'_, '_>,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        cache: &UserDataMap,
+    ) -> Result<(), GlesError> {
+        self.element.capture_framebuffer(frame, src, dst, cache)
+    }
+}
+
+/// Parameters of a [`ShadowElement`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowParameters {
+    /// Color of the shadow with premultiplied alpha
+    pub color: Color32F,
+    /// Blur radius of the shadow in logical coordinates
+    ///
+    /// Like for css box shadows, the shadow is blurred with a standard deviation of half the
+    /// blur radius.
+    pub blur_radius: f32,
+    /// Distance the shadow is grown by on all sides in logical coordinates, negative values shrink it
+    pub spread: f32,
+    /// Offset of the shadow relative to the shadow casting rectangle in logical coordinates
+    pub offset: Point<i32, Logical>,
+}
+
+impl Default for ShadowParameters {
+    fn default() -> Self {
+        ShadowParameters {
+            color: Color32F::new(0.0, 0.0, 0.0, 0.5),
+            blur_radius: 24.0,
+            spread: 0.0,
+            offset: (0, 4).into(),
+        }
+    }
+}
+
+impl ShadowParameters {
+    // How far the blurred shadow extends beyond its rectangle
+    fn blur_extent(&self) -> f32 {
+        self.blur_radius.max(0.0) * 1.5
+    }
+}
+
+// Program used by `ShadowElement`, stored in the user data of the `EGLContext`
+#[derive(Debug, Clone)]
+struct ShadowProgram(GlesPixelProgram);
+
+impl ShadowProgram {
+    fn get(frame: &mut GlesFrame<'_, '_>) -> Result<GlesPixelProgram, GlesError> {
+        if let Some(program) = frame.egl_context().user_data().get::<ShadowProgram>() {
+            return Ok(program.0.clone());
+        }
+
+        let mut guard = frame.renderer();
+        let renderer = guard.as_mut();
+        let program = renderer.compile_custom_pixel_shader(
+            shaders::FRAGMENT_SHADER_SHADOW,
+            &[
+                UniformName::new("color", UniformType::_4f),
+                UniformName::new("shadow_rect", UniformType::_4f),
+                UniformName::new("shadow_radius", UniformType::_4f),
+                UniformName::new("sigma", UniformType::_1f),
+                UniformName::new("cutout_rect", UniformType::_4f),
+                UniformName::new("cutout_radius", UniformType::_4f),
+            ],
+        )?;
+        renderer
+            .egl_context()
+            .user_data()
+            .insert_if_missing(|| ShadowProgram(program.clone()));
+        Ok(program)
+    }
+}
+
+/// Render element drawing a shadow around a rounded rectangle
+///
+/// The shadow is only drawn outside of the shadow casting rectangle, so it does not shine through
+/// translucent contents on top of it. The element never reports opaque regions.
+#[derive(Debug, Clone)]
+pub struct ShadowElement {
+    id: Id,
+    commit_counter: CommitCounter,
+    area: Rectangle<i32, Logical>,
+    corner_radius: CornerRadius,
+    parameters: ShadowParameters,
+    alpha: f32,
+}
+
+impl ShadowElement {
+    /// Create a new [`ShadowElement`] for the shadow casting `area`
+    ///
+    /// The corner radius is expected in logical coordinates.
+    pub fn new(
+        area: Rectangle<i32, Logical>,
+        corner_radius: impl Into<CornerRadius>,
+        parameters: ShadowParameters,
+        alpha: f32,
+    ) -> Self {
+        ShadowElement {
+            id: Id::new(),
+            commit_counter: CommitCounter::default(),
+            area,
+            corner_radius: corner_radius.into(),
+            parameters,
+            alpha,
+        }
+    }
+
+    /// Resize the shadow casting area
+    pub fn resize(&mut self, area: Rectangle<i32, Logical>, corner_radius: impl Into<CornerRadius>) {
+        let corner_radius = corner_radius.into();
+        if self.area != area || self.corner_radius != corner_radius {
+            self.area = area;
+            self.corner_radius = corner_radius;
+            self.commit_counter.increment();
+        }
+    }
+
+    /// Update the parameters of the shadow
+    pub fn set_parameters(&mut self, parameters: ShadowParameters) {
+        if self.parameters != parameters {
+            self.parameters = parameters;
+            self.commit_counter.increment();
+        }
+    }
+
+    /// Returns the current parameters of the shadow
+    pub fn parameters(&self) -> ShadowParameters {
+        self.parameters
+    }
+
+    /// Set the alpha value the shadow is drawn with
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.alpha = alpha;
+    }
+
+    // Area covered by the blurred shadow
+    fn shadow_area(&self) -> Rectangle<i32, Logical> {
+        let grow = (self.parameters.spread + self.parameters.blur_extent()).ceil() as i32;
+        let loc = self.area.loc + self.parameters.offset - Point::from((grow, grow));
+        let size = Size::from((
+            (self.area.size.w + grow * 2).max(0),
+            (self.area.size.h + grow * 2).max(0),
+        ));
+        Rectangle::new(loc, size)
+    }
+}
+
+impl Element for ShadowElement {
+    fn id(&self) -> &Id {
+        &self.id
+    }
+
+    fn current_commit(&self) -> CommitCounter {
+        self.commit_counter
+    }
+
+    fn src(&self) -> Rectangle<f64, Buffer> {
+        Rectangle::from_size(self.shadow_area().size.to_f64().to_buffer(1.0, Transform::Normal))
+    }
+
+    fn geometry(&self, scale: Scale<f64>) -> Rectangle<i32, Physical> {
+        self.shadow_area().to_physical_precise_round(scale)
+    }
+
+    fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    fn kind(&self) -> Kind {
+        Kind::Unspecified
+    }
+}
+
+impl RenderElement<GlesRenderer> for ShadowElement {
+    #[profiling::function]
+    fn draw(
+        &self,
+        frame: &mut GlesFrame<'_, '_>,
+        _src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        _opaque_regions: &[Rectangle<i32, Physical>],
+        _cache: Option<&UserDataMap>,
+    ) -> Result<(), GlesError> {
+        let shadow_area = self.shadow_area();
+        if shadow_area.is_empty() {
+            return Ok(());
+        }
+        let program = ShadowProgram::get(frame)?;
+
+        // everything is passed to the shader in physical coordinates relative to `dst`
+        let scale = dst.size.to_f64() / shadow_area.size.to_f64();
+        let radius_scale = scale.x.min(scale.y) as f32;
+        let to_physical = |rect: Rectangle<f64, Logical>| {
+            [
+                (rect.loc.x * scale.x) as f32,
+                (rect.loc.y * scale.y) as f32,
+                (rect.size.w * scale.x) as f32,
+                (rect.size.h * scale.y) as f32,
+            ]
+        };
+
+        let spread = self.parameters.spread as f64;
+        let cutout_rect = Rectangle::new(self.area.loc - shadow_area.loc, self.area.size).to_f64();
+        let mut shadow_rect = Rectangle::new(
+            cutout_rect.loc + self.parameters.offset.to_f64() - Point::from((spread, spread)),
+            cutout_rect.size + Size::from((spread * 2.0, spread * 2.0)),
+        );
+        shadow_rect.size.w = shadow_rect.size.w.max(0.0);
+        shadow_rect.size.h = shadow_rect.size.h.max(0.0);
+
+        let cutout_radius = self.corner_radius.fit_to(cutout_rect.size);
+        let shadow_radius = cutout_radius
+            .expanded_by(self.parameters.spread)
+            .fit_to(shadow_rect.size);
+        let sigma = (self.parameters.blur_radius * 0.5 * radius_scale).max(0.5);
+
+        frame.render_pixel_shader_to(
+            &program,
+            Rectangle::from_size((dst.size.w as f64, dst.size.h as f64).into()),
+            dst,
+            (dst.size.w, dst.size.h).into(),
+            Some(damage),
+            self.alpha,
+            &[
+                Uniform::new("color", self.parameters.color.components()),
+                Uniform::new("shadow_rect", to_physical(shadow_rect)),
+                Uniform::new("shadow_radius", shadow_radius.scaled_by(radius_scale).to_array()),
+                Uniform::new("sigma", sigma),
+                Uniform::new("cutout_rect", to_physical(cutout_rect)),
+                Uniform::new("cutout_radius", cutout_radius.scaled_by(radius_scale).to_array()),
+            ],
+        )
+    }
+
+    #[inline]
+    fn underlying_storage(&self, _renderer: &mut GlesRenderer) -> Option<UnderlyingStorage<'_>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::renderer::element::solid::SolidColorRenderElement;
+
+    fn radius(top_left: f32, top_right: f32, bottom_right: f32, bottom_left: f32) -> CornerRadius {
+        CornerRadius {
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+        }
+    }
+
+    fn solid(geometry: Rectangle<i32, Physical>) -> SolidColorRenderElement {
+        SolidColorRenderElement::new(
+            Id::new(),
+            geometry,
+            CommitCounter::default(),
+            Color32F::BLACK,
+            Kind::Unspecified,
+        )
+    }
+
+    fn area(rects: &[Rectangle<i32, Physical>]) -> i32 {
+        rects.iter().map(|rect| rect.size.w * rect.size.h).sum()
+    }
+
+    #[test]
+    fn fit_to() {
+        let size = Size::<f64, Physical>::from((100.0, 50.0));
+        assert_eq!(CornerRadius::from(10.0).fit_to(size), CornerRadius::from(10.0));
+        // adjacent corners may not exceed the side they share
+        assert_eq!(CornerRadius::from(40.0).fit_to(size), CornerRadius::from(25.0));
+        assert_eq!(
+            radius(80.0, 20.0, 0.0, 0.0).fit_to(size),
+            radius(50.0, 12.5, 0.0, 0.0)
+        );
+        assert_eq!(
+            radius(-5.0, 10.0, 0.0, 0.0).fit_to(size),
+            radius(0.0, 10.0, 0.0, 0.0)
+        );
+        assert!(
+            CornerRadius::from(10.0)
+                .fit_to(Size::<f64, Physical>::default())
+                .is_zero()
+        );
+        assert_eq!(CornerRadius::default().fit_to(size), CornerRadius::default());
+    }
+
+    #[test]
+    fn transformed() {
+        let radii = radius(1.0, 2.0, 3.0, 4.0);
+        assert_eq!(radii.transformed(Transform::Normal), radii);
+        assert_eq!(radii.transformed(Transform::_90), radius(4.0, 1.0, 2.0, 3.0));
+        assert_eq!(radii.transformed(Transform::_180), radius(3.0, 4.0, 1.0, 2.0));
+        assert_eq!(radii.transformed(Transform::_270), radius(2.0, 3.0, 4.0, 1.0));
+        assert_eq!(radii.transformed(Transform::Flipped), radius(2.0, 1.0, 4.0, 3.0));
+        assert_eq!(
+            radii.transformed(Transform::Flipped90),
+            radius(1.0, 4.0, 3.0, 2.0)
+        );
+        assert_eq!(
+            radii.transformed(Transform::Flipped270),
+            radius(3.0, 2.0, 1.0, 4.0)
+        );
+        for transform in [Transform::_90, Transform::_270, Transform::Flipped180] {
+            assert_eq!(
+                radii.transformed(transform).transformed(transform.invert()),
+                radii
+            );
+        }
+    }
+
+    #[test]
+    fn corner_rects() {
+        let rect = Rectangle::new((10, 20).into(), (100, 50).into());
+        assert_eq!(
+            radius(5.0, 0.0, 2.5, 10.0).corner_rects(rect),
+            [
+                Rectangle::new((10, 20).into(), (5, 5).into()),
+                Rectangle::new((110, 20).into(), (0, 0).into()),
+                Rectangle::new((107, 67).into(), (3, 3).into()),
+                Rectangle::new((10, 60).into(), (10, 10).into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn opaque_regions_exclude_corners() {
+        let geometry = Rectangle::from_size((100, 100).into());
+        let element = RoundedCornerElement::from_element(
+            solid(geometry),
+            1.0,
+            geometry,
+            10.0,
+            &mut RoundedCornerState::default(),
+        );
+        assert!(element.is_clipped());
+
+        let opaque = element
+            .opaque_regions(Scale::from(1.0))
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(area(&opaque), 100 * 100 - 4 * 10 * 10);
+        let corners = CornerRadius::from(10.0).corner_rects(geometry);
+        assert!(
+            opaque
+                .iter()
+                .all(|rect| geometry.contains_rect(*rect) && !corners.iter().any(|c| c.overlaps(*rect)))
+        );
+    }
+
+    #[test]
+    fn opaque_regions_clipped_to_geometry() {
+        // the element is larger than the rounded rectangle and positioned relative to it
+        let element = solid(Rectangle::new((-50, -50).into(), (200, 200).into()));
+        let geometry = Rectangle::from_size((100, 100).into());
+        let element = RoundedCornerElement::from_element(
+            element,
+            1.0,
+            geometry,
+            0.0,
+            &mut RoundedCornerState::default(),
+        );
+        assert!(element.is_clipped());
+
+        let opaque = element
+            .opaque_regions(Scale::from(1.0))
+            .into_iter()
+            .collect::<Vec<_>>();
+        // opaque regions are relative to the element
+        assert_eq!(opaque, vec![Rectangle::new((50, 50).into(), (100, 100).into())]);
+    }
+
+    #[test]
+    fn opaque_regions_unclipped() {
+        // the element doesn't reach into the rounded corners
+        let element = solid(Rectangle::new((20, 20).into(), (50, 50).into()));
+        let geometry = Rectangle::from_size((100, 100).into());
+        let element = RoundedCornerElement::from_element(
+            element,
+            1.0,
+            geometry,
+            10.0,
+            &mut RoundedCornerState::default(),
+        );
+        assert!(!element.is_clipped());
+
+        let opaque = element
+            .opaque_regions(Scale::from(1.0))
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(opaque, vec![Rectangle::from_size((50, 50).into())]);
+    }
+
+    #[test]
+    fn clip_changes_damage_element() {
+        let geometry = Rectangle::from_size((100, 100).into());
+        let scale = Scale::from(1.0);
+        let mut state = RoundedCornerState::default();
+
+        let element = RoundedCornerElement::from_element(solid(geometry), scale, geometry, 10.0, &mut state);
+        let commit = element.current_commit();
+        assert_eq!(element.damage_since(scale, None).len(), 1);
+
+        // nothing changed
+        let element = RoundedCornerElement::from_element(solid(geometry), scale, geometry, 10.0, &mut state);
+        assert_eq!(element.current_commit(), commit);
+        assert!(element.damage_since(scale, Some(commit)).is_empty());
+
+        // the radius changed, while the wrapped element didn't
+        let element = RoundedCornerElement::from_element(solid(geometry), scale, geometry, 20.0, &mut state);
+        assert!(element.current_commit() > commit);
+        assert_eq!(
+            element.damage_since(scale, Some(commit)).to_vec(),
+            vec![Rectangle::from_size((100, 100).into())]
+        );
+        let commit = element.current_commit();
+        assert!(element.damage_since(scale, Some(commit)).is_empty());
+
+        // the clipping rectangle changed
+        let clip = Rectangle::from_size((90, 90).into());
+        let element = RoundedCornerElement::from_element(solid(geometry), scale, clip, 20.0, &mut state);
+        assert!(element.current_commit() > commit);
+        assert_eq!(element.damage_since(scale, Some(commit)).len(), 1);
+    }
+}
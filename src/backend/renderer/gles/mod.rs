@@ -425,6 +425,8 @@ pub struct GlesFrame<'frame, 'buffer> {
     transform: Transform,
     size: Size<i32, Physical>,
     tex_program_override: Option<(GlesTexProgram, Vec<Uniform<'static>>)>,
+    // pixel shader replacing `draw_solid`, gets the color as an additional `color` uniform
+    solid_program_override: Option<(GlesPixelProgram, Vec<Uniform<'static>>)>,
     finished: AtomicBool,
 
     span: EnteredSpan,
@@ -440,6 +442,7 @@ impl fmt::Debug for GlesFrame<'_, '_> {
             .field("current_projection", &self.current_projection)
             .field("transform", &self.transform)
             .field("tex_program_override", &self.tex_program_override)
+            .field("solid_program_override", &self.solid_program_override)
             .field("size", &self.size)
             .field("finished", &self.finished)
             .finish_non_exhaustive()
@@ -2299,6 +2302,7 @@ impl Renderer for GlesRenderer {
             transform,
             size: output_size,
             tex_program_override: None,
+            solid_program_override: None,
             finished: AtomicBool::new(false),
 
             span,
@@ -2430,7 +2434,10 @@ impl Frame for GlesFrame<'_, '_> {
             self.renderer.gl.Disable(ffi::BLEND);
         }
 
+        // clearing is not affected by a clipped element
+        let solid_program_override = self.solid_program_override.take();
         let res = self.draw_solid(Rectangle::from_size(self.size), at, color);
+        self.solid_program_override = solid_program_override;
 
         unsafe {
             self.renderer.gl.Enable(ffi::BLEND);
@@ -2453,7 +2460,8 @@ impl Frame for GlesFrame<'_, '_> {
             return Ok(());
         }
 
-        let is_opaque = color.is_opaque();
+        // an overridden program may not cover every pixel
+        let is_opaque = color.is_opaque() && self.solid_program_override.is_none();
 
         let scope = self
             .renderer
@@ -2607,6 +2615,23 @@ impl GlesFrame<'_, '_> {
             return Ok(());
         }
 
+        if let Some((program, mut uniforms)) = self.solid_program_override.clone() {
+            uniforms.push(Uniform::new(
+                "color",
+                [color.r(), color.g(), color.b(), color.a()],
+            ));
+            let size = Size::<i32, BufferCoord>::from((dest.size.w, dest.size.h));
+            return self.render_pixel_shader_to(
+                &program,
+                Rectangle::from_size(size.to_f64()),
+                dest,
+                size,
+                Some(damage),
+                1.0,
+                &uniforms,
+            );
+        }
+
         let mut mat = Mat3::IDENTITY;
         mat = self.current_projection * mat;
 
@@ -0,0 +1,48 @@
+#version 100
+
+//_DEFINES_
+
+#if defined(EXTERNAL)
+#extension GL_OES_EGL_image_external : require
+#endif
+
+precision highp float;
+#if defined(EXTERNAL)
+uniform samplerExternalOES tex;
+#else
+uniform sampler2D tex;
+#endif
+
+uniform float alpha;
+varying vec2 v_coords;
+
+#if defined(DEBUG_FLAGS)
+uniform float tint;
+#endif
+
+uniform vec2 half_pixel;
+uniform float offset;
+
+// dual kawase downsampling
+void main() {
+    vec2 step = half_pixel * offset;
+    vec4 color = texture2D(tex, v_coords) * 4.0;
+    color += texture2D(tex, v_coords - step);
+    color += texture2D(tex, v_coords + step);
+    color += texture2D(tex, v_coords + vec2(step.x, -step.y));
+    color += texture2D(tex, v_coords - vec2(step.x, -step.y));
+    color = color / 8.0;
+
+#if defined(NO_ALPHA)
+    color = vec4(color.rgb, 1.0) * alpha;
+#else
+    color = color * alpha;
+#endif
+
+#if defined(DEBUG_FLAGS)
+    if (tint == 1.0)
+        color = vec4(0.0, 0.2, 0.0, 0.2) + color * 0.8;
+#endif
+
+    gl_FragColor = color;
+}
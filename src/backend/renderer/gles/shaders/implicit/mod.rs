@@ -7,6 +7,14 @@ pub(in super::super) const FRAGMENT_SHADER: &str = include_str!("./texture.frag"
 pub(in super::super) const VERTEX_SHADER_SOLID: &str = include_str!("./solid.vert");
 pub(in super::super) const FRAGMENT_SHADER_SOLID: &str = include_str!("./solid.frag");
 
+pub(in super::super) const FRAGMENT_SHADER_BLUR_DOWN: &str = include_str!("./blur_down.frag");
+pub(in super::super) const FRAGMENT_SHADER_BLUR_UP: &str = include_str!("./blur_up.frag");
+
+pub(in super::super) const FRAGMENT_SHADER_ROUNDED_CORNERS: &str = include_str!("./rounded_corners.frag");
+pub(in super::super) const FRAGMENT_SHADER_ROUNDED_CORNERS_SOLID: &str =
+    include_str!("./rounded_corners_solid.frag");
+pub(in super::super) const FRAGMENT_SHADER_SHADOW: &str = include_str!("./shadow.frag");
+
 #[derive(Debug)]
 pub(in super::super) struct GlesTexProgramInternal {
     pub(in super::super) program: ffi::types::GLuint,
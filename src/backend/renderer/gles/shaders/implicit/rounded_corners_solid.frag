@@ -0,0 +1,52 @@
+precision highp float;
+
+varying vec2 v_coords;
+uniform vec2 size;
+uniform float alpha;
+
+#if defined(DEBUG_FLAGS)
+uniform float tint;
+#endif
+
+// premultiplied color of the solid
+uniform vec4 color;
+// clipping rectangle (x, y, width, height) in framebuffer pixels
+uniform vec4 clip_rect;
+// radii of the top-left, top-right, bottom-right and bottom-left corners in framebuffer pixels
+uniform vec4 corner_radius;
+
+float rounding_alpha(vec2 coords, vec2 size, vec4 radius) {
+    vec2 center;
+    float r;
+
+    if (coords.x < radius.x && coords.y < radius.x) {
+        r = radius.x;
+        center = vec2(r, r);
+    } else if (coords.x > size.x - radius.y && coords.y < radius.y) {
+        r = radius.y;
+        center = vec2(size.x - r, r);
+    } else if (coords.x > size.x - radius.z && coords.y > size.y - radius.z) {
+        r = radius.z;
+        center = size - vec2(r, r);
+    } else if (coords.x < radius.w && coords.y > size.y - radius.w) {
+        r = radius.w;
+        center = vec2(r, size.y - r);
+    } else {
+        return 1.0;
+    }
+
+    return 1.0 - smoothstep(r - 0.5, r + 0.5, distance(coords, center));
+}
+
+void main() {
+    vec2 coords = gl_FragCoord.xy - clip_rect.xy;
+    vec2 inside = step(vec2(0.0, 0.0), coords) * step(coords, clip_rect.zw);
+    vec4 mix_color = color * alpha * inside.x * inside.y * rounding_alpha(coords, clip_rect.zw, corner_radius);
+
+#if defined(DEBUG_FLAGS)
+    if (tint == 1.0)
+        mix_color = vec4(0.0, 0.2, 0.0, 0.2) + mix_color * 0.8;
+#endif
+
+    gl_FragColor = mix_color;
+}
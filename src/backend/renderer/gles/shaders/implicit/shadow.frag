@@ -0,0 +1,94 @@
+precision highp float;
+
+varying vec2 v_coords;
+uniform vec2 size;
+uniform float alpha;
+
+#if defined(DEBUG_FLAGS)
+uniform float tint;
+#endif
+
+// premultiplied color of the shadow
+uniform vec4 color;
+// rectangle (x, y, width, height) and corner radii of the shadow in pixels
+uniform vec4 shadow_rect;
+uniform vec4 shadow_radius;
+// standard deviation of the gaussian blur in pixels
+uniform float sigma;
+// rectangle and corner radii of the shadow casting area, which is not drawn into
+uniform vec4 cutout_rect;
+uniform vec4 cutout_radius;
+
+// radius of the corner closest to `point`, which is relative to the center of the rectangle
+float select_radius(vec2 point, vec4 radius) {
+    if (point.x < 0.0) {
+        return point.y < 0.0 ? radius.x : radius.w;
+    } else {
+        return point.y < 0.0 ? radius.y : radius.z;
+    }
+}
+
+// signed distance to the border of a rounded rectangle, negative inside of it
+float rounded_rect_distance(vec2 point, vec4 rect, vec4 radius) {
+    vec2 half_size = rect.zw * 0.5;
+    point -= rect.xy + half_size;
+    float r = min(select_radius(point, radius), min(half_size.x, half_size.y));
+    vec2 q = abs(point) - half_size + r;
+    return min(max(q.x, q.y), 0.0) + length(max(q, 0.0)) - r;
+}
+
+float gaussian(float x, float deviation) {
+    const float pi = 3.141592653589793;
+    return exp(-(x * x) / (2.0 * deviation * deviation)) / (sqrt(2.0 * pi) * deviation);
+}
+
+vec2 erf(vec2 x) {
+    vec2 s = sign(x);
+    vec2 a = abs(x);
+    x = 1.0 + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+    x *= x;
+    return s - s / (x * x);
+}
+
+// blurred rounded rectangle along the x axis for a given y, integrated analytically
+float shadow_x(float x, float y, float deviation, float corner, vec2 half_size) {
+    float delta = min(half_size.y - corner - abs(y), 0.0);
+    float curved = half_size.x - corner + sqrt(max(0.0, corner * corner - delta * delta));
+    vec2 integral = 0.5 + 0.5 * erf((x + vec2(-curved, curved)) * (sqrt(0.5) / deviation));
+    return integral.y - integral.x;
+}
+
+// blurred rounded rectangle, sampling the gaussian along the y axis
+float rounded_rect_shadow(vec2 point, vec4 rect, vec4 radius, float deviation) {
+    vec2 half_size = rect.zw * 0.5;
+    point -= rect.xy + half_size;
+    float corner = min(select_radius(point, radius), min(half_size.x, half_size.y));
+
+    float low = point.y - half_size.y;
+    float high = point.y + half_size.y;
+    float start = clamp(-3.0 * deviation, low, high);
+    float end = clamp(3.0 * deviation, low, high);
+
+    float dy = (end - start) / 4.0;
+    float y = start + dy * 0.5;
+    float value = 0.0;
+    for (int i = 0; i < 4; i++) {
+        value += shadow_x(point.x, point.y - y, deviation, corner, half_size) * gaussian(y, deviation) * dy;
+        y += dy;
+    }
+    return value;
+}
+
+void main() {
+    vec2 point = v_coords * size;
+    float shadow = rounded_rect_shadow(point, shadow_rect, shadow_radius, sigma);
+    float cutout = clamp(0.5 - rounded_rect_distance(point, cutout_rect, cutout_radius), 0.0, 1.0);
+    vec4 result = color * shadow * (1.0 - cutout) * alpha;
+
+#if defined(DEBUG_FLAGS)
+    if (tint == 1.0)
+        result = vec4(0.0, 0.2, 0.0, 0.2) + result * 0.8;
+#endif
+
+    gl_FragColor = result;
+}
@@ -18,7 +18,7 @@ use crate::{
         renderer::{
             Bind, Blit, BlitFrame, Color32F, DebugFlags, ExportMem, ImportDma, ImportMem, Offscreen,
             Renderer, RendererSuper, TextureFilter,
-            element::UnderlyingStorage,
+            element::{UnderlyingStorage, blur::BlurElement},
             gles::{element::*, *},
             sync,
         },
@@ -663,3 +663,87 @@ impl RenderElement<GlowRenderer> for TextureShaderElement {
         RenderElement::<GlesRenderer>::underlying_storage(self, renderer.borrow_mut())
     }
 }
+
+impl RenderElement<GlowRenderer> for BlurElement {
+    #[profiling::function]
+    fn draw(
+        &self,
+        frame: &mut GlowFrame<'_, '_>,
+        src: Rectangle<f64, BufferCoord>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        opaque_regions: &[Rectangle<i32, Physical>],
+        cache: Option<&UserDataMap>,
+    ) -> Result<(), GlesError> {
+        RenderElement::<GlesRenderer>::draw(self, frame.borrow_mut(), src, dst, damage, opaque_regions, cache)
+    }
+
+    fn underlying_storage(&self, renderer: &mut GlowRenderer) -> Option<UnderlyingStorage<'_>> {
+        RenderElement::<GlesRenderer>::underlying_storage(self, renderer.borrow_mut())
+    }
+
+    fn capture_framebuffer(
+        &self,
+        frame: &mut GlowFrame<'_, '_>,
+        src: Rectangle<f64, BufferCoord>,
+        dst: Rectangle<i32, Physical>,
+        cache: &UserDataMap,
+    ) -> Result<(), GlesError> {
+        RenderElement::<GlesRenderer>::capture_framebuffer(self, frame.borrow_mut(), src, dst, cache)
+    }
+}
+
+impl<E: RenderElement<GlowRenderer>> RenderElement<GlowRenderer> for RoundedCornerElement<E> {
+    #[profiling::function]
+    fn draw(
+        &self,
+        frame: &mut GlowFrame<'_, '_>,
+        src: Rectangle<f64, BufferCoord>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        opaque_regions: &[Rectangle<i32, Physical>],
+        cache: Option<&UserDataMap>,
+    ) -> Result<(), GlesError> {
+        self.draw_clipped(frame, dst, |frame| {
+            self.element()
+                .draw(frame, src, dst, damage, opaque_regions, cache)
+        })
+    }
+
+    fn underlying_storage(&self, renderer: &mut GlowRenderer) -> Option<UnderlyingStorage<'_>> {
+        if self.is_clipped() {
+            None
+        } else {
+            self.element().underlying_storage(renderer)
+        }
+    }
+
+    fn capture_framebuffer(
+        &self,
+        frame: &mut GlowFrame<'_, '_>,
+        src: Rectangle<f64, BufferCoord>,
+        dst: Rectangle<i32, Physical>,
+        cache: &UserDataMap,
+    ) -> Result<(), GlesError> {
+        self.element().capture_framebuffer(frame, src, dst, cache)
+    }
+}
+
+impl RenderElement<GlowRenderer> for ShadowElement {
+    #[profiling::function]
+    fn draw(
+        &self,
+        frame: &mut GlowFrame<'_, '_>,
+        src: Rectangle<f64, BufferCoord>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        opaque_regions: &[Rectangle<i32, Physical>],
+        cache: Option<&UserDataMap>,
+    ) -> Result<(), GlesError> {
+        RenderElement::<GlesRenderer>::draw(self, frame.borrow_mut(), src, dst, damage, opaque_regions, cache)
+    }
+
+    fn underlying_storage(&self, renderer: &mut GlowRenderer) -> Option<UnderlyingStorage<'_>> {
+        RenderElement::<GlesRenderer>::underlying_storage(self, renderer.borrow_mut())
+    }
+}
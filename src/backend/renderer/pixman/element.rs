@@ -0,0 +1,271 @@
+use std::cell::RefCell;
+
+use pixman::{FormatCode, Image, Operation};
+
+use crate::{
+    backend::renderer::{
+        Frame,
+        element::{Element, RenderElement, UnderlyingStorage, blur::BlurElement},
+    },
+    utils::{Buffer as BufferCoords, Physical, Rectangle, user_data::UserDataMap},
+};
+
+use super::{PixmanError, PixmanFrame, PixmanRenderer, PixmanTargetInternal, PixmanTexture};
+
+// Three box blurs are close enough to a gaussian blur
+const BOX_BLUR_ITERATIONS: usize = 3;
+
+// Captured contents of a `BlurElement`, stored in its effects cache
+#[derive(Debug, Default)]
+struct BlurState {
+    area: Rectangle<i32, Physical>,
+    texture: Option<PixmanTexture>,
+}
+
+/// Blurs the premultiplied pixels of an `a8r8g8b8` image
+fn box_blur(image: &mut Image<'static, 'static>, radius: usize) {
+    let width = image.width();
+    let height = image.height();
+    let stride = image.stride() / 4;
+    if width == 0 || height == 0 || radius == 0 {
+        return;
+    }
+
+    // SAFETY: the image owns `stride * height` pixels and is not accessed otherwise while we hold the slice
+    let data = unsafe { std::slice::from_raw_parts_mut(image.data(), stride * height) };
+    let mut line = Vec::with_capacity(width.max(height));
+    for _ in 0..BOX_BLUR_ITERATIONS {
+        for y in 0..height {
+            blur_line(data, y * stride, 1, width, radius, &mut line);
+        }
+        for x in 0..width {
+            blur_line(data, x, stride, height, radius, &mut line);
+        }
+    }
+}
+
+fn blur_line(data: &mut [u32], start: usize, step: usize, len: usize, radius: usize, line: &mut Vec<u32>) {
+    line.clear();
+    line.extend((0..len).map(|i| data[start + i * step]));
+
+    let channel = |pixel: u32, c: usize| (pixel >> (c * 8)) & 0xff;
+    // the edges are extended by repeating the outermost pixels
+    let at = |i: isize| line[i.clamp(0, len as isize - 1) as usize];
+
+    let window = (radius * 2 + 1) as u32;
+    let radius = radius as isize;
+    let mut sums = [0u32; 4];
+    for i in -radius..=radius {
+        for (c, sum) in sums.iter_mut().enumerate() {
+            *sum += channel(at(i), c);
+        }
+    }
+
+    for i in 0..len {
+        data[start + i * step] = sums.iter().enumerate().fold(0, |pixel, (c, sum)| {
+            pixel | (((sum + window / 2) / window) << (c * 8))
+        });
+
+        let added = at(i as isize + radius + 1);
+        let removed = at(i as isize - radius);
+        for (c, sum) in sums.iter_mut().enumerate() {
+            *sum = *sum + channel(added, c) - channel(removed, c);
+        }
+    }
+}
+
+impl RenderElement<PixmanRenderer> for BlurElement {
+    #[profiling::function]
+    fn draw(
+        &self,
+        frame: &mut PixmanFrame<'_, '_>,
+        _src: Rectangle<f64, BufferCoords>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        _opaque_regions: &[Rectangle<i32, Physical>],
+        cache: Option<&UserDataMap>,
+    ) -> Result<(), PixmanError> {
+        let Some(state) = cache.and_then(|cache| cache.get::<RefCell<BlurState>>()) else {
+            return Ok(());
+        };
+        let state = state.borrow();
+        let Some(texture) = state.texture.as_ref() else {
+            return Ok(());
+        };
+        let Some((src, dst, damage)) = self.captured_src_dst(state.area, dst, damage, frame.transform) else {
+            return Ok(());
+        };
+
+        frame.render_texture_from_to(texture, src, dst, &damage, &[], frame.transform, self.alpha())
+    }
+
+    #[inline]
+    fn underlying_storage(&self, _renderer: &mut PixmanRenderer) -> Option<UnderlyingStorage<'_>> {
+        None
+    }
+
+    #[profiling::function]
+    fn capture_framebuffer(
+        &self,
+        frame: &mut PixmanFrame<'_, '_>,
+        _src: Rectangle<f64, BufferCoords>,
+        dst: Rectangle<i32, Physical>,
+        cache: &UserDataMap,
+    ) -> Result<(), PixmanError> {
+        let mut state = cache
+            .get_or_insert(|| RefCell::new(BlurState::default()))
+            .borrow_mut();
+        // nothing to blur, the contents behind the element stay as they are
+        let radius = self.parameters().radius() as usize;
+        if radius == 0 {
+            state.texture = None;
+            return Ok(());
+        }
+        let Some(area) = self
+            .sample_area(dst)
+            .intersection(Rectangle::from_size(frame.size))
+        else {
+            state.texture = None;
+            return Ok(());
+        };
+        let fb_area = frame.transform.transform_rect_in(area, &frame.size);
+
+        let mut image = Image::new(
+            FormatCode::A8R8G8B8,
+            fb_area.size.w as usize,
+            fb_area.size.h as usize,
+            false,
+        )
+        .map_err(|_| PixmanError::Unsupported)?;
+
+        let binding;
+        let target_image = match &frame.target.0 {
+            PixmanTargetInternal::Dmabuf { image, .. } => {
+                binding = image.0.image.lock().unwrap();
+                &*binding
+            }
+            PixmanTargetInternal::Image(b) => &**b,
+        };
+        image.composite32(
+            Operation::Src,
+            target_image,
+            None,
+            (fb_area.loc.x, fb_area.loc.y),
+            (0, 0),
+            (0, 0),
+            (fb_area.size.w, fb_area.size.h),
+        );
+
+        box_blur(&mut image, (radius / BOX_BLUR_ITERATIONS).max(1));
+
+        state.texture = Some(PixmanTexture::from(image));
+        state.area = area;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blur(pixels: &[u32], radius: usize) -> Vec<u32> {
+        let mut data = pixels.to_vec();
+        let mut line = Vec::new();
+        blur_line(&mut data, 0, 1, pixels.len(), radius, &mut line);
+        data
+    }
+
+    #[test]
+    fn blur_line_uniform() {
+        let pixels = [0x80ff_4020; 8];
+        assert_eq!(blur(&pixels, 3), pixels);
+    }
+
+    #[test]
+    fn blur_line_averages_channels() {
+        // single bright pixel spreads over the window of 3 pixels
+        let pixels = [0, 0, 0xffff_ffff, 0, 0];
+        assert_eq!(blur(&pixels, 1), [0, 0x5555_5555, 0x5555_5555, 0x5555_5555, 0]);
+
+        // channels are averaged independently
+        let pixels = [0xff00_0000, 0x00ff_0000, 0x0000_ff00];
+        assert_eq!(blur(&pixels, 1)[1], 0x5555_5500);
+    }
+
+    #[test]
+    fn blur_line_extends_edges() {
+        let pixels = [0xff, 0, 0, 0];
+        // the first pixel is repeated beyond the edge
+        assert_eq!(blur(&pixels, 1), [0xaa, 0x55, 0, 0]);
+        // a radius larger than the line
+        assert_eq!(blur(&[0x10, 0x20], 5), [0x17, 0x19]);
+    }
+
+    #[test]
+    fn blur_line_with_step() {
+        // blurs a column of a 2x3 image
+        let mut data = vec![0x30, 1, 0, 2, 0, 3];
+        let mut line = Vec::new();
+        blur_line(&mut data, 0, 2, 3, 1, &mut line);
+        assert_eq!(data, vec![0x20, 1, 0x10, 2, 0, 3]);
+    }
+
+    #[test]
+    fn box_blur_image() {
+        let mut image = Image::new(FormatCode::A8R8G8B8, 5, 5, true).unwrap();
+        // SAFETY: the image owns 5 * 5 pixels without padding
+        let data = unsafe { std::slice::from_raw_parts_mut(image.data(), 25) };
+        data[12] = 0xffff_ffff;
+
+        box_blur(&mut image, 0);
+        let data = unsafe { std::slice::from_raw_parts(image.data(), 25) };
+        assert_eq!(data.iter().filter(|pixel| **pixel != 0).count(), 1);
+
+        box_blur(&mut image, 1);
+        let data = unsafe { std::slice::from_raw_parts(image.data(), 25) };
+        // the blur is mirror symmetric and spreads the pixel over the whole image
+        assert!(data.iter().all(|pixel| *pixel != 0));
+        assert_eq!(data[0], data[24]);
+        assert_eq!(data[2], data[22]);
+        assert_eq!(data[10], data[14]);
+        assert!(data[12] > data[7] && data[7] > data[2]);
+    }
+
+    #[test]
+    fn capture_without_radius() {
+        use crate::{
+            backend::renderer::{Bind, Renderer, element::blur::BlurParameters},
+            utils::{Logical, Transform},
+        };
+
+        let capture = |parameters: BlurParameters| {
+            let mut renderer = PixmanRenderer::new().unwrap();
+            let mut image = Image::new(FormatCode::A8R8G8B8, 64, 64, true).unwrap();
+            let mut target = renderer.bind(&mut image).unwrap();
+            let mut frame = renderer
+                .render(&mut target, (64, 64).into(), Transform::Normal)
+                .unwrap();
+
+            let area = Rectangle::<i32, Logical>::new((16, 16).into(), (32, 32).into());
+            let element = BlurElement::new(area, parameters, 1.0);
+            let cache = UserDataMap::new();
+            RenderElement::<PixmanRenderer>::capture_framebuffer(
+                &element,
+                &mut frame,
+                Rectangle::from_size((32.0, 32.0).into()),
+                area.to_physical(1),
+                &cache,
+            )
+            .unwrap();
+            let state = cache.get::<RefCell<BlurState>>().unwrap().borrow();
+            state.texture.is_some()
+        };
+
+        assert!(capture(BlurParameters::default()));
+        // nothing is captured, so nothing gets drawn
+        assert!(!capture(BlurParameters {
+            passes: 2,
+            offset: 0.0,
+        }));
+    }
+}
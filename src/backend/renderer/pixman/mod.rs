@@ -42,6 +42,7 @@ use super::{
     RendererSuper, Texture, TextureFilter, TextureMapping, sync::SyncPoint,
 };
 
+mod element;
 mod error;
 
 pub use error::*;
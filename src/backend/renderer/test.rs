@@ -107,10 +107,13 @@ impl ImportMem for DummyRenderer {
         &mut self,
         _data: &[u8],
         _format: Fourcc,
-        _size: Size<i32, Buffer>,
+        size: Size<i32, Buffer>,
         _flipped: bool,
     ) -> Result<Self::TextureId, Self::Error> {
-        unimplemented!()
+        Ok(DummyTexture {
+            width: size.w as u32,
+            height: size.h as u32,
+        })
     }
 
     fn update_memory(
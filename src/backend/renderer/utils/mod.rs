@@ -42,6 +42,15 @@ impl CommitCounter {
             .filter(|commit| commit <= self)
             .map(|commit| self.0.wrapping_sub(commit.0))
     }
+
+    // Sum of two counters, e.g. for an element combining its own changes with the ones of a wrapped element
+    pub(crate) fn wrapping_add(self, other: CommitCounter) -> CommitCounter {
+        CommitCounter(self.0.wrapping_add(other.0))
+    }
+
+    pub(crate) fn wrapping_sub(self, other: CommitCounter) -> CommitCounter {
+        CommitCounter(self.0.wrapping_sub(other.0))
+    }
 }
 
 impl From<usize> for CommitCounter {
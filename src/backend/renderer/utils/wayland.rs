@@ -19,7 +19,7 @@ use crate::{
 use std::{
     any::Any,
     collections::{HashMap, hash_map::Entry},
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, Weak},
 };
 
 use super::{CommitCounter, DamageBag, DamageSet, DamageSnapshot, SurfaceView};
@@ -354,6 +354,72 @@ impl RendererSurfaceState {
         self.buffer_has_alpha = None;
         self.opaque_regions.clear();
     }
+
+    // Moves the buffer and textures into a new state, resetting this one
+    fn take(&mut self) -> RendererSurfaceState {
+        let state = RendererSurfaceState {
+            buffer_dimensions: self.buffer_dimensions,
+            buffer_scale: self.buffer_scale,
+            buffer_transform: self.buffer_transform,
+            buffer_has_alpha: self.buffer_has_alpha,
+            buffer: self.buffer.clone(),
+            textures: std::mem::take(&mut self.textures),
+            surface_view: self.surface_view,
+            opaque_regions: self.opaque_regions.clone(),
+            ..Default::default()
+        };
+        self.reset();
+        state
+    }
+}
+
+/// Storage for the renderer state of a surface, that outlives the surface
+///
+/// See [`retain_surface_state`].
+pub(crate) type RetainedSurfaceState = Arc<Mutex<Option<RendererSurfaceState>>>;
+
+#[derive(Debug, Default)]
+struct RetainedSurfaceStateRef(Mutex<Weak<Mutex<Option<RendererSurfaceState>>>>);
+
+/// Retains the buffer and textures of a surface in `storage` once the surface is destroyed
+///
+/// Instead of releasing them, [`on_commit_buffer_handler`] moves the state of a destroyed surface into
+/// `storage`, if it is still alive at that point.
+#[cfg(feature = "xwayland")]
+pub(crate) fn retain_surface_state(surface: &WlSurface, storage: &RetainedSurfaceState) {
+    compositor::with_states(surface, |states| {
+        states
+            .data_map
+            .insert_if_missing_threadsafe(RetainedSurfaceStateRef::default);
+        *states
+            .data_map
+            .get::<RetainedSurfaceStateRef>()
+            .unwrap()
+            .0
+            .lock()
+            .unwrap() = Arc::downgrade(storage);
+    });
+}
+
+/// Moves the buffer and textures of a surface into `storage` right away
+///
+/// Does nothing, if the surface has no buffer attached.
+#[cfg(feature = "xwayland")]
+pub(crate) fn take_surface_state(surface: &WlSurface, storage: &RetainedSurfaceState) {
+    compositor::with_states(surface, |states| take_surface_state_internal(states, storage));
+}
+
+fn take_surface_state_internal(states: &SurfaceData, storage: &RetainedSurfaceState) {
+    let Some(mut state) = states
+        .data_map
+        .get::<RendererSurfaceStateUserData>()
+        .map(|s| s.lock().unwrap())
+    else {
+        return;
+    };
+    if state.buffer.is_some() {
+        *storage.lock().unwrap() = Some(state.take());
+    }
 }
 
 /// Handler to let smithay take over buffer management.
@@ -397,6 +463,13 @@ pub fn on_commit_buffer_handler<D: 'static>(surface: &WlSurface) {
                 // release during drop. This also enables us to free resources earlier
                 // like the stored textures
                 compositor::with_states(surface, |data| {
+                    let retained = data
+                        .data_map
+                        .get::<RetainedSurfaceStateRef>()
+                        .and_then(|storage| storage.0.lock().unwrap().upgrade());
+                    if let Some(storage) = retained {
+                        take_surface_state_internal(data, &storage);
+                    }
                     if let Some(mut state) = data
                         .data_map
                         .get::<RendererSurfaceStateUserData>()
@@ -684,3 +757,46 @@ where
 
     Ok(Some(render_damage))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{
+        allocator::Fourcc,
+        renderer::{
+            ImportMem,
+            test::{DummyRenderer, DummyTexture},
+        },
+    };
+
+    #[test]
+    fn take_keeps_textures() {
+        let mut renderer = DummyRenderer;
+        let texture = renderer
+            .import_memory(&[], Fourcc::Argb8888, (10, 10).into(), false)
+            .unwrap();
+        let view = SurfaceView {
+            src: Rectangle::from_size((10.0, 10.0).into()),
+            dst: (10, 10).into(),
+            offset: Point::default(),
+        };
+        let mut state = RendererSurfaceState {
+            buffer_scale: 2,
+            surface_view: Some(view),
+            ..Default::default()
+        };
+        state
+            .textures
+            .insert(renderer.context_id().erased(), Box::new(texture));
+
+        let taken = state.take();
+        assert!(taken.texture::<DummyTexture>(renderer.context_id()).is_some());
+        assert_eq!(taken.view(), Some(view));
+        assert_eq!(taken.buffer_scale(), 2);
+
+        // the surface is still alive and keeps its scale for the next commit
+        assert!(state.texture::<DummyTexture>(renderer.context_id()).is_none());
+        assert_eq!(state.view(), None);
+        assert_eq!(state.buffer_scale(), 2);
+    }
+}
@@ -0,0 +1,842 @@
+//! Recording and replay of input events
+//!
+//! Bugs in input handling (grabs, focus, drag and drop, ...) can be hard to reproduce.
+//! This module allows to record the input events of any [`InputBackend`] with an [`InputRecorder`]
+//! and to replay them later with the [`InputReplay`] backend, which makes it possible to write
+//! deterministic tests against a [`Seat`](crate::input::Seat).
+//!
+//! Recordings can be stored in a line based text format, using the [`Display`](std::fmt::Display)
+//! and [`FromStr`](std::str::FromStr) implementations of [`InputRecording`]:
+//!
+//! ```text
+//! device 0 id="event3" name="AT Translated Set 2 keyboard" capabilities=keyboard
+//! 1000 0 device-added
+//! 1200 0 keyboard key=30 state=pressed count=1
+//! 1300 0 keyboard key=30 state=released count=0
+//! ```
+//!
+//! Each event line starts with the timestamp in microseconds, the index of the device and the kind of event,
+//! followed by its data as `key=value` pairs.
+//!
+//! ## Replaying
+//!
+//! [`InputReplay`] can be inserted into a calloop event loop, emitting the recorded events with their
+//! original timing or as fast as possible (see [`ReplayTiming`]). For tests it is often easier to pull the
+//! events synchronously via [`InputReplay::next_event`]:
+//!
+//! ```no_run
+//! use smithay::backend::replay::{InputRecording, InputReplay, ReplayTiming};
+//!
+//! # let text = "";
+//! let recording: InputRecording = text.parse().expect("Invalid recording");
+//! let mut replay = InputReplay::new(recording, ReplayTiming::Immediate).expect("Unknown device");
+//! while let Some(event) = replay.next_event() {
+//!     // process_input_event(event)
+//! }
+//! ```
+
+use std::{
+    collections::VecDeque,
+    hash::{Hash, Hasher},
+    io,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use calloop::{
+    EventSource, Poll, PostAction, Readiness, Token, TokenFactory,
+    timer::{TimeoutAction, Timer},
+};
+
+use crate::backend::input::{
+    AbsolutePositionEvent, Axis, AxisRelativeDirection, AxisSource, ButtonState, Device, DeviceCapability,
+    DeviceConfig, Event, GestureBeginEvent, GestureEndEvent, GestureHoldBeginEvent, GestureHoldEndEvent,
+    GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent, GestureSwipeBeginEvent,
+    GestureSwipeEndEvent, GestureSwipeUpdateEvent, InputBackend, InputEvent, KeyState, KeyboardKeyEvent,
+    Keycode, PointerAxisEvent, PointerButtonEvent, PointerMotionAbsoluteEvent, PointerMotionEvent,
+    ProximityState, Switch, SwitchState, SwitchToggleEvent, TabletToolAxisEvent, TabletToolButtonEvent,
+    TabletToolDescriptor, TabletToolEvent, TabletToolProximityEvent, TabletToolTipEvent, TabletToolTipState,
+    TouchCancelEvent, TouchDownEvent, TouchEvent, TouchFrameEvent, TouchMotionEvent, TouchSlot, TouchUpEvent,
+    UnusedEvent,
+};
+
+mod recording;
+
+pub use recording::{
+    AbsolutePosition, AxisValue, ChangedTabletAxes, GestureBegin, GestureEnd, GesturePinchUpdate,
+    GestureSwipeUpdate, InputRecorder, InputRecording, KeyboardKey, ParseRecordingError, PointerAxis,
+    PointerButton, PointerMotion, RecordedDevice, RecordedEvent, RecordedEventKind, SwitchToggle,
+    TabletToolAxes, TabletToolButton, TabletToolProximity, TabletToolTip, TouchPosition,
+};
+
+/// Marker used to define the `InputBackend` types for replayed input.
+#[derive(Debug)]
+pub struct ReplayInput;
+
+/// Device of a replayed recording
+#[derive(Debug, Clone)]
+pub struct ReplayDevice(Arc<RecordedDevice>);
+
+impl ReplayDevice {
+    /// Returns the recorded description of this device
+    pub fn recorded(&self) -> &RecordedDevice {
+        &self.0
+    }
+}
+
+impl PartialEq for ReplayDevice {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ReplayDevice {}
+
+impl Hash for ReplayDevice {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state)
+    }
+}
+
+impl Device for ReplayDevice {
+    fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    fn name(&self) -> String {
+        self.0.name.clone()
+    }
+
+    fn has_capability(&self, capability: DeviceCapability) -> bool {
+        self.0.capabilities.contains(&capability)
+    }
+
+    fn usb_id(&self) -> Option<(u32, u32)> {
+        self.0.usb_id
+    }
+
+    fn syspath(&self) -> Option<PathBuf> {
+        self.0.syspath.clone()
+    }
+}
+
+// Replayed events were already processed by the recorded backend, there is nothing to configure.
+impl DeviceConfig for ReplayDevice {}
+
+/// Replayed input event carrying the recorded data of type `T`
+#[derive(Debug, Clone)]
+pub struct ReplayEvent<T> {
+    time: u64,
+    device: ReplayDevice,
+    data: T,
+}
+
+impl<T> ReplayEvent<T> {
+    /// Returns the recorded data of this event
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+}
+
+impl<T> Event<ReplayInput> for ReplayEvent<T> {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> ReplayDevice {
+        self.device.clone()
+    }
+}
+
+impl KeyboardKeyEvent<ReplayInput> for ReplayEvent<KeyboardKey> {
+    fn key_code(&self) -> Keycode {
+        self.data.key.into()
+    }
+
+    fn state(&self) -> KeyState {
+        self.data.state
+    }
+
+    fn count(&self) -> u32 {
+        self.data.count
+    }
+}
+
+impl PointerMotionEvent<ReplayInput> for ReplayEvent<PointerMotion> {
+    fn delta_x(&self) -> f64 {
+        self.data.delta.x
+    }
+
+    fn delta_y(&self) -> f64 {
+        self.data.delta.y
+    }
+
+    fn delta_x_unaccel(&self) -> f64 {
+        self.data.delta_unaccel.x
+    }
+
+    fn delta_y_unaccel(&self) -> f64 {
+        self.data.delta_unaccel.y
+    }
+}
+
+impl PointerButtonEvent<ReplayInput> for ReplayEvent<PointerButton> {
+    fn button_code(&self) -> u32 {
+        self.data.button
+    }
+
+    fn state(&self) -> ButtonState {
+        self.data.state
+    }
+}
+
+impl PointerAxisEvent<ReplayInput> for ReplayEvent<PointerAxis> {
+    fn amount(&self, axis: Axis) -> Option<f64> {
+        self.axis(axis).amount
+    }
+
+    fn amount_v120(&self, axis: Axis) -> Option<f64> {
+        self.axis(axis).amount_v120
+    }
+
+    fn source(&self) -> AxisSource {
+        self.data.source
+    }
+
+    fn relative_direction(&self, axis: Axis) -> AxisRelativeDirection {
+        self.axis(axis).relative_direction
+    }
+}
+
+impl ReplayEvent<PointerAxis> {
+    fn axis(&self, axis: Axis) -> &AxisValue {
+        match axis {
+            Axis::Horizontal => &self.data.horizontal,
+            Axis::Vertical => &self.data.vertical,
+        }
+    }
+}
+
+impl GestureBeginEvent<ReplayInput> for ReplayEvent<GestureBegin> {
+    fn fingers(&self) -> u32 {
+        self.data.fingers
+    }
+}
+impl GestureSwipeBeginEvent<ReplayInput> for ReplayEvent<GestureBegin> {}
+impl GesturePinchBeginEvent<ReplayInput> for ReplayEvent<GestureBegin> {}
+impl GestureHoldBeginEvent<ReplayInput> for ReplayEvent<GestureBegin> {}
+
+impl GestureEndEvent<ReplayInput> for ReplayEvent<GestureEnd> {
+    fn cancelled(&self) -> bool {
+        self.data.cancelled
+    }
+}
+impl GestureSwipeEndEvent<ReplayInput> for ReplayEvent<GestureEnd> {}
+impl GesturePinchEndEvent<ReplayInput> for ReplayEvent<GestureEnd> {}
+impl GestureHoldEndEvent<ReplayInput> for ReplayEvent<GestureEnd> {}
+
+impl GestureSwipeUpdateEvent<ReplayInput> for ReplayEvent<GestureSwipeUpdate> {
+    fn delta_x(&self) -> f64 {
+        self.data.delta.x
+    }
+
+    fn delta_y(&self) -> f64 {
+        self.data.delta.y
+    }
+}
+
+impl GesturePinchUpdateEvent<ReplayInput> for ReplayEvent<GesturePinchUpdate> {
+    fn delta_x(&self) -> f64 {
+        self.data.delta.x
+    }
+
+    fn delta_y(&self) -> f64 {
+        self.data.delta.y
+    }
+
+    fn scale(&self) -> f64 {
+        self.data.scale
+    }
+
+    fn rotation(&self) -> f64 {
+        self.data.rotation
+    }
+}
+
+/// Implements [`AbsolutePositionEvent`] for an event, given an expression for its [`AbsolutePosition`]
+macro_rules! absolute_position_event {
+    ($ty:ty, |$event:ident| $position:expr) => {
+        impl AbsolutePositionEvent<ReplayInput> for ReplayEvent<$ty> {
+            fn x(&self) -> f64 {
+                let $event = self;
+                $position.raw.x
+            }
+
+            fn y(&self) -> f64 {
+                let $event = self;
+                $position.raw.y
+            }
+
+            fn x_transformed(&self, width: i32) -> f64 {
+                let $event = self;
+                $position.normalized.x * width as f64
+            }
+
+            fn y_transformed(&self, height: i32) -> f64 {
+                let $event = self;
+                $position.normalized.y * height as f64
+            }
+        }
+    };
+}
+
+absolute_position_event!(AbsolutePosition, |event| event.data);
+absolute_position_event!(TouchPosition, |event| event.data.position);
+absolute_position_event!(TabletToolAxes, |event| event.data.position);
+absolute_position_event!(TabletToolProximity, |event| event.data.axes.position);
+absolute_position_event!(TabletToolTip, |event| event.data.axes.position);
+absolute_position_event!(TabletToolButton, |event| event.data.axes.position);
+
+impl PointerMotionAbsoluteEvent<ReplayInput> for ReplayEvent<AbsolutePosition> {}
+
+impl TouchEvent<ReplayInput> for ReplayEvent<TouchPosition> {
+    fn slot(&self) -> TouchSlot {
+        self.data.slot
+    }
+}
+impl TouchDownEvent<ReplayInput> for ReplayEvent<TouchPosition> {}
+impl TouchMotionEvent<ReplayInput> for ReplayEvent<TouchPosition> {}
+
+impl TouchEvent<ReplayInput> for ReplayEvent<TouchSlot> {
+    fn slot(&self) -> TouchSlot {
+        self.data
+    }
+}
+impl TouchUpEvent<ReplayInput> for ReplayEvent<TouchSlot> {}
+impl TouchCancelEvent<ReplayInput> for ReplayEvent<TouchSlot> {}
+
+impl TouchFrameEvent<ReplayInput> for ReplayEvent<()> {}
+
+/// Implements [`TabletToolEvent`] for an event, given an expression for its [`TabletToolAxes`]
+macro_rules! tablet_tool_event {
+    ($ty:ty, |$event:ident| $axes:expr) => {
+        impl TabletToolEvent<ReplayInput> for ReplayEvent<$ty> {
+            fn tool(&self) -> TabletToolDescriptor {
+                let $event = self;
+                $axes.tool.clone()
+            }
+
+            fn delta_x(&self) -> f64 {
+                let $event = self;
+                $axes.delta.x
+            }
+
+            fn delta_y(&self) -> f64 {
+                let $event = self;
+                $axes.delta.y
+            }
+
+            fn distance(&self) -> f64 {
+                let $event = self;
+                $axes.distance
+            }
+
+            fn distance_has_changed(&self) -> bool {
+                let $event = self;
+                $axes.changed.contains(ChangedTabletAxes::DISTANCE)
+            }
+
+            fn pressure(&self) -> f64 {
+                let $event = self;
+                $axes.pressure
+            }
+
+            fn pressure_has_changed(&self) -> bool {
+                let $event = self;
+                $axes.changed.contains(ChangedTabletAxes::PRESSURE)
+            }
+
+            fn slider_position(&self) -> f64 {
+                let $event = self;
+                $axes.slider
+            }
+
+            fn slider_has_changed(&self) -> bool {
+                let $event = self;
+                $axes.changed.contains(ChangedTabletAxes::SLIDER)
+            }
+
+            fn tilt_x(&self) -> f64 {
+                let $event = self;
+                $axes.tilt_x
+            }
+
+            fn tilt_x_has_changed(&self) -> bool {
+                let $event = self;
+                $axes.changed.contains(ChangedTabletAxes::TILT_X)
+            }
+
+            fn tilt_y(&self) -> f64 {
+                let $event = self;
+                $axes.tilt_y
+            }
+
+            fn tilt_y_has_changed(&self) -> bool {
+                let $event = self;
+                $axes.changed.contains(ChangedTabletAxes::TILT_Y)
+            }
+
+            fn rotation(&self) -> f64 {
+                let $event = self;
+                $axes.rotation
+            }
+
+            fn rotation_has_changed(&self) -> bool {
+                let $event = self;
+                $axes.changed.contains(ChangedTabletAxes::ROTATION)
+            }
+
+            fn wheel_delta(&self) -> f64 {
+                let $event = self;
+                $axes.wheel_delta
+            }
+
+            fn wheel_delta_discrete(&self) -> i32 {
+                let $event = self;
+                $axes.wheel_delta_discrete
+            }
+
+            fn wheel_has_changed(&self) -> bool {
+                let $event = self;
+                $axes.changed.contains(ChangedTabletAxes::WHEEL)
+            }
+        }
+    };
+}
+
+tablet_tool_event!(TabletToolAxes, |event| event.data);
+tablet_tool_event!(TabletToolProximity, |event| event.data.axes);
+tablet_tool_event!(TabletToolTip, |event| event.data.axes);
+tablet_tool_event!(TabletToolButton, |event| event.data.axes);
+
+impl TabletToolAxisEvent<ReplayInput> for ReplayEvent<TabletToolAxes> {}
+
+impl TabletToolProximityEvent<ReplayInput> for ReplayEvent<TabletToolProximity> {
+    fn state(&self) -> ProximityState {
+        self.data.state
+    }
+}
+
+impl TabletToolTipEvent<ReplayInput> for ReplayEvent<TabletToolTip> {
+    fn tip_state(&self) -> TabletToolTipState {
+        self.data.state
+    }
+}
+
+impl TabletToolButtonEvent<ReplayInput> for ReplayEvent<TabletToolButton> {
+    fn button(&self) -> u32 {
+        self.data.button
+    }
+
+    fn seat_button_count(&self) -> u32 {
+        self.data.seat_button_count
+    }
+
+    fn button_state(&self) -> ButtonState {
+        self.data.state
+    }
+}
+
+impl SwitchToggleEvent<ReplayInput> for ReplayEvent<SwitchToggle> {
+    fn switch(&self) -> Option<Switch> {
+        self.data.switch
+    }
+
+    fn state(&self) -> SwitchState {
+        self.data.state
+    }
+}
+
+impl InputBackend for ReplayInput {
+    type Device = ReplayDevice;
+    type KeyboardKeyEvent = ReplayEvent<KeyboardKey>;
+    type PointerAxisEvent = ReplayEvent<PointerAxis>;
+    type PointerButtonEvent = ReplayEvent<PointerButton>;
+    type PointerMotionEvent = ReplayEvent<PointerMotion>;
+    type PointerMotionAbsoluteEvent = ReplayEvent<AbsolutePosition>;
+
+    type GestureSwipeBeginEvent = ReplayEvent<GestureBegin>;
+    type GestureSwipeUpdateEvent = ReplayEvent<GestureSwipeUpdate>;
+    type GestureSwipeEndEvent = ReplayEvent<GestureEnd>;
+    type GesturePinchBeginEvent = ReplayEvent<GestureBegin>;
+    type GesturePinchUpdateEvent = ReplayEvent<GesturePinchUpdate>;
+    type GesturePinchEndEvent = ReplayEvent<GestureEnd>;
+    type GestureHoldBeginEvent = ReplayEvent<GestureBegin>;
+    type GestureHoldEndEvent = ReplayEvent<GestureEnd>;
+
+    type TouchDownEvent = ReplayEvent<TouchPosition>;
+    type TouchUpEvent = ReplayEvent<TouchSlot>;
+    type TouchMotionEvent = ReplayEvent<TouchPosition>;
+    type TouchCancelEvent = ReplayEvent<TouchSlot>;
+    type TouchFrameEvent = ReplayEvent<()>;
+    type TabletToolAxisEvent = ReplayEvent<TabletToolAxes>;
+    type TabletToolProximityEvent = ReplayEvent<TabletToolProximity>;
+    type TabletToolTipEvent = ReplayEvent<TabletToolTip>;
+    type TabletToolButtonEvent = ReplayEvent<TabletToolButton>;
+
+    type SwitchToggleEvent = ReplayEvent<SwitchToggle>;
+
+    type SpecialEvent = UnusedEvent;
+}
+
+/// Timing used to replay a recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayTiming {
+    /// Keep the original delays between the recorded events
+    Original,
+    /// Emit all events as fast as possible
+    Immediate,
+}
+
+/// [`InputBackend`] replaying an [`InputRecording`]
+///
+/// Can be inserted into a calloop event loop, which will emit the recorded events according to the
+/// [`ReplayTiming`] and remove the source once all events have been emitted.
+#[derive(Debug)]
+pub struct InputReplay {
+    devices: Vec<ReplayDevice>,
+    events: VecDeque<RecordedEvent>,
+    timing: ReplayTiming,
+    timer: Timer,
+    start: Option<(Instant, u64)>,
+}
+
+/// Error returned by [`InputReplay::new`] if an event refers to a device missing from the recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Recorded event {event} refers to unknown device {device}")]
+pub struct UnknownDeviceError {
+    /// Index of the event in [`InputRecording::events`]
+    pub event: usize,
+    /// Device index of the event
+    pub device: usize,
+}
+
+impl InputReplay {
+    /// Create a new replay of `recording`
+    ///
+    /// Fails if any event refers to a device not contained in the recording. Recordings parsed from text are
+    /// always valid, but recordings can also be assembled manually.
+    pub fn new(recording: InputRecording, timing: ReplayTiming) -> Result<Self, UnknownDeviceError> {
+        if let Some((index, event)) = recording
+            .events
+            .iter()
+            .enumerate()
+            .find(|(_, event)| event.device >= recording.devices.len())
+        {
+            return Err(UnknownDeviceError {
+                event: index,
+                device: event.device,
+            });
+        }
+
+        Ok(InputReplay {
+            devices: recording
+                .devices
+                .into_iter()
+                .map(|device| ReplayDevice(Arc::new(device)))
+                .collect(),
+            events: recording.events.into(),
+            timing,
+            timer: Timer::immediate(),
+            start: None,
+        })
+    }
+
+    /// Returns the timing used when inserted into an event loop
+    pub fn timing(&self) -> ReplayTiming {
+        self.timing
+    }
+
+    /// Returns the number of events not yet replayed
+    pub fn remaining(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns the next event, regardless of its timing
+    pub fn next_event(&mut self) -> Option<InputEvent<ReplayInput>> {
+        let event = self.events.pop_front()?;
+        Some(replay_event(&self.devices, event))
+    }
+}
+
+impl Iterator for InputReplay {
+    type Item = InputEvent<ReplayInput>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event()
+    }
+}
+
+fn replay_event(devices: &[ReplayDevice], event: RecordedEvent) -> InputEvent<ReplayInput> {
+    // device indices are validated in `InputReplay::new`
+    let device = devices[event.device].clone();
+    let time = event.time;
+    macro_rules! wrap {
+        ($data:expr) => {
+            ReplayEvent {
+                time,
+                device: device.clone(),
+                data: $data,
+            }
+        };
+    }
+
+    match event.kind {
+        RecordedEventKind::DeviceAdded => InputEvent::DeviceAdded { device },
+        RecordedEventKind::DeviceRemoved => InputEvent::DeviceRemoved { device },
+        RecordedEventKind::Keyboard(data) => InputEvent::Keyboard { event: wrap!(data) },
+        RecordedEventKind::PointerMotion(data) => InputEvent::PointerMotion { event: wrap!(data) },
+        RecordedEventKind::PointerMotionAbsolute(data) => {
+            InputEvent::PointerMotionAbsolute { event: wrap!(data) }
+        }
+        RecordedEventKind::PointerButton(data) => InputEvent::PointerButton { event: wrap!(data) },
+        RecordedEventKind::PointerAxis(data) => InputEvent::PointerAxis { event: wrap!(data) },
+        RecordedEventKind::GestureSwipeBegin(data) => InputEvent::GestureSwipeBegin { event: wrap!(data) },
+        RecordedEventKind::GestureSwipeUpdate(data) => InputEvent::GestureSwipeUpdate { event: wrap!(data) },
+        RecordedEventKind::GestureSwipeEnd(data) => InputEvent::GestureSwipeEnd { event: wrap!(data) },
+        RecordedEventKind::GesturePinchBegin(data) => InputEvent::GesturePinchBegin { event: wrap!(data) },
+        RecordedEventKind::GesturePinchUpdate(data) => InputEvent::GesturePinchUpdate { event: wrap!(data) },
+        RecordedEventKind::GesturePinchEnd(data) => InputEvent::GesturePinchEnd { event: wrap!(data) },
+        RecordedEventKind::GestureHoldBegin(data) => InputEvent::GestureHoldBegin { event: wrap!(data) },
+        RecordedEventKind::GestureHoldEnd(data) => InputEvent::GestureHoldEnd { event: wrap!(data) },
+        RecordedEventKind::TouchDown(data) => InputEvent::TouchDown { event: wrap!(data) },
+        RecordedEventKind::TouchMotion(data) => InputEvent::TouchMotion { event: wrap!(data) },
+        RecordedEventKind::TouchUp(data) => InputEvent::TouchUp { event: wrap!(data) },
+        RecordedEventKind::TouchCancel(data) => InputEvent::TouchCancel { event: wrap!(data) },
+        RecordedEventKind::TouchFrame => InputEvent::TouchFrame { event: wrap!(()) },
+        RecordedEventKind::TabletToolAxis(data) => InputEvent::TabletToolAxis { event: wrap!(data) },
+        RecordedEventKind::TabletToolProximity(data) => {
+            InputEvent::TabletToolProximity { event: wrap!(data) }
+        }
+        RecordedEventKind::TabletToolTip(data) => InputEvent::TabletToolTip { event: wrap!(data) },
+        RecordedEventKind::TabletToolButton(data) => InputEvent::TabletToolButton { event: wrap!(data) },
+        RecordedEventKind::SwitchToggle(data) => InputEvent::SwitchToggle { event: wrap!(data) },
+    }
+}
+
+impl EventSource for InputReplay {
+    type Event = InputEvent<ReplayInput>;
+    type Metadata = ();
+    type Ret = ();
+    type Error = io::Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: F,
+    ) -> Result<PostAction, Self::Error>
+    where
+        F: FnMut(Self::Event, &mut ()),
+    {
+        let devices = &self.devices;
+        let events = &mut self.events;
+        let start = &mut self.start;
+        let timing = self.timing;
+
+        self.timer.process_events(readiness, token, |deadline, _| {
+            while let Some(event) = events.front() {
+                if timing == ReplayTiming::Original {
+                    let (start_instant, start_time) = *start.get_or_insert((deadline, event.time));
+                    let due = start_instant + Duration::from_micros(event.time.saturating_sub(start_time));
+                    if due > deadline {
+                        return TimeoutAction::ToInstant(due);
+                    }
+                }
+
+                let event = events.pop_front().unwrap();
+                callback(replay_event(devices, event), &mut ());
+            }
+
+            TimeoutAction::Drop
+        })
+    }
+
+    fn register(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        self.timer.register(poll, token_factory)
+    }
+
+    fn reregister(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        self.timer.reregister(poll, token_factory)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        self.timer.unregister(poll)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{Logical, Point, Size};
+
+    const RECORDING: &str = r#"
+device 0 id="event3" name="Test \"keyboard\"" capabilities=keyboard,pointer usb=046d:c52b
+device 1 id="event7" name="Touchscreen" capabilities=touch syspath="/sys/devices/virtual/input/event7"
+0 0 device-added
+0 1 device-added
+1000 0 keyboard key=30 state=pressed count=1
+1500 0 keyboard key=30 state=released count=0
+2000 0 pointer-motion dx=1.5 dy=-2 ux=1 uy=-1.25
+2500 0 pointer-axis source=wheel v=15 v-v120=120 v-direction=inverted
+3000 1 touch-down slot=0 x=10 y=20 nx=0.25 ny=0.5
+3000 1 touch-frame
+3500 1 touch-up slot=0
+4000 0 switch-toggle switch=lid state=on
+"#;
+
+    #[test]
+    fn parse_and_replay() {
+        let recording: InputRecording = RECORDING.parse().unwrap();
+        assert_eq!(recording.devices.len(), 2);
+        assert_eq!(recording.devices[0].name, "Test \"keyboard\"");
+        assert_eq!(recording.devices[0].usb_id, Some((0x046d, 0xc52b)));
+        assert_eq!(recording.events.len(), 10);
+
+        let mut replay = InputReplay::new(recording, ReplayTiming::Immediate).unwrap();
+        assert!(matches!(
+            replay.next_event(),
+            Some(InputEvent::DeviceAdded { .. })
+        ));
+        assert!(matches!(
+            replay.next_event(),
+            Some(InputEvent::DeviceAdded { .. })
+        ));
+        let Some(InputEvent::Keyboard { event }) = replay.next_event() else {
+            panic!("expected keyboard event");
+        };
+        assert_eq!(event.time(), 1000);
+        assert_eq!(event.key_code(), Keycode::new(30));
+        assert_eq!(event.state(), KeyState::Pressed);
+        assert_eq!(event.device().name(), "Test \"keyboard\"");
+
+        let Some(InputEvent::TouchDown { event }) = replay.nth(3) else {
+            panic!("expected touch down event");
+        };
+        assert_eq!(event.slot(), Some(0).into());
+        assert_eq!(
+            event.position_transformed(Size::from((1000, 800))),
+            Point::<f64, Logical>::from((250.0, 400.0))
+        );
+        assert!(event.device().has_capability(DeviceCapability::Touch));
+    }
+
+    #[test]
+    fn replay_into_seat() {
+        use crate::input::{
+            pointer::{ButtonEvent, MotionEvent},
+            testing::{TestEvent, TestFocus, TestState},
+        };
+        use crate::utils::SERIAL_COUNTER;
+
+        const BTN_LEFT: u32 = 0x110;
+        let recording: InputRecording = r#"
+device 0 id="event4" name="Mouse" capabilities=pointer
+0 0 device-added
+1000 0 pointer-motion-absolute x=100 y=80 nx=0.1 ny=0.1
+2000 0 pointer-button button=272 state=pressed
+3000 0 pointer-motion-absolute x=300 y=240 nx=0.3 ny=0.3
+4000 0 pointer-button button=272 state=released
+"#
+        .parse()
+        .unwrap();
+
+        let (mut state, mut seat) = TestState::new();
+        let pointer = seat.add_pointer();
+        let mut replay = InputReplay::new(recording, ReplayTiming::Immediate).unwrap();
+        let mut grab_start = Vec::new();
+        while let Some(event) = replay.next_event() {
+            let serial = SERIAL_COUNTER.next_serial();
+            match event {
+                InputEvent::PointerMotionAbsolute { event } => {
+                    let motion = MotionEvent {
+                        location: event.position_transformed(Size::from((1000, 800))),
+                        serial,
+                        time: event.time_msec(),
+                    };
+                    pointer.motion(&mut state, Some((TestFocus, Point::default())), &motion);
+                }
+                InputEvent::PointerButton { event } => {
+                    let button = ButtonEvent {
+                        serial,
+                        time: event.time_msec(),
+                        button: event.button_code(),
+                        state: event.state(),
+                    };
+                    pointer.button(&mut state, &button);
+                    grab_start.push(pointer.grab_start_data().map(|start_data| start_data.location));
+                }
+                _ => continue,
+            }
+            pointer.frame(&mut state);
+        }
+
+        // pressing the button starts an implicit grab at the pointer location, releasing it ends the grab
+        assert_eq!(grab_start, [Some(Point::from((100.0, 80.0))), None]);
+        assert!(!pointer.is_grabbed());
+        assert_eq!(pointer.current_location(), Point::from((300.0, 240.0)));
+        assert_eq!(pointer.current_focus(), Some(TestFocus));
+        assert_eq!(
+            state.take_events(),
+            [
+                TestEvent::PointerMotion((100.0, 80.0).into()),
+                TestEvent::PointerFrame,
+                TestEvent::PointerButton(BTN_LEFT, ButtonState::Pressed),
+                TestEvent::PointerFrame,
+                TestEvent::PointerMotion((300.0, 240.0).into()),
+                TestEvent::PointerFrame,
+                TestEvent::PointerButton(BTN_LEFT, ButtonState::Released),
+                TestEvent::PointerFrame,
+            ]
+        );
+    }
+
+    #[test]
+    fn record_round_trip() {
+        let recording: InputRecording = RECORDING.parse().unwrap();
+
+        let mut recorder = InputRecorder::new((1000, 800).into());
+        for event in InputReplay::new(recording.clone(), ReplayTiming::Immediate).unwrap() {
+            recorder.record(&event);
+        }
+        let recorded = recorder.into_recording();
+        assert_eq!(recorded, recording);
+
+        let text = recorded.to_string();
+        assert_eq!(text.parse::<InputRecording>().unwrap(), recording);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = "device 0 id=\"a\" name=\"b\"\n5 1 touch-frame"
+            .parse::<InputRecording>()
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+
+        let err = "device 0 id=\"a\" name=\"b\"\n5 0 keyboard key=1"
+            .parse::<InputRecording>()
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    fn unknown_device() {
+        let mut recording: InputRecording = RECORDING.parse().unwrap();
+        recording.events[4].device = 2;
+        assert_eq!(
+            InputReplay::new(recording, ReplayTiming::Immediate).unwrap_err(),
+            UnknownDeviceError { event: 4, device: 2 }
+        );
+    }
+}
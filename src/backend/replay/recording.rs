@@ -0,0 +1,1140 @@
+use std::{borrow::Cow, collections::HashMap, fmt, path::PathBuf, str::FromStr};
+
+use bitflags::bitflags;
+
+use crate::{
+    backend::input::{
+        AbsolutePositionEvent, Axis, AxisRelativeDirection, AxisSource, ButtonState, Device,
+        DeviceCapability, Event, GestureBeginEvent, GestureEndEvent, GesturePinchUpdateEvent,
+        GestureSwipeUpdateEvent, InputBackend, InputEvent, KeyState, KeyboardKeyEvent, PointerAxisEvent,
+        PointerButtonEvent, PointerMotionEvent, ProximityState, Switch, SwitchState, SwitchToggleEvent,
+        TabletToolButtonEvent, TabletToolCapabilities, TabletToolDescriptor, TabletToolEvent,
+        TabletToolProximityEvent, TabletToolTipEvent, TabletToolTipState, TabletToolType, TouchEvent,
+        TouchSlot,
+    },
+    utils::{Logical, Point, Raw, Size},
+};
+
+/// A recorded stream of input events
+///
+/// The recording can be written to and parsed from a line based text format using its
+/// [`Display`](fmt::Display) and [`FromStr`] implementations.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputRecording {
+    /// Devices referenced by the recorded events
+    pub devices: Vec<RecordedDevice>,
+    /// Recorded events in order
+    pub events: Vec<RecordedEvent>,
+}
+
+/// Description of a recorded input device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedDevice {
+    /// Identifier of the device, see [`Device::id`]
+    pub id: String,
+    /// Name of the device, see [`Device::name`]
+    pub name: String,
+    /// Capabilities of the device
+    pub capabilities: Vec<DeviceCapability>,
+    /// USB vendor and product id, see [`Device::usb_id`]
+    pub usb_id: Option<(u32, u32)>,
+    /// Syspath of the device, see [`Device::syspath`]
+    pub syspath: Option<PathBuf>,
+}
+
+/// A recorded input event
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedEvent {
+    /// Timestamp of the event in microseconds
+    pub time: u64,
+    /// Index of the device in [`InputRecording::devices`]
+    pub device: usize,
+    /// Kind and data of the event
+    pub kind: RecordedEventKind,
+}
+
+/// Kind and data of a recorded event, mirroring the variants of [`InputEvent`]
+#[allow(missing_docs)] // self explanatory
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedEventKind {
+    DeviceAdded,
+    DeviceRemoved,
+    Keyboard(KeyboardKey),
+    PointerMotion(PointerMotion),
+    PointerMotionAbsolute(AbsolutePosition),
+    PointerButton(PointerButton),
+    PointerAxis(PointerAxis),
+    GestureSwipeBegin(GestureBegin),
+    GestureSwipeUpdate(GestureSwipeUpdate),
+    GestureSwipeEnd(GestureEnd),
+    GesturePinchBegin(GestureBegin),
+    GesturePinchUpdate(GesturePinchUpdate),
+    GesturePinchEnd(GestureEnd),
+    GestureHoldBegin(GestureBegin),
+    GestureHoldEnd(GestureEnd),
+    TouchDown(TouchPosition),
+    TouchMotion(TouchPosition),
+    TouchUp(TouchSlot),
+    TouchCancel(TouchSlot),
+    TouchFrame,
+    TabletToolAxis(TabletToolAxes),
+    TabletToolProximity(TabletToolProximity),
+    TabletToolTip(TabletToolTip),
+    TabletToolButton(TabletToolButton),
+    SwitchToggle(SwitchToggle),
+}
+
+/// Recorded keyboard key event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyboardKey {
+    /// Raw keycode, see [`KeyboardKeyEvent::key_code`]
+    pub key: u32,
+    /// State of the key
+    pub state: KeyState,
+    /// Total number of keys pressed on all devices on the associated seat
+    pub count: u32,
+}
+
+/// Recorded relative pointer motion
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointerMotion {
+    /// Accelerated delta
+    pub delta: Point<f64, Logical>,
+    /// Unaccelerated delta
+    pub delta_unaccel: Point<f64, Logical>,
+}
+
+/// Recorded absolute position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AbsolutePosition {
+    /// Untransformed position, see [`AbsolutePositionEvent::position`]
+    pub raw: Point<f64, Raw>,
+    /// Transformed position normalized to the `[0, 1]` range
+    pub normalized: Point<f64, Raw>,
+}
+
+/// Recorded pointer button event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointerButton {
+    /// Button code, see [`PointerButtonEvent::button_code`]
+    pub button: u32,
+    /// State of the button
+    pub state: ButtonState,
+}
+
+/// Recorded pointer axis event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointerAxis {
+    /// Source of the scroll event
+    pub source: AxisSource,
+    /// Horizontal axis
+    pub horizontal: AxisValue,
+    /// Vertical axis
+    pub vertical: AxisValue,
+}
+
+/// Recorded value of a single scroll axis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisValue {
+    /// Continuous amount, see [`PointerAxisEvent::amount`]
+    pub amount: Option<f64>,
+    /// High-resolution discrete amount, see [`PointerAxisEvent::amount_v120`]
+    pub amount_v120: Option<f64>,
+    /// Relative direction of the physical motion
+    pub relative_direction: AxisRelativeDirection,
+}
+
+/// Recorded start of a gesture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GestureBegin {
+    /// Number of fingers
+    pub fingers: u32,
+}
+
+/// Recorded end of a gesture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GestureEnd {
+    /// Whether the gesture was cancelled
+    pub cancelled: bool,
+}
+
+/// Recorded swipe gesture update
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureSwipeUpdate {
+    /// Delta of the center of the fingers
+    pub delta: Point<f64, Logical>,
+}
+
+/// Recorded pinch gesture update
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GesturePinchUpdate {
+    /// Delta of the center of the fingers
+    pub delta: Point<f64, Logical>,
+    /// Absolute scale compared to the begin event
+    pub scale: f64,
+    /// Relative angle in degrees since the last event
+    pub rotation: f64,
+}
+
+/// Recorded touch down or motion event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchPosition {
+    /// Multi-touch slot
+    pub slot: TouchSlot,
+    /// Position of the touch point
+    pub position: AbsolutePosition,
+}
+
+bitflags! {
+    /// Tablet tool axes that changed in a recorded tablet tool event
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ChangedTabletAxes: u32 {
+        /// Distance axis
+        const DISTANCE = 1;
+        /// Pressure axis
+        const PRESSURE = 2;
+        /// Slider axis
+        const SLIDER = 4;
+        /// Tilt axis in x direction
+        const TILT_X = 8;
+        /// Tilt axis in y direction
+        const TILT_Y = 16;
+        /// Z-rotation axis
+        const ROTATION = 32;
+        /// Wheel axis
+        const WHEEL = 64;
+    }
+}
+
+/// Recorded state of a tablet tool
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabletToolAxes {
+    /// The tool
+    pub tool: TabletToolDescriptor,
+    /// Position of the tool
+    pub position: AbsolutePosition,
+    /// Delta of the position
+    pub delta: Point<f64, Logical>,
+    /// Distance axis
+    pub distance: f64,
+    /// Pressure axis
+    pub pressure: f64,
+    /// Slider axis
+    pub slider: f64,
+    /// Tilt axis in x direction
+    pub tilt_x: f64,
+    /// Tilt axis in y direction
+    pub tilt_y: f64,
+    /// Z-rotation axis
+    pub rotation: f64,
+    /// Wheel delta in degrees
+    pub wheel_delta: f64,
+    /// Wheel delta in discrete steps
+    pub wheel_delta_discrete: i32,
+    /// Axes that changed in this event
+    pub changed: ChangedTabletAxes,
+}
+
+/// Recorded tablet tool proximity event
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabletToolProximity {
+    /// State of the tool
+    pub axes: TabletToolAxes,
+    /// New proximity state
+    pub state: ProximityState,
+}
+
+/// Recorded tablet tool tip event
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabletToolTip {
+    /// State of the tool
+    pub axes: TabletToolAxes,
+    /// New tip state
+    pub state: TabletToolTipState,
+}
+
+/// Recorded tablet tool button event
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabletToolButton {
+    /// State of the tool
+    pub axes: TabletToolAxes,
+    /// Button code
+    pub button: u32,
+    /// Total number of buttons pressed on all devices on the associated seat
+    pub seat_button_count: u32,
+    /// State of the button
+    pub state: ButtonState,
+}
+
+/// Recorded switch toggle event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwitchToggle {
+    /// The switch, `None` if unknown
+    pub switch: Option<Switch>,
+    /// New state of the switch
+    pub state: SwitchState,
+}
+
+/// Records input events of any [`InputBackend`] into an [`InputRecording`]
+///
+/// Absolute positions are recorded in their raw form and transformed into the `coordinate_space`
+/// given at creation, which should match the space the compositor transforms them into.
+#[derive(Debug)]
+pub struct InputRecorder {
+    recording: InputRecording,
+    devices: HashMap<String, usize>,
+    coordinate_space: Size<i32, Logical>,
+    last_time: u64,
+}
+
+impl InputRecorder {
+    /// Create a new recorder transforming absolute positions into `coordinate_space`
+    pub fn new(coordinate_space: Size<i32, Logical>) -> Self {
+        InputRecorder {
+            recording: InputRecording::default(),
+            devices: HashMap::new(),
+            coordinate_space,
+            last_time: 0,
+        }
+    }
+
+    /// Set the coordinate space absolute positions are transformed into
+    pub fn set_coordinate_space(&mut self, coordinate_space: Size<i32, Logical>) {
+        self.coordinate_space = coordinate_space;
+    }
+
+    /// Returns the events recorded so far
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+
+    /// Stop recording and return the recorded events
+    pub fn into_recording(self) -> InputRecording {
+        self.recording
+    }
+
+    /// Record an event
+    ///
+    /// Events of devices, that were added before recording started, implicitly add the device
+    /// to the recording. Backend specific [`InputEvent::Special`] events are ignored.
+    pub fn record<B: InputBackend>(&mut self, event: &InputEvent<B>) {
+        let (time, device, kind) = match event {
+            InputEvent::DeviceAdded { device } => {
+                // a device with the same id might have been re-added
+                self.devices.remove(&device.id());
+                (
+                    self.last_time,
+                    self.device(device),
+                    RecordedEventKind::DeviceAdded,
+                )
+            }
+            InputEvent::DeviceRemoved { device } => {
+                let index = self.device(device);
+                self.devices.remove(&device.id());
+                (self.last_time, index, RecordedEventKind::DeviceRemoved)
+            }
+            InputEvent::Keyboard { event } => self.event(
+                event,
+                RecordedEventKind::Keyboard(KeyboardKey {
+                    key: event.key_code().raw(),
+                    state: event.state(),
+                    count: event.count(),
+                }),
+            ),
+            InputEvent::PointerMotion { event } => self.event(
+                event,
+                RecordedEventKind::PointerMotion(PointerMotion {
+                    delta: event.delta(),
+                    delta_unaccel: event.delta_unaccel(),
+                }),
+            ),
+            InputEvent::PointerMotionAbsolute { event } => {
+                let position = self.position(event);
+                self.event(event, RecordedEventKind::PointerMotionAbsolute(position))
+            }
+            InputEvent::PointerButton { event } => self.event(
+                event,
+                RecordedEventKind::PointerButton(PointerButton {
+                    button: event.button_code(),
+                    state: event.state(),
+                }),
+            ),
+            InputEvent::PointerAxis { event } => {
+                let axis = |axis| AxisValue {
+                    amount: event.amount(axis),
+                    amount_v120: event.amount_v120(axis),
+                    relative_direction: event.relative_direction(axis),
+                };
+                let kind = RecordedEventKind::PointerAxis(PointerAxis {
+                    source: event.source(),
+                    horizontal: axis(Axis::Horizontal),
+                    vertical: axis(Axis::Vertical),
+                });
+                self.event(event, kind)
+            }
+            InputEvent::GestureSwipeBegin { event } => self.event(
+                event,
+                RecordedEventKind::GestureSwipeBegin(GestureBegin {
+                    fingers: event.fingers(),
+                }),
+            ),
+            InputEvent::GestureSwipeUpdate { event } => self.event(
+                event,
+                RecordedEventKind::GestureSwipeUpdate(GestureSwipeUpdate {
+                    delta: GestureSwipeUpdateEvent::delta(event),
+                }),
+            ),
+            InputEvent::GestureSwipeEnd { event } => self.event(
+                event,
+                RecordedEventKind::GestureSwipeEnd(GestureEnd {
+                    cancelled: event.cancelled(),
+                }),
+            ),
+            InputEvent::GesturePinchBegin { event } => self.event(
+                event,
+                RecordedEventKind::GesturePinchBegin(GestureBegin {
+                    fingers: event.fingers(),
+                }),
+            ),
+            InputEvent::GesturePinchUpdate { event } => self.event(
+                event,
+                RecordedEventKind::GesturePinchUpdate(GesturePinchUpdate {
+                    delta: GesturePinchUpdateEvent::delta(event),
+                    scale: event.scale(),
+                    rotation: event.rotation(),
+                }),
+            ),
+            InputEvent::GesturePinchEnd { event } => self.event(
+                event,
+                RecordedEventKind::GesturePinchEnd(GestureEnd {
+                    cancelled: event.cancelled(),
+                }),
+            ),
+            InputEvent::GestureHoldBegin { event } => self.event(
+                event,
+                RecordedEventKind::GestureHoldBegin(GestureBegin {
+                    fingers: event.fingers(),
+                }),
+            ),
+            InputEvent::GestureHoldEnd { event } => self.event(
+                event,
+                RecordedEventKind::GestureHoldEnd(GestureEnd {
+                    cancelled: event.cancelled(),
+                }),
+            ),
+            InputEvent::TouchDown { event } => {
+                let position = TouchPosition {
+                    slot: event.slot(),
+                    position: self.position(event),
+                };
+                self.event(event, RecordedEventKind::TouchDown(position))
+            }
+            InputEvent::TouchMotion { event } => {
+                let position = TouchPosition {
+                    slot: event.slot(),
+                    position: self.position(event),
+                };
+                self.event(event, RecordedEventKind::TouchMotion(position))
+            }
+            InputEvent::TouchUp { event } => self.event(event, RecordedEventKind::TouchUp(event.slot())),
+            InputEvent::TouchCancel { event } => {
+                self.event(event, RecordedEventKind::TouchCancel(event.slot()))
+            }
+            InputEvent::TouchFrame { event } => self.event(event, RecordedEventKind::TouchFrame),
+            InputEvent::TabletToolAxis { event } => {
+                let axes = self.tablet_axes(event);
+                self.event(event, RecordedEventKind::TabletToolAxis(axes))
+            }
+            InputEvent::TabletToolProximity { event } => {
+                let kind = RecordedEventKind::TabletToolProximity(TabletToolProximity {
+                    axes: self.tablet_axes(event),
+                    state: event.state(),
+                });
+                self.event(event, kind)
+            }
+            InputEvent::TabletToolTip { event } => {
+                let kind = RecordedEventKind::TabletToolTip(TabletToolTip {
+                    axes: self.tablet_axes(event),
+                    state: event.tip_state(),
+                });
+                self.event(event, kind)
+            }
+            InputEvent::TabletToolButton { event } => {
+                let kind = RecordedEventKind::TabletToolButton(TabletToolButton {
+                    axes: self.tablet_axes(event),
+                    button: event.button(),
+                    seat_button_count: event.seat_button_count(),
+                    state: event.button_state(),
+                });
+                self.event(event, kind)
+            }
+            InputEvent::SwitchToggle { event } => self.event(
+                event,
+                RecordedEventKind::SwitchToggle(SwitchToggle {
+                    switch: event.switch(),
+                    state: event.state(),
+                }),
+            ),
+            InputEvent::Special(_) => return,
+        };
+
+        self.last_time = time;
+        self.recording.events.push(RecordedEvent { time, device, kind });
+    }
+
+    fn event<B: InputBackend>(
+        &mut self,
+        event: &impl Event<B>,
+        kind: RecordedEventKind,
+    ) -> (u64, usize, RecordedEventKind) {
+        (event.time(), self.device(&event.device()), kind)
+    }
+
+    fn device<D: Device>(&mut self, device: &D) -> usize {
+        let id = device.id();
+        if let Some(index) = self.devices.get(&id) {
+            return *index;
+        }
+
+        const CAPABILITIES: [DeviceCapability; 7] = [
+            DeviceCapability::Keyboard,
+            DeviceCapability::Pointer,
+            DeviceCapability::Touch,
+            DeviceCapability::TabletTool,
+            DeviceCapability::TabletPad,
+            DeviceCapability::Gesture,
+            DeviceCapability::Switch,
+        ];
+
+        let index = self.recording.devices.len();
+        self.recording.devices.push(RecordedDevice {
+            id: id.clone(),
+            name: device.name(),
+            capabilities: CAPABILITIES
+                .into_iter()
+                .filter(|cap| device.has_capability(*cap))
+                .collect(),
+            usb_id: device.usb_id(),
+            syspath: device.syspath(),
+        });
+        self.devices.insert(id, index);
+        index
+    }
+
+    fn position<B: InputBackend>(&self, event: &impl AbsolutePositionEvent<B>) -> AbsolutePosition {
+        let size = self.coordinate_space;
+        let transformed = event.position_transformed(size);
+        AbsolutePosition {
+            raw: event.position(),
+            normalized: (
+                transformed.x / size.w.max(1) as f64,
+                transformed.y / size.h.max(1) as f64,
+            )
+                .into(),
+        }
+    }
+
+    fn tablet_axes<B: InputBackend>(&self, event: &impl TabletToolEvent<B>) -> TabletToolAxes {
+        let mut changed = ChangedTabletAxes::empty();
+        changed.set(ChangedTabletAxes::DISTANCE, event.distance_has_changed());
+        changed.set(ChangedTabletAxes::PRESSURE, event.pressure_has_changed());
+        changed.set(ChangedTabletAxes::SLIDER, event.slider_has_changed());
+        changed.set(ChangedTabletAxes::TILT_X, event.tilt_x_has_changed());
+        changed.set(ChangedTabletAxes::TILT_Y, event.tilt_y_has_changed());
+        changed.set(ChangedTabletAxes::ROTATION, event.rotation_has_changed());
+        changed.set(ChangedTabletAxes::WHEEL, event.wheel_has_changed());
+
+        TabletToolAxes {
+            tool: event.tool(),
+            position: self.position(event),
+            delta: TabletToolEvent::delta(event),
+            distance: event.distance(),
+            pressure: event.pressure(),
+            slider: event.slider_position(),
+            tilt_x: event.tilt_x(),
+            tilt_y: event.tilt_y(),
+            rotation: event.rotation(),
+            wheel_delta: event.wheel_delta(),
+            wheel_delta_discrete: event.wheel_delta_discrete(),
+            changed,
+        }
+    }
+}
+
+/// Error returned when parsing an [`InputRecording`] fails
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid input recording at line {line}: {reason}")]
+pub struct ParseRecordingError {
+    /// Line number (starting at 1) of the invalid line
+    pub line: usize,
+    /// Description of the problem
+    pub reason: String,
+}
+
+/// Conversion of enums from and to the tokens used in the text format
+trait Token: Sized {
+    fn token(&self) -> &'static str;
+    fn from_token(token: &str) -> Option<Self>;
+}
+
+macro_rules! tokens {
+    ($ty:ty { $($variant:path => $token:literal),* $(,)? }) => {
+        impl Token for $ty {
+            fn token(&self) -> &'static str {
+                match self {
+                    $($variant => $token,)*
+                }
+            }
+
+            fn from_token(token: &str) -> Option<Self> {
+                match token {
+                    $($token => Some($variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+tokens!(KeyState {
+    KeyState::Pressed => "pressed",
+    KeyState::Released => "released",
+});
+tokens!(ButtonState {
+    ButtonState::Pressed => "pressed",
+    ButtonState::Released => "released",
+});
+tokens!(AxisSource {
+    AxisSource::Finger => "finger",
+    AxisSource::Continuous => "continuous",
+    AxisSource::Wheel => "wheel",
+    AxisSource::WheelTilt => "wheel-tilt",
+});
+tokens!(AxisRelativeDirection {
+    AxisRelativeDirection::Identical => "identical",
+    AxisRelativeDirection::Inverted => "inverted",
+});
+tokens!(ProximityState {
+    ProximityState::In => "in",
+    ProximityState::Out => "out",
+});
+tokens!(TabletToolTipState {
+    TabletToolTipState::Down => "down",
+    TabletToolTipState::Up => "up",
+});
+tokens!(TabletToolType {
+    TabletToolType::Pen => "pen",
+    TabletToolType::Eraser => "eraser",
+    TabletToolType::Brush => "brush",
+    TabletToolType::Pencil => "pencil",
+    TabletToolType::Airbrush => "airbrush",
+    TabletToolType::Mouse => "mouse",
+    TabletToolType::Lens => "lens",
+    TabletToolType::Totem => "totem",
+    TabletToolType::Unknown => "unknown",
+});
+tokens!(Switch {
+    Switch::Lid => "lid",
+    Switch::TabletMode => "tablet-mode",
+});
+tokens!(SwitchState {
+    SwitchState::Off => "off",
+    SwitchState::On => "on",
+});
+tokens!(DeviceCapability {
+    DeviceCapability::Keyboard => "keyboard",
+    DeviceCapability::Pointer => "pointer",
+    DeviceCapability::Touch => "touch",
+    DeviceCapability::TabletTool => "tablet-tool",
+    DeviceCapability::TabletPad => "tablet-pad",
+    DeviceCapability::Gesture => "gesture",
+    DeviceCapability::Switch => "switch",
+});
+
+/// Writes the `key=value` fields of a single line
+struct FieldWriter<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+}
+
+impl FieldWriter<'_, '_> {
+    fn field(&mut self, key: &str, value: impl fmt::Display) -> fmt::Result {
+        write!(self.f, " {key}={value}")
+    }
+
+    fn opt_field(&mut self, key: &str, value: Option<impl fmt::Display>) -> fmt::Result {
+        match value {
+            Some(value) => self.field(key, value),
+            None => Ok(()),
+        }
+    }
+
+    fn token(&mut self, key: &str, value: &impl Token) -> fmt::Result {
+        self.field(key, value.token())
+    }
+
+    fn string(&mut self, key: &str, value: &str) -> fmt::Result {
+        write!(self.f, " {key}=\"")?;
+        for c in value.chars() {
+            match c {
+                '"' => self.f.write_str("\\\"")?,
+                '\\' => self.f.write_str("\\\\")?,
+                '\n' => self.f.write_str("\\n")?,
+                c => write!(self.f, "{c}")?,
+            }
+        }
+        self.f.write_str("\"")
+    }
+
+    fn point<K>(&mut self, prefix: &str, point: Point<f64, K>) -> fmt::Result {
+        self.field(&format!("{prefix}x"), point.x)?;
+        self.field(&format!("{prefix}y"), point.y)
+    }
+
+    fn position(&mut self, position: &AbsolutePosition) -> fmt::Result {
+        self.point("", position.raw)?;
+        self.point("n", position.normalized)
+    }
+
+    fn slot(&mut self, slot: TouchSlot) -> fmt::Result {
+        let slot = i32::from(slot);
+        self.opt_field("slot", (slot >= 0).then_some(slot))
+    }
+
+    fn axis(&mut self, prefix: &str, axis: &AxisValue) -> fmt::Result {
+        self.opt_field(prefix, axis.amount)?;
+        self.opt_field(&format!("{prefix}-v120"), axis.amount_v120)?;
+        if axis.relative_direction != AxisRelativeDirection::Identical {
+            self.token(&format!("{prefix}-direction"), &axis.relative_direction)?;
+        }
+        Ok(())
+    }
+
+    fn tablet(&mut self, axes: &TabletToolAxes) -> fmt::Result {
+        self.token("tool", &axes.tool.tool_type)?;
+        self.field("serial", axes.tool.hardware_serial)?;
+        self.field("wacom-id", axes.tool.hardware_id_wacom)?;
+        self.field("capabilities", axes.tool.capabilities.bits())?;
+        self.position(&axes.position)?;
+        self.point("d", axes.delta)?;
+        self.field("distance", axes.distance)?;
+        self.field("pressure", axes.pressure)?;
+        self.field("slider", axes.slider)?;
+        self.field("tilt-x", axes.tilt_x)?;
+        self.field("tilt-y", axes.tilt_y)?;
+        self.field("rotation", axes.rotation)?;
+        self.field("wheel", axes.wheel_delta)?;
+        self.field("wheel-discrete", axes.wheel_delta_discrete)?;
+        self.field("changed", axes.changed.bits())
+    }
+}
+
+impl fmt::Display for InputRecording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# smithay input recording")?;
+
+        for (index, device) in self.devices.iter().enumerate() {
+            write!(f, "device {index}")?;
+            let mut w = FieldWriter { f: &mut *f };
+            w.string("id", &device.id)?;
+            w.string("name", &device.name)?;
+            let capabilities = device
+                .capabilities
+                .iter()
+                .map(|cap| cap.token())
+                .collect::<Vec<_>>()
+                .join(",");
+            w.field("capabilities", capabilities)?;
+            if let Some((vendor, product)) = device.usb_id {
+                w.field("usb", format_args!("{vendor:04x}:{product:04x}"))?;
+            }
+            if let Some(syspath) = device.syspath.as_ref() {
+                w.string("syspath", &syspath.to_string_lossy())?;
+            }
+            writeln!(f)?;
+        }
+
+        for event in &self.events {
+            write!(f, "{} {} {}", event.time, event.device, event.kind.name())?;
+            let mut w = FieldWriter { f: &mut *f };
+            match &event.kind {
+                RecordedEventKind::DeviceAdded
+                | RecordedEventKind::DeviceRemoved
+                | RecordedEventKind::TouchFrame => {}
+                RecordedEventKind::Keyboard(key) => {
+                    w.field("key", key.key)?;
+                    w.token("state", &key.state)?;
+                    w.field("count", key.count)?;
+                }
+                RecordedEventKind::PointerMotion(motion) => {
+                    w.point("d", motion.delta)?;
+                    w.point("u", motion.delta_unaccel)?;
+                }
+                RecordedEventKind::PointerMotionAbsolute(position) => w.position(position)?,
+                RecordedEventKind::PointerButton(button) => {
+                    w.field("button", button.button)?;
+                    w.token("state", &button.state)?;
+                }
+                RecordedEventKind::PointerAxis(axis) => {
+                    w.token("source", &axis.source)?;
+                    w.axis("h", &axis.horizontal)?;
+                    w.axis("v", &axis.vertical)?;
+                }
+                RecordedEventKind::GestureSwipeBegin(begin)
+                | RecordedEventKind::GesturePinchBegin(begin)
+                | RecordedEventKind::GestureHoldBegin(begin) => w.field("fingers", begin.fingers)?,
+                RecordedEventKind::GestureSwipeEnd(end)
+                | RecordedEventKind::GesturePinchEnd(end)
+                | RecordedEventKind::GestureHoldEnd(end) => w.field("cancelled", end.cancelled)?,
+                RecordedEventKind::GestureSwipeUpdate(update) => w.point("d", update.delta)?,
+                RecordedEventKind::GesturePinchUpdate(update) => {
+                    w.point("d", update.delta)?;
+                    w.field("scale", update.scale)?;
+                    w.field("rotation", update.rotation)?;
+                }
+                RecordedEventKind::TouchDown(touch) | RecordedEventKind::TouchMotion(touch) => {
+                    w.slot(touch.slot)?;
+                    w.position(&touch.position)?;
+                }
+                RecordedEventKind::TouchUp(slot) | RecordedEventKind::TouchCancel(slot) => w.slot(*slot)?,
+                RecordedEventKind::TabletToolAxis(axes) => w.tablet(axes)?,
+                RecordedEventKind::TabletToolProximity(proximity) => {
+                    w.token("state", &proximity.state)?;
+                    w.tablet(&proximity.axes)?;
+                }
+                RecordedEventKind::TabletToolTip(tip) => {
+                    w.token("state", &tip.state)?;
+                    w.tablet(&tip.axes)?;
+                }
+                RecordedEventKind::TabletToolButton(button) => {
+                    w.field("button", button.button)?;
+                    w.field("seat-count", button.seat_button_count)?;
+                    w.token("state", &button.state)?;
+                    w.tablet(&button.axes)?;
+                }
+                RecordedEventKind::SwitchToggle(toggle) => {
+                    if let Some(switch) = toggle.switch.as_ref() {
+                        w.token("switch", switch)?;
+                    }
+                    w.token("state", &toggle.state)?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl RecordedEventKind {
+    fn name(&self) -> &'static str {
+        match self {
+            RecordedEventKind::DeviceAdded => "device-added",
+            RecordedEventKind::DeviceRemoved => "device-removed",
+            RecordedEventKind::Keyboard(_) => "keyboard",
+            RecordedEventKind::PointerMotion(_) => "pointer-motion",
+            RecordedEventKind::PointerMotionAbsolute(_) => "pointer-motion-absolute",
+            RecordedEventKind::PointerButton(_) => "pointer-button",
+            RecordedEventKind::PointerAxis(_) => "pointer-axis",
+            RecordedEventKind::GestureSwipeBegin(_) => "gesture-swipe-begin",
+            RecordedEventKind::GestureSwipeUpdate(_) => "gesture-swipe-update",
+            RecordedEventKind::GestureSwipeEnd(_) => "gesture-swipe-end",
+            RecordedEventKind::GesturePinchBegin(_) => "gesture-pinch-begin",
+            RecordedEventKind::GesturePinchUpdate(_) => "gesture-pinch-update",
+            RecordedEventKind::GesturePinchEnd(_) => "gesture-pinch-end",
+            RecordedEventKind::GestureHoldBegin(_) => "gesture-hold-begin",
+            RecordedEventKind::GestureHoldEnd(_) => "gesture-hold-end",
+            RecordedEventKind::TouchDown(_) => "touch-down",
+            RecordedEventKind::TouchMotion(_) => "touch-motion",
+            RecordedEventKind::TouchUp(_) => "touch-up",
+            RecordedEventKind::TouchCancel(_) => "touch-cancel",
+            RecordedEventKind::TouchFrame => "touch-frame",
+            RecordedEventKind::TabletToolAxis(_) => "tablet-tool-axis",
+            RecordedEventKind::TabletToolProximity(_) => "tablet-tool-proximity",
+            RecordedEventKind::TabletToolTip(_) => "tablet-tool-tip",
+            RecordedEventKind::TabletToolButton(_) => "tablet-tool-button",
+            RecordedEventKind::SwitchToggle(_) => "switch-toggle",
+        }
+    }
+}
+
+/// `key=value` fields of a single line
+struct Fields<'a> {
+    fields: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+impl<'a> Fields<'a> {
+    /// Splits `input` at whitespace outside of quoted strings
+    fn split(input: &'a str) -> Result<(Vec<&'a str>, Fields<'a>), String> {
+        let mut words = Vec::new();
+        let mut fields = Vec::new();
+        let mut rest = input.trim_start();
+
+        while !rest.is_empty() {
+            let Some((key, value)) = rest
+                .split_once('=')
+                .filter(|(key, _)| !key.contains(char::is_whitespace))
+            else {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                words.push(&rest[..end]);
+                rest = rest[end..].trim_start();
+                continue;
+            };
+
+            if let Some(quoted) = value.strip_prefix('"') {
+                let mut unescaped = String::new();
+                let mut chars = quoted.char_indices();
+                let end = loop {
+                    match chars.next() {
+                        Some((i, '"')) => break i,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, 'n')) => unescaped.push('\n'),
+                            Some((_, c)) => unescaped.push(c),
+                            None => return Err(format!("unterminated string for `{key}`")),
+                        },
+                        Some((_, c)) => unescaped.push(c),
+                        None => return Err(format!("unterminated string for `{key}`")),
+                    }
+                };
+                fields.push((key, Cow::Owned(unescaped)));
+                rest = quoted[end + 1..].trim_start();
+            } else {
+                let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                fields.push((key, Cow::Borrowed(&value[..end])));
+                rest = value[end..].trim_start();
+            }
+        }
+
+        Ok((words, Fields { fields }))
+    }
+
+    fn raw(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|(k, _)| *k == key).map(|(_, v)| &**v)
+    }
+
+    fn opt<T: FromStr>(&self, key: &str) -> Result<Option<T>, String> {
+        self.raw(key)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("invalid value `{value}` for `{key}`"))
+            })
+            .transpose()
+    }
+
+    fn get<T: FromStr>(&self, key: &str) -> Result<T, String> {
+        self.opt(key)?.ok_or_else(|| format!("missing `{key}`"))
+    }
+
+    fn opt_token<T: Token>(&self, key: &str) -> Result<Option<T>, String> {
+        self.raw(key)
+            .map(|value| T::from_token(value).ok_or_else(|| format!("invalid value `{value}` for `{key}`")))
+            .transpose()
+    }
+
+    fn token<T: Token>(&self, key: &str) -> Result<T, String> {
+        self.opt_token(key)?.ok_or_else(|| format!("missing `{key}`"))
+    }
+
+    fn point<K>(&self, prefix: &str) -> Result<Point<f64, K>, String> {
+        Ok((self.get(&format!("{prefix}x"))?, self.get(&format!("{prefix}y"))?).into())
+    }
+
+    fn position(&self) -> Result<AbsolutePosition, String> {
+        Ok(AbsolutePosition {
+            raw: self.point("")?,
+            normalized: self.point("n")?,
+        })
+    }
+
+    fn slot(&self) -> Result<TouchSlot, String> {
+        Ok(self.opt::<u32>("slot")?.into())
+    }
+
+    fn axis(&self, prefix: &str) -> Result<AxisValue, String> {
+        Ok(AxisValue {
+            amount: self.opt(prefix)?,
+            amount_v120: self.opt(&format!("{prefix}-v120"))?,
+            relative_direction: self
+                .opt_token(&format!("{prefix}-direction"))?
+                .unwrap_or(AxisRelativeDirection::Identical),
+        })
+    }
+
+    fn tablet(&self) -> Result<TabletToolAxes, String> {
+        Ok(TabletToolAxes {
+            tool: TabletToolDescriptor {
+                tool_type: self.token("tool")?,
+                hardware_serial: self.get("serial")?,
+                hardware_id_wacom: self.get("wacom-id")?,
+                capabilities: TabletToolCapabilities::from_bits_retain(self.get("capabilities")?),
+            },
+            position: self.position()?,
+            delta: self.point("d")?,
+            distance: self.get("distance")?,
+            pressure: self.get("pressure")?,
+            slider: self.get("slider")?,
+            tilt_x: self.get("tilt-x")?,
+            tilt_y: self.get("tilt-y")?,
+            rotation: self.get("rotation")?,
+            wheel_delta: self.get("wheel")?,
+            wheel_delta_discrete: self.get("wheel-discrete")?,
+            changed: ChangedTabletAxes::from_bits_retain(self.get("changed")?),
+        })
+    }
+}
+
+impl FromStr for InputRecording {
+    type Err = ParseRecordingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut recording = InputRecording::default();
+
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            parse_line(&mut recording, line).map_err(|reason| ParseRecordingError {
+                line: index + 1,
+                reason,
+            })?;
+        }
+
+        Ok(recording)
+    }
+}
+
+fn parse_line(recording: &mut InputRecording, line: &str) -> Result<(), String> {
+    let (words, fields) = Fields::split(line)?;
+
+    if let ["device", index] = words[..] {
+        if index.parse::<usize>().ok() != Some(recording.devices.len()) {
+            return Err(format!("unexpected device index `{index}`"));
+        }
+
+        let capabilities = fields
+            .raw("capabilities")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|cap| !cap.is_empty())
+            .map(|cap| DeviceCapability::from_token(cap).ok_or_else(|| format!("unknown capability `{cap}`")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let usb_id = fields
+            .raw("usb")
+            .map(|usb| {
+                usb.split_once(':')
+                    .and_then(|(vendor, product)| {
+                        Some((
+                            u32::from_str_radix(vendor, 16).ok()?,
+                            u32::from_str_radix(product, 16).ok()?,
+                        ))
+                    })
+                    .ok_or_else(|| format!("invalid usb id `{usb}`"))
+            })
+            .transpose()?;
+
+        recording.devices.push(RecordedDevice {
+            id: fields.get("id")?,
+            name: fields.get("name")?,
+            capabilities,
+            usb_id,
+            syspath: fields.raw("syspath").map(PathBuf::from),
+        });
+        return Ok(());
+    }
+
+    let [time, device, name] = words[..] else {
+        return Err(String::from("expected `<time> <device> <event>`"));
+    };
+    let time = time.parse().map_err(|_| format!("invalid time `{time}`"))?;
+    let device = device
+        .parse::<usize>()
+        .ok()
+        .filter(|device| *device < recording.devices.len())
+        .ok_or_else(|| format!("unknown device `{device}`"))?;
+
+    let kind = match name {
+        "device-added" => RecordedEventKind::DeviceAdded,
+        "device-removed" => RecordedEventKind::DeviceRemoved,
+        "keyboard" => RecordedEventKind::Keyboard(KeyboardKey {
+            key: fields.get("key")?,
+            state: fields.token("state")?,
+            count: fields.get("count")?,
+        }),
+        "pointer-motion" => RecordedEventKind::PointerMotion(PointerMotion {
+            delta: fields.point("d")?,
+            delta_unaccel: fields.point("u")?,
+        }),
+        "pointer-motion-absolute" => RecordedEventKind::PointerMotionAbsolute(fields.position()?),
+        "pointer-button" => RecordedEventKind::PointerButton(PointerButton {
+            button: fields.get("button")?,
+            state: fields.token("state")?,
+        }),
+        "pointer-axis" => RecordedEventKind::PointerAxis(PointerAxis {
+            source: fields.token("source")?,
+            horizontal: fields.axis("h")?,
+            vertical: fields.axis("v")?,
+        }),
+        "gesture-swipe-begin" => RecordedEventKind::GestureSwipeBegin(GestureBegin {
+            fingers: fields.get("fingers")?,
+        }),
+        "gesture-swipe-update" => RecordedEventKind::GestureSwipeUpdate(GestureSwipeUpdate {
+            delta: fields.point("d")?,
+        }),
+        "gesture-swipe-end" => RecordedEventKind::GestureSwipeEnd(GestureEnd {
+            cancelled: fields.get("cancelled")?,
+        }),
+        "gesture-pinch-begin" => RecordedEventKind::GesturePinchBegin(GestureBegin {
+            fingers: fields.get("fingers")?,
+        }),
+        "gesture-pinch-update" => RecordedEventKind::GesturePinchUpdate(GesturePinchUpdate {
+            delta: fields.point("d")?,
+            scale: fields.get("scale")?,
+            rotation: fields.get("rotation")?,
+        }),
+        "gesture-pinch-end" => RecordedEventKind::GesturePinchEnd(GestureEnd {
+            cancelled: fields.get("cancelled")?,
+        }),
+        "gesture-hold-begin" => RecordedEventKind::GestureHoldBegin(GestureBegin {
+            fingers: fields.get("fingers")?,
+        }),
+        "gesture-hold-end" => RecordedEventKind::GestureHoldEnd(GestureEnd {
+            cancelled: fields.get("cancelled")?,
+        }),
+        "touch-down" => RecordedEventKind::TouchDown(TouchPosition {
+            slot: fields.slot()?,
+            position: fields.position()?,
+        }),
+        "touch-motion" => RecordedEventKind::TouchMotion(TouchPosition {
+            slot: fields.slot()?,
+            position: fields.position()?,
+        }),
+        "touch-up" => RecordedEventKind::TouchUp(fields.slot()?),
+        "touch-cancel" => RecordedEventKind::TouchCancel(fields.slot()?),
+        "touch-frame" => RecordedEventKind::TouchFrame,
+        "tablet-tool-axis" => RecordedEventKind::TabletToolAxis(fields.tablet()?),
+        "tablet-tool-proximity" => RecordedEventKind::TabletToolProximity(TabletToolProximity {
+            axes: fields.tablet()?,
+            state: fields.token("state")?,
+        }),
+        "tablet-tool-tip" => RecordedEventKind::TabletToolTip(TabletToolTip {
+            axes: fields.tablet()?,
+            state: fields.token("state")?,
+        }),
+        "tablet-tool-button" => RecordedEventKind::TabletToolButton(TabletToolButton {
+            axes: fields.tablet()?,
+            button: fields.get("button")?,
+            seat_button_count: fields.get("seat-count")?,
+            state: fields.token("state")?,
+        }),
+        "switch-toggle" => RecordedEventKind::SwitchToggle(SwitchToggle {
+            switch: fields.opt_token("switch")?,
+            state: fields.token("state")?,
+        }),
+        name => return Err(format!("unknown event `{name}`")),
+    };
+
+    recording.events.push(RecordedEvent { time, device, kind });
+    Ok(())
+}
@@ -61,6 +61,7 @@ use tracing::{debug, debug_span, info, warn};
 /// given handler of any changes. Can be used to provide hot-plug functionality for gpus and
 /// attached monitors.
 pub struct UdevBackend {
+    seat: String,
     devices: UdevDevices,
     monitor: MonitorSocket,
     token: Option<Token>,
@@ -72,6 +73,7 @@ impl fmt::Debug for UdevBackend {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use udev::AsRaw;
         f.debug_struct("UdevBackend")
+            .field("seat", &self.seat)
             .field("devices", &self.devices)
             .field("monitor", &format!("MonitorSocket ({:?})", self.monitor.as_raw()))
             .finish()
@@ -111,6 +113,7 @@ impl UdevBackend {
 
         drop(_guard);
         Ok(UdevBackend {
+            seat: seat.to_owned(),
             devices,
             monitor,
             token: None,
@@ -158,6 +161,10 @@ impl EventSource for UdevBackend {
             match event.event_type() {
                 // New device
                 EventType::Add => {
+                    if seat_name(&event) != self.seat {
+                        debug!("Ignoring device of seat {}", seat_name(&event));
+                        continue;
+                    }
                     if let (Some(path), Some(devnum)) = (event.devnode(), event.devnum()) {
                         info!("New device: #{} at {}", devnum, path.display());
                         if self.devices.insert(devnum, path.to_path_buf()).is_none() {
@@ -331,17 +338,51 @@ fn gpus_for_seat<S: AsRef<str>>(seat: S) -> io::Result<Vec<Device>> {
     enumerator.match_sysname("card[0-9]*")?;
     let gpus = enumerator
         .scan_devices()?
-        .filter(|device| {
-            device
-                .property_value("ID_SEAT")
-                .map(|x| x.to_os_string())
-                .unwrap_or_else(|| OsString::from("seat0"))
-                == *seat.as_ref()
-        })
+        .filter(|device| seat_name(device) == seat.as_ref())
         .collect::<Vec<_>>();
     Ok(gpus)
 }
 
+/// Returns the name of the seat a device is assigned to
+///
+/// The seat is set by the `ID_SEAT` udev property, devices without it belong to `seat0`.
+pub fn seat_name(device: &Device) -> String {
+    device
+        .property_value("ID_SEAT")
+        .map(|seat| seat.to_string_lossy().into_owned())
+        .unwrap_or_else(|| String::from("seat0"))
+}
+
+/// Returns the names of all seats available on the system
+///
+/// Seats are defined by devices tagged with `master-of-seat`, `seat0` always exists.
+pub fn seats() -> io::Result<Vec<String>> {
+    let mut enumerator = Enumerator::new()?;
+    enumerator.match_tag("master-of-seat")?;
+    let mut seats = enumerator
+        .scan_devices()?
+        .map(|device| seat_name(&device))
+        .chain(std::iter::once(String::from("seat0")))
+        .collect::<Vec<_>>();
+    seats.sort();
+    seats.dedup();
+    Ok(seats)
+}
+
+/// Returns the seat a GPU named by it's [`dev_t`] is assigned to.
+///
+/// Each [`UdevBackend`] only reports the GPUs of its own seat, this allows a compositor driving
+/// multiple seats to find the seat a GPU belongs to, see [`seat_name`].
+pub fn gpu_seat(dev: dev_t) -> io::Result<Option<String>> {
+    let mut enumerator = Enumerator::new()?;
+    enumerator.match_subsystem("drm")?;
+    enumerator.match_sysname("card[0-9]*")?;
+    Ok(enumerator
+        .scan_devices()?
+        .find(|device| device.devnum() == Some(dev))
+        .map(|device| seat_name(&device)))
+}
+
 /// Returns the loaded driver for a device named by it's [`dev_t`].
 pub fn driver(dev: dev_t) -> io::Result<Option<OsString>> {
     let mut enumerator = Enumerator::new()?;
@@ -7,9 +7,9 @@ use winit::{
 
 use crate::backend::input::{
     self, AbsolutePositionEvent, Axis, AxisRelativeDirection, AxisSource, ButtonState, Device,
-    DeviceCapability, Event, InputBackend, KeyState, KeyboardKeyEvent, Keycode, PointerAxisEvent,
-    PointerButtonEvent, PointerMotionAbsoluteEvent, TouchCancelEvent, TouchDownEvent, TouchEvent,
-    TouchMotionEvent, TouchSlot, TouchUpEvent, UnusedEvent,
+    DeviceCapability, EmulatedDeviceConfig, Event, InputBackend, KeyState, KeyboardKeyEvent, Keycode,
+    PointerAxisEvent, PointerButtonEvent, PointerMotionAbsoluteEvent, TouchCancelEvent, TouchDownEvent,
+    TouchEvent, TouchMotionEvent, TouchSlot, TouchUpEvent, UnusedEvent, emulated_device_config,
 };
 
 /// Marker used to define the `InputBackend` types for the winit backend.
@@ -17,8 +17,21 @@ use crate::backend::input::{
 pub struct WinitInput;
 
 /// Virtual input device used by the backend to associate input events
-#[derive(PartialEq, Eq, Hash, Debug)]
-pub struct WinitVirtualDevice;
+///
+/// Natural scrolling, left-handed buttons and the calibration matrix can be configured
+/// through [`DeviceConfig`](input::DeviceConfig) and are applied to the generated events.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct WinitVirtualDevice {
+    pub(crate) config: EmulatedDeviceConfig,
+}
+
+impl WinitVirtualDevice {
+    pub(crate) fn new() -> Self {
+        WinitVirtualDevice {
+            config: EmulatedDeviceConfig::default(),
+        }
+    }
+}
 
 impl Device for WinitVirtualDevice {
     fn id(&self) -> String {
@@ -45,10 +58,13 @@ impl Device for WinitVirtualDevice {
     }
 }
 
+emulated_device_config!(WinitVirtualDevice);
+
 /// Winit-Backend internal event wrapping `winit`'s types into a [`KeyboardKeyEvent`]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct WinitKeyboardInputEvent {
     pub(crate) time: u64,
+    pub(crate) device: WinitVirtualDevice,
     pub(crate) key: u32,
     pub(crate) count: u32,
     pub(crate) state: ElementState,
@@ -60,7 +76,7 @@ impl Event<WinitInput> for WinitKeyboardInputEvent {
     }
 
     fn device(&self) -> WinitVirtualDevice {
-        WinitVirtualDevice
+        self.device.clone()
     }
 }
 
@@ -82,6 +98,7 @@ impl KeyboardKeyEvent<WinitInput> for WinitKeyboardInputEvent {
 #[derive(Debug, Clone)]
 pub struct WinitMouseMovedEvent {
     pub(crate) time: u64,
+    pub(crate) device: WinitVirtualDevice,
     pub(crate) position: RelativePosition,
     pub(crate) global_position: PhysicalPosition<f64>,
 }
@@ -92,7 +109,7 @@ impl Event<WinitInput> for WinitMouseMovedEvent {
     }
 
     fn device(&self) -> WinitVirtualDevice {
-        WinitVirtualDevice
+        self.device.clone()
     }
 }
 
@@ -116,9 +133,10 @@ impl AbsolutePositionEvent<WinitInput> for WinitMouseMovedEvent {
 }
 
 /// Winit-Backend internal event wrapping `winit`'s types into a [`PointerAxisEvent`]
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct WinitMouseWheelEvent {
     pub(crate) time: u64,
+    pub(crate) device: WinitVirtualDevice,
     pub(crate) delta: MouseScrollDelta,
 }
 
@@ -128,7 +146,7 @@ impl Event<WinitInput> for WinitMouseWheelEvent {
     }
 
     fn device(&self) -> WinitVirtualDevice {
-        WinitVirtualDevice
+        self.device.clone()
     }
 }
 
@@ -164,9 +182,10 @@ impl PointerAxisEvent<WinitInput> for WinitMouseWheelEvent {
 }
 
 /// Winit-Backend internal event wrapping `winit`'s types into a [`PointerButtonEvent`]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct WinitMouseInputEvent {
     pub(crate) time: u64,
+    pub(crate) device: WinitVirtualDevice,
     pub(crate) button: WinitMouseButton,
     pub(crate) state: ElementState,
     pub(crate) is_x11: bool,
@@ -178,7 +197,7 @@ impl Event<WinitInput> for WinitMouseInputEvent {
     }
 
     fn device(&self) -> WinitVirtualDevice {
-        WinitVirtualDevice
+        self.device.clone()
     }
 }
 
@@ -209,6 +228,7 @@ impl PointerButtonEvent<WinitInput> for WinitMouseInputEvent {
 #[derive(Debug, Clone)]
 pub struct WinitTouchStartedEvent {
     pub(crate) time: u64,
+    pub(crate) device: WinitVirtualDevice,
     pub(crate) position: RelativePosition,
     pub(crate) global_position: PhysicalPosition<f64>,
     pub(crate) id: u64,
@@ -220,7 +240,7 @@ impl Event<WinitInput> for WinitTouchStartedEvent {
     }
 
     fn device(&self) -> WinitVirtualDevice {
-        WinitVirtualDevice
+        self.device.clone()
     }
 }
 
@@ -254,6 +274,7 @@ impl AbsolutePositionEvent<WinitInput> for WinitTouchStartedEvent {
 #[derive(Debug, Clone)]
 pub struct WinitTouchMovedEvent {
     pub(crate) time: u64,
+    pub(crate) device: WinitVirtualDevice,
     pub(crate) position: RelativePosition,
     pub(crate) global_position: PhysicalPosition<f64>,
     pub(crate) id: u64,
@@ -265,7 +286,7 @@ impl Event<WinitInput> for WinitTouchMovedEvent {
     }
 
     fn device(&self) -> WinitVirtualDevice {
-        WinitVirtualDevice
+        self.device.clone()
     }
 }
 
@@ -296,9 +317,10 @@ impl AbsolutePositionEvent<WinitInput> for WinitTouchMovedEvent {
 }
 
 /// Winit-Backend internal event wrapping `winit`'s types into a `TouchUpEvent`
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct WinitTouchEndedEvent {
     pub(crate) time: u64,
+    pub(crate) device: WinitVirtualDevice,
     pub(crate) id: u64,
 }
 
@@ -308,7 +330,7 @@ impl Event<WinitInput> for WinitTouchEndedEvent {
     }
 
     fn device(&self) -> WinitVirtualDevice {
-        WinitVirtualDevice
+        self.device.clone()
     }
 }
 
@@ -321,9 +343,10 @@ impl TouchEvent<WinitInput> for WinitTouchEndedEvent {
 }
 
 /// Winit-Backend internal event wrapping `winit`'s types into a [`TouchCancelEvent`]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct WinitTouchCancelledEvent {
     pub(crate) time: u64,
+    pub(crate) device: WinitVirtualDevice,
     pub(crate) id: u64,
 }
 
@@ -333,7 +356,7 @@ impl Event<WinitInput> for WinitTouchCancelledEvent {
     }
 
     fn device(&self) -> WinitVirtualDevice {
-        WinitVirtualDevice
+        self.device.clone()
     }
 }
 
@@ -30,8 +30,10 @@ use winit::platform::scancode::PhysicalKeyExtScancode;
 use winit::raw_window_handle::{HasWindowHandle, RawWindowHandle};
 use winit::{
     application::ApplicationHandler,
-    dpi::LogicalSize,
-    event::{ButtonSource, ElementState, PointerKind, PointerSource, WindowEvent},
+    dpi::{LogicalSize, PhysicalPosition},
+    event::{
+        ButtonSource, ElementState, MouseButton, MouseScrollDelta, PointerKind, PointerSource, WindowEvent,
+    },
     event_loop::{
         ActiveEventLoop, EventLoop,
         pump_events::{EventLoopExtPumpEvents, PumpStatus},
@@ -123,6 +125,7 @@ where
         window: None,
         window_create_error: None,
         is_x11: false,
+        device: WinitVirtualDevice::new(),
     };
     let mut initial_events = Vec::new();
     while window_event_loop_inner.window.is_none() {
@@ -393,6 +396,7 @@ struct WinitEventLoopInner {
     key_counter: u32,
     is_x11: bool,
     scale_factor: f64,
+    device: WinitVirtualDevice,
 }
 
 /// Abstracted event loop of a [`WinitWindow`].
@@ -466,7 +470,7 @@ impl<F: FnMut(WinitEvent)> ApplicationHandler for WinitEventLoopApp<'_, F> {
             }
 
             (self.callback)(WinitEvent::Input(InputEvent::DeviceAdded {
-                device: WinitVirtualDevice,
+                device: self.inner.device.clone(),
             }));
         }
     }
@@ -521,6 +525,7 @@ impl<F: FnMut(WinitEvent)> ApplicationHandler for WinitEventLoopApp<'_, F> {
                 let event = InputEvent::Keyboard {
                     event: WinitKeyboardInputEvent {
                         time: self.timestamp(),
+                        device: self.inner.device.clone(),
                         key: scancode,
                         count: self.inner.key_counter,
                         state: event.state,
@@ -532,6 +537,7 @@ impl<F: FnMut(WinitEvent)> ApplicationHandler for WinitEventLoopApp<'_, F> {
                 let size = window.surface_size();
                 let x = position.x / size.width as f64;
                 let y = position.y / size.height as f64;
+                let (x, y) = self.inner.device.config.calibrate(x, y);
                 let relative_position = RelativePosition::new(x, y);
 
                 match source {
@@ -539,6 +545,7 @@ impl<F: FnMut(WinitEvent)> ApplicationHandler for WinitEventLoopApp<'_, F> {
                         let event = InputEvent::PointerMotionAbsolute {
                             event: WinitMouseMovedEvent {
                                 time: self.timestamp(),
+                                device: self.inner.device.clone(),
                                 position: relative_position,
                                 global_position: position,
                             },
@@ -549,6 +556,7 @@ impl<F: FnMut(WinitEvent)> ApplicationHandler for WinitEventLoopApp<'_, F> {
                         let event = InputEvent::TouchMotion {
                             event: WinitTouchMovedEvent {
                                 time: self.timestamp(),
+                                device: self.inner.device.clone(),
                                 position: relative_position,
                                 global_position: position,
                                 id: finger_id.into_raw() as u64,
@@ -561,10 +569,21 @@ impl<F: FnMut(WinitEvent)> ApplicationHandler for WinitEventLoopApp<'_, F> {
                     PointerSource::Unknown => {}
                 }
             }
-            WindowEvent::MouseWheel { delta, .. } => {
+            WindowEvent::MouseWheel { mut delta, .. } => {
+                if self.inner.device.config.natural_scroll() {
+                    delta = match delta {
+                        MouseScrollDelta::LineDelta(x, y) => MouseScrollDelta::LineDelta(-x, -y),
+                        MouseScrollDelta::PixelDelta(pos) => {
+                            MouseScrollDelta::PixelDelta(PhysicalPosition::new(-pos.x, -pos.y))
+                        }
+                        delta => delta,
+                    };
+                }
+
                 let event = InputEvent::PointerAxis {
                     event: WinitMouseWheelEvent {
                         time: self.timestamp(),
+                        device: self.inner.device.clone(),
                         delta,
                     },
                 };
@@ -577,10 +596,19 @@ impl<F: FnMut(WinitEvent)> ApplicationHandler for WinitEventLoopApp<'_, F> {
                 ..
             } => {
                 match button {
-                    ButtonSource::Mouse(button) => {
+                    ButtonSource::Mouse(mut button) => {
+                        if self.inner.device.config.left_handed() {
+                            button = match button {
+                                MouseButton::Left => MouseButton::Right,
+                                MouseButton::Right => MouseButton::Left,
+                                button => button,
+                            };
+                        }
+
                         let event = InputEvent::PointerButton {
                             event: WinitMouseInputEvent {
                                 time: self.timestamp(),
+                                device: self.inner.device.clone(),
                                 button,
                                 state,
                                 is_x11: self.inner.is_x11,
@@ -593,9 +621,11 @@ impl<F: FnMut(WinitEvent)> ApplicationHandler for WinitEventLoopApp<'_, F> {
                             let size = window.surface_size();
                             let x = position.x / size.width as f64;
                             let y = position.y / size.width as f64;
+                            let (x, y) = self.inner.device.config.calibrate(x, y);
                             let event = InputEvent::TouchDown {
                                 event: WinitTouchStartedEvent {
                                     time: self.timestamp(),
+                                    device: self.inner.device.clone(),
                                     global_position: position,
                                     position: RelativePosition::new(x, y),
                                     id: finger_id.into_raw() as u64,
@@ -608,9 +638,11 @@ impl<F: FnMut(WinitEvent)> ApplicationHandler for WinitEventLoopApp<'_, F> {
                             let size = window.surface_size();
                             let x = position.x / size.width as f64;
                             let y = position.y / size.width as f64;
+                            let (x, y) = self.inner.device.config.calibrate(x, y);
                             let event = InputEvent::TouchMotion {
                                 event: WinitTouchMovedEvent {
                                     time: self.timestamp(),
+                                    device: self.inner.device.clone(),
                                     position: RelativePosition::new(x, y),
                                     global_position: position,
                                     id: finger_id.into_raw() as u64,
@@ -621,6 +653,7 @@ impl<F: FnMut(WinitEvent)> ApplicationHandler for WinitEventLoopApp<'_, F> {
                             let event = InputEvent::TouchUp {
                                 event: WinitTouchEndedEvent {
                                     time: self.timestamp(),
+                                    device: self.inner.device.clone(),
                                     id: finger_id.into_raw() as u64,
                                 },
                             };
@@ -640,6 +673,7 @@ impl<F: FnMut(WinitEvent)> ApplicationHandler for WinitEventLoopApp<'_, F> {
                 let event = InputEvent::TouchCancel {
                     event: WinitTouchCancelledEvent {
                         time: self.timestamp(),
+                        device: self.inner.device.clone(),
                         id: finger_id.into_raw() as u64,
                     },
                 };
@@ -4,8 +4,9 @@ use super::{Window, WindowTemporary, window_inner::WindowInner};
 use crate::{
     backend::input::{
         self, AbsolutePositionEvent, Axis, AxisRelativeDirection, AxisSource, ButtonState, Device,
-        DeviceCapability, InputBackend, KeyState, KeyboardKeyEvent, Keycode, PointerAxisEvent,
-        PointerButtonEvent, PointerMotionAbsoluteEvent, UnusedEvent,
+        DeviceCapability, EmulatedDeviceConfig, InputBackend, KeyState, KeyboardKeyEvent, Keycode,
+        PointerAxisEvent, PointerButtonEvent, PointerMotionAbsoluteEvent, UnusedEvent,
+        emulated_device_config,
     },
     utils::{Logical, Size},
 };
@@ -16,8 +17,21 @@ use std::sync::Weak;
 pub struct X11Input;
 
 /// Virtual input device used by the backend to associate input events.
-#[derive(Debug, PartialEq, Eq, Hash)]
-pub struct X11VirtualDevice;
+///
+/// Natural scrolling, left-handed buttons and the calibration matrix can be configured
+/// through [`DeviceConfig`](input::DeviceConfig) and are applied to the generated events.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct X11VirtualDevice {
+    pub(crate) config: EmulatedDeviceConfig,
+}
+
+impl X11VirtualDevice {
+    pub(crate) fn new() -> Self {
+        X11VirtualDevice {
+            config: EmulatedDeviceConfig::default(),
+        }
+    }
+}
 
 impl Device for X11VirtualDevice {
     fn id(&self) -> String {
@@ -44,10 +58,13 @@ impl Device for X11VirtualDevice {
     }
 }
 
+emulated_device_config!(X11VirtualDevice);
+
 /// X11-Backend internal event wrapping `X11`'s types into a [`KeyboardKeyEvent`].
 #[derive(Debug, Clone)]
 pub struct X11KeyboardInputEvent {
     pub(crate) time: u32,
+    pub(crate) device: X11VirtualDevice,
     pub(crate) key: Keycode,
     pub(crate) count: u32,
     pub(crate) state: KeyState,
@@ -69,7 +86,7 @@ impl input::Event<X11Input> for X11KeyboardInputEvent {
     }
 
     fn device(&self) -> X11VirtualDevice {
-        X11VirtualDevice
+        self.device.clone()
     }
 }
 
@@ -91,6 +108,7 @@ impl KeyboardKeyEvent<X11Input> for X11KeyboardInputEvent {
 #[derive(Debug, Clone)]
 pub struct X11MouseWheelEvent {
     pub(crate) time: u32,
+    pub(crate) device: X11VirtualDevice,
     pub(crate) axis: Axis,
     pub(crate) amount: f64,
     pub(crate) window: Weak<WindowInner>,
@@ -111,7 +129,7 @@ impl input::Event<X11Input> for X11MouseWheelEvent {
     }
 
     fn device(&self) -> X11VirtualDevice {
-        X11VirtualDevice
+        self.device.clone()
     }
 }
 
@@ -145,6 +163,7 @@ impl PointerAxisEvent<X11Input> for X11MouseWheelEvent {
 #[derive(Debug, Clone)]
 pub struct X11MouseInputEvent {
     pub(crate) time: u32,
+    pub(crate) device: X11VirtualDevice,
     pub(crate) raw: u32,
     pub(crate) state: ButtonState,
     pub(crate) window: Weak<WindowInner>,
@@ -165,7 +184,7 @@ impl input::Event<X11Input> for X11MouseInputEvent {
     }
 
     fn device(&self) -> X11VirtualDevice {
-        X11VirtualDevice
+        self.device.clone()
     }
 }
 
@@ -183,6 +202,7 @@ impl PointerButtonEvent<X11Input> for X11MouseInputEvent {
 #[derive(Debug, Clone)]
 pub struct X11MouseMovedEvent {
     pub(crate) time: u32,
+    pub(crate) device: X11VirtualDevice,
     pub(crate) x: f64,
     pub(crate) y: f64,
     pub(crate) size: Size<u16, Logical>,
@@ -204,7 +224,7 @@ impl input::Event<X11Input> for X11MouseMovedEvent {
     }
 
     fn device(&self) -> X11VirtualDevice {
-        X11VirtualDevice
+        self.device.clone()
     }
 }
 
@@ -268,6 +268,7 @@ impl X11Backend {
             depth,
             visual_id,
             devices: false,
+            device: X11VirtualDevice::new(),
         };
 
         drop(_guard);
@@ -630,6 +631,7 @@ pub(crate) struct X11Inner {
     depth: x11::xproto::Depth,
     visual_id: u32,
     devices: bool,
+    device: X11VirtualDevice,
 }
 
 impl X11Inner {
@@ -639,6 +641,10 @@ impl X11Inner {
         inner.windows.get(id).cloned()
     }
 
+    fn device(inner: &Arc<Mutex<X11Inner>>) -> X11VirtualDevice {
+        inner.lock().unwrap().device.clone()
+    }
+
     #[profiling::function]
     fn process_event<F>(inner: &Arc<Mutex<X11Inner>>, event: x11::Event, callback: &mut F)
     where
@@ -650,7 +656,7 @@ impl X11Inner {
                 callback(
                     Input {
                         event: InputEvent::DeviceAdded {
-                            device: X11VirtualDevice,
+                            device: inner.device.clone(),
                         },
                         window_id: None,
                     },
@@ -661,7 +667,7 @@ impl X11Inner {
                 callback(
                     Input {
                         event: InputEvent::DeviceRemoved {
-                            device: X11VirtualDevice,
+                            device: inner.device.clone(),
                         },
                         window_id: None,
                     },
@@ -716,6 +722,8 @@ impl X11Inner {
                     // 7 => Axis::Horizontal +1.0
                     // Others => ??
 
+                    let device = X11Inner::device(inner);
+
                     // Scrolling
                     if button_press.detail >= 4 && button_press.detail <= 7 {
                         callback(
@@ -723,6 +731,7 @@ impl X11Inner {
                                 event: InputEvent::PointerAxis {
                                     event: X11MouseWheelEvent {
                                         time: button_press.time,
+                                        device: device.clone(),
                                         axis: match button_press.detail {
                                             // Up | Down
                                             4 | 5 => Axis::Vertical,
@@ -732,7 +741,7 @@ impl X11Inner {
 
                                             _ => unreachable!(),
                                         },
-                                        amount: match button_press.detail {
+                                        amount: device.config.scroll(match button_press.detail {
                                             // Up | Right
                                             4 | 7 => 1.0,
 
@@ -740,7 +749,7 @@ impl X11Inner {
                                             5 | 6 => -1.0,
 
                                             _ => unreachable!(),
-                                        },
+                                        }),
                                         window,
                                     },
                                 },
@@ -754,7 +763,8 @@ impl X11Inner {
                                 event: InputEvent::PointerButton {
                                     event: X11MouseInputEvent {
                                         time: button_press.time,
-                                        raw: button_press.detail,
+                                        raw: left_handed_button(&device, button_press.detail),
+                                        device,
                                         state: ButtonState::Pressed,
                                         window,
                                     },
@@ -776,12 +786,14 @@ impl X11Inner {
                 }
 
                 if let Some(window) = X11Inner::window_ref_from_id(inner, &button_release.event) {
+                    let device = X11Inner::device(inner);
                     callback(
                         Input {
                             event: InputEvent::PointerButton {
                                 event: X11MouseInputEvent {
                                     time: button_release.time,
-                                    raw: button_release.detail,
+                                    raw: left_handed_button(&device, button_release.detail),
+                                    device,
                                     state: ButtonState::Released,
                                     window,
                                 },
@@ -807,6 +819,7 @@ impl X11Inner {
                             event: InputEvent::Keyboard {
                                 event: X11KeyboardInputEvent {
                                     time: key_press.time,
+                                    device: X11Inner::device(inner),
                                     key: Keycode::from(key_press.detail),
                                     count,
                                     state: KeyState::Pressed,
@@ -838,6 +851,7 @@ impl X11Inner {
                             event: InputEvent::Keyboard {
                                 event: X11KeyboardInputEvent {
                                     time: key_release.time,
+                                    device: X11Inner::device(inner),
                                     key: Keycode::from(key_release.detail),
                                     count,
                                     state: KeyState::Released,
@@ -860,12 +874,22 @@ impl X11Inner {
                     let y = fixed_point_to_float(motion_notify.event_y);
 
                     let window_size = { *window.size.lock().unwrap() };
+                    let device = X11Inner::device(inner);
+                    let (w, h) = (window_size.w as f64, window_size.h as f64);
+                    // the calibration matrix works on normalized coordinates, which don't exist for empty windows
+                    let (x, y) = if w > 0.0 && h > 0.0 {
+                        let (x, y) = device.config.calibrate(x / w, y / h);
+                        (x * w, y * h)
+                    } else {
+                        (x, y)
+                    };
 
                     callback(
                         Input {
                             event: InputEvent::PointerMotionAbsolute {
                                 event: X11MouseMovedEvent {
                                     time: motion_notify.time,
+                                    device,
                                     x,
                                     y,
                                     size: window_size,
@@ -985,6 +1009,15 @@ impl X11Inner {
     }
 }
 
+/// Swaps the left and right buttons, if the device is configured to be left-handed
+fn left_handed_button(device: &X11VirtualDevice, raw: u32) -> u32 {
+    match raw {
+        1 if device.config.left_handed() => 3,
+        3 if device.config.left_handed() => 1,
+        raw => raw,
+    }
+}
+
 fn fixed_point_to_float(value: i32) -> f64 {
     let int = value >> 16;
     let frac = value & 0xffff;
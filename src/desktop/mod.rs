@@ -54,15 +54,23 @@ pub use self::space::Space;
 
 #[cfg(feature = "wayland_frontend")]
 pub use self::wayland::{
+    grabs,
     layer::{LayerMap, LayerSurface, layer_map_for_output},
+    placement,
     popup::*,
+    snapping,
+    snapshot::{WindowSnapshot, capture_source_window, set_capture_source_window},
     utils,
     window::*,
 };
 #[cfg(feature = "wayland_frontend")]
 mod wayland {
+    pub mod grabs;
     pub(crate) mod layer;
+    pub mod placement;
     pub mod popup;
+    pub mod snapping;
+    pub(crate) mod snapshot;
     pub mod utils;
     pub mod window;
 }
@@ -0,0 +1,988 @@
+//! Interactive move and resize grabs for [`Window`]s
+//!
+//! The grabs in this module implement the interactive move and resize operations requested by
+//! clients via `xdg_toplevel.move`/`xdg_toplevel.resize` or the corresponding `_NET_WM_MOVERESIZE`
+//! messages of X11 windows. The operations don't depend on the input device, [`PointerMoveResizeGrab`],
+//! [`TouchMoveResizeGrab`] and [`TabletToolMoveResizeGrab`] only drive them with the events of the respective
+//! device.
+//!
+//! The grabs don't know about the way your compositor arranges windows, so they use the
+//! [`MoveResizeHandler`] trait to query and update the location of the grabbed window.
+//!
+//! Resizing is asynchronous for wayland clients: the final size is only known once the client
+//! acknowledged the last configure and committed a matching buffer. To properly move windows that
+//! are resized by their top or left edge, you need to forward the relevant events of the resized
+//! window:
+//!
+//! - call [`ack_configure`] from [`XdgShellHandler::ack_configure`](crate::wayland::shell::xdg::XdgShellHandler::ack_configure)
+//! - call [`handle_commit`] on every commit of the window's surface
+//!
+//! The current state of a resize operation can be queried with [`resize_state`].
+
+use std::{fmt, sync::Mutex};
+
+use wayland_protocols::xdg::shell::server::xdg_toplevel;
+
+use crate::{
+    backend::input::ButtonState,
+    desktop::{Window, WindowSurface},
+    input::{
+        SeatHandler,
+        pointer::{
+            AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent, GesturePinchBeginEvent,
+            GesturePinchEndEvent, GesturePinchUpdateEvent, GestureSwipeBeginEvent, GestureSwipeEndEvent,
+            GestureSwipeUpdateEvent, GrabStartData as PointerGrabStartData,
+            MotionEvent as PointerMotionEvent, PointerGrab, PointerInnerHandle, RelativeMotionEvent,
+        },
+        tablet::{
+            TabletSeatHandler,
+            tool::{
+                AxisFrame as TabletToolAxisFrame, ButtonEvent as TabletToolButtonEvent,
+                DownEvent as TabletToolDownEvent, GrabStartData as TabletToolGrabStartData, GrabTrigger,
+                MotionEvent as TabletToolMotionEvent, ProximityOutEvent, TabletToolGrab,
+                TabletToolInnerHandle, UpEvent as TabletToolUpEvent,
+            },
+        },
+        touch::{
+            DownEvent, GrabStartData as TouchGrabStartData, MotionEvent as TouchMotionEvent,
+            OrientationEvent, ShapeEvent, TouchGrab, TouchInnerHandle, UpEvent,
+        },
+    },
+    utils::{IsAlive, Logical, Point, Serial, Size},
+    wayland::{
+        compositor::with_states,
+        shell::xdg::{SurfaceCachedState, ToplevelConfigure},
+    },
+};
+#[cfg(feature = "xwayland")]
+use crate::{utils::Rectangle, xwayland::xwm::ResizeEdge as X11ResizeEdge};
+
+/// Handler for the interactive move and resize grabs
+pub trait MoveResizeHandler: SeatHandler + Sized + 'static {
+    /// Returns the current location of the window, if it is mapped.
+    ///
+    /// The location refers to the top-left corner of the window geometry,
+    /// like the location of elements in a [`Space`](crate::desktop::Space).
+    fn window_location(&self, window: &Window) -> Option<Point<i32, Logical>>;
+
+    /// Moves the window to a new location
+    fn set_window_location(&mut self, window: &Window, location: Point<i32, Logical>);
+
+    /// Adjusts the location of a window during an interactive move
+    ///
+    /// Called with the location the window would be moved to and the current location of the input device
+    /// before [`MoveResizeHandler::set_window_location`]. This can be used to implement snapping,
+    /// see [`snapping`](crate::desktop::snapping). By default the location is not altered.
+    fn move_location(
+        &mut self,
+        window: &Window,
+        location: Point<i32, Logical>,
+        input_location: Point<f64, Logical>,
+    ) -> Point<i32, Logical> {
+        let _ = (window, input_location);
+        location
+    }
+
+    /// An interactive move of the window has finished
+    fn move_finished(&mut self, window: &Window) {
+        let _ = window;
+    }
+}
+
+bitflags::bitflags! {
+    /// Edges of a window that are being dragged during a resize operation
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct ResizeEdge: u32 {
+        /// No edge
+        const NONE = 0;
+        /// Top edge
+        const TOP = 1;
+        /// Bottom edge
+        const BOTTOM = 2;
+        /// Left edge
+        const LEFT = 4;
+        /// Top-left corner
+        const TOP_LEFT = 5;
+        /// Bottom-left corner
+        const BOTTOM_LEFT = 6;
+        /// Right edge
+        const RIGHT = 8;
+        /// Top-right corner
+        const TOP_RIGHT = 9;
+        /// Bottom-right corner
+        const BOTTOM_RIGHT = 10;
+    }
+}
+
+impl From<xdg_toplevel::ResizeEdge> for ResizeEdge {
+    #[inline]
+    fn from(x: xdg_toplevel::ResizeEdge) -> Self {
+        Self::from_bits(x as u32).unwrap_or(ResizeEdge::NONE)
+    }
+}
+
+impl From<ResizeEdge> for xdg_toplevel::ResizeEdge {
+    #[inline]
+    fn from(x: ResizeEdge) -> Self {
+        Self::try_from(x.bits()).unwrap_or(xdg_toplevel::ResizeEdge::None)
+    }
+}
+
+#[cfg(feature = "xwayland")]
+impl From<X11ResizeEdge> for ResizeEdge {
+    #[inline]
+    fn from(edge: X11ResizeEdge) -> Self {
+        match edge {
+            X11ResizeEdge::Bottom => ResizeEdge::BOTTOM,
+            X11ResizeEdge::BottomLeft => ResizeEdge::BOTTOM_LEFT,
+            X11ResizeEdge::BottomRight => ResizeEdge::BOTTOM_RIGHT,
+            X11ResizeEdge::Left => ResizeEdge::LEFT,
+            X11ResizeEdge::Right => ResizeEdge::RIGHT,
+            X11ResizeEdge::Top => ResizeEdge::TOP,
+            X11ResizeEdge::TopLeft => ResizeEdge::TOP_LEFT,
+            X11ResizeEdge::TopRight => ResizeEdge::TOP_RIGHT,
+        }
+    }
+}
+
+/// Information about a resize operation
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ResizeData {
+    /// The edges the window is being resized with
+    pub edges: ResizeEdge,
+    /// The initial window location
+    pub initial_window_location: Point<i32, Logical>,
+    /// The initial window size (geometry width and height)
+    pub initial_window_size: Size<i32, Logical>,
+}
+
+impl ResizeData {
+    /// Location of the window for a given size
+    ///
+    /// Windows resized by their top or left edge need to be moved,
+    /// so that the opposite edge stays in place.
+    pub fn location_for_size(&self, size: Size<i32, Logical>) -> Point<i32, Logical> {
+        let mut location = self.initial_window_location;
+        if self.edges.intersects(ResizeEdge::LEFT) {
+            location.x += self.initial_window_size.w - size.w;
+        }
+        if self.edges.intersects(ResizeEdge::TOP) {
+            location.y += self.initial_window_size.h - size.h;
+        }
+        location
+    }
+
+    /// Size of the window after the input device moved by `delta` since the start of the resize
+    ///
+    /// The size is clamped to `min_size` and `max_size`, with `0` meaning unconstrained.
+    fn size_for_delta(
+        &self,
+        delta: Point<f64, Logical>,
+        min_size: Size<i32, Logical>,
+        max_size: Size<i32, Logical>,
+    ) -> Size<i32, Logical> {
+        let (mut dx, mut dy) = delta.into();
+        let mut width = self.initial_window_size.w;
+        let mut height = self.initial_window_size.h;
+
+        if self.edges.intersects(ResizeEdge::LEFT | ResizeEdge::RIGHT) {
+            if self.edges.intersects(ResizeEdge::LEFT) {
+                dx = -dx;
+            }
+            width = (width as f64 + dx) as i32;
+        }
+
+        if self.edges.intersects(ResizeEdge::TOP | ResizeEdge::BOTTOM) {
+            if self.edges.intersects(ResizeEdge::TOP) {
+                dy = -dy;
+            }
+            height = (height as f64 + dy) as i32;
+        }
+
+        let max_width = if max_size.w == 0 { i32::MAX } else { max_size.w };
+        let max_height = if max_size.h == 0 { i32::MAX } else { max_size.h };
+
+        (
+            width.max(min_size.w.max(1)).min(max_width),
+            height.max(min_size.h.max(1)).min(max_height),
+        )
+            .into()
+    }
+}
+
+/// State of the resize operation of a window
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum ResizeState {
+    /// The window is not being resized
+    #[default]
+    NotResizing,
+    /// The window is currently being resized
+    Resizing(ResizeData),
+    /// The resize has finished, and the client needs to ack the final configure
+    WaitingForFinalAck(ResizeData, Serial),
+    /// The resize has finished, and the client needs to commit its final state
+    WaitingForCommit(ResizeData),
+}
+
+impl ResizeState {
+    /// Returns the data of the resize operation, if any
+    pub fn data(&self) -> Option<&ResizeData> {
+        match self {
+            ResizeState::NotResizing => None,
+            ResizeState::Resizing(data)
+            | ResizeState::WaitingForFinalAck(data, _)
+            | ResizeState::WaitingForCommit(data) => Some(data),
+        }
+    }
+
+    /// The state after the client acked the configure with the given serial
+    fn acked(self, serial: Serial) -> Self {
+        match self {
+            ResizeState::WaitingForFinalAck(data, final_serial) if serial.is_no_older_than(&final_serial) => {
+                ResizeState::WaitingForCommit(data)
+            }
+            state => state,
+        }
+    }
+
+    /// The state after the client committed
+    fn committed(self) -> Self {
+        match self {
+            ResizeState::WaitingForCommit(_) => ResizeState::NotResizing,
+            state => state,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ResizeStateUserData(Mutex<ResizeState>);
+
+/// Returns the state of the resize operation of a window
+pub fn resize_state(window: &Window) -> ResizeState {
+    window
+        .user_data()
+        .get::<ResizeStateUserData>()
+        .map(|data| *data.0.lock().unwrap())
+        .unwrap_or_default()
+}
+
+fn set_resize_state(window: &Window, state: ResizeState) {
+    let user_data = window.user_data();
+    user_data.insert_if_missing_threadsafe(ResizeStateUserData::default);
+    *user_data.get::<ResizeStateUserData>().unwrap().0.lock().unwrap() = state;
+}
+
+/// Tracks the acknowledgement of the final configure of a resize operation
+///
+/// Needs to be called for configures acked by wayland windows.
+pub fn ack_configure(window: &Window, configure: &ToplevelConfigure) {
+    let state = resize_state(window);
+    let new_state = state.acked(configure.serial);
+    if new_state != state {
+        set_resize_state(window, new_state);
+    }
+}
+
+/// Updates the location of a window resized by its top or left edge
+///
+/// Needs to be called on every commit of the window. Finishes the resize operation,
+/// once the window committed its final state.
+pub fn handle_commit<D: MoveResizeHandler>(data: &mut D, window: &Window) {
+    let state = resize_state(window);
+    let Some(resize_data) = state.data() else {
+        return;
+    };
+
+    if resize_data.edges.intersects(ResizeEdge::TOP_LEFT) {
+        if let Some(mut location) = data.window_location(window) {
+            let new_location = resize_data.location_for_size(window.geometry().size);
+            if resize_data.edges.intersects(ResizeEdge::LEFT) {
+                location.x = new_location.x;
+            }
+            if resize_data.edges.intersects(ResizeEdge::TOP) {
+                location.y = new_location.y;
+            }
+            data.set_window_location(window, location);
+        }
+    }
+
+    let new_state = state.committed();
+    if new_state != state {
+        set_resize_state(window, new_state);
+    }
+}
+
+#[derive(Debug)]
+enum OperationKind {
+    Move {
+        initial_window_location: Point<i32, Logical>,
+    },
+    Resize {
+        data: ResizeData,
+        last_window_size: Size<i32, Logical>,
+    },
+}
+
+/// Move or resize operation of a window, driven by the location of the grabbing input device
+///
+/// Shared by all grabs of this module, which only translate the events of their input device.
+#[derive(Debug)]
+struct MoveResizeOperation {
+    window: Window,
+    start_location: Point<f64, Logical>,
+    kind: OperationKind,
+}
+
+impl MoveResizeOperation {
+    fn new_move(
+        window: Window,
+        start_location: Point<f64, Logical>,
+        initial_window_location: Point<i32, Logical>,
+    ) -> Self {
+        MoveResizeOperation {
+            window,
+            start_location,
+            kind: OperationKind::Move {
+                initial_window_location,
+            },
+        }
+    }
+
+    fn new_resize(
+        window: Window,
+        edges: ResizeEdge,
+        start_location: Point<f64, Logical>,
+        initial_window_location: Point<i32, Logical>,
+    ) -> Self {
+        let initial_window_size = window.geometry().size;
+        let data = ResizeData {
+            edges,
+            initial_window_location,
+            initial_window_size,
+        };
+        set_resize_state(&window, ResizeState::Resizing(data));
+
+        MoveResizeOperation {
+            window,
+            start_location,
+            kind: OperationKind::Resize {
+                data,
+                last_window_size: initial_window_size,
+            },
+        }
+    }
+
+    fn edges(&self) -> Option<ResizeEdge> {
+        match self.kind {
+            OperationKind::Move { .. } => None,
+            OperationKind::Resize { data, .. } => Some(data.edges),
+        }
+    }
+
+    /// Returns `false` if the window is gone and the grab should be released
+    fn motion<D: MoveResizeHandler>(&mut self, data: &mut D, location: Point<f64, Logical>) -> bool {
+        // It is impossible to get `min_size` and `max_size` of dead toplevel, so we return early.
+        if !self.window.alive() {
+            return false;
+        }
+
+        let delta = location - self.start_location;
+        match &mut self.kind {
+            OperationKind::Move {
+                initial_window_location,
+            } => {
+                let new_location = initial_window_location.to_f64() + delta;
+                let new_location = data.move_location(&self.window, new_location.to_i32_round(), location);
+                data.set_window_location(&self.window, new_location);
+            }
+            OperationKind::Resize {
+                data: resize_data,
+                last_window_size,
+            } => {
+                let (min_size, max_size) = size_constraints(&self.window);
+                *last_window_size = resize_data.size_for_delta(delta, min_size, max_size);
+                configure_size(data, &self.window, resize_data, *last_window_size, true);
+            }
+        }
+
+        true
+    }
+
+    fn finish<D: MoveResizeHandler>(&mut self, data: &mut D) {
+        match self.kind {
+            OperationKind::Move { .. } => data.move_finished(&self.window),
+            OperationKind::Resize {
+                data: resize_data,
+                last_window_size,
+            } => {
+                // If toplevel is dead, we can't resize it
+                if !self.window.alive() {
+                    set_resize_state(&self.window, ResizeState::NotResizing);
+                    return;
+                }
+
+                let state = match configure_size(data, &self.window, &resize_data, last_window_size, false) {
+                    Some(serial) => ResizeState::WaitingForFinalAck(resize_data, serial),
+                    None => ResizeState::WaitingForCommit(resize_data),
+                };
+                set_resize_state(&self.window, state);
+            }
+        }
+    }
+}
+
+/// Configures the window to the given size, returning the serial of the configure sent to wayland windows
+#[cfg_attr(not(feature = "xwayland"), allow(unused_variables))]
+fn configure_size<D: MoveResizeHandler>(
+    data: &mut D,
+    window: &Window,
+    resize_data: &ResizeData,
+    size: Size<i32, Logical>,
+    resizing: bool,
+) -> Option<Serial> {
+    match window.underlying_surface() {
+        WindowSurface::Wayland(xdg) => {
+            xdg.with_pending_state(|state| {
+                if resizing {
+                    state.states.set(xdg_toplevel::State::Resizing);
+                } else {
+                    state.states.unset(xdg_toplevel::State::Resizing);
+                }
+                state.size = Some(size);
+            });
+            xdg.send_pending_configure()
+        }
+        #[cfg(feature = "xwayland")]
+        WindowSurface::X11(x11) => {
+            let location = resize_data.location_for_size(size);
+            data.set_window_location(window, location);
+            if let Err(err) = x11.configure_with_sync(Rectangle::new(location, size), None) {
+                tracing::warn!(?err, "Failed to configure X11 window during resize");
+            }
+            None
+        }
+    }
+}
+
+/// Returns the min and max size of a window, `0` meaning unconstrained.
+fn size_constraints(window: &Window) -> (Size<i32, Logical>, Size<i32, Logical>) {
+    match window.underlying_surface() {
+        WindowSurface::Wayland(xdg) => with_states(xdg.wl_surface(), |states| {
+            let mut guard = states.cached_state.get::<SurfaceCachedState>();
+            let data = guard.current();
+            (data.min_size, data.max_size)
+        }),
+        #[cfg(feature = "xwayland")]
+        WindowSurface::X11(x11) => (
+            x11.min_size().unwrap_or_default(),
+            x11.max_size().unwrap_or_default(),
+        ),
+    }
+}
+
+// Forwards pointer gestures unchanged, they don't affect move and resize operations
+macro_rules! forward_pointer_gestures {
+    () => {
+        forward_pointer_gestures!(
+            gesture_swipe_begin: GestureSwipeBeginEvent,
+            gesture_swipe_update: GestureSwipeUpdateEvent,
+            gesture_swipe_end: GestureSwipeEndEvent,
+            gesture_pinch_begin: GesturePinchBeginEvent,
+            gesture_pinch_update: GesturePinchUpdateEvent,
+            gesture_pinch_end: GesturePinchEndEvent,
+            gesture_hold_begin: GestureHoldBeginEvent,
+            gesture_hold_end: GestureHoldEndEvent,
+        );
+    };
+    ($($method:ident: $event:ty),+ $(,)?) => {
+        $(
+            fn $method(&mut self, data: &mut D, handle: &mut PointerInnerHandle<'_, D>, event: &$event) {
+                handle.$method(data, event);
+            }
+        )*
+    };
+}
+
+/// Grab moving or resizing a window with the pointer
+///
+/// The grab is released once all buttons are released. A resize operation is finished, when the grab is unset.
+pub struct PointerMoveResizeGrab<D: SeatHandler> {
+    start_data: PointerGrabStartData<D>,
+    operation: MoveResizeOperation,
+}
+
+impl<D: SeatHandler + 'static> fmt::Debug for PointerMoveResizeGrab<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PointerMoveResizeGrab")
+            .field("start_data", &self.start_data)
+            .field("operation", &self.operation)
+            .finish()
+    }
+}
+
+impl<D: SeatHandler> PointerMoveResizeGrab<D> {
+    /// Create a new move grab from an implicit pointer grab
+    ///
+    /// `initial_window_location` is the location of the window at the start of the grab.
+    pub fn new_move(
+        start_data: PointerGrabStartData<D>,
+        window: Window,
+        initial_window_location: Point<i32, Logical>,
+    ) -> Self {
+        let operation = MoveResizeOperation::new_move(window, start_data.location, initial_window_location);
+        PointerMoveResizeGrab {
+            start_data,
+            operation,
+        }
+    }
+
+    /// Create a new resize grab from an implicit pointer grab
+    ///
+    /// `initial_window_location` is the location of the window at the start of the grab.
+    /// Marks the window as [`ResizeState::Resizing`].
+    pub fn new_resize(
+        start_data: PointerGrabStartData<D>,
+        window: Window,
+        edges: ResizeEdge,
+        initial_window_location: Point<i32, Logical>,
+    ) -> Self {
+        let operation =
+            MoveResizeOperation::new_resize(window, edges, start_data.location, initial_window_location);
+        PointerMoveResizeGrab {
+            start_data,
+            operation,
+        }
+    }
+
+    /// The window moved or resized by this grab
+    pub fn window(&self) -> &Window {
+        &self.operation.window
+    }
+
+    /// The edges the window is resized with, `None` for move grabs
+    pub fn edges(&self) -> Option<ResizeEdge> {
+        self.operation.edges()
+    }
+}
+
+impl<D: MoveResizeHandler> PointerGrab<D> for PointerMoveResizeGrab<D> {
+    fn motion(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        _focus: Option<(D::PointerFocus, Point<f64, Logical>)>,
+        event: &PointerMotionEvent,
+    ) {
+        // While the grab is active, no client has pointer focus
+        handle.motion(data, None, event);
+
+        if !self.operation.motion(data, event.location) {
+            handle.unset_grab(self, data, event.serial, event.time, true);
+        }
+    }
+
+    fn relative_motion(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        focus: Option<(D::PointerFocus, Point<f64, Logical>)>,
+        event: &RelativeMotionEvent,
+    ) {
+        handle.relative_motion(data, focus, event);
+    }
+
+    fn button(&mut self, data: &mut D, handle: &mut PointerInnerHandle<'_, D>, event: &ButtonEvent) {
+        handle.button(data, event);
+        if handle.current_pressed().is_empty() {
+            // No more buttons are pressed, release the grab.
+            handle.unset_grab(self, data, event.serial, event.time, true);
+        }
+    }
+
+    fn axis(&mut self, data: &mut D, handle: &mut PointerInnerHandle<'_, D>, details: AxisFrame) {
+        handle.axis(data, details)
+    }
+
+    fn frame(&mut self, data: &mut D, handle: &mut PointerInnerHandle<'_, D>) {
+        handle.frame(data);
+    }
+
+    forward_pointer_gestures!();
+
+    fn start_data(&self) -> &PointerGrabStartData<D> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, data: &mut D) {
+        self.operation.finish(data);
+    }
+}
+
+/// Grab moving or resizing a window with a touch point
+///
+/// The grab is released once the touch point is lifted. A resize operation is finished, when the grab is unset.
+pub struct TouchMoveResizeGrab<D: SeatHandler> {
+    start_data: TouchGrabStartData<D>,
+    operation: MoveResizeOperation,
+}
+
+impl<D: SeatHandler + 'static> fmt::Debug for TouchMoveResizeGrab<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TouchMoveResizeGrab")
+            .field("start_data", &self.start_data)
+            .field("operation", &self.operation)
+            .finish()
+    }
+}
+
+impl<D: SeatHandler> TouchMoveResizeGrab<D> {
+    /// Create a new move grab from an implicit touch grab
+    ///
+    /// `initial_window_location` is the location of the window at the start of the grab.
+    pub fn new_move(
+        start_data: TouchGrabStartData<D>,
+        window: Window,
+        initial_window_location: Point<i32, Logical>,
+    ) -> Self {
+        let operation = MoveResizeOperation::new_move(window, start_data.location, initial_window_location);
+        TouchMoveResizeGrab {
+            start_data,
+            operation,
+        }
+    }
+
+    /// Create a new resize grab from an implicit touch grab
+    ///
+    /// `initial_window_location` is the location of the window at the start of the grab.
+    /// Marks the window as [`ResizeState::Resizing`].
+    pub fn new_resize(
+        start_data: TouchGrabStartData<D>,
+        window: Window,
+        edges: ResizeEdge,
+        initial_window_location: Point<i32, Logical>,
+    ) -> Self {
+        let operation =
+            MoveResizeOperation::new_resize(window, edges, start_data.location, initial_window_location);
+        TouchMoveResizeGrab {
+            start_data,
+            operation,
+        }
+    }
+
+    /// The window moved or resized by this grab
+    pub fn window(&self) -> &Window {
+        &self.operation.window
+    }
+
+    /// The edges the window is resized with, `None` for move grabs
+    pub fn edges(&self) -> Option<ResizeEdge> {
+        self.operation.edges()
+    }
+}
+
+impl<D: MoveResizeHandler> TouchGrab<D> for TouchMoveResizeGrab<D> {
+    fn down(
+        &mut self,
+        _data: &mut D,
+        _handle: &mut TouchInnerHandle<'_, D>,
+        _focus: Option<(D::TouchFocus, Point<f64, Logical>)>,
+        _event: &DownEvent,
+    ) {
+    }
+
+    fn up(&mut self, data: &mut D, handle: &mut TouchInnerHandle<'_, D>, event: &UpEvent) {
+        if event.slot != self.start_data.slot {
+            return;
+        }
+
+        handle.up(data, event);
+        handle.unset_grab(self, data);
+    }
+
+    fn motion(
+        &mut self,
+        data: &mut D,
+        handle: &mut TouchInnerHandle<'_, D>,
+        _focus: Option<(D::TouchFocus, Point<f64, Logical>)>,
+        event: &TouchMotionEvent,
+    ) {
+        if event.slot != self.start_data.slot {
+            return;
+        }
+
+        if !self.operation.motion(data, event.location) {
+            handle.unset_grab(self, data);
+        }
+    }
+
+    fn frame(&mut self, _data: &mut D, _handle: &mut TouchInnerHandle<'_, D>) {}
+
+    fn cancel(&mut self, data: &mut D, handle: &mut TouchInnerHandle<'_, D>) {
+        handle.cancel(data);
+        handle.unset_grab(self, data);
+    }
+
+    fn shape(&mut self, data: &mut D, handle: &mut TouchInnerHandle<'_, D>, event: &ShapeEvent) {
+        handle.shape(data, event);
+    }
+
+    fn orientation(&mut self, data: &mut D, handle: &mut TouchInnerHandle<'_, D>, event: &OrientationEvent) {
+        handle.orientation(data, event);
+    }
+
+    fn start_data(&self) -> &TouchGrabStartData<D> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, data: &mut D) {
+        self.operation.finish(data);
+    }
+}
+
+/// Grab moving or resizing a window with a tablet tool
+///
+/// The grab is released once the tip or button that started it is released, or the tool leaves proximity.
+/// A resize operation is finished, when the grab is unset.
+pub struct TabletToolMoveResizeGrab<D: TabletSeatHandler> {
+    start_data: TabletToolGrabStartData<D>,
+    operation: MoveResizeOperation,
+}
+
+impl<D: TabletSeatHandler + 'static> fmt::Debug for TabletToolMoveResizeGrab<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TabletToolMoveResizeGrab")
+            .field("start_data", &self.start_data)
+            .field("operation", &self.operation)
+            .finish()
+    }
+}
+
+impl<D: TabletSeatHandler> TabletToolMoveResizeGrab<D> {
+    /// Create a new move grab from an implicit tablet tool grab
+    ///
+    /// `initial_window_location` is the location of the window at the start of the grab.
+    pub fn new_move(
+        start_data: TabletToolGrabStartData<D>,
+        window: Window,
+        initial_window_location: Point<i32, Logical>,
+    ) -> Self {
+        let operation = MoveResizeOperation::new_move(window, start_data.location, initial_window_location);
+        TabletToolMoveResizeGrab {
+            start_data,
+            operation,
+        }
+    }
+
+    /// Create a new resize grab from an implicit tablet tool grab
+    ///
+    /// `initial_window_location` is the location of the window at the start of the grab.
+    /// Marks the window as [`ResizeState::Resizing`].
+    pub fn new_resize(
+        start_data: TabletToolGrabStartData<D>,
+        window: Window,
+        edges: ResizeEdge,
+        initial_window_location: Point<i32, Logical>,
+    ) -> Self {
+        let operation =
+            MoveResizeOperation::new_resize(window, edges, start_data.location, initial_window_location);
+        TabletToolMoveResizeGrab {
+            start_data,
+            operation,
+        }
+    }
+
+    /// The window moved or resized by this grab
+    pub fn window(&self) -> &Window {
+        &self.operation.window
+    }
+
+    /// The edges the window is resized with, `None` for move grabs
+    pub fn edges(&self) -> Option<ResizeEdge> {
+        self.operation.edges()
+    }
+}
+
+impl<D: MoveResizeHandler + TabletSeatHandler> TabletToolGrab<D> for TabletToolMoveResizeGrab<D> {
+    fn start_data(&self) -> &TabletToolGrabStartData<D> {
+        &self.start_data
+    }
+
+    fn proximity_out(
+        &mut self,
+        data: &mut D,
+        handle: &mut TabletToolInnerHandle<'_, D>,
+        event: &ProximityOutEvent,
+    ) {
+        handle.proximity_out(data, event);
+        handle.unset_grab(self, data, event.serial, event.time, false);
+    }
+
+    fn motion(
+        &mut self,
+        data: &mut D,
+        handle: &mut TabletToolInnerHandle<'_, D>,
+        _focus: Option<(D::ToolFocus, Point<f64, Logical>)>,
+        event: &TabletToolMotionEvent,
+    ) {
+        // While the grab is active, no client has tool focus
+        handle.motion(data, None, event);
+
+        if !self.operation.motion(data, event.location) {
+            handle.unset_grab(self, data, event.serial, event.time, true);
+        }
+    }
+
+    fn down(&mut self, data: &mut D, handle: &mut TabletToolInnerHandle<'_, D>, event: &TabletToolDownEvent) {
+        handle.down(data, event);
+    }
+
+    fn up(&mut self, data: &mut D, handle: &mut TabletToolInnerHandle<'_, D>, event: &TabletToolUpEvent) {
+        handle.up(data, event);
+        if matches!(self.start_data.trigger, GrabTrigger::Tip) {
+            handle.unset_grab(self, data, event.serial, event.time, true);
+        }
+    }
+
+    fn button(
+        &mut self,
+        data: &mut D,
+        handle: &mut TabletToolInnerHandle<'_, D>,
+        event: &TabletToolButtonEvent,
+    ) {
+        handle.button(data, event);
+        let released = match self.start_data.trigger {
+            GrabTrigger::Button(button) => button == event.button && event.state == ButtonState::Released,
+            _ => false,
+        };
+        if released {
+            handle.unset_grab(self, data, event.serial, event.time, true);
+        }
+    }
+
+    fn axis(&mut self, data: &mut D, handle: &mut TabletToolInnerHandle<'_, D>, frame: TabletToolAxisFrame) {
+        handle.axis(data, frame);
+    }
+
+    fn frame(&mut self, data: &mut D, handle: &mut TabletToolInnerHandle<'_, D>, time: u32) {
+        handle.frame(data, time);
+    }
+
+    fn unset(&mut self, data: &mut D) {
+        self.operation.finish(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn location_for_size() {
+        let data = ResizeData {
+            edges: ResizeEdge::TOP_LEFT,
+            initial_window_location: (100, 100).into(),
+            initial_window_size: (400, 300).into(),
+        };
+        assert_eq!(data.location_for_size((300, 350).into()), Point::from((200, 50)));
+
+        let data = ResizeData {
+            edges: ResizeEdge::BOTTOM_RIGHT,
+            ..data
+        };
+        assert_eq!(data.location_for_size((300, 350).into()), Point::from((100, 100)));
+    }
+
+    #[test]
+    fn resize_top_left() {
+        let data = ResizeData {
+            edges: ResizeEdge::TOP_LEFT,
+            initial_window_location: (100, 100).into(),
+            initial_window_size: (400, 300).into(),
+        };
+        let unconstrained = Size::default();
+
+        // dragging the top-left corner up and left grows the window and moves its origin
+        let size = data.size_for_delta((-50.0, -20.0).into(), unconstrained, unconstrained);
+        assert_eq!(size, Size::from((450, 320)));
+        assert_eq!(data.location_for_size(size), Point::from((50, 80)));
+
+        // while dragging it down and right shrinks it, keeping the bottom-right corner in place
+        let size = data.size_for_delta((30.0, 40.0).into(), unconstrained, unconstrained);
+        assert_eq!(size, Size::from((370, 260)));
+        assert_eq!(data.location_for_size(size), Point::from((130, 140)));
+    }
+
+    #[test]
+    fn resize_single_edge() {
+        let data = ResizeData {
+            edges: ResizeEdge::RIGHT,
+            initial_window_location: (0, 0).into(),
+            initial_window_size: (400, 300).into(),
+        };
+        let size = data.size_for_delta((25.0, 100.0).into(), Size::default(), Size::default());
+        assert_eq!(size, Size::from((425, 300)));
+        assert_eq!(data.location_for_size(size), Point::from((0, 0)));
+    }
+
+    #[test]
+    fn resize_clamped() {
+        let data = ResizeData {
+            edges: ResizeEdge::BOTTOM_LEFT,
+            initial_window_location: (100, 100).into(),
+            initial_window_size: (400, 300).into(),
+        };
+        let min_size = Size::from((200, 150));
+        let max_size = Size::from((500, 0));
+
+        let size = data.size_for_delta((300.0, -250.0).into(), min_size, max_size);
+        assert_eq!(size, Size::from((200, 150)));
+        // the right edge stays in place, even when the size is clamped
+        assert_eq!(data.location_for_size(size), Point::from((300, 100)));
+
+        // a max size of `0` doesn't constrain the window
+        let size = data.size_for_delta((-300.0, 1000.0).into(), min_size, max_size);
+        assert_eq!(size, Size::from((500, 1300)));
+
+        // windows never shrink below 1x1, even without a min size
+        let size = data.size_for_delta((1000.0, -1000.0).into(), Size::default(), Size::default());
+        assert_eq!(size, Size::from((1, 1)));
+    }
+
+    #[test]
+    fn configure_serial_tracking() {
+        let data = ResizeData {
+            edges: ResizeEdge::TOP,
+            initial_window_location: (0, 0).into(),
+            initial_window_size: (400, 300).into(),
+        };
+
+        // commits during the resize don't finish it
+        let resizing = ResizeState::Resizing(data);
+        assert_eq!(resizing.committed(), resizing);
+        assert_eq!(resizing.acked(Serial::from(10)), resizing);
+
+        // neither do commits or acks of configures sent before the final one
+        let waiting = ResizeState::WaitingForFinalAck(data, Serial::from(5));
+        assert_eq!(waiting.committed(), waiting);
+        assert_eq!(waiting.acked(Serial::from(4)), waiting);
+
+        let acked = waiting.acked(Serial::from(5));
+        assert_eq!(acked, ResizeState::WaitingForCommit(data));
+        assert_eq!(acked.data(), Some(&data));
+        assert_eq!(waiting.acked(Serial::from(6)), acked);
+
+        assert_eq!(acked.committed(), ResizeState::NotResizing);
+        assert_eq!(ResizeState::NotResizing.committed().data(), None);
+    }
+
+    #[test]
+    fn xdg_edge_conversion() {
+        for edge in [
+            xdg_toplevel::ResizeEdge::Top,
+            xdg_toplevel::ResizeEdge::BottomLeft,
+            xdg_toplevel::ResizeEdge::Right,
+        ] {
+            assert_eq!(xdg_toplevel::ResizeEdge::from(ResizeEdge::from(edge)), edge);
+        }
+    }
+}
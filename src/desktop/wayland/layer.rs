@@ -1,3 +1,5 @@
+#[cfg(feature = "xwayland")]
+use crate::xwayland::xwm::WmStrut;
 use crate::{
     backend::renderer::utils::RendererSurfaceStateUserData,
     desktop::{PopupManager, utils::*},
@@ -53,23 +55,26 @@ pub fn layer_map_for_output(o: &Output) -> MutexGuard<'_, LayerMap> {
         Mutex::new(LayerMap {
             layers: IndexSet::new(),
             output: o.downgrade(),
-            zone: Rectangle::from_size(
-                o.current_mode()
-                    .map(|mode| {
-                        let logical_size = mode
-                            .size
-                            .to_f64()
-                            .to_logical(o.current_scale().fractional_scale())
-                            .to_i32_round();
-                        o.current_transform().transform_size(logical_size)
-                    })
-                    .unwrap_or_else(|| (0, 0).into()),
-            ),
+            zone: Rectangle::from_size(output_size(o)),
         })
     });
     userdata.get::<Mutex<LayerMap>>().unwrap().lock().unwrap()
 }
 
+fn output_size(output: &Output) -> Size<i32, Logical> {
+    output
+        .current_mode()
+        .map(|mode| {
+            let logical_size = mode
+                .size
+                .to_f64()
+                .to_logical(output.current_scale().fractional_scale())
+                .to_i32_round();
+            output.current_transform().transform_size(logical_size)
+        })
+        .unwrap_or_else(|| (0, 0).into())
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum LayerError {
     #[error("Layer is already mapped to a different map")]
@@ -139,6 +144,35 @@ impl LayerMap {
         self.zone
     }
 
+    /// Return the area of this output, that is neither exclusive to any [`LayerSurface`]s
+    /// nor reserved by any of the given struts of X11 dock windows.
+    ///
+    /// `output_location` is the location of the output in X11 coordinates and `screen_size`
+    /// the size of the X11 screen as returned by [`X11Wm::screen_size`]. Struts are usually
+    /// collected from all mapped [`X11Surface::strut`]s.
+    ///
+    /// Like [`LayerMap::non_exclusive_zone`] the returned area is relative to the output.
+    ///
+    /// [`X11Wm::screen_size`]: crate::xwayland::X11Wm::screen_size
+    /// [`X11Surface::strut`]: crate::xwayland::X11Surface::strut
+    #[cfg(feature = "xwayland")]
+    pub fn usable_area(
+        &self,
+        output_location: Point<i32, Logical>,
+        screen_size: Size<i32, Logical>,
+        struts: impl IntoIterator<Item = WmStrut>,
+    ) -> Rectangle<i32, Logical> {
+        let Some(output) = self.output() else {
+            return self.zone;
+        };
+        let output_geo = Rectangle::new(output_location, output_size(&output));
+        let zone = struts.into_iter().fold(
+            Rectangle::new(self.zone.loc + output_location, self.zone.size),
+            |zone, strut| strut.shrink_area(zone, output_geo, screen_size),
+        );
+        Rectangle::new(zone.loc - output_location, zone.size)
+    }
+
     /// Returns the geometry of a given mapped [`LayerSurface`].
     ///
     /// If the surface was not previously mapped onto this layer map,
@@ -253,19 +287,7 @@ impl LayerMap {
             let span = debug_span!("layer_map", output = output.name());
             let _guard = span.enter();
 
-            let output_rect = Rectangle::from_size(
-                output
-                    .current_mode()
-                    .map(|mode| {
-                        let logical_size = mode
-                            .size
-                            .to_f64()
-                            .to_logical(output.current_scale().fractional_scale())
-                            .to_i32_round();
-                        output.current_transform().transform_size(logical_size)
-                    })
-                    .unwrap_or_else(|| (0, 0).into()),
-            );
+            let output_rect = Rectangle::from_size(output_size(&output));
             let zone = output_rect;
             let mut zone: Rectangle<_, Logical> = Rectangle::new(
                 Point::new(Saturating(zone.loc.x), Saturating(zone.loc.y)),
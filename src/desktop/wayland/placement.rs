@@ -0,0 +1,362 @@
+//! Placement policies for newly mapped windows
+//!
+//! [`Space`] doesn't decide where new elements are mapped, this module provides some common
+//! strategies to find an initial location for a window, which can then be passed to
+//! [`Space::map_element`].
+//!
+//! Windows are placed inside the usable area of an output, which excludes the exclusive zones
+//! of layer surfaces (see [`LayerMap::non_exclusive_zone`](crate::desktop::LayerMap::non_exclusive_zone)).
+//! Dialogs are by default centered on their parent window, which is resolved from
+//! [`ToplevelSurface::parent`](crate::wayland::shell::xdg::ToplevelSurface::parent) for wayland
+//! windows and the `WM_TRANSIENT_FOR` hint for X11 windows (see [`find_parent`] and [`is_dialog`]).
+//!
+//! ```no_run
+//! # use smithay::desktop::{Space, Window};
+//! # use smithay::output::Output;
+//! use smithay::desktop::placement::{self, PlacementPolicy, WindowPlacement};
+//!
+//! # let space: Space<Window> = unimplemented!();
+//! # let output: Output = unimplemented!();
+//! # let window: Window = unimplemented!();
+//! # let pointer_location = (0.0, 0.0).into();
+//! let placement = WindowPlacement::new(PlacementPolicy::Smart);
+//! let location = placement.place_window(&space, &window, &output, Some(pointer_location));
+//! # let mut space = space;
+//! space.map_element(window, location, true);
+//! ```
+
+use crate::{
+    desktop::{Space, Window, WindowSurface, layer_map_for_output, space::SpaceElement},
+    output::Output,
+    utils::{Logical, Point, Rectangle, Size},
+    wayland::{
+        compositor::with_states,
+        shell::xdg::{XdgToplevelSurfaceData, dialog::ToplevelDialogHint},
+    },
+};
+
+#[cfg(feature = "xwayland")]
+use crate::xwayland::xwm::WmWindowType;
+
+/// Strategy used to place new windows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlacementPolicy {
+    /// Place windows at the first free location of a diagonal cascade starting at the top-left
+    /// corner of the usable area
+    Cascade,
+    /// Center windows in the usable area of the output
+    Centered,
+    /// Center windows under the cursor, keeping them inside the usable area of the output
+    UnderCursor,
+    /// Place windows at the top-left most location, that overlaps the least with existing windows
+    Smart,
+}
+
+/// Configuration for placing new windows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowPlacement {
+    /// Policy used for windows, that aren't centered on their parent
+    pub policy: PlacementPolicy,
+    /// Center dialogs on their parent, regardless of the policy
+    pub center_on_parent: bool,
+    /// Offset between windows placed with [`PlacementPolicy::Cascade`]
+    ///
+    /// Components smaller than `1` are treated as `1`.
+    pub cascade_offset: Point<i32, Logical>,
+}
+
+impl WindowPlacement {
+    /// Create a new placement configuration for the given policy
+    ///
+    /// Dialogs will be centered on their parent.
+    pub fn new(policy: PlacementPolicy) -> Self {
+        WindowPlacement {
+            policy,
+            center_on_parent: true,
+            cascade_offset: Point::from((32, 32)),
+        }
+    }
+
+    /// Compute the location of a new `element` on `output`.
+    ///
+    /// - `parent` to center the element on, if it is a dialog. It needs to be mapped in the `space`
+    ///   to be taken into account. See [`WindowPlacement::place_window`] for resolving it automatically.
+    /// - `pointer` location in space coordinates used by [`PlacementPolicy::UnderCursor`].
+    ///   If `None`, the policy falls back to [`PlacementPolicy::Centered`].
+    ///
+    /// The returned location is meant to be passed to [`Space::map_element`]. If `element` is already mapped
+    /// it is ignored as an obstacle. Returns the origin of the space, if `output` is not mapped in the `space`.
+    pub fn place<E: SpaceElement + PartialEq>(
+        &self,
+        space: &Space<E>,
+        element: &E,
+        output: &Output,
+        parent: Option<&E>,
+        pointer: Option<Point<f64, Logical>>,
+    ) -> Point<i32, Logical> {
+        let Some(area) = usable_area(space, output) else {
+            return Point::default();
+        };
+        let size = element.geometry().size;
+
+        if self.center_on_parent {
+            if let Some(parent_geo) = parent.and_then(|parent| space.element_geometry(parent)) {
+                return clamp_to_area(center_in(parent_geo, size), size, area);
+            }
+        }
+
+        match self.policy {
+            PlacementPolicy::Centered => clamp_to_area(center_in(area, size), size, area),
+            PlacementPolicy::UnderCursor => match pointer {
+                Some(pointer) => {
+                    let loc = pointer.to_i32_round() - Point::from((size.w / 2, size.h / 2));
+                    clamp_to_area(loc, size, area)
+                }
+                None => clamp_to_area(center_in(area, size), size, area),
+            },
+            PlacementPolicy::Cascade => {
+                let occupied = space
+                    .elements()
+                    .filter(|e| *e != element)
+                    .filter_map(|e| space.element_geometry(e))
+                    .map(|geo| geo.loc)
+                    .collect::<Vec<_>>();
+                cascade(area, size, self.cascade_offset, &occupied)
+            }
+            PlacementPolicy::Smart => {
+                let obstacles = space
+                    .elements()
+                    .filter(|e| *e != element)
+                    .filter_map(|e| space.element_geometry(e))
+                    .filter_map(|geo| geo.intersection(area))
+                    .collect::<Vec<_>>();
+                smart(area, size, &obstacles)
+            }
+        }
+    }
+
+    /// Compute the location of a new `window` on `output`.
+    ///
+    /// Same as [`WindowPlacement::place`], but centers the window on its parent only,
+    /// if it is a dialog (see [`is_dialog`] and [`find_parent`]).
+    pub fn place_window(
+        &self,
+        space: &Space<Window>,
+        window: &Window,
+        output: &Output,
+        pointer: Option<Point<f64, Logical>>,
+    ) -> Point<i32, Logical> {
+        let parent = if is_dialog(window) {
+            find_parent(space.elements(), window)
+        } else {
+            None
+        };
+        self.place(space, window, output, parent, pointer)
+    }
+}
+
+/// Returns the area of `output` in space coordinates, that isn't occupied by exclusive zones of layer surfaces
+pub fn usable_area<E: SpaceElement + PartialEq>(
+    space: &Space<E>,
+    output: &Output,
+) -> Option<Rectangle<i32, Logical>> {
+    let output_geo = space.output_geometry(output)?;
+    let mut zone = layer_map_for_output(output).non_exclusive_zone();
+    zone.loc += output_geo.loc;
+    Some(zone)
+}
+
+/// Returns the parent window of `window` out of the given `windows`
+///
+/// Uses the parent set via `xdg_toplevel.set_parent` for wayland windows and
+/// the `WM_TRANSIENT_FOR` hint for X11 windows.
+pub fn find_parent<'a>(windows: impl IntoIterator<Item = &'a Window>, window: &Window) -> Option<&'a Window> {
+    match window.underlying_surface() {
+        WindowSurface::Wayland(toplevel) => {
+            let parent = toplevel.parent()?;
+            windows.into_iter().find(|w| {
+                w.toplevel()
+                    .is_some_and(|toplevel| *toplevel.wl_surface() == parent)
+            })
+        }
+        #[cfg(feature = "xwayland")]
+        WindowSurface::X11(surface) => {
+            let parent = surface.is_transient_for()?;
+            windows.into_iter().find(|w| {
+                w.x11_surface()
+                    .is_some_and(|surface| surface.window_id() == parent)
+            })
+        }
+    }
+}
+
+/// Returns if the window is a dialog
+///
+/// This is the case for wayland windows using the xdg-dialog protocol and
+/// X11 windows, that are modal, transient for another window or of the dialog type.
+pub fn is_dialog(window: &Window) -> bool {
+    match window.underlying_surface() {
+        WindowSurface::Wayland(toplevel) => with_states(toplevel.wl_surface(), |states| {
+            states
+                .data_map
+                .get::<XdgToplevelSurfaceData>()
+                .map(|data| data.lock().unwrap().dialog_hint != ToplevelDialogHint::Unknown)
+                .unwrap_or(false)
+        }),
+        #[cfg(feature = "xwayland")]
+        WindowSurface::X11(surface) => {
+            surface.is_modal()
+                || surface.is_transient_for().is_some()
+                || surface.window_type() == Some(WmWindowType::Dialog)
+        }
+    }
+}
+
+fn center_in(area: Rectangle<i32, Logical>, size: Size<i32, Logical>) -> Point<i32, Logical> {
+    area.loc + Point::from(((area.size.w - size.w) / 2, (area.size.h - size.h) / 2))
+}
+
+/// Moves `loc` so that a rectangle of `size` at it stays inside `area`.
+///
+/// Rectangles larger than `area` are aligned to its top-left corner.
+fn clamp_to_area(
+    loc: Point<i32, Logical>,
+    size: Size<i32, Logical>,
+    area: Rectangle<i32, Logical>,
+) -> Point<i32, Logical> {
+    let max_x = area.loc.x + (area.size.w - size.w).max(0);
+    let max_y = area.loc.y + (area.size.h - size.h).max(0);
+    Point::from((loc.x.clamp(area.loc.x, max_x), loc.y.clamp(area.loc.y, max_y)))
+}
+
+fn cascade(
+    area: Rectangle<i32, Logical>,
+    size: Size<i32, Logical>,
+    offset: Point<i32, Logical>,
+    occupied: &[Point<i32, Logical>],
+) -> Point<i32, Logical> {
+    // the window has to move right and down with every step to eventually leave the area
+    let offset = Point::from((offset.x.max(1), offset.y.max(1)));
+    let mut start = area.loc;
+    let mut loc = start;
+
+    while occupied.contains(&loc) {
+        loc += offset;
+
+        // restart from the top, slightly shifted to the right, once we run out of space
+        if loc.x + size.w > area.loc.x + area.size.w || loc.y + size.h > area.loc.y + area.size.h {
+            start.x += offset.x;
+            if start.x + size.w > area.loc.x + area.size.w {
+                return area.loc;
+            }
+            loc = start;
+        }
+    }
+
+    clamp_to_area(loc, size, area)
+}
+
+fn smart(
+    area: Rectangle<i32, Logical>,
+    size: Size<i32, Logical>,
+    obstacles: &[Rectangle<i32, Logical>],
+) -> Point<i32, Logical> {
+    // windows can only be placed at the edges of the area or existing windows
+    // to minimize the overlap, so use those as candidates.
+    let mut xs = vec![area.loc.x, area.loc.x + area.size.w - size.w];
+    let mut ys = vec![area.loc.y, area.loc.y + area.size.h - size.h];
+    for obstacle in obstacles {
+        xs.push(obstacle.loc.x + obstacle.size.w);
+        xs.push(obstacle.loc.x - size.w);
+        ys.push(obstacle.loc.y + obstacle.size.h);
+        ys.push(obstacle.loc.y - size.h);
+    }
+
+    let mut best: Option<(i64, Point<i32, Logical>)> = None;
+    for &y in &ys {
+        for &x in &xs {
+            let loc = clamp_to_area(Point::from((x, y)), size, area);
+            let rect = Rectangle::new(loc, size);
+            let overlap = obstacles
+                .iter()
+                .filter_map(|obstacle| obstacle.intersection(rect))
+                .map(|overlap| overlap.size.w as i64 * overlap.size.h as i64)
+                .sum::<i64>();
+
+            let better = match best {
+                None => true,
+                Some((best_overlap, best_loc)) => {
+                    overlap < best_overlap
+                        || (overlap == best_overlap && (loc.y, loc.x) < (best_loc.y, best_loc.x))
+                }
+            };
+            if better {
+                best = Some((overlap, loc));
+            }
+        }
+    }
+
+    best.map(|(_, loc)| loc).unwrap_or(area.loc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area() -> Rectangle<i32, Logical> {
+        Rectangle::new((0, 30).into(), (1000, 770).into())
+    }
+
+    #[test]
+    fn clamp_oversized() {
+        let loc = clamp_to_area((500, 500).into(), (2000, 2000).into(), area());
+        assert_eq!(loc, Point::from((0, 30)));
+    }
+
+    #[test]
+    fn cascade_steps_over_occupied() {
+        let size = Size::from((400, 300));
+        let occupied = [Point::from((0, 30)), Point::from((32, 62))];
+        assert_eq!(
+            cascade(area(), size, (32, 32).into(), &occupied),
+            Point::from((64, 94))
+        );
+    }
+
+    #[test]
+    fn cascade_non_positive_offset() {
+        let size = Size::from((400, 300));
+        let occupied = [Point::from((0, 30)), Point::from((1, 31))];
+        assert_eq!(
+            cascade(area(), size, (0, 0).into(), &occupied),
+            Point::from((2, 32))
+        );
+        assert_eq!(
+            cascade(area(), size, (-32, -32).into(), &occupied),
+            Point::from((2, 32))
+        );
+    }
+
+    #[test]
+    fn cascade_full_area() {
+        // every location is occupied, so cascading falls back to the top-left corner
+        let size = Size::from((900, 700));
+        let occupied = (0..=100)
+            .flat_map(|x| (0..=100).map(move |y| Point::from((x, 30 + y))))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cascade(area(), size, (32, 32).into(), &occupied),
+            Point::from((0, 30))
+        );
+    }
+
+    #[test]
+    fn smart_avoids_overlap() {
+        let size = Size::from((400, 300));
+        let obstacles = [Rectangle::new((0, 30).into(), (500, 400).into())];
+        assert_eq!(smart(area(), size, &obstacles), Point::from((500, 30)));
+
+        let empty = smart(area(), size, &[]);
+        assert_eq!(empty, Point::from((0, 30)));
+    }
+}
@@ -0,0 +1,384 @@
+//! Snapping of windows during interactive moves
+//!
+//! [`snap`] adjusts the location of an element moved around in a [`Space`], so that it sticks to the
+//! edges of the usable area of outputs and to the edges of other elements, if it comes closer than the
+//! configured thresholds. Moving the pointer against the edge of an output activates a [`SnapZone`],
+//! which is meant to resize the element to half or a quarter of the usable area, once the move finishes.
+//!
+//! [`SnapPreview`] provides a render element to show the geometry of the active zone.
+//!
+//! Snapping can be added to the move grabs of [`grabs`](crate::desktop::grabs) by implementing
+//! [`MoveResizeHandler::move_location`](crate::desktop::grabs::MoveResizeHandler::move_location):
+//!
+//! ```no_run
+//! # use smithay::desktop::{Space, Window};
+//! # use smithay::utils::{Logical, Point};
+//! use smithay::desktop::snapping::{self, SnapConfig, SnapPreview};
+//!
+//! # struct State { space: Space<Window>, snap_config: SnapConfig, snap_preview: SnapPreview }
+//! # impl State {
+//! fn move_location(
+//!     &mut self,
+//!     window: &Window,
+//!     location: Point<i32, Logical>,
+//!     input_location: Point<f64, Logical>,
+//! ) -> Point<i32, Logical> {
+//!     let result = snapping::snap(&self.snap_config, &self.space, window, location, input_location);
+//!     self.snap_preview.set_geometry(result.zone.map(|(_, geometry)| geometry));
+//!     result.location
+//! }
+//! # }
+//! ```
+
+use crate::{
+    backend::renderer::{
+        Color32F,
+        element::{
+            Kind,
+            solid::{SolidColorBuffer, SolidColorRenderElement},
+        },
+    },
+    desktop::{Space, placement::usable_area, space::SpaceElement},
+    utils::{Logical, Point, Rectangle, Scale, Size},
+};
+
+/// Configuration of the snapping behavior
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapConfig {
+    /// Distance at which elements snap to the edges of the usable area of an output
+    ///
+    /// `0` disables snapping to outputs.
+    pub edge_threshold: i32,
+    /// Distance at which elements snap to the edges of other elements
+    ///
+    /// `0` disables snapping to other elements.
+    pub element_threshold: i32,
+    /// Distance of the pointer to an output edge, that activates a [`SnapZone`]
+    ///
+    /// `None` disables snap zones.
+    pub zone_threshold: Option<i32>,
+    /// Size of the corners of an output, that activate quarter instead of half zones
+    pub zone_corner_size: i32,
+}
+
+impl Default for SnapConfig {
+    fn default() -> Self {
+        SnapConfig {
+            edge_threshold: 16,
+            element_threshold: 12,
+            zone_threshold: Some(2),
+            zone_corner_size: 64,
+        }
+    }
+}
+
+/// Zone of the usable area of an output an element can be snapped into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SnapZone {
+    /// The whole usable area
+    Maximized,
+    /// The left half
+    LeftHalf,
+    /// The right half
+    RightHalf,
+    /// The top-left quarter
+    TopLeftQuarter,
+    /// The top-right quarter
+    TopRightQuarter,
+    /// The bottom-left quarter
+    BottomLeftQuarter,
+    /// The bottom-right quarter
+    BottomRightQuarter,
+}
+
+impl SnapZone {
+    /// Returns the geometry of the zone inside of the given area
+    pub fn geometry(&self, area: Rectangle<i32, Logical>) -> Rectangle<i32, Logical> {
+        let left_w = area.size.w / 2;
+        let top_h = area.size.h / 2;
+        let right = Rectangle::new(
+            (area.loc.x + left_w, area.loc.y).into(),
+            (area.size.w - left_w, area.size.h).into(),
+        );
+        let left = Rectangle::new(area.loc, (left_w, area.size.h).into());
+
+        let top = |half: Rectangle<i32, Logical>| Rectangle::new(half.loc, (half.size.w, top_h).into());
+        let bottom = |half: Rectangle<i32, Logical>| {
+            Rectangle::new(
+                (half.loc.x, half.loc.y + top_h).into(),
+                (half.size.w, half.size.h - top_h).into(),
+            )
+        };
+
+        match self {
+            SnapZone::Maximized => area,
+            SnapZone::LeftHalf => left,
+            SnapZone::RightHalf => right,
+            SnapZone::TopLeftQuarter => top(left),
+            SnapZone::TopRightQuarter => top(right),
+            SnapZone::BottomLeftQuarter => bottom(left),
+            SnapZone::BottomRightQuarter => bottom(right),
+        }
+    }
+}
+
+/// Result of [`snap`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapResult {
+    /// The adjusted location of the element
+    pub location: Point<i32, Logical>,
+    /// The active snap zone and its geometry in space coordinates, if any
+    pub zone: Option<(SnapZone, Rectangle<i32, Logical>)>,
+}
+
+/// Snap an element moved to `location` to output and element edges
+///
+/// - `location` is the location the element would be moved to without snapping
+/// - `pointer` is the location of the input device moving the element, used to activate snap zones
+///
+/// `element` itself is ignored as a snapping target. If a zone is active, `location` is returned unaltered.
+pub fn snap<E: SpaceElement + PartialEq>(
+    config: &SnapConfig,
+    space: &Space<E>,
+    element: &E,
+    location: Point<i32, Logical>,
+    pointer: Point<f64, Logical>,
+) -> SnapResult {
+    if let Some(threshold) = config.zone_threshold {
+        if let Some(zone) = zone_at(config, space, pointer, threshold) {
+            return SnapResult {
+                location,
+                zone: Some(zone),
+            };
+        }
+    }
+
+    let rect = Rectangle::new(location, element.geometry().size);
+    let mut x_edges = Vec::new();
+    let mut y_edges = Vec::new();
+
+    if config.edge_threshold > 0 {
+        for area in space.outputs().filter_map(|output| usable_area(space, output)) {
+            if !expand(area, config.edge_threshold).overlaps(rect) {
+                continue;
+            }
+            x_edges.push((area.loc.x, config.edge_threshold));
+            x_edges.push((area.loc.x + area.size.w, config.edge_threshold));
+            y_edges.push((area.loc.y, config.edge_threshold));
+            y_edges.push((area.loc.y + area.size.h, config.edge_threshold));
+        }
+    }
+
+    if config.element_threshold > 0 {
+        let threshold = config.element_threshold;
+        for geo in space
+            .elements()
+            .filter(|e| *e != element)
+            .filter_map(|e| space.element_geometry(e))
+        {
+            // only snap to edges the element could actually touch
+            if ranges_overlap(rect.loc.y, rect.size.h, geo.loc.y, geo.size.h, threshold) {
+                x_edges.push((geo.loc.x, threshold));
+                x_edges.push((geo.loc.x + geo.size.w, threshold));
+            }
+            if ranges_overlap(rect.loc.x, rect.size.w, geo.loc.x, geo.size.w, threshold) {
+                y_edges.push((geo.loc.y, threshold));
+                y_edges.push((geo.loc.y + geo.size.h, threshold));
+            }
+        }
+    }
+
+    SnapResult {
+        location: Point::from((
+            snap_axis(rect.loc.x, rect.size.w, &x_edges),
+            snap_axis(rect.loc.y, rect.size.h, &y_edges),
+        )),
+        zone: None,
+    }
+}
+
+fn zone_at<E: SpaceElement + PartialEq>(
+    config: &SnapConfig,
+    space: &Space<E>,
+    pointer: Point<f64, Logical>,
+    threshold: i32,
+) -> Option<(SnapZone, Rectangle<i32, Logical>)> {
+    let output = space.output_under(pointer).next()?;
+    let output_geo = space.output_geometry(output)?.to_f64();
+    let threshold = threshold as f64;
+    let corner = config.zone_corner_size as f64;
+
+    // zones are only activated by edges, that don't lead to another output
+    let is_outer_edge = |offset: (f64, f64)| {
+        let outside = pointer + Point::from((offset.0 * (threshold + 1.), offset.1 * (threshold + 1.)));
+        space.output_under(outside).next().is_none()
+    };
+
+    let left = pointer.x - output_geo.loc.x;
+    let right = output_geo.loc.x + output_geo.size.w - pointer.x;
+    let top = pointer.y - output_geo.loc.y;
+    let bottom = output_geo.loc.y + output_geo.size.h - pointer.y;
+
+    let zone = if left <= threshold && is_outer_edge((-1., 0.)) {
+        if top < corner {
+            SnapZone::TopLeftQuarter
+        } else if bottom < corner {
+            SnapZone::BottomLeftQuarter
+        } else {
+            SnapZone::LeftHalf
+        }
+    } else if right <= threshold && is_outer_edge((1., 0.)) {
+        if top < corner {
+            SnapZone::TopRightQuarter
+        } else if bottom < corner {
+            SnapZone::BottomRightQuarter
+        } else {
+            SnapZone::RightHalf
+        }
+    } else if top <= threshold && is_outer_edge((0., -1.)) {
+        if left < corner {
+            SnapZone::TopLeftQuarter
+        } else if right < corner {
+            SnapZone::TopRightQuarter
+        } else {
+            SnapZone::Maximized
+        }
+    } else if bottom <= threshold && is_outer_edge((0., 1.)) {
+        if left < corner {
+            SnapZone::BottomLeftQuarter
+        } else if right < corner {
+            SnapZone::BottomRightQuarter
+        } else {
+            return None;
+        }
+    } else {
+        return None;
+    };
+
+    let area = usable_area(space, output)?;
+    Some((zone, zone.geometry(area)))
+}
+
+fn expand(rect: Rectangle<i32, Logical>, amount: i32) -> Rectangle<i32, Logical> {
+    Rectangle::new(
+        rect.loc - Point::from((amount, amount)),
+        rect.size + Size::from((amount * 2, amount * 2)),
+    )
+}
+
+fn ranges_overlap(a_start: i32, a_len: i32, b_start: i32, b_len: i32, tolerance: i32) -> bool {
+    a_start <= b_start + b_len + tolerance && b_start <= a_start + a_len + tolerance
+}
+
+/// Snaps a range starting at `start` with length `len` to the closest edge in range.
+///
+/// Each edge is given with the threshold it attracts ranges in.
+fn snap_axis(start: i32, len: i32, edges: &[(i32, i32)]) -> i32 {
+    let mut best: Option<(i32, i32)> = None;
+    for &(edge, threshold) in edges {
+        for new_start in [edge, edge - len] {
+            let distance = (new_start - start).abs();
+            if distance <= threshold && best.is_none_or(|(best_distance, _)| distance < best_distance) {
+                best = Some((distance, new_start));
+            }
+        }
+    }
+    best.map(|(_, new_start)| new_start).unwrap_or(start)
+}
+
+/// Preview of the active [`SnapZone`]
+///
+/// Renders a solid color rectangle at the geometry of the zone.
+#[derive(Debug, Clone)]
+pub struct SnapPreview {
+    buffer: SolidColorBuffer,
+    geometry: Option<Rectangle<i32, Logical>>,
+}
+
+impl SnapPreview {
+    /// Create a new preview with the given color
+    ///
+    /// The color should usually be translucent.
+    pub fn new(color: impl Into<Color32F>) -> Self {
+        SnapPreview {
+            buffer: SolidColorBuffer::new(Size::default(), color),
+            geometry: None,
+        }
+    }
+
+    /// Set the geometry of the preview in space coordinates, `None` hides the preview
+    pub fn set_geometry(&mut self, geometry: Option<Rectangle<i32, Logical>>) {
+        if let Some(geometry) = geometry {
+            self.buffer.resize(geometry.size);
+        }
+        self.geometry = geometry;
+    }
+
+    /// Returns the current geometry of the preview
+    pub fn geometry(&self) -> Option<Rectangle<i32, Logical>> {
+        self.geometry
+    }
+
+    /// Set the color of the preview
+    pub fn set_color(&mut self, color: impl Into<Color32F>) {
+        self.buffer.set_color(color);
+    }
+
+    /// Create a render element for the preview
+    ///
+    /// `output_location` is the location of the output in space coordinates, the element is rendered
+    /// relative to it. Returns `None` if the preview is hidden.
+    pub fn render_element(
+        &self,
+        output_location: Point<i32, Logical>,
+        scale: impl Into<Scale<f64>>,
+        alpha: f32,
+    ) -> Option<SolidColorRenderElement> {
+        let geometry = self.geometry?;
+        let scale = scale.into();
+        let location = (geometry.loc - output_location).to_physical_precise_round(scale);
+        Some(SolidColorRenderElement::from_buffer(
+            &self.buffer,
+            location,
+            scale,
+            alpha,
+            Kind::Unspecified,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snap_to_closest_edge() {
+        let edges = [(0, 16), (1920, 16), (500, 8)];
+        // left edge close to the output edge
+        assert_eq!(snap_axis(10, 300, &edges), 0);
+        // right edge close to the output edge
+        assert_eq!(snap_axis(1610, 300, &edges), 1620);
+        // out of range
+        assert_eq!(snap_axis(100, 300, &edges), 100);
+        // closer edge wins
+        assert_eq!(snap_axis(195, 300, &edges), 200);
+    }
+
+    #[test]
+    fn zone_geometry() {
+        let area = Rectangle::new((0, 30).into(), (1001, 771).into());
+        assert_eq!(SnapZone::Maximized.geometry(area), area);
+        assert_eq!(
+            SnapZone::LeftHalf.geometry(area),
+            Rectangle::new((0, 30).into(), (500, 771).into())
+        );
+        assert_eq!(
+            SnapZone::RightHalf.geometry(area),
+            Rectangle::new((500, 30).into(), (501, 771).into())
+        );
+        assert_eq!(
+            SnapZone::BottomRightQuarter.geometry(area),
+            Rectangle::new((500, 415).into(), (501, 386).into())
+        );
+    }
+}
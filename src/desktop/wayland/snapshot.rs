@@ -0,0 +1,435 @@
+use std::{fmt, sync::Mutex};
+
+use tracing::warn;
+use wayland_server::protocol::wl_surface::WlSurface;
+
+use crate::{
+    backend::renderer::{
+        ContextId, ImportAll, Renderer, Texture,
+        element::{AsRenderElements, Id, Kind, texture::TextureRenderElement},
+        utils::{
+            Buffer, RendererSurfaceState, RendererSurfaceStateUserData, SurfaceView, import_surface_tree,
+        },
+    },
+    desktop::{WeakWindow, Window},
+    utils::{Logical, Physical, Point, Rectangle, Scale, Transform},
+    wayland::{
+        compositor::{TraversalAction, with_surface_tree_downward},
+        image_capture_source::ImageCaptureSource,
+        seat::WaylandFocus,
+    },
+};
+
+struct SnapshotSurface<T: Texture> {
+    id: Id,
+    texture: T,
+    // relative to the origin of the root surface
+    location: Point<i32, Logical>,
+    buffer_scale: i32,
+    buffer_transform: Transform,
+    view: SurfaceView,
+}
+
+/// The last committed buffers of a window
+///
+/// Hidden windows are usually not rendered and clients may even unmap their surfaces, like
+/// X11 clients do for windows minimized through `_NET_WM_STATE_HIDDEN`. A [`WindowSnapshot`]
+/// keeps the last committed buffers of a window alive, so it can still be rendered, e.g. as a
+/// preview in a window switcher or into the frames of a toplevel [`ImageCaptureSource`].
+///
+/// The X11 window manager keeps the last buffer of unmapped X11 windows automatically, so
+/// [`WindowSnapshot::from_window`] works for them until they are mapped again. Wayland clients
+/// may attach a `NULL` buffer at any time, so a snapshot should be taken before hiding a window
+/// and dropped once it is shown again:
+///
+/// ```no_run
+/// # use smithay::backend::renderer::{ImportAll, Renderer, Texture};
+/// # use smithay::desktop::{Window, WindowSnapshot};
+/// # fn hide<R: Renderer + ImportAll>(renderer: &mut R, window: &Window)
+/// # where R::TextureId: Texture + Clone + 'static
+/// # {
+/// if let Ok(Some(snapshot)) = WindowSnapshot::from_window(renderer, window) {
+///     window.user_data().insert_if_missing(|| std::cell::RefCell::new(snapshot));
+/// }
+/// # }
+/// ```
+///
+/// Holding the snapshot keeps the buffers of the client alive and prevents them from being released.
+///
+/// ## Coordinates
+///
+/// Like for a [`Window`], all coordinates of a snapshot are relative to the origin of the root
+/// surface and [`AsRenderElements::render_elements`] expects the location of that origin. To place
+/// the window geometry at a given position, the snapshot has to be rendered at that position minus
+/// the location of [`WindowSnapshot::geometry`], just like a [`Space`](crate::desktop::Space) does.
+///
+/// ## Toplevel capture
+///
+/// Smithay doesn't render the frames of image capture sessions itself. A compositor capturing a
+/// toplevel through the [`ImageCopyCaptureHandler`] can associate the window with the capture
+/// source using [`set_capture_source_window`], once it is created. For each [`Frame`], it then
+/// takes a snapshot of the source, reports buffer constraints based on the size of
+/// [`WindowSnapshot::geometry`] and renders the elements of the snapshot into the buffer:
+///
+/// ```no_run
+/// # use smithay::backend::renderer::{ImportAll, Renderer, Texture};
+/// # use smithay::desktop::WindowSnapshot;
+/// # use smithay::utils::Scale;
+/// # use smithay::wayland::image_capture_source::ImageCaptureSource;
+/// # fn capture<R, T>(renderer: &mut R, source: &ImageCaptureSource)
+/// # where R: Renderer<TextureId = T> + ImportAll, T: Texture + Clone + 'static
+/// # {
+/// if let Ok(Some(snapshot)) = WindowSnapshot::from_capture_source(renderer, source) {
+///     let elements = snapshot.capture_elements(renderer, Scale::from(1.0));
+///     // render the elements into the buffer of the frame, e.g. using an `OutputDamageTracker`
+/// }
+/// # }
+/// ```
+///
+/// [`ImageCopyCaptureHandler`]: crate::wayland::image_copy_capture::ImageCopyCaptureHandler
+/// [`Frame`]: crate::wayland::image_copy_capture::Frame
+pub struct WindowSnapshot<T: Texture> {
+    context_id: ContextId<T>,
+    surfaces: Vec<SnapshotSurface<T>>,
+    // keeps the client from reusing the buffers backing the textures
+    _buffers: Vec<Buffer>,
+    geometry: Rectangle<i32, Logical>,
+    bbox: Rectangle<i32, Logical>,
+}
+
+impl<T: Texture> fmt::Debug for WindowSnapshot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WindowSnapshot")
+            .field("context_id", &self.context_id)
+            .field("surfaces", &self.surfaces.len())
+            .field("geometry", &self.geometry)
+            .field("bbox", &self.bbox)
+            .finish()
+    }
+}
+
+impl<T: Texture + Clone + 'static> WindowSnapshot<T> {
+    /// Takes a snapshot of the surfaces of a [`Window`], not including its popups
+    ///
+    /// Works for both wayland and X11 windows. Returns `None`, if the window currently has no
+    /// buffers attached. The geometry of the snapshot is the one of the window, see
+    /// [`Window::geometry`].
+    ///
+    /// X11 clients unmap their windows to iconify them, which destroys the underlying surface.
+    /// The last buffer of an unmapped X11 window is kept until it gets mapped again, so this also
+    /// works after the window got unmapped, as long as the buffer was rendered with `renderer`
+    /// before.
+    pub fn from_window<R>(renderer: &mut R, window: &Window) -> Result<Option<Self>, R::Error>
+    where
+        R: Renderer<TextureId = T> + ImportAll,
+    {
+        let snapshot = match window.wl_surface() {
+            Some(surface) => Self::from_surface_tree(renderer, &surface)?,
+            #[cfg(feature = "xwayland")]
+            None if window.is_x11() => {
+                let retained = window.x11_surface().unwrap().retained_state();
+                let state = retained.lock().unwrap();
+                state
+                    .as_ref()
+                    .and_then(|state| snapshot_surface(&renderer.context_id(), state, Point::default()))
+                    .and_then(|(surface, buffer)| {
+                        Self::new(renderer.context_id(), vec![surface], vec![buffer])
+                    })
+            }
+            None => None,
+        };
+        Ok(snapshot.map(|snapshot| WindowSnapshot {
+            geometry: window.geometry(),
+            ..snapshot
+        }))
+    }
+
+    /// Takes a snapshot of the window associated with a toplevel [`ImageCaptureSource`]
+    ///
+    /// Returns `None`, if no window was associated with the source using
+    /// [`set_capture_source_window`], the window is gone or it has no buffers, see
+    /// [`WindowSnapshot::from_window`].
+    pub fn from_capture_source<R>(
+        renderer: &mut R,
+        source: &ImageCaptureSource,
+    ) -> Result<Option<Self>, R::Error>
+    where
+        R: Renderer<TextureId = T> + ImportAll,
+    {
+        match capture_source_window(source) {
+            Some(window) => Self::from_window(renderer, &window),
+            None => Ok(None),
+        }
+    }
+
+    /// Takes a snapshot of a surface and its subsurfaces
+    ///
+    /// Returns `None`, if none of the surfaces currently has a buffer attached.
+    pub fn from_surface_tree<R>(renderer: &mut R, surface: &WlSurface) -> Result<Option<Self>, R::Error>
+    where
+        R: Renderer<TextureId = T> + ImportAll,
+    {
+        import_surface_tree(renderer, surface)?;
+
+        let context_id = renderer.context_id();
+        let mut surfaces = Vec::new();
+        let mut buffers = Vec::new();
+        with_surface_tree_downward(
+            surface,
+            Point::<i32, Logical>::default(),
+            |_, states, location| {
+                let view = states
+                    .data_map
+                    .get::<RendererSurfaceStateUserData>()
+                    .and_then(|data| data.lock().unwrap().view());
+                match view {
+                    Some(view) => TraversalAction::DoChildren(*location + view.offset),
+                    None => TraversalAction::SkipChildren,
+                }
+            },
+            |_, states, location| {
+                let Some(data) = states.data_map.get::<RendererSurfaceStateUserData>() else {
+                    return;
+                };
+                if let Some((surface, buffer)) =
+                    snapshot_surface(&context_id, &data.lock().unwrap(), *location)
+                {
+                    surfaces.push(surface);
+                    buffers.push(buffer);
+                }
+            },
+            |_, _, _| true,
+        );
+
+        Ok(Self::new(context_id, surfaces, buffers))
+    }
+}
+
+impl<T: Texture> WindowSnapshot<T> {
+    fn new(
+        context_id: ContextId<T>,
+        surfaces: Vec<SnapshotSurface<T>>,
+        buffers: Vec<Buffer>,
+    ) -> Option<Self> {
+        let bbox = surfaces
+            .iter()
+            .map(|surface| Rectangle::new(surface.location, surface.view.dst))
+            .reduce(|bbox, rect| bbox.merge(rect))?;
+        Some(WindowSnapshot {
+            context_id,
+            surfaces,
+            _buffers: buffers,
+            geometry: bbox,
+            bbox,
+        })
+    }
+
+    /// Returns the geometry of the snapshot relative to the origin of the root surface
+    ///
+    /// This is the geometry of the window at the time the snapshot was taken, see
+    /// [`Window::geometry`], or the bounding box of the surfaces for snapshots of surface trees.
+    pub fn geometry(&self) -> Rectangle<i32, Logical> {
+        self.geometry
+    }
+
+    /// Returns the bounding box of all surfaces of the snapshot relative to the origin of the root surface
+    pub fn bbox(&self) -> Rectangle<i32, Logical> {
+        self.bbox
+    }
+}
+
+impl<T: Texture + Clone + 'static> WindowSnapshot<T> {
+    /// Returns the render elements of the snapshot for a toplevel capture frame
+    ///
+    /// The elements place the window geometry at the origin of the frame.
+    pub fn capture_elements<R>(&self, renderer: &mut R, scale: Scale<f64>) -> Vec<TextureRenderElement<T>>
+    where
+        R: Renderer<TextureId = T>,
+    {
+        let location = (Point::default() - self.geometry.loc).to_physical_precise_round(scale);
+        self.render_elements(renderer, location, scale, 1.0)
+    }
+}
+
+fn snapshot_surface<T: Texture + Clone + 'static>(
+    context_id: &ContextId<T>,
+    data: &RendererSurfaceState,
+    location: Point<i32, Logical>,
+) -> Option<(SnapshotSurface<T>, Buffer)> {
+    let (Some(view), Some(buffer)) = (data.view(), data.buffer()) else {
+        return None;
+    };
+    let Some(texture) = data.texture(context_id.clone()) else {
+        warn!("Surface buffer was not imported, skipping it in snapshot");
+        return None;
+    };
+    let surface = SnapshotSurface {
+        id: Id::new(),
+        texture: texture.clone(),
+        location: location + view.offset,
+        buffer_scale: data.buffer_scale(),
+        buffer_transform: data.buffer_transform(),
+        view,
+    };
+    Some((surface, buffer.clone()))
+}
+
+#[derive(Debug)]
+struct CaptureSourceWindow(Mutex<WeakWindow>);
+
+/// Associates a [`Window`] with a toplevel [`ImageCaptureSource`]
+///
+/// Call this from [`ToplevelCaptureSourceHandler::toplevel_source_created`] with the window of the
+/// foreign toplevel, to render the frames of the source using [`WindowSnapshot::from_capture_source`].
+/// Only a weak reference to the window is stored in the source.
+///
+/// [`ToplevelCaptureSourceHandler::toplevel_source_created`]: crate::wayland::image_capture_source::ToplevelCaptureSourceHandler::toplevel_source_created
+pub fn set_capture_source_window(source: &ImageCaptureSource, window: &Window) {
+    let user_data = source.user_data();
+    user_data.insert_if_missing_threadsafe(|| CaptureSourceWindow(Mutex::new(window.downgrade())));
+    *user_data.get::<CaptureSourceWindow>().unwrap().0.lock().unwrap() = window.downgrade();
+}
+
+/// Returns the [`Window`] associated with a toplevel [`ImageCaptureSource`], see [`set_capture_source_window`]
+pub fn capture_source_window(source: &ImageCaptureSource) -> Option<Window> {
+    source
+        .user_data()
+        .get::<CaptureSourceWindow>()
+        .and_then(|window| window.0.lock().unwrap().upgrade())
+}
+
+impl<R, T> AsRenderElements<R> for WindowSnapshot<T>
+where
+    R: Renderer<TextureId = T>,
+    T: Texture + Clone + 'static,
+{
+    type RenderElement = TextureRenderElement<T>;
+
+    fn render_elements<C: From<Self::RenderElement>>(
+        &self,
+        _renderer: &mut R,
+        location: Point<i32, Physical>,
+        scale: Scale<f64>,
+        alpha: f32,
+    ) -> Vec<C> {
+        self.surfaces
+            .iter()
+            .map(|surface| {
+                let location = location.to_f64() + surface.location.to_f64().to_physical(scale);
+                TextureRenderElement::from_static_texture(
+                    surface.id.clone(),
+                    self.context_id.clone(),
+                    location,
+                    surface.texture.clone(),
+                    surface.buffer_scale,
+                    surface.buffer_transform,
+                    Some(alpha),
+                    Some(surface.view.src),
+                    Some(surface.view.dst),
+                    None,
+                    Kind::Unspecified,
+                )
+                .into()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{
+        allocator::Fourcc,
+        renderer::{
+            ImportMem,
+            element::Element,
+            test::{DummyRenderer, DummyTexture},
+        },
+    };
+
+    fn surface(
+        renderer: &mut DummyRenderer,
+        location: (i32, i32),
+        size: (i32, i32),
+        buffer_scale: i32,
+    ) -> SnapshotSurface<DummyTexture> {
+        let buffer_size = (size.0 * buffer_scale, size.1 * buffer_scale);
+        SnapshotSurface {
+            id: Id::new(),
+            texture: renderer
+                .import_memory(&[], Fourcc::Argb8888, buffer_size.into(), false)
+                .unwrap(),
+            location: location.into(),
+            buffer_scale,
+            buffer_transform: Transform::Normal,
+            view: SurfaceView {
+                src: Rectangle::from_size(size.into()).to_f64(),
+                dst: size.into(),
+                offset: Point::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn capture_source_without_window() {
+        let mut renderer = DummyRenderer;
+        let source = ImageCaptureSource::new();
+        assert!(capture_source_window(&source).is_none());
+        assert!(
+            WindowSnapshot::from_capture_source(&mut renderer, &source)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn empty_snapshot() {
+        let renderer = DummyRenderer;
+        assert!(WindowSnapshot::new(renderer.context_id(), Vec::new(), Vec::new()).is_none());
+    }
+
+    #[test]
+    fn bbox_includes_subsurfaces() {
+        let mut renderer = DummyRenderer;
+        let surfaces = vec![
+            surface(&mut renderer, (0, 0), (800, 600), 1),
+            // a subsurface above and left of the root surface
+            surface(&mut renderer, (-20, -30), (100, 50), 2),
+        ];
+        let snapshot = WindowSnapshot::new(renderer.context_id(), surfaces, Vec::new()).unwrap();
+        assert_eq!(
+            snapshot.bbox(),
+            Rectangle::new((-20, -30).into(), (820, 630).into())
+        );
+        assert_eq!(snapshot.geometry(), snapshot.bbox());
+    }
+
+    #[test]
+    fn render_relative_to_root_surface() {
+        let mut renderer = DummyRenderer;
+        let surfaces = vec![
+            surface(&mut renderer, (0, 0), (800, 600), 1),
+            surface(&mut renderer, (10, 20), (100, 50), 2),
+        ];
+        let mut snapshot = WindowSnapshot::new(renderer.context_id(), surfaces, Vec::new()).unwrap();
+        // e.g. client side shadows around the window geometry
+        snapshot.geometry = Rectangle::new((5, 5).into(), (790, 590).into());
+
+        let scale = Scale::from(2.0);
+        let elements = snapshot.capture_elements(&mut renderer, scale);
+
+        assert_eq!(elements.len(), 2);
+        assert_eq!(
+            elements[0].geometry(scale),
+            Rectangle::new((-10, -10).into(), (1600, 1200).into())
+        );
+        assert_eq!(
+            elements[1].geometry(scale),
+            Rectangle::new((10, 30).into(), (200, 100).into())
+        );
+        assert_eq!(
+            elements[1].src(),
+            Rectangle::from_size((200, 100).into()).to_f64()
+        );
+    }
+}
@@ -1,18 +1,24 @@
 #[cfg(feature = "wayland_frontend")]
 use crate::utils::Serial;
-#[cfg(feature = "xwayland")]
-use crate::{desktop::space::SpaceElement, xwayland::X11Surface};
 use crate::{
+    backend::renderer::element::Kind,
     desktop::{PopupManager, space::RenderZindex, utils::*},
     output::Output,
     utils::{IsAlive, Logical, Point, Rectangle, user_data::UserDataMap},
     wayland::{
         compositor::{SurfaceData, with_states},
+        content_type::ContentTypeSurfaceCachedState,
         dmabuf::DmabufFeedback,
         seat::WaylandFocus,
         shell::xdg::{SurfaceCachedState, ToplevelSurface},
+        xdg_toplevel_icon::ToplevelIconCachedState,
     },
 };
+#[cfg(feature = "xwayland")]
+use crate::{
+    desktop::space::SpaceElement,
+    xwayland::{X11Surface, xwm::X11Icon},
+};
 use std::{
     borrow::Cow,
     hash::{Hash, Hasher},
@@ -23,7 +29,8 @@ use std::{
     time::Duration,
 };
 use wayland_protocols::{
-    wp::presentation_time::server::wp_presentation_feedback, xdg::shell::server::xdg_toplevel,
+    wp::{content_type::v1::server::wp_content_type_v1, presentation_time::server::wp_presentation_feedback},
+    xdg::shell::server::xdg_toplevel,
 };
 use wayland_server::protocol::wl_surface;
 
@@ -40,6 +47,18 @@ pub enum WindowSurface {
     X11(X11Surface),
 }
 
+/// Represents the icon of a [`Window`]
+#[derive(Debug, Clone)]
+pub enum WindowIcon {
+    /// Icon set by a wayland client through the xdg-toplevel-icon protocol
+    ///
+    /// See [`xdg_toplevel_icon`](crate::wayland::xdg_toplevel_icon).
+    Wayland(ToplevelIconCachedState),
+    /// Icons set by an X11 client through the `_NET_WM_ICON` property, in all provided sizes
+    #[cfg(feature = "xwayland")]
+    X11(Arc<[X11Icon]>),
+}
+
 #[derive(Debug)]
 pub(crate) struct WindowInner {
     pub(crate) id: usize,
@@ -487,6 +506,58 @@ impl Window {
         }
     }
 
+    /// Returns the [`Kind`] render elements of this window should use
+    ///
+    /// Wayland windows hinting a `video` or `game` content type through the
+    /// [`content_type`](crate::wayland::content_type) protocol and X11 windows following
+    /// [`X11Surface::scanout_kind`] are considered [`Kind::ScanoutCandidate`]s. The fullscreen
+    /// state is not taken into account.
+    pub fn scanout_kind(&self) -> Kind {
+        match &self.0.surface {
+            WindowSurface::Wayland(s) => {
+                let content_type = with_states(s.wl_surface(), |states| {
+                    *states
+                        .cached_state
+                        .get::<ContentTypeSurfaceCachedState>()
+                        .current()
+                        .content_type()
+                });
+                if matches!(
+                    content_type,
+                    wp_content_type_v1::Type::Video | wp_content_type_v1::Type::Game
+                ) {
+                    Kind::ScanoutCandidate
+                } else {
+                    Kind::Unspecified
+                }
+            }
+            #[cfg(feature = "xwayland")]
+            WindowSurface::X11(s) => s.scanout_kind(),
+        }
+    }
+
+    /// Returns the icon of this window, if the client provided one
+    pub fn icon(&self) -> Option<WindowIcon> {
+        match &self.0.surface {
+            WindowSurface::Wayland(s) => {
+                let icon = with_states(s.wl_surface(), |states| {
+                    states
+                        .cached_state
+                        .get::<ToplevelIconCachedState>()
+                        .current()
+                        .clone()
+                });
+                (icon.icon_name().is_some() || !icon.buffers().is_empty())
+                    .then_some(WindowIcon::Wayland(icon))
+            }
+            #[cfg(feature = "xwayland")]
+            WindowSurface::X11(s) => {
+                let icons = s.icons();
+                (!icons.is_empty()).then_some(WindowIcon::X11(icons))
+            }
+        }
+    }
+
     /// Returns the underlying surface
     pub fn underlying_surface(&self) -> &WindowSurface {
         &self.0.surface
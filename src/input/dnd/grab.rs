@@ -18,6 +18,15 @@ use crate::{
             GestureSwipeUpdateEvent, GrabStartData as PointerGrabStartData,
             MotionEvent as PointerMotionEvent, PointerGrab, PointerInnerHandle, RelativeMotionEvent,
         },
+        tablet::{
+            TabletSeatHandler,
+            tool::{
+                AxisFrame as TabletToolAxisFrame, ButtonEvent as TabletToolButtonEvent,
+                DownEvent as TabletToolDownEvent, GrabStartData as TabletToolGrabStartData,
+                MotionEvent as TabletToolMotionEvent, ProximityOutEvent, TabletToolGrab,
+                TabletToolInnerHandle, UpEvent as TabletToolUpEvent,
+            },
+        },
         touch::{
             DownEvent, GrabStartData as TouchGrabStartData, MotionEvent as TouchMotionEvent, TouchGrab,
             TouchInnerHandle, UpEvent,
@@ -35,6 +44,12 @@ pub enum GrabType {
     Pointer,
     /// Touch input was validated for the requested grab
     Touch,
+    /// Tablet tool input was validated for the requested grab
+    ///
+    /// The tool holding the grab can be found using [`TabletSeat::tool_with_grab`].
+    ///
+    /// [`TabletSeat::tool_with_grab`]: crate::input::tablet::TabletSeat::tool_with_grab
+    TabletTool,
 }
 
 /// Grab during a client-initiated DnD operation.
@@ -131,12 +146,70 @@ where
     }
 }
 
+/// Grab during a client-initiated DnD operation started by a tablet tool.
+///
+/// Works like [`DnDGrab`], but requires the [`TabletSeatHandler::ToolFocus`] to be the
+/// [`SeatHandler::PointerFocus`], so that the drop target can be reported as [`DndTarget::TabletTool`].
+pub struct TabletToolDnDGrab<D: TabletSeatHandler, S: Source>
+where
+    D::ToolFocus: DndFocus<D>,
+{
+    start_data: TabletToolGrabStartData<D>,
+    grab: DnDGrab<D, S, D::ToolFocus>,
+}
+
+impl<D, S> fmt::Debug for TabletToolDnDGrab<D, S>
+where
+    D: TabletSeatHandler + 'static,
+    S: Source + fmt::Debug,
+    D::ToolFocus: DndFocus<D> + fmt::Debug,
+    <D::ToolFocus as DndFocus<D>>::OfferData<S>: fmt::Debug + 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TabletToolDnDGrab")
+            .field("start_data", &self.start_data)
+            .field("grab", &self.grab)
+            .finish()
+    }
+}
+
+impl<D: TabletSeatHandler<ToolFocus = <D as SeatHandler>::PointerFocus>, S: Source> TabletToolDnDGrab<D, S>
+where
+    D::ToolFocus: DndFocus<D>,
+{
+    /// Create a new TabletToolDnDGrab from an implicit tablet tool grab for a given source
+    pub fn new(
+        #[cfg(feature = "wayland_frontend")] dh: &DisplayHandle,
+        start_data: TabletToolGrabStartData<D>,
+        source: S,
+        seat: Seat<D>,
+    ) -> Self {
+        let grab = DnDGrab {
+            #[cfg(feature = "wayland_frontend")]
+            dh: dh.clone(),
+            pointer_start_data: None,
+            touch_start_data: None,
+            last_position: start_data.location,
+            data_source: Arc::new(source),
+            current_focus: None,
+            offer_data: None,
+            seat,
+            should_drop: false,
+        };
+        Self { start_data, grab }
+    }
+}
+
 /// Enum over DndFocus candidates receiving a drop from `DnDGrab`
 pub enum DndTarget<'a, D: SeatHandler> {
     /// A Pointer-based DnDGrab ended on a `D::PointerFocus`
     Pointer(&'a D::PointerFocus),
     /// A Touch-based DnDGrab ended on a `D::TouchFocus`
     Touch(&'a D::TouchFocus),
+    /// A TabletTool-based DnDGrab ended on a `D::ToolFocus`
+    ///
+    /// Tablet tool grabs require `D::ToolFocus` to be `D::PointerFocus`, see [`TabletToolDnDGrab`].
+    TabletTool(&'a D::PointerFocus),
 }
 
 impl<D: SeatHandler> fmt::Debug for DndTarget<'_, D>
@@ -148,6 +221,7 @@ where
         match self {
             Self::Pointer(p) => f.debug_tuple("DndTarget::Pointer").field(p).finish(),
             Self::Touch(t) => f.debug_tuple("DndTarget::Touch").field(t).finish(),
+            Self::TabletTool(t) => f.debug_tuple("DndTarget::TabletTool").field(t).finish(),
         }
     }
 }
@@ -157,11 +231,12 @@ impl<'a, D: SeatHandler> DndTarget<'a, D> {
     ///
     /// ## Panics
     ///
-    /// Panics if the self value equals `Touch`.
+    /// Panics if the self value equals `Touch` or `TabletTool`.
     pub fn unwrap_pointer(self) -> &'a D::PointerFocus {
         match self {
             DndTarget::Pointer(p) => p,
             DndTarget::Touch(_) => panic!("unwrap_pointer on touch-based dnd grab target"),
+            DndTarget::TabletTool(_) => panic!("unwrap_pointer on tablet-tool-based dnd grab target"),
         }
     }
 
@@ -169,11 +244,25 @@ impl<'a, D: SeatHandler> DndTarget<'a, D> {
     ///
     /// ## Panics
     ///
-    /// Panics if the self value equals `Pointer`.
+    /// Panics if the self value equals `Pointer` or `TabletTool`.
     pub fn unwrap_touch(self) -> &'a D::TouchFocus {
         match self {
-            DndTarget::Pointer(_) => panic!("unwrap_pointer on pointer-based dnd grab target"),
+            DndTarget::Pointer(_) => panic!("unwrap_touch on pointer-based dnd grab target"),
             DndTarget::Touch(t) => t,
+            DndTarget::TabletTool(_) => panic!("unwrap_touch on tablet-tool-based dnd grab target"),
+        }
+    }
+
+    /// Returns the contained `TabletTool`-value, consuming `self`.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the self value equals `Pointer` or `Touch`.
+    pub fn unwrap_tablet_tool(self) -> &'a D::PointerFocus {
+        match self {
+            DndTarget::Pointer(_) => panic!("unwrap_tablet_tool on pointer-based dnd grab target"),
+            DndTarget::Touch(_) => panic!("unwrap_tablet_tool on touch-based dnd grab target"),
+            DndTarget::TabletTool(t) => t,
         }
     }
 }
@@ -184,6 +273,7 @@ impl<'a, F, D: SeatHandler<PointerFocus = F, TouchFocus = F>> DndTarget<'a, D> {
         match self {
             DndTarget::Pointer(p) => p,
             DndTarget::Touch(t) => t,
+            DndTarget::TabletTool(t) => t,
         }
     }
 }
@@ -558,6 +648,87 @@ where
     }
 }
 
+impl<D, S> TabletToolGrab<D> for TabletToolDnDGrab<D, S>
+where
+    D: DndGrabHandler,
+    D: TabletSeatHandler<ToolFocus = <D as SeatHandler>::PointerFocus>,
+    <D as TabletSeatHandler>::ToolFocus: DndFocus<D> + 'static,
+    D: 'static,
+    S: Source,
+{
+    fn start_data(&self) -> &TabletToolGrabStartData<D> {
+        &self.start_data
+    }
+
+    fn proximity_out(
+        &mut self,
+        data: &mut D,
+        handle: &mut TabletToolInnerHandle<'_, D>,
+        event: &ProximityOutEvent,
+    ) {
+        handle.proximity_out(data, event);
+        // the tool left without lifting the tip first, abandon the drag
+        handle.unset_grab(self, data, event.serial, event.time, false);
+    }
+
+    fn motion(
+        &mut self,
+        data: &mut D,
+        handle: &mut TabletToolInnerHandle<'_, D>,
+        focus: Option<(<D as TabletSeatHandler>::ToolFocus, Point<f64, Logical>)>,
+        event: &TabletToolMotionEvent,
+    ) {
+        handle.motion(data, self.tool_focus(), event);
+
+        self.grab.last_position = event.location;
+
+        self.grab
+            .update_focus(data, focus, event.location, event.serial, event.time);
+    }
+
+    fn down(
+        &mut self,
+        _data: &mut D,
+        _handle: &mut TabletToolInnerHandle<'_, D>,
+        _event: &TabletToolDownEvent,
+    ) {
+        // Ignore
+    }
+
+    fn up(&mut self, data: &mut D, handle: &mut TabletToolInnerHandle<'_, D>, event: &TabletToolUpEvent) {
+        handle.up(data, event);
+
+        // the user dropped, proceed to the drop
+        self.grab.should_drop = true;
+        handle.unset_grab(self, data, event.serial, event.time, true);
+    }
+
+    fn button(
+        &mut self,
+        data: &mut D,
+        handle: &mut TabletToolInnerHandle<'_, D>,
+        event: &TabletToolButtonEvent,
+    ) {
+        handle.button(data, event);
+    }
+
+    fn axis(&mut self, data: &mut D, handle: &mut TabletToolInnerHandle<'_, D>, frame: TabletToolAxisFrame) {
+        handle.axis(data, frame);
+    }
+
+    fn frame(&mut self, data: &mut D, handle: &mut TabletToolInnerHandle<'_, D>, time: u32) {
+        handle.frame(data, time);
+    }
+
+    fn unset(&mut self, data: &mut D) {
+        if self.grab.should_drop {
+            self.grab.drop(data, |focus| DndTarget::TabletTool(focus));
+        } else {
+            self.grab.cancel(data);
+        }
+    }
+}
+
 #[cfg(not(feature = "xwayland"))]
 impl<D, S> DnDGrab<D, S, D::PointerFocus>
 where
@@ -586,6 +757,20 @@ where
     }
 }
 
+#[cfg(not(feature = "xwayland"))]
+impl<D, S> TabletToolDnDGrab<D, S>
+where
+    D: DndGrabHandler,
+    D: TabletSeatHandler<ToolFocus = <D as SeatHandler>::PointerFocus>,
+    <D as TabletSeatHandler>::ToolFocus: DndFocus<D> + 'static,
+    D: 'static,
+    S: Source,
+{
+    fn tool_focus(&self) -> Option<(<D as TabletSeatHandler>::ToolFocus, Point<f64, Logical>)> {
+        None
+    }
+}
+
 #[cfg(feature = "xwayland")]
 impl<D, S> DnDGrab<D, S, D::PointerFocus>
 where
@@ -633,3 +818,23 @@ where
             })
     }
 }
+
+#[cfg(feature = "xwayland")]
+impl<D, S> TabletToolDnDGrab<D, S>
+where
+    D: DndGrabHandler,
+    D: TabletSeatHandler<ToolFocus = <D as SeatHandler>::PointerFocus>,
+    <D as TabletSeatHandler>::ToolFocus: DndFocus<D> + 'static,
+    D: 'static,
+    S: Source,
+{
+    fn tool_focus(&self) -> Option<(<D as TabletSeatHandler>::ToolFocus, Point<f64, Logical>)> {
+        // While the grab is active, we don't want any focus except for xwayland
+        self.start_data.focus.clone().filter(|(focus, _)| {
+            focus.wl_surface().is_some_and(|s| {
+                s.client()
+                    .is_some_and(|c| c.get_data::<XWaylandClientData>().is_some())
+            })
+        })
+    }
+}
@@ -9,7 +9,7 @@ use std::sync::RwLock;
 use std::{
     default::Default,
     fmt, io,
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, MutexGuard},
 };
 use thiserror::Error;
 use tracing::{debug, info, info_span, instrument, trace};
@@ -29,6 +29,9 @@ pub use keymap_file::{KeymapFile, KeymapFileId};
 mod modifiers_state;
 pub use modifiers_state::{ModifiersState, SerializedMods};
 
+mod remap;
+pub use remap::{KeyRemap, KeyRemapHandler, KeyRemapState, RemappedKeyEvent, TapHold};
+
 mod xkb_config;
 pub use xkb_config::XkbConfig;
 
@@ -137,6 +140,9 @@ pub struct Xkb {
     context: xkb::Context,
     keymap: xkb::Keymap,
     state: xkb::State,
+    compose: Option<xkb::compose::State>,
+    // whether the compose state accepted the last pressed key
+    compose_fed: bool,
 }
 
 impl Xkb {
@@ -164,6 +170,20 @@ impl Xkb {
         &self.state
     }
 
+    /// Status of the compose sequence, `None` if no compose table is set.
+    ///
+    /// See [`KeyboardHandle::set_compose_table_from_locale`].
+    pub fn compose_status(&self) -> Option<xkb::compose::Status> {
+        self.compose.as_ref().map(|compose| compose.status())
+    }
+
+    fn reset_compose(&mut self) {
+        if let Some(compose) = self.compose.as_mut() {
+            compose.reset();
+        }
+        self.compose_fed = false;
+    }
+
     /// Get the active layout of the keyboard.
     pub fn active_layout(&self) -> Layout {
         (0..self.keymap.num_layouts())
@@ -195,6 +215,10 @@ impl fmt::Debug for Xkb {
             .field("context", &self.context.get_raw_ptr())
             .field("keymap", &self.keymap.get_raw_ptr())
             .field("state", &self.state.get_raw_ptr())
+            .field(
+                "compose",
+                &self.compose.as_ref().map(|compose| compose.get_raw_ptr()),
+            )
             .finish()
     }
 }
@@ -232,6 +256,10 @@ pub(crate) struct KbdInternal<D: SeatHandler> {
     pub(crate) forwarded_pressed_keys: HashSet<Keycode>,
     pub(crate) mods_state: ModifiersState,
     xkb: Arc<Mutex<Xkb>>,
+    default_keymap: xkb::Keymap,
+    source_keymaps: HashMap<KeyboardSource, xkb::Keymap>,
+    source_layouts: HashMap<KeyboardSource, xkb::LayoutIndex>,
+    active_source: KeyboardSource,
     pub(crate) repeat_rate: i32,
     pub(crate) repeat_delay: i32,
     led_mapping: LedMapping,
@@ -249,6 +277,7 @@ impl<D: SeatHandler> fmt::Debug for KbdInternal<D> {
             .field("forwarded_pressed_keys", &self.forwarded_pressed_keys)
             .field("mods_state", &self.mods_state)
             .field("xkb", &self.xkb)
+            .field("active_source", &self.active_source)
             .field("repeat_rate", &self.repeat_rate)
             .field("repeat_delay", &self.repeat_delay)
             .finish()
@@ -284,10 +313,16 @@ impl<D: SeatHandler + 'static> KbdInternal<D> {
             key_sources: HashMap::new(),
             forwarded_pressed_keys: HashSet::new(),
             mods_state: ModifiersState::default(),
+            default_keymap: keymap.clone(),
+            source_keymaps: HashMap::new(),
+            source_layouts: HashMap::new(),
+            active_source: KeyboardSource::MAIN,
             xkb: Arc::new(Mutex::new(Xkb {
                 context,
                 keymap,
                 state,
+                compose: None,
+                compose_fed: false,
             })),
             repeat_rate,
             repeat_delay,
@@ -297,6 +332,73 @@ impl<D: SeatHandler + 'static> KbdInternal<D> {
         })
     }
 
+    fn source_keymap(&self, source: KeyboardSource) -> &xkb::Keymap {
+        self.source_keymaps.get(&source).unwrap_or(&self.default_keymap)
+    }
+
+    // Replace the keymap of the seat state, replaying the currently pressed keys. If `locked`
+    // is set, the locked modifiers and the given layout are carried over to the new state.
+    //
+    // Returns whether the leds changed.
+    fn load_keymap(
+        &mut self,
+        keymap: &xkb::Keymap,
+        locked: Option<(ModifiersState, xkb::LayoutIndex)>,
+    ) -> bool {
+        let mut state = xkb::State::new(keymap);
+        for key in &self.pressed_keys {
+            state.update_key(*key, xkb::KeyDirection::Down);
+        }
+        if let Some((mods, layout)) = locked {
+            let locked_mods = mods.serialize_back(&state).locked;
+            let layout = if layout < keymap.num_layouts() { layout } else { 0 };
+            state.update_mask(
+                state.serialize_mods(xkb::STATE_MODS_DEPRESSED),
+                state.serialize_mods(xkb::STATE_MODS_LATCHED),
+                locked_mods,
+                0,
+                0,
+                layout,
+            );
+        }
+
+        let led_mapping = LedMapping::from_keymap(keymap);
+        self.led_mapping = led_mapping;
+        self.mods_state.update_with(&state);
+        let leds_changed = self.led_state.update_with(&state, &led_mapping);
+        let mut xkb = self.xkb.lock().unwrap();
+        xkb.keymap = keymap.clone();
+        xkb.state = state;
+        // a sequence started on the previous keymap can't be finished on the new one
+        xkb.reset_compose();
+        leds_changed
+    }
+
+    // Make `source` the source of the following key events, switching to its keymap.
+    //
+    // The layout of the previous source is remembered and restored once it becomes active again.
+    // Returns `None` if the keymap didn't change, otherwise whether the leds changed.
+    fn activate_source(&mut self, source: KeyboardSource) -> Option<bool> {
+        if self.active_source == source {
+            return None;
+        }
+        let previous = std::mem::replace(&mut self.active_source, source);
+        let keymap = self.source_keymap(source).clone();
+        if keymap.get_raw_ptr() == self.source_keymap(previous).get_raw_ptr() {
+            return None;
+        }
+
+        let layout = self
+            .xkb
+            .lock()
+            .unwrap()
+            .state
+            .serialize_layout(xkb::STATE_LAYOUT_LOCKED);
+        self.source_layouts.insert(previous, layout);
+        let layout = self.source_layouts.get(&source).copied().unwrap_or(0);
+        Some(self.load_keymap(&keymap, Some((self.mods_state, layout))))
+    }
+
     // Feed a key event from `source` into the shared seat state. Returns
     // `(modifiers_changed, leds_changed, is_transition)`.
     //
@@ -338,6 +440,15 @@ impl<D: SeatHandler + 'static> KbdInternal<D> {
         // Offset the keycode by 8, as the evdev XKB rules reflect X's
         // broken keycode system, which starts at 8.
         let mut xkb = self.xkb.lock().unwrap();
+        if state == KeyState::Pressed {
+            let xkb = &mut *xkb;
+            xkb.compose_fed = match xkb.compose.as_mut() {
+                Some(compose) => {
+                    compose.feed(xkb.state.key_get_one_sym(keycode)) == xkb::compose::FeedResult::Accepted
+                }
+                None => false,
+            };
+        }
         let state_components = xkb.state.update_key(keycode, direction);
         let modifiers_changed = state_components != 0;
         if modifiers_changed {
@@ -417,6 +528,9 @@ pub enum Error {
     /// libxkbcommon could not load the specified keymap
     #[error("Libxkbcommon could not load the specified keymap")]
     BadKeymap,
+    /// libxkbcommon could not load the specified compose table
+    #[error("Libxkbcommon could not load the specified compose table")]
+    BadComposeTable,
     /// Smithay could not create a tempfile to share the keymap with clients
     #[error("Failed to create tempfile to share the keymap: {0}")]
     IoError(io::Error),
@@ -487,6 +601,32 @@ impl<'a> KeysymHandle<'a> {
         self.xkb.lock().unwrap().state.key_get_syms(self.keycode).to_vec()
     }
 
+    /// Returns the text produced by the key, taking the compose state of the keyboard into account.
+    ///
+    /// This is meant to be called for key presses while the key event is processed, e.g. in the
+    /// filter of [`KeyboardHandle::input`]. Returns `None` if the key doesn't produce any text, while a
+    /// compose sequence is in progress or if the sequence was cancelled by this key.
+    pub fn utf8(&self) -> Option<String> {
+        let xkb = self.xkb.lock().unwrap();
+        let composed = match xkb.compose.as_ref() {
+            Some(compose) if xkb.compose_fed => match compose.status() {
+                xkb::compose::Status::Composing | xkb::compose::Status::Cancelled => return None,
+                xkb::compose::Status::Composed => compose.utf8(),
+                xkb::compose::Status::Nothing => None,
+            },
+            _ => None,
+        };
+        composed.or_else(|| {
+            let text = xkb.state.key_get_utf8(self.keycode);
+            (!text.is_empty()).then_some(text)
+        })
+    }
+
+    /// Returns the status of the compose sequence after this key, `None` if no compose table is set.
+    pub fn compose_status(&self) -> Option<xkb::compose::Status> {
+        self.xkb.lock().unwrap().compose_status()
+    }
+
     /// Returns the syms for the underlying keycode without any modifications by the current keymap state applied.
     pub fn raw_syms(&self) -> Vec<Keysym> {
         let xkb = self.xkb.lock().unwrap();
@@ -614,6 +754,7 @@ impl fmt::Debug for XkbContext<'_> {
 /// Reference to the XkbLayout in the active keymap.
 ///
 /// The layout may become invalid after calling [`KeyboardHandle::set_xkb_config`]
+/// or when key events arrive from a [`KeyboardSource`] with a different keymap.
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Layout(pub xkb::LayoutIndex);
 
@@ -847,21 +988,42 @@ impl<D: SeatHandler + 'static> KeyboardHandle<D> {
 
     fn update_xkb_state(&self, data: &mut D, keymap: xkb::Keymap) {
         let mut internal = self.arc.internal.lock().unwrap();
+        internal.default_keymap = keymap.clone();
+        if internal.source_keymaps.contains_key(&internal.active_source) {
+            // the active source uses its own keymap
+            return;
+        }
 
-        let mut state = xkb::State::new(&keymap);
-        for key in &internal.pressed_keys {
-            state.update_key(*key, xkb::KeyDirection::Down);
+        let leds_changed = internal.load_keymap(&keymap, None);
+        self.keymap_changed(data, internal, leds_changed);
+    }
+
+    fn update_source_keymap(&self, data: &mut D, source: KeyboardSource, keymap: Option<xkb::Keymap>) {
+        let mut internal = self.arc.internal.lock().unwrap();
+        match keymap {
+            Some(keymap) => {
+                internal.source_keymaps.insert(source, keymap);
+            }
+            None => {
+                internal.source_layouts.remove(&source);
+                if internal.source_keymaps.remove(&source).is_none() {
+                    return;
+                }
+            }
+        }
+        if internal.active_source != source {
+            return;
         }
 
-        let led_mapping = LedMapping::from_keymap(&keymap);
-        internal.led_mapping = led_mapping;
-        internal.mods_state.update_with(&state);
-        let leds_changed = internal.led_state.update_with(&state, &led_mapping);
-        let mut xkb = internal.xkb.lock().unwrap();
-        xkb.keymap = keymap.clone();
-        xkb.state = state;
-        drop(xkb);
+        let keymap = internal.source_keymap(source).clone();
+        let leds_changed = internal.load_keymap(&keymap, None);
+        self.keymap_changed(data, internal, leds_changed);
+    }
 
+    // Broadcast the keymap loaded into the seat state to the clients
+    fn keymap_changed(&self, data: &mut D, mut internal: MutexGuard<'_, KbdInternal<D>>, leds_changed: bool) {
+        #[cfg(feature = "wayland_frontend")]
+        let keymap = internal.xkb.lock().unwrap().keymap.clone();
         let mods = internal.mods_state;
         let focus = internal.focus.as_mut().map(|(focus, _)| focus);
 
@@ -914,6 +1076,158 @@ impl<D: SeatHandler + 'static> KeyboardHandle<D> {
         Ok(())
     }
 
+    /// Change the [`XkbConfig`] used for key events of a specific [`KeyboardSource`].
+    ///
+    /// Sources without a keymap of their own use the keymap set by [`KeyboardHandle::set_xkb_config`].
+    /// Whenever a key event arrives from a source with a different keymap than the previous one,
+    /// the seat switches to the keymap of that source and sends it to the clients.
+    pub fn set_source_xkb_config(
+        &self,
+        data: &mut D,
+        source: KeyboardSource,
+        xkb_config: XkbConfig<'_>,
+    ) -> Result<(), Error> {
+        let keymap = xkb_config
+            .compile_keymap(&self.arc.internal.lock().unwrap().xkb.lock().unwrap().context)
+            .map_err(|_| {
+                debug!("Loading keymap from XkbConfig failed");
+                Error::BadKeymap
+            })?;
+        self.update_source_keymap(data, source, Some(keymap));
+        Ok(())
+    }
+
+    /// Change the [`Keymap`](xkb::Keymap) used for key events of a specific [`KeyboardSource`].
+    ///
+    /// The input is a keymap in XKB_KEYMAP_FORMAT_TEXT_V1 format.
+    /// See [`KeyboardHandle::set_source_xkb_config`].
+    pub fn set_source_keymap_from_string(
+        &self,
+        data: &mut D,
+        source: KeyboardSource,
+        keymap: String,
+    ) -> Result<(), Error> {
+        let keymap = xkb::Keymap::new_from_string(
+            &self.arc.internal.lock().unwrap().xkb.lock().unwrap().context,
+            keymap,
+            xkb::KEYMAP_FORMAT_TEXT_V1,
+            xkb::KEYMAP_COMPILE_NO_FLAGS,
+        )
+        .ok_or_else(|| {
+            debug!("Loading keymap from string failed");
+            Error::BadKeymap
+        })?;
+        self.update_source_keymap(data, source, Some(keymap));
+        Ok(())
+    }
+
+    /// Remove the keymap of a specific [`KeyboardSource`], making it use the keymap of the seat again.
+    pub fn reset_source_keymap(&self, data: &mut D, source: KeyboardSource) {
+        self.update_source_keymap(data, source, None);
+    }
+
+    /// The [`KeyboardSource`] of the last key event, whose keymap is currently active.
+    pub fn active_source(&self) -> KeyboardSource {
+        self.arc.internal.lock().unwrap().active_source
+    }
+
+    /// Load the compose table for the given locale, enabling compose sequences and dead keys.
+    ///
+    /// The locale is usually taken from the `LC_ALL`, `LC_CTYPE` or `LANG` environment variables.
+    /// libxkbcommon also honors the `XCOMPOSEFILE` environment variable and `~/.XCompose`.
+    /// The result of a sequence is available through [`KeysymHandle::utf8`].
+    pub fn set_compose_table_from_locale(&self, locale: &std::ffi::OsStr) -> Result<(), Error> {
+        let internal = self.arc.internal.lock().unwrap();
+        let mut xkb = internal.xkb.lock().unwrap();
+        let table =
+            xkb::compose::Table::new_from_locale(&xkb.context, locale, xkb::compose::COMPILE_NO_FLAGS)
+                .map_err(|_| {
+                    debug!(?locale, "Loading compose table from locale failed");
+                    Error::BadComposeTable
+                })?;
+        xkb.compose = Some(xkb::compose::State::new(&table, xkb::compose::STATE_NO_FLAGS));
+        xkb.compose_fed = false;
+        Ok(())
+    }
+
+    /// Load a compose table from the content of a Compose file, enabling compose sequences and dead keys.
+    ///
+    /// The `locale` is used to resolve includes of the system compose table.
+    /// See [`KeyboardHandle::set_compose_table_from_locale`].
+    pub fn set_compose_table_from_buffer(&self, buffer: &[u8], locale: &str) -> Result<(), Error> {
+        let internal = self.arc.internal.lock().unwrap();
+        let mut xkb = internal.xkb.lock().unwrap();
+        let table = xkb::compose::Table::new_from_buffer(
+            &xkb.context,
+            buffer,
+            locale,
+            xkb::compose::FORMAT_TEXT_V1,
+            xkb::compose::COMPILE_NO_FLAGS,
+        )
+        .map_err(|_| {
+            debug!("Loading compose table from buffer failed");
+            Error::BadComposeTable
+        })?;
+        xkb.compose = Some(xkb::compose::State::new(&table, xkb::compose::STATE_NO_FLAGS));
+        xkb.compose_fed = false;
+        Ok(())
+    }
+
+    /// Remove the compose table, disabling compose sequences.
+    pub fn unset_compose_table(&self) {
+        let internal = self.arc.internal.lock().unwrap();
+        let mut xkb = internal.xkb.lock().unwrap();
+        xkb.compose = None;
+        xkb.compose_fed = false;
+    }
+
+    /// Abort the compose sequence in progress, if any.
+    ///
+    /// Useful e.g. when the keyboard focus changes in the middle of a sequence.
+    pub fn reset_compose(&self) {
+        let internal = self.arc.internal.lock().unwrap();
+        internal.xkb.lock().unwrap().reset_compose();
+    }
+
+    /// Run a sequence of keysyms through the compose table of the keyboard.
+    ///
+    /// Complete compose sequences are replaced by their result, incomplete and cancelled sequences
+    /// are dropped. The compose state of the keyboard itself is not affected. Returns the keysyms
+    /// unchanged if no compose table is set.
+    pub fn compose_keysyms(&self, keysyms: &[Keysym]) -> Vec<Keysym> {
+        let internal = self.arc.internal.lock().unwrap();
+        let xkb = internal.xkb.lock().unwrap();
+        let Some(table) = xkb.compose.as_ref().map(|compose| compose.compose_table()) else {
+            return keysyms.to_vec();
+        };
+        drop(xkb);
+        drop(internal);
+
+        let mut compose = xkb::compose::State::new(&table, xkb::compose::STATE_NO_FLAGS);
+        let mut result = Vec::with_capacity(keysyms.len());
+        for keysym in keysyms {
+            if compose.feed(*keysym) == xkb::compose::FeedResult::Ignored {
+                result.push(*keysym);
+                continue;
+            }
+            match compose.status() {
+                xkb::compose::Status::Composed => match compose.keysym() {
+                    Some(keysym) => result.push(keysym),
+                    None => result.extend(
+                        compose
+                            .utf8()
+                            .unwrap_or_default()
+                            .chars()
+                            .map(|ch| xkb::utf32_to_keysym(ch as u32)),
+                    ),
+                },
+                xkb::compose::Status::Nothing => result.push(*keysym),
+                xkb::compose::Status::Composing | xkb::compose::Status::Cancelled => {}
+            }
+        }
+        result
+    }
+
     /// Access the underlying Xkb state and perform mutable operations on it, like
     /// changing layouts.
     ///
@@ -1056,6 +1370,10 @@ impl<D: SeatHandler + 'static> KeyboardHandle<D> {
         trace!("Handling keystroke");
 
         let mut guard = self.arc.internal.lock().unwrap();
+        if let Some(leds_changed) = guard.activate_source(source) {
+            self.keymap_changed(data, guard, leds_changed);
+            guard = self.arc.internal.lock().unwrap();
+        }
         let (mods_changed, leds_changed, is_transition) = guard.key_input(source, keycode, state);
         let led_state = guard.led_state;
         let mods_state = guard.mods_state;
@@ -1119,14 +1437,32 @@ impl<D: SeatHandler + 'static> KeyboardHandle<D> {
         state: KeyState,
         filter: F,
     ) -> (T, bool)
+    where
+        F: FnOnce(&mut D, &ModifiersState, KeysymHandle<'_>) -> T,
+    {
+        self.input_intercept_from_source(KeyboardSource::MAIN, data, keycode, state, filter)
+    }
+
+    /// Like [`KeyboardHandle::input_intercept`], but attributes the event to a specific [`KeyboardSource`].
+    pub fn input_intercept_from_source<T, F>(
+        &self,
+        source: KeyboardSource,
+        data: &mut D,
+        keycode: Keycode,
+        state: KeyState,
+        filter: F,
+    ) -> (T, bool)
     where
         F: FnOnce(&mut D, &ModifiersState, KeysymHandle<'_>) -> T,
     {
         trace!("Handling keystroke");
 
         let mut guard = self.arc.internal.lock().unwrap();
-        let (mods_changed, leds_changed, _is_transition) =
-            guard.key_input(KeyboardSource::MAIN, keycode, state);
+        if let Some(leds_changed) = guard.activate_source(source) {
+            self.keymap_changed(data, guard, leds_changed);
+            guard = self.arc.internal.lock().unwrap();
+        }
+        let (mods_changed, leds_changed, _is_transition) = guard.key_input(source, keycode, state);
         let led_state = guard.led_state;
         let mods_state = guard.mods_state;
         let xkb = guard.xkb.clone();
@@ -1371,6 +1707,9 @@ where
     /// **never** touched, so shortcut handling and physical-keyboard state are unaffected.
     /// `modifiers(0)` accompanies the injection keymap so any modifier the seat currently holds
     /// (e.g. a physically-held Shift) doesn't alter the injected characters.
+    ///
+    /// The keysyms are injected as given, use [`KeyboardHandle::inject_composed_text_keysyms`]
+    /// to resolve compose sequences first.
     pub fn inject_text_keysyms(&self, data: &mut D, keysyms: &[Keysym]) {
         const FIRST: u32 = 9;
         let mut keycode_decls = String::new();
@@ -1415,6 +1754,8 @@ where
             state: xkb::State::new(&keymap),
             keymap,
             context,
+            compose: None,
+            compose_fed: false,
         });
         let injection_keymap = KeymapFile::new(&xkb.lock().unwrap().keymap);
 
@@ -1448,6 +1789,16 @@ where
         let focus = guard.focus.as_mut().map(|(focus, _)| focus);
         self.send_keymap(data, &focus, &seat_keymap, seat_mods);
     }
+
+    /// Inject a batch of keysyms as text, resolving compose sequences first.
+    ///
+    /// Compose sequences, like dead keys followed by a letter, are resolved using the compose
+    /// table of the keyboard, see [`KeyboardHandle::compose_keysyms`]. Otherwise identical to
+    /// [`KeyboardHandle::inject_text_keysyms`].
+    pub fn inject_composed_text_keysyms(&self, data: &mut D, keysyms: &[Keysym]) {
+        let keysyms = self.compose_keysyms(keysyms);
+        self.inject_text_keysyms(data, &keysyms);
+    }
 }
 
 impl<D> KeyboardHandle<D>
@@ -1675,3 +2026,251 @@ impl<D: SeatHandler + 'static> KeyboardGrab<D> for DefaultGrab {
 
     fn unset(&mut self, _data: &mut D) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::testing::{TestFocus, TestState};
+
+    // `y` on the us layout, `z` on the de layout and `KEYMAP_Z`
+    const KEY_Y: Keycode = Keycode::new(29);
+    const KEY_CAPS_LOCK: Keycode = Keycode::new(66);
+
+    fn keyboard() -> (TestState, KeyboardHandle<TestState>) {
+        let (state, mut seat) = TestState::new();
+        let keyboard = seat
+            .add_keyboard(
+                XkbConfig {
+                    layout: "us",
+                    ..XkbConfig::default()
+                },
+                200,
+                25,
+            )
+            .unwrap();
+        (state, keyboard)
+    }
+
+    /// A self-contained keymap with `z` on `KEY_Y`, like the de layout
+    const KEYMAP_Z: &str = r#"xkb_keymap {
+        xkb_keycodes "test" {
+            minimum = 8;
+            maximum = 255;
+            <AD06> = 29;
+            <CAPS> = 66;
+        };
+        xkb_types "test" {
+            type "ONE_LEVEL" {
+                modifiers = none;
+                level_name[Level1] = "Any";
+            };
+            type "ALPHABETIC" {
+                modifiers = Shift+Lock;
+                map[Shift] = Level2;
+                map[Lock] = Level2;
+                level_name[Level1] = "Base";
+                level_name[Level2] = "Caps";
+            };
+        };
+        xkb_compatibility "test" {
+            interpret Caps_Lock {
+                action = LockMods(modifiers = Lock);
+            };
+        };
+        xkb_symbols "test" {
+            key <AD06> { type = "ALPHABETIC", [ z, Z ] };
+            key <CAPS> { type = "ONE_LEVEL", [ Caps_Lock ] };
+            modifier_map Lock { <CAPS> };
+        };
+    };"#;
+
+    /// Taps a key and returns the keysym it produced
+    fn tap(
+        state: &mut TestState,
+        keyboard: &KeyboardHandle<TestState>,
+        source: KeyboardSource,
+        keycode: Keycode,
+    ) -> Keysym {
+        let (sym, _) = keyboard.input_intercept_from_source(
+            source,
+            state,
+            keycode,
+            KeyState::Pressed,
+            |_, _, handle| handle.modified_sym(),
+        );
+        keyboard.input_intercept_from_source(source, state, keycode, KeyState::Released, |_, _, _| ());
+        sym
+    }
+
+    #[test]
+    fn source_keymaps() {
+        let (mut state, keyboard) = keyboard();
+        let aux = KeyboardSource::new_auxiliary();
+        keyboard
+            .set_source_keymap_from_string(&mut state, aux, KEYMAP_Z.into())
+            .unwrap();
+        assert_eq!(keyboard.active_source(), KeyboardSource::MAIN);
+
+        assert_eq!(tap(&mut state, &keyboard, KeyboardSource::MAIN, KEY_Y), Keysym::y);
+        assert_eq!(tap(&mut state, &keyboard, aux, KEY_Y), Keysym::z);
+        assert_eq!(keyboard.active_source(), aux);
+        assert_eq!(tap(&mut state, &keyboard, KeyboardSource::MAIN, KEY_Y), Keysym::y);
+        assert_eq!(keyboard.active_source(), KeyboardSource::MAIN);
+
+        keyboard.reset_source_keymap(&mut state, aux);
+        assert_eq!(tap(&mut state, &keyboard, aux, KEY_Y), Keysym::y);
+
+        assert!(matches!(
+            keyboard.set_source_keymap_from_string(&mut state, aux, "invalid".into()),
+            Err(Error::BadKeymap)
+        ));
+    }
+
+    #[test]
+    fn source_keymap_of_active_source() {
+        let (mut state, keyboard) = keyboard();
+        let aux = KeyboardSource::new_auxiliary();
+        assert_eq!(tap(&mut state, &keyboard, aux, KEY_Y), Keysym::y);
+
+        // changing the keymap of the active source applies immediately
+        keyboard
+            .set_source_keymap_from_string(&mut state, aux, KEYMAP_Z.into())
+            .unwrap();
+        assert_eq!(tap(&mut state, &keyboard, aux, KEY_Y), Keysym::z);
+        keyboard.reset_source_keymap(&mut state, aux);
+        assert_eq!(tap(&mut state, &keyboard, aux, KEY_Y), Keysym::y);
+    }
+
+    #[test]
+    fn source_keymap_keeps_locked_modifiers() {
+        let (mut state, keyboard) = keyboard();
+        keyboard.set_focus(&mut state, Some(TestFocus), SERIAL_COUNTER.next_serial());
+        let aux = KeyboardSource::new_auxiliary();
+        keyboard
+            .set_source_keymap_from_string(&mut state, aux, KEYMAP_Z.into())
+            .unwrap();
+
+        tap(&mut state, &keyboard, KeyboardSource::MAIN, KEY_CAPS_LOCK);
+        assert!(keyboard.modifier_state().caps_lock);
+        assert_eq!(tap(&mut state, &keyboard, aux, KEY_Y), Keysym::Z);
+        assert!(keyboard.modifier_state().caps_lock);
+    }
+
+    const KEY_A: Keycode = Keycode::new(38);
+    const KEY_E: Keycode = Keycode::new(26);
+    const KEY_B: Keycode = Keycode::new(56);
+    const COMPOSE: &[u8] = "<a> <e> : \"æ\" ae\n".as_bytes();
+
+    /// Taps a key and returns the compose status and text after it
+    fn tap_compose(
+        state: &mut TestState,
+        keyboard: &KeyboardHandle<TestState>,
+        keycode: Keycode,
+    ) -> (Option<xkb::compose::Status>, Option<String>) {
+        let (result, _) = keyboard.input_intercept(state, keycode, KeyState::Pressed, |_, _, handle| {
+            (handle.compose_status(), handle.utf8())
+        });
+        keyboard.input_intercept(state, keycode, KeyState::Released, |_, _, _| ());
+        result
+    }
+
+    #[test]
+    fn compose_sequence() {
+        let (mut state, keyboard) = keyboard();
+        assert_eq!(
+            tap_compose(&mut state, &keyboard, KEY_A),
+            (None, Some("a".into()))
+        );
+
+        keyboard.set_compose_table_from_buffer(COMPOSE, "C").unwrap();
+        assert_eq!(
+            tap_compose(&mut state, &keyboard, KEY_A),
+            (Some(xkb::compose::Status::Composing), None)
+        );
+        assert_eq!(
+            tap_compose(&mut state, &keyboard, KEY_E),
+            (Some(xkb::compose::Status::Composed), Some("æ".into()))
+        );
+        assert_eq!(
+            tap_compose(&mut state, &keyboard, KEY_B),
+            (Some(xkb::compose::Status::Nothing), Some("b".into()))
+        );
+
+        // aborting the sequence
+        tap_compose(&mut state, &keyboard, KEY_A);
+        keyboard.reset_compose();
+        assert_eq!(
+            tap_compose(&mut state, &keyboard, KEY_E),
+            (Some(xkb::compose::Status::Nothing), Some("e".into()))
+        );
+
+        keyboard.unset_compose_table();
+        assert_eq!(
+            tap_compose(&mut state, &keyboard, KEY_A),
+            (None, Some("a".into()))
+        );
+    }
+
+    #[test]
+    fn keymap_change_resets_compose() {
+        let (mut state, keyboard) = keyboard();
+        keyboard.set_compose_table_from_buffer(COMPOSE, "C").unwrap();
+        assert_eq!(
+            tap_compose(&mut state, &keyboard, KEY_A),
+            (Some(xkb::compose::Status::Composing), None)
+        );
+
+        keyboard
+            .set_keymap_from_string(&mut state, KEYMAP_Z.into())
+            .unwrap();
+        assert_eq!(
+            tap_compose(&mut state, &keyboard, KEY_Y),
+            (Some(xkb::compose::Status::Nothing), Some("z".into()))
+        );
+    }
+
+    #[test]
+    fn compose_keysyms() {
+        let (_state, keyboard) = keyboard();
+        let keysyms = [Keysym::a, Keysym::e, Keysym::b];
+        assert_eq!(keyboard.compose_keysyms(&keysyms), keysyms);
+
+        keyboard.set_compose_table_from_buffer(COMPOSE, "C").unwrap();
+        assert_eq!(keyboard.compose_keysyms(&keysyms), [Keysym::ae, Keysym::b]);
+        // incomplete sequences are dropped
+        assert_eq!(keyboard.compose_keysyms(&[Keysym::b, Keysym::a]), [Keysym::b]);
+    }
+
+    #[cfg(feature = "wayland_frontend")]
+    #[test]
+    fn inject_composed_text() {
+        use crate::input::testing::TestEvent;
+
+        let (mut state, keyboard) = keyboard();
+        keyboard.set_compose_table_from_buffer(COMPOSE, "C").unwrap();
+        keyboard.set_focus(&mut state, Some(TestFocus), SERIAL_COUNTER.next_serial());
+        state.take_events();
+
+        let pressed_keys = |state: &mut TestState| {
+            state
+                .take_events()
+                .into_iter()
+                .filter_map(|event| match event {
+                    TestEvent::Key {
+                        sym,
+                        state: KeyState::Pressed,
+                        ..
+                    } => Some(sym),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // plain injection leaves the keysyms alone
+        keyboard.inject_text_keysyms(&mut state, &[Keysym::a, Keysym::e, Keysym::b]);
+        assert_eq!(pressed_keys(&mut state), [Keysym::a, Keysym::e, Keysym::b]);
+
+        keyboard.inject_composed_text_keysyms(&mut state, &[Keysym::a, Keysym::e, Keysym::b]);
+        assert_eq!(pressed_keys(&mut state), [Keysym::ae, Keysym::b]);
+    }
+}
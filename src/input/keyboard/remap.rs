@@ -0,0 +1,410 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use calloop::{LoopHandle, RegistrationToken, timer::TimeoutAction, timer::Timer};
+
+use crate::backend::input::KeyState;
+
+use super::{KeyboardSource, Keycode};
+
+/// Handler trait for the key remapping layer
+pub trait KeyRemapHandler: Sized {
+    /// [`KeyRemapState`] getter
+    fn key_remap_state(&mut self) -> &mut KeyRemapState<Self>;
+
+    /// A tap-hold key resolved asynchronously, after its timeout expired
+    fn remapped_key(&mut self, event: RemappedKeyEvent);
+}
+
+/// A key with a different behavior on tap and hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TapHold {
+    /// Key emitted when the key is released before the timeout, without pressing other keys
+    pub tap: Keycode,
+    /// Key emitted when the key is held longer than the timeout, or another key is pressed
+    pub hold: Keycode,
+    /// Time the key has to be held to act as [`TapHold::hold`]
+    pub timeout: Duration,
+}
+
+/// Set of remapping rules
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyRemap {
+    keys: HashMap<Keycode, Keycode>,
+    tap_holds: HashMap<Keycode, TapHold>,
+}
+
+impl KeyRemap {
+    /// Create an empty set of rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remap `from` to `to`
+    ///
+    /// Remapping is not transitive, swapping two keys requires a rule for each of them.
+    pub fn remap(mut self, from: Keycode, to: Keycode) -> Self {
+        self.tap_holds.remove(&from);
+        self.keys.insert(from, to);
+        self
+    }
+
+    /// Emit `tap` when `key` is tapped and `hold` when it is held for longer than `timeout`
+    pub fn tap_hold(mut self, key: Keycode, tap: Keycode, hold: Keycode, timeout: Duration) -> Self {
+        self.keys.remove(&key);
+        self.tap_holds.insert(key, TapHold { tap, hold, timeout });
+        self
+    }
+
+    /// Returns if the set contains no rules
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.tap_holds.is_empty()
+    }
+}
+
+/// Key event emitted by the remapping layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemappedKeyEvent {
+    /// Source of the original key event
+    pub source: KeyboardSource,
+    /// Remapped keycode
+    pub keycode: Keycode,
+    /// State of the key
+    pub state: KeyState,
+    /// Timestamp of the event, with millisecond granularity
+    pub time: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PressedKey {
+    /// The key was pressed as the given keycode
+    Emitted(Keycode),
+    /// The key is a tap-hold key, which didn't resolve yet
+    Pending,
+}
+
+#[derive(Debug)]
+struct PendingTapHold {
+    id: u64,
+    source: KeyboardSource,
+    key: Keycode,
+    tap_hold: TapHold,
+    time: u32,
+    token: Option<RegistrationToken>,
+}
+
+/// State of the key remapping layer
+///
+/// The remapping layer sits between the input backend and the [`KeyboardHandle`](super::KeyboardHandle),
+/// similar to what tools like keyd do. It allows to swap keys and to overload keys with a different
+/// behavior on tap and hold (e.g. Caps Lock acting as Escape when tapped and as Control when held),
+/// without rebuilding the keymap. Rules can be set per [`KeyboardSource`], e.g. to remap a single device.
+///
+/// Keys are remapped on the keycode level: every key event from the backend is fed into
+/// [`KeyRemapState::input`], which returns the resulting key events. Tap-hold keys resolve
+/// asynchronously once their timeout expires, these events are delivered through
+/// [`KeyRemapHandler::remapped_key`]. All resulting events should be passed to
+/// [`KeyboardHandle::input_from_source`](super::KeyboardHandle::input_from_source), or to
+/// [`KeyboardHandle::input_intercept_from_source`](super::KeyboardHandle::input_intercept_from_source) and
+/// [`KeyboardHandle::input_forward`](super::KeyboardHandle::input_forward), so the pressed keys
+/// of the seat follow the remapped keys.
+///
+/// The release of a key always releases the key emitted for its press, even if the remapping
+/// changed in between, so no key stays pressed.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use smithay::input::keyboard::{Keycode, KeyRemap, KeyRemapHandler, KeyRemapState, RemappedKeyEvent};
+/// # use smithay::reexports::calloop::LoopHandle;
+///
+/// # struct State { remap: KeyRemapState<State> }
+/// impl KeyRemapHandler for State {
+///     fn key_remap_state(&mut self) -> &mut KeyRemapState<Self> {
+///         &mut self.remap
+///     }
+///     fn remapped_key(&mut self, event: RemappedKeyEvent) {
+///         // forward the event to the keyboard of the seat
+///     }
+/// }
+///
+/// # let loop_handle: LoopHandle<'static, State> = unimplemented!();
+/// let mut state = KeyRemapState::new(loop_handle);
+/// // caps lock: escape on tap, control on hold
+/// state.set_remap(KeyRemap::new().tap_hold(
+///     Keycode::new(66),
+///     Keycode::new(9),
+///     Keycode::new(37),
+///     Duration::from_millis(200),
+/// ));
+/// ```
+pub struct KeyRemapState<D> {
+    loop_handle: LoopHandle<'static, D>,
+    remap: KeyRemap,
+    source_remaps: HashMap<KeyboardSource, KeyRemap>,
+    pressed: HashMap<(KeyboardSource, Keycode), PressedKey>,
+    pending: Option<PendingTapHold>,
+    next_id: u64,
+}
+
+impl<D> fmt::Debug for KeyRemapState<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyRemapState")
+            .field("remap", &self.remap)
+            .field("source_remaps", &self.source_remaps)
+            .field("pressed", &self.pressed)
+            .field("pending", &self.pending)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<D: KeyRemapHandler + 'static> KeyRemapState<D> {
+    /// Create a new remapping layer without any rules
+    pub fn new(loop_handle: LoopHandle<'static, D>) -> Self {
+        KeyRemapState {
+            loop_handle,
+            remap: KeyRemap::default(),
+            source_remaps: HashMap::new(),
+            pressed: HashMap::new(),
+            pending: None,
+            next_id: 0,
+        }
+    }
+
+    /// Set the rules used for every source without rules of its own
+    pub fn set_remap(&mut self, remap: KeyRemap) {
+        self.remap = remap;
+    }
+
+    /// Set the rules of a specific source, e.g. a single device, or remove them with `None`
+    pub fn set_source_remap(&mut self, source: KeyboardSource, remap: Option<KeyRemap>) {
+        match remap {
+            Some(remap) => {
+                self.source_remaps.insert(source, remap);
+            }
+            None => {
+                self.source_remaps.remove(&source);
+            }
+        }
+    }
+
+    /// Feed a key event from the input backend, returning the remapped key events
+    pub fn input(
+        &mut self,
+        source: KeyboardSource,
+        keycode: Keycode,
+        state: KeyState,
+        time: u32,
+    ) -> Vec<RemappedKeyEvent> {
+        let mut events = Vec::new();
+
+        match state {
+            KeyState::Pressed => {
+                if self.pressed.contains_key(&(source, keycode)) {
+                    return events;
+                }
+
+                // pressing another key while a tap-hold key is pending makes it act as held
+                if let Some(pending) = self.pending.take() {
+                    if let Some(token) = pending.token {
+                        self.loop_handle.remove(token);
+                    }
+                    events.push(self.resolve_hold(pending, time));
+                }
+
+                let remap = self.source_remaps.get(&source).unwrap_or(&self.remap);
+                if let Some(tap_hold) = remap.tap_holds.get(&keycode).copied() {
+                    self.start_tap_hold(source, keycode, tap_hold, time);
+                    self.pressed.insert((source, keycode), PressedKey::Pending);
+                } else {
+                    let remapped = remap.keys.get(&keycode).copied().unwrap_or(keycode);
+                    self.pressed
+                        .insert((source, keycode), PressedKey::Emitted(remapped));
+                    events.push(RemappedKeyEvent {
+                        source,
+                        keycode: remapped,
+                        state,
+                        time,
+                    });
+                }
+            }
+            KeyState::Released => match self.pressed.remove(&(source, keycode)) {
+                Some(PressedKey::Emitted(remapped)) => events.push(RemappedKeyEvent {
+                    source,
+                    keycode: remapped,
+                    state,
+                    time,
+                }),
+                Some(PressedKey::Pending) => {
+                    if let Some(pending) = self.pending.take() {
+                        if let Some(token) = pending.token {
+                            self.loop_handle.remove(token);
+                        }
+                        for state in [KeyState::Pressed, KeyState::Released] {
+                            events.push(RemappedKeyEvent {
+                                source,
+                                keycode: pending.tap_hold.tap,
+                                state,
+                                time,
+                            });
+                        }
+                    }
+                }
+                // the key was pressed before the remapping layer saw it
+                None => events.push(RemappedKeyEvent {
+                    source,
+                    keycode,
+                    state,
+                    time,
+                }),
+            },
+        }
+
+        events
+    }
+
+    /// Returns if a tap-hold key is waiting to be resolved
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    fn start_tap_hold(&mut self, source: KeyboardSource, key: Keycode, tap_hold: TapHold, time: u32) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let token = self
+            .loop_handle
+            .insert_source(Timer::from_duration(tap_hold.timeout), move |_, _, data| {
+                if let Some(event) = data.key_remap_state().hold_timeout(id) {
+                    data.remapped_key(event);
+                }
+                TimeoutAction::Drop
+            })
+            .ok();
+
+        self.pending = Some(PendingTapHold {
+            id,
+            source,
+            key,
+            tap_hold,
+            time,
+            token,
+        });
+    }
+
+    fn hold_timeout(&mut self, id: u64) -> Option<RemappedKeyEvent> {
+        if self.pending.as_ref().is_none_or(|pending| pending.id != id) {
+            return None;
+        }
+        let pending = self.pending.take().unwrap();
+        let time = pending
+            .time
+            .wrapping_add(pending.tap_hold.timeout.as_millis() as u32);
+        Some(self.resolve_hold(pending, time))
+    }
+
+    fn resolve_hold(&mut self, pending: PendingTapHold, time: u32) -> RemappedKeyEvent {
+        self.pressed.insert(
+            (pending.source, pending.key),
+            PressedKey::Emitted(pending.tap_hold.hold),
+        );
+        RemappedKeyEvent {
+            source: pending.source,
+            keycode: pending.tap_hold.hold,
+            state: KeyState::Pressed,
+            time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calloop::EventLoop;
+
+    struct State {
+        remap: KeyRemapState<State>,
+        events: Vec<RemappedKeyEvent>,
+    }
+
+    impl KeyRemapHandler for State {
+        fn key_remap_state(&mut self) -> &mut KeyRemapState<Self> {
+            &mut self.remap
+        }
+
+        fn remapped_key(&mut self, event: RemappedKeyEvent) {
+            self.events.push(event);
+        }
+    }
+
+    const CAPS: Keycode = Keycode::new(66);
+    const ESC: Keycode = Keycode::new(9);
+    const CTRL: Keycode = Keycode::new(37);
+    const A: Keycode = Keycode::new(38);
+    const B: Keycode = Keycode::new(56);
+
+    fn keys(events: &[RemappedKeyEvent]) -> Vec<(Keycode, KeyState)> {
+        events.iter().map(|event| (event.keycode, event.state)).collect()
+    }
+
+    fn state(event_loop: &EventLoop<'static, State>) -> State {
+        let mut remap = KeyRemapState::new(event_loop.handle());
+        remap.set_remap(
+            KeyRemap::new()
+                .remap(A, B)
+                .tap_hold(CAPS, ESC, CTRL, Duration::from_millis(10)),
+        );
+        State {
+            remap,
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn remap_and_tap() {
+        let event_loop = EventLoop::try_new().unwrap();
+        let mut state = state(&event_loop);
+        let source = KeyboardSource::MAIN;
+
+        let events = state.remap.input(source, A, KeyState::Pressed, 0);
+        assert_eq!(keys(&events), [(B, KeyState::Pressed)]);
+        // changing the rules doesn't leave the remapped key pressed
+        state.remap.set_remap(KeyRemap::new());
+        let events = state.remap.input(source, A, KeyState::Released, 1);
+        assert_eq!(keys(&events), [(B, KeyState::Released)]);
+
+        state
+            .remap
+            .set_remap(KeyRemap::new().tap_hold(CAPS, ESC, CTRL, Duration::from_secs(1)));
+        assert!(state.remap.input(source, CAPS, KeyState::Pressed, 2).is_empty());
+        assert!(state.remap.is_pending());
+        let events = state.remap.input(source, CAPS, KeyState::Released, 3);
+        assert_eq!(
+            keys(&events),
+            [(ESC, KeyState::Pressed), (ESC, KeyState::Released)]
+        );
+    }
+
+    #[test]
+    fn hold() {
+        let mut event_loop = EventLoop::try_new().unwrap();
+        let mut state = state(&event_loop);
+        let source = KeyboardSource::MAIN;
+
+        // another key press resolves the key as held
+        state.remap.input(source, CAPS, KeyState::Pressed, 0);
+        let events = state.remap.input(source, A, KeyState::Pressed, 1);
+        assert_eq!(keys(&events), [(CTRL, KeyState::Pressed), (B, KeyState::Pressed)]);
+        state.remap.input(source, A, KeyState::Released, 2);
+        let events = state.remap.input(source, CAPS, KeyState::Released, 3);
+        assert_eq!(keys(&events), [(CTRL, KeyState::Released)]);
+
+        // so does the timeout
+        state.remap.input(source, CAPS, KeyState::Pressed, 4);
+        std::thread::sleep(Duration::from_millis(20));
+        event_loop.dispatch(Duration::ZERO, &mut state).unwrap();
+        assert_eq!(keys(&state.events), [(CTRL, KeyState::Pressed)]);
+        let events = state.remap.input(source, CAPS, KeyState::Released, 30);
+        assert_eq!(keys(&events), [(CTRL, KeyState::Released)]);
+    }
+}
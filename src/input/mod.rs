@@ -151,6 +151,12 @@ pub mod pointer;
 pub mod tablet;
 pub mod touch;
 
+mod seat_assignment;
+pub use seat_assignment::SeatAssignment;
+
+#[cfg(test)]
+pub(crate) mod testing;
+
 /// Handler trait for Seats
 pub trait SeatHandler: Sized + 'static {
     /// Type used to represent the target currently holding the keyboard focus
@@ -272,6 +278,8 @@ pub(crate) struct Inner<D: SeatHandler> {
     pub(crate) pointer: Option<PointerHandle<D>>,
     pub(crate) keyboard: Option<KeyboardHandle<D>>,
     pub(crate) touch: Option<TouchHandle<D>>,
+    // set by `TabletSeatTrait::tablet_seat`, as `D` is not bound on `TabletSeatHandler` here
+    pub(crate) tablet_tool_grab: Option<fn(&Seat<D>, Serial) -> bool>,
 
     #[cfg(feature = "wayland_frontend")]
     pub(crate) global: Option<wayland_server::backend::GlobalId>,
@@ -356,6 +364,7 @@ impl<D: SeatHandler> SeatState<D> {
                 pointer: None,
                 keyboard: None,
                 touch: None,
+                tablet_tool_grab: None,
 
                 #[cfg(feature = "wayland_frontend")]
                 global: None,
@@ -724,6 +733,19 @@ impl<D: SeatHandler + 'static> Seat<D> {
         self.arc.inner.lock().unwrap().touch.clone()
     }
 
+    /// Check if a tablet tool of this seat is currently grabbed with this serial
+    ///
+    /// Always `false` for seats without a [`TabletSeat`](tablet::TabletSeat), see
+    /// [`TabletSeatTrait::tablet_seat`](tablet::TabletSeatTrait::tablet_seat).
+    pub fn has_tablet_tool_grab(&self, serial: Serial) -> bool {
+        let hook = self.arc.inner.lock().unwrap().tablet_tool_grab;
+        hook.is_some_and(|has_grab| has_grab(self, serial))
+    }
+
+    pub(crate) fn set_tablet_tool_grab_hook(&self, hook: fn(&Seat<D>, Serial) -> bool) {
+        self.arc.inner.lock().unwrap().tablet_tool_grab = Some(hook);
+    }
+
     /// Remove the touch capability from this seat
     ///
     /// Clients will be appropriately notified.
@@ -0,0 +1,398 @@
+use crate::utils::{Logical, Point, Rectangle};
+
+bitflags::bitflags! {
+    /// Directions of pointer motion blocked by a [`PointerBarrier`]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct BarrierDirections: u32 {
+        /// Motion towards increasing x coordinates
+        const POSITIVE_X = 1;
+        /// Motion towards decreasing x coordinates
+        const NEGATIVE_X = 2;
+        /// Motion towards increasing y coordinates
+        const POSITIVE_Y = 4;
+        /// Motion towards decreasing y coordinates
+        const NEGATIVE_Y = 8;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Orientation {
+    // barrier along x = pos, spanning y in [start, end]
+    Vertical,
+    // barrier along y = pos, spanning x in [start, end]
+    Horizontal,
+}
+
+/// An axis-aligned line segment restricting pointer motion
+///
+/// Pointer motion crossing the barrier in one of its blocked directions is stopped at the barrier,
+/// until the pointer has been pushed against it for more than its resistance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointerBarrier {
+    orientation: Orientation,
+    pos: f64,
+    start: f64,
+    end: f64,
+    blocked: BarrierDirections,
+    resistance: f64,
+}
+
+impl PointerBarrier {
+    /// Create a vertical barrier at `x`, spanning from `y1` to `y2`
+    ///
+    /// Only [`BarrierDirections::POSITIVE_X`] and [`BarrierDirections::NEGATIVE_X`] are relevant
+    /// for a vertical barrier.
+    pub fn vertical(x: f64, y1: f64, y2: f64, blocked: BarrierDirections) -> Self {
+        PointerBarrier {
+            orientation: Orientation::Vertical,
+            pos: x,
+            start: y1.min(y2),
+            end: y1.max(y2),
+            blocked,
+            resistance: f64::INFINITY,
+        }
+    }
+
+    /// Create a horizontal barrier at `y`, spanning from `x1` to `x2`
+    ///
+    /// Only [`BarrierDirections::POSITIVE_Y`] and [`BarrierDirections::NEGATIVE_Y`] are relevant
+    /// for a horizontal barrier.
+    pub fn horizontal(y: f64, x1: f64, x2: f64, blocked: BarrierDirections) -> Self {
+        PointerBarrier {
+            orientation: Orientation::Horizontal,
+            pos: y,
+            start: x1.min(x2),
+            end: x1.max(x2),
+            blocked,
+            resistance: f64::INFINITY,
+        }
+    }
+
+    /// Create a barrier on the shared edge of two adjacent rectangles, blocking motion from `from`
+    /// into `to`
+    ///
+    /// This is mostly useful to add resistance between two outputs. Returns `None`, if the
+    /// rectangles do not share an edge.
+    pub fn between(from: Rectangle<i32, Logical>, to: Rectangle<i32, Logical>) -> Option<Self> {
+        let (from, to) = (from.to_f64(), to.to_f64());
+        let overlap = |a1: f64, a2: f64, b1: f64, b2: f64| {
+            let (start, end) = (a1.max(b1), a2.min(b2));
+            (start < end).then_some((start, end))
+        };
+        let x_overlap = || {
+            overlap(
+                from.loc.x,
+                from.loc.x + from.size.w,
+                to.loc.x,
+                to.loc.x + to.size.w,
+            )
+        };
+        let y_overlap = || {
+            overlap(
+                from.loc.y,
+                from.loc.y + from.size.h,
+                to.loc.y,
+                to.loc.y + to.size.h,
+            )
+        };
+
+        if from.loc.x + from.size.w == to.loc.x {
+            y_overlap().map(|(y1, y2)| Self::vertical(to.loc.x, y1, y2, BarrierDirections::POSITIVE_X))
+        } else if to.loc.x + to.size.w == from.loc.x {
+            y_overlap().map(|(y1, y2)| Self::vertical(from.loc.x, y1, y2, BarrierDirections::NEGATIVE_X))
+        } else if from.loc.y + from.size.h == to.loc.y {
+            x_overlap().map(|(x1, x2)| Self::horizontal(to.loc.y, x1, x2, BarrierDirections::POSITIVE_Y))
+        } else if to.loc.y + to.size.h == from.loc.y {
+            x_overlap().map(|(x1, x2)| Self::horizontal(from.loc.y, x1, x2, BarrierDirections::NEGATIVE_Y))
+        } else {
+            None
+        }
+    }
+
+    /// Set the resistance of this barrier
+    ///
+    /// The resistance is the distance in logical pixels the pointer has to be pushed against the
+    /// barrier before it is allowed to cross. By default barriers have an infinite resistance and
+    /// can never be crossed in their blocked directions.
+    pub fn with_resistance(mut self, resistance: f64) -> Self {
+        self.resistance = resistance.max(0.0);
+        self
+    }
+
+    /// Resistance of this barrier in logical pixels
+    pub fn resistance(&self) -> f64 {
+        self.resistance
+    }
+
+    /// Directions blocked by this barrier
+    pub fn blocked(&self) -> BarrierDirections {
+        self.blocked
+    }
+
+    // Returns the distance the motion would have overshot the barrier, if the motion from `from`
+    // to `to` crosses the barrier in a blocked direction.
+    fn blocked_crossing(&self, from: Point<f64, Logical>, to: Point<f64, Logical>) -> Option<f64> {
+        let (from_pos, to_pos, from_other, to_other, positive, negative) = match self.orientation {
+            Orientation::Vertical => (
+                from.x,
+                to.x,
+                from.y,
+                to.y,
+                BarrierDirections::POSITIVE_X,
+                BarrierDirections::NEGATIVE_X,
+            ),
+            Orientation::Horizontal => (
+                from.y,
+                to.y,
+                from.x,
+                to.x,
+                BarrierDirections::POSITIVE_Y,
+                BarrierDirections::NEGATIVE_Y,
+            ),
+        };
+
+        let crossing = if from_pos <= self.pos && to_pos > self.pos {
+            positive
+        } else if from_pos >= self.pos && to_pos < self.pos {
+            negative
+        } else {
+            return None;
+        };
+        if !self.blocked.contains(crossing) {
+            return None;
+        }
+
+        let t = if to_pos == from_pos {
+            0.0
+        } else {
+            (self.pos - from_pos) / (to_pos - from_pos)
+        };
+        let other = from_other + t * (to_other - from_other);
+        (self.start..=self.end)
+            .contains(&other)
+            .then(|| (to_pos - self.pos).abs())
+    }
+
+    fn clamp(&self, to: &mut Point<f64, Logical>) {
+        match self.orientation {
+            Orientation::Vertical => to.x = self.pos,
+            Orientation::Horizontal => to.y = self.pos,
+        }
+    }
+}
+
+/// Identifier of a barrier added to [`PointerBarriers`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BarrierId(usize);
+
+/// A barrier hit during [`PointerBarriers::constrain`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BarrierHit {
+    /// The barrier that was hit
+    pub barrier: BarrierId,
+    /// Accumulated distance the pointer was pushed against the barrier
+    pub pressure: f64,
+    /// Whether the pointer was released through the barrier
+    pub released: bool,
+}
+
+/// Result of constraining pointer motion
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstrainedMotion {
+    /// The new location of the pointer
+    pub location: Point<f64, Logical>,
+    /// Barriers hit by the motion
+    ///
+    /// This can be used to implement pressure based actions, like hot corners.
+    pub hits: Vec<BarrierHit>,
+}
+
+#[derive(Debug)]
+struct BarrierState {
+    id: BarrierId,
+    barrier: PointerBarrier,
+    pressure: f64,
+}
+
+/// Set of pointer barriers and the area the pointer is confined to
+///
+/// Usually accessed through [`PointerHandle::with_barriers`](super::PointerHandle::with_barriers)
+/// and applied to relative pointer motion using
+/// [`PointerHandle::constrain_motion`](super::PointerHandle::constrain_motion).
+#[derive(Debug, Default)]
+pub struct PointerBarriers {
+    barriers: Vec<BarrierState>,
+    next_id: usize,
+    outputs: Vec<Rectangle<f64, Logical>>,
+}
+
+impl PointerBarriers {
+    /// Create an empty set of barriers, not confining the pointer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a barrier
+    pub fn add_barrier(&mut self, barrier: PointerBarrier) -> BarrierId {
+        let id = BarrierId(self.next_id);
+        self.next_id += 1;
+        self.barriers.push(BarrierState {
+            id,
+            barrier,
+            pressure: 0.0,
+        });
+        id
+    }
+
+    /// Remove a previously added barrier
+    pub fn remove_barrier(&mut self, id: BarrierId) -> Option<PointerBarrier> {
+        let idx = self.barriers.iter().position(|state| state.id == id)?;
+        Some(self.barriers.remove(idx).barrier)
+    }
+
+    /// Remove all barriers
+    pub fn clear_barriers(&mut self) {
+        self.barriers.clear();
+    }
+
+    /// Access a previously added barrier
+    pub fn barrier(&self, id: BarrierId) -> Option<&PointerBarrier> {
+        self.barriers
+            .iter()
+            .find(|state| state.id == id)
+            .map(|state| &state.barrier)
+    }
+
+    /// Set the geometries of all outputs
+    ///
+    /// The pointer is clamped to the union of these rectangles, excluding their right and bottom
+    /// edges. Empty rectangles or rectangles with a negative size are ignored. If no outputs are set, the pointer location is not clamped.
+    pub fn set_outputs(&mut self, outputs: impl IntoIterator<Item = Rectangle<i32, Logical>>) {
+        self.outputs = outputs
+            .into_iter()
+            .filter(|geo| geo.size.w > 0 && geo.size.h > 0)
+            .map(|geo| geo.to_f64())
+            .collect();
+    }
+
+    /// Constrain a relative motion of `delta` starting at `location`
+    ///
+    /// Applies all barriers to the motion, updating their accumulated pressure, and clamps the
+    /// resulting location to the union of the output geometries. Locations outside of all outputs
+    /// are moved to the closest point on any output, so the pointer slides along edges instead of
+    /// getting stuck when moving between outputs of different sizes.
+    pub fn constrain(
+        &mut self,
+        location: Point<f64, Logical>,
+        delta: Point<f64, Logical>,
+    ) -> ConstrainedMotion {
+        let mut to = location + delta;
+        let mut hits = Vec::new();
+
+        for state in &mut self.barriers {
+            let Some(overshoot) = state.barrier.blocked_crossing(location, to) else {
+                state.pressure = 0.0;
+                continue;
+            };
+
+            state.pressure += overshoot;
+            let released = state.pressure > state.barrier.resistance;
+            hits.push(BarrierHit {
+                barrier: state.id,
+                pressure: state.pressure,
+                released,
+            });
+            if released {
+                state.pressure = 0.0;
+            } else {
+                state.barrier.clamp(&mut to);
+            }
+        }
+
+        ConstrainedMotion {
+            location: self.clamp_to_outputs(to),
+            hits,
+        }
+    }
+
+    fn clamp_to_outputs(&self, location: Point<f64, Logical>) -> Point<f64, Logical> {
+        self.outputs
+            .iter()
+            .map(|geo| -> Point<f64, Logical> {
+                (
+                    // the right and bottom edges are exclusive, the last pixel is one before them
+                    location.x.clamp(geo.loc.x, geo.loc.x + geo.size.w - 1.0),
+                    location.y.clamp(geo.loc.y, geo.loc.y + geo.size.h - 1.0),
+                )
+                    .into()
+            })
+            .min_by(|a, b| {
+                let dist = |p: &Point<f64, Logical>| (p.x - location.x).powi(2) + (p.y - location.y).powi(2);
+                dist(a).total_cmp(&dist(b))
+            })
+            .unwrap_or(location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamp_between_outputs_of_different_sizes() {
+        let mut barriers = PointerBarriers::new();
+        barriers.set_outputs([
+            Rectangle::new((0, 0).into(), (1920, 1080).into()),
+            Rectangle::new((1920, 0).into(), (1280, 720).into()),
+        ]);
+
+        // moving right below the smaller output stops at the edge of the larger one
+        let motion = barriers.constrain((1900.0, 1000.0).into(), (40.0, 0.0).into());
+        assert_eq!(motion.location, Point::from((1919.0, 1000.0)));
+        // moving diagonally across slides along the bottom edge of the smaller output
+        let motion = barriers.constrain((1900.0, 700.0).into(), (40.0, 30.0).into());
+        assert_eq!(motion.location, Point::from((1940.0, 719.0)));
+    }
+
+    #[test]
+    fn barrier_resistance() {
+        let mut barriers = PointerBarriers::new();
+        let edge = PointerBarrier::between(
+            Rectangle::new((0, 0).into(), (1920, 1080).into()),
+            Rectangle::new((1920, 0).into(), (1920, 1080).into()),
+        )
+        .unwrap()
+        .with_resistance(20.0);
+        let id = barriers.add_barrier(edge);
+
+        let motion = barriers.constrain((1910.0, 500.0).into(), (20.0, 0.0).into());
+        assert_eq!(motion.location, Point::from((1920.0, 500.0)));
+        assert!(!motion.hits[0].released);
+
+        let motion = barriers.constrain(motion.location, (15.0, 0.0).into());
+        assert_eq!(motion.location, Point::from((1935.0, 500.0)));
+        assert_eq!(motion.hits[0].barrier, id);
+        assert!(motion.hits[0].released);
+
+        // moving back is not blocked
+        let motion = barriers.constrain(motion.location, (-30.0, 0.0).into());
+        assert_eq!(motion.location, Point::from((1905.0, 500.0)));
+        assert!(motion.hits.is_empty());
+    }
+
+    #[test]
+    fn ignore_invalid_outputs() {
+        let mut barriers = PointerBarriers::new();
+        let mut invalid = Rectangle::new((1920, 0).into(), (1280, 720).into());
+        invalid.size.w = -1280;
+        barriers.set_outputs([Rectangle::new((0, 0).into(), (1920, 1080).into()), invalid]);
+
+        let motion = barriers.constrain((1900.0, 500.0).into(), (40.0, 0.0).into());
+        assert_eq!(motion.location, Point::from((1919.0, 500.0)));
+
+        let empty = Rectangle::new((1920, 0).into(), (0, 720).into());
+        barriers.set_outputs([Rectangle::new((0, 0).into(), (1920, 1080).into()), empty]);
+        let motion = barriers.constrain((1900.0, 500.0).into(), (40.0, 0.0).into());
+        assert_eq!(motion.location, Point::from((1919.0, 500.0)));
+    }
+}
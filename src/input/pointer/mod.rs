@@ -12,6 +12,11 @@ use crate::{
     utils::{Clock, IsAlive, Logical, Monotonic, Point},
 };
 
+mod barrier;
+pub use barrier::{
+    BarrierDirections, BarrierHit, BarrierId, ConstrainedMotion, PointerBarrier, PointerBarriers,
+};
+
 mod cursor_image;
 pub use cursor_icon::CursorIcon;
 pub use cursor_image::{CursorImageAttributes, CursorImageStatus, CursorImageSurfaceData};
@@ -228,6 +233,9 @@ impl<D: SeatHandler + 'static> PointerHandle<D> {
     ///
     /// This will internally take care of notifying the appropriate client objects
     /// of enter/motion/leave events.
+    ///
+    /// The location is used as is, [`PointerBarriers`] are not applied. Use
+    /// [`PointerHandle::constrain_motion`] to compute the location from relative motion.
     #[instrument(level = "trace", parent = &self.span, skip(self, data, focus), fields(focus = ?focus.as_ref().map(|(_, loc)| ("...", loc))))]
     pub fn motion(
         &self,
@@ -465,6 +473,29 @@ impl<D: SeatHandler + 'static> PointerHandle<D> {
         self.inner.lock().unwrap().location = location;
     }
 
+    /// Access the pointer barriers and output area of this pointer
+    pub fn with_barriers<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce(&mut PointerBarriers) -> T,
+    {
+        f(&mut self.inner.lock().unwrap().barriers)
+    }
+
+    /// Apply the pointer barriers to a relative motion of `delta` from the current location
+    ///
+    /// The returned location is clamped to the union of the outputs set via
+    /// [`PointerBarriers::set_outputs`] and should be passed on to [`PointerHandle::motion`].
+    /// This does not send any events and does not update the location of the pointer.
+    ///
+    /// Barriers only take effect if the compositor calls this for the relative motion events of
+    /// its input devices, neither [`PointerHandle::motion`] nor [`PointerHandle::relative_motion`]
+    /// apply them. Absolute motion, like from tablets or touchscreens, usually shouldn't be constrained.
+    pub fn constrain_motion(&self, delta: Point<f64, Logical>) -> ConstrainedMotion {
+        let mut inner = self.inner.lock().unwrap();
+        let location = inner.location;
+        inner.barriers.constrain(location, delta)
+    }
+
     /// Access the [`Serial`] of the last `pointer_enter` event, if that focus is still active.
     ///
     /// In other words this will return `None` again, once a `pointer_leave` event occurred.
@@ -705,6 +736,7 @@ pub(crate) struct PointerInternal<D: SeatHandler> {
     location: Point<f64, Logical>,
     grab: GrabStatus<dyn PointerGrab<D>>,
     pressed_buttons: Vec<u32>,
+    barriers: PointerBarriers,
 }
 
 // image_callback does not implement debug, so we have to impl Debug manually
@@ -716,6 +748,7 @@ impl<D: SeatHandler> fmt::Debug for PointerInternal<D> {
             .field("location", &self.location)
             .field("grab", &self.grab)
             .field("pressed_buttons", &self.pressed_buttons)
+            .field("barriers", &self.barriers)
             .field("image_callback", &"...")
             .finish()
     }
@@ -729,6 +762,7 @@ impl<D: SeatHandler + 'static> PointerInternal<D> {
             location: (0.0, 0.0).into(),
             grab: GrabStatus::None,
             pressed_buttons: Vec::new(),
+            barriers: PointerBarriers::new(),
         }
     }
 
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::backend::input::Device;
+
+use super::{Seat, SeatHandler};
+
+/// Assignment of input devices to multiple [`Seat`]s
+///
+/// Allows a single compositor to drive several seats, each with its own focus, cursor and
+/// keyboard state. Devices are assigned to seats by name, usually the seat the device was assigned
+/// to through udev, see `backend::libinput::seat_name` and `backend::udev::seat_name`.
+/// Seats are created on demand when the first device of a seat is added.
+///
+/// Input events should then be routed to the seat returned by [`SeatAssignment::seat_for_device`].
+///
+/// Only input devices are tracked. GPUs are assigned to seats by udev as well, but outputs are not
+/// tied to a [`Seat`] in smithay: use one `UdevBackend` per seat, which only reports the GPUs of its
+/// seat, or look up the seat of a GPU with `backend::udev::gpu_seat`.
+///
+/// ```no_run
+/// # use smithay::input::{SeatState, SeatHandler, SeatAssignment};
+/// # use smithay::backend::input::Device;
+/// # fn example<D: SeatHandler, Dev: Device>(seat_state: &mut SeatState<D>, device: &Dev) {
+/// let mut assignment = SeatAssignment::<D>::new();
+/// let seat = assignment.add_device(device, "seat0", |name| {
+///     let seat = seat_state.new_seat(name);
+///     // add the capabilities of the seat
+///     seat
+/// });
+/// # }
+/// ```
+pub struct SeatAssignment<D: SeatHandler> {
+    seats: HashMap<String, Seat<D>>,
+    devices: HashMap<String, String>,
+}
+
+impl<D: SeatHandler> fmt::Debug for SeatAssignment<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeatAssignment")
+            .field("seats", &self.seats)
+            .field("devices", &self.devices)
+            .finish()
+    }
+}
+
+impl<D: SeatHandler> Default for SeatAssignment<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D: SeatHandler> SeatAssignment<D> {
+    /// Create an empty assignment
+    pub fn new() -> Self {
+        SeatAssignment {
+            seats: HashMap::new(),
+            devices: HashMap::new(),
+        }
+    }
+
+    /// Assign a device to the seat of the given name
+    ///
+    /// `create_seat` is called to create the seat, if this is the first device of the seat.
+    /// A device that was already assigned to another seat is moved to the new one.
+    pub fn add_device<Dev, F>(&mut self, device: &Dev, seat_name: &str, create_seat: F) -> Seat<D>
+    where
+        Dev: Device + ?Sized,
+        F: FnOnce(&str) -> Seat<D>,
+    {
+        self.devices.insert(device.id(), seat_name.to_owned());
+        self.seats
+            .entry(seat_name.to_owned())
+            .or_insert_with(|| create_seat(seat_name))
+            .clone()
+    }
+
+    /// Remove a device from its seat
+    ///
+    /// Returns the seat the device was assigned to, together with the number of devices still
+    /// assigned to it. The seat itself stays known until [`SeatAssignment::remove_seat`] is called.
+    pub fn remove_device<Dev: Device + ?Sized>(&mut self, device: &Dev) -> Option<(Seat<D>, usize)> {
+        let name = self.devices.remove(&device.id())?;
+        let seat = self.seats.get(&name)?.clone();
+        Some((seat, self.device_count(&name)))
+    }
+
+    /// Forget about a seat and all devices assigned to it
+    pub fn remove_seat(&mut self, seat_name: &str) -> Option<Seat<D>> {
+        self.devices.retain(|_, name| name != seat_name);
+        self.seats.remove(seat_name)
+    }
+
+    /// The seat a device is assigned to
+    pub fn seat_for_device<Dev: Device + ?Sized>(&self, device: &Dev) -> Option<&Seat<D>> {
+        self.devices
+            .get(&device.id())
+            .and_then(|name| self.seats.get(name))
+    }
+
+    /// The seat of the given name
+    pub fn seat(&self, seat_name: &str) -> Option<&Seat<D>> {
+        self.seats.get(seat_name)
+    }
+
+    /// Iterate over all known seats
+    pub fn seats(&self) -> impl Iterator<Item = &Seat<D>> {
+        self.seats.values()
+    }
+
+    /// Number of devices assigned to the seat of the given name
+    pub fn device_count(&self, seat_name: &str) -> usize {
+        self.devices.values().filter(|name| *name == seat_name).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::backend::input::DeviceCapability;
+    use crate::input::testing::TestState;
+
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    struct TestDevice(&'static str);
+
+    impl Device for TestDevice {
+        fn id(&self) -> String {
+            self.0.to_owned()
+        }
+
+        fn name(&self) -> String {
+            self.0.to_owned()
+        }
+
+        fn has_capability(&self, _capability: DeviceCapability) -> bool {
+            true
+        }
+
+        fn usb_id(&self) -> Option<(u32, u32)> {
+            None
+        }
+
+        fn syspath(&self) -> Option<PathBuf> {
+            None
+        }
+    }
+
+    #[test]
+    fn add_device() {
+        let (mut state, _) = TestState::new();
+        let mut assignment = SeatAssignment::new();
+        let mut created = Vec::new();
+        let mut create_seat = |name: &str| {
+            created.push(name.to_owned());
+            state.seat_state.new_seat(name)
+        };
+
+        let keyboard = TestDevice("keyboard");
+        let mouse = TestDevice("mouse");
+        let seat0 = assignment.add_device(&keyboard, "seat0", &mut create_seat);
+        assert_eq!(assignment.add_device(&mouse, "seat0", &mut create_seat), seat0);
+        assert_eq!(assignment.device_count("seat0"), 2);
+
+        // moving a device to another seat
+        let seat1 = assignment.add_device(&mouse, "seat1", &mut create_seat);
+        assert_ne!(seat0, seat1);
+        assert_eq!(created, ["seat0", "seat1"]);
+        assert_eq!(assignment.seat_for_device(&keyboard), Some(&seat0));
+        assert_eq!(assignment.seat_for_device(&mouse), Some(&seat1));
+        assert_eq!(assignment.device_count("seat0"), 1);
+        assert_eq!(assignment.device_count("seat1"), 1);
+        assert_eq!(assignment.seats().count(), 2);
+    }
+
+    #[test]
+    fn remove_device() {
+        let (mut state, _) = TestState::new();
+        let mut assignment = SeatAssignment::new();
+        let keyboard = TestDevice("keyboard");
+        let mouse = TestDevice("mouse");
+        let seat0 = assignment.add_device(&keyboard, "seat0", |name| state.seat_state.new_seat(name));
+        assignment.add_device(&mouse, "seat0", |_| unreachable!());
+
+        assert_eq!(assignment.remove_device(&keyboard), Some((seat0.clone(), 1)));
+        assert_eq!(assignment.remove_device(&keyboard), None);
+        assert_eq!(assignment.seat_for_device(&keyboard), None);
+        assert_eq!(assignment.remove_device(&mouse), Some((seat0.clone(), 0)));
+        // the seat stays known without devices
+        assert_eq!(assignment.seat("seat0"), Some(&seat0));
+    }
+
+    #[test]
+    fn remove_seat() {
+        let (mut state, _) = TestState::new();
+        let mut assignment = SeatAssignment::new();
+        let keyboard = TestDevice("keyboard");
+        let mouse = TestDevice("mouse");
+        let seat0 = assignment.add_device(&keyboard, "seat0", |name| state.seat_state.new_seat(name));
+        let seat1 = assignment.add_device(&mouse, "seat1", |name| state.seat_state.new_seat(name));
+
+        assert_eq!(assignment.remove_seat("seat0"), Some(seat0));
+        assert_eq!(assignment.remove_seat("seat0"), None);
+        assert_eq!(assignment.seat_for_device(&keyboard), None);
+        assert_eq!(assignment.device_count("seat0"), 0);
+        assert_eq!(assignment.seat_for_device(&mouse), Some(&seat1));
+        assert_eq!(assignment.seats().count(), 1);
+    }
+}
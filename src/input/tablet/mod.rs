@@ -159,6 +159,7 @@ use crate::{
             TabletToolTarget,
         },
     },
+    utils::Serial,
 };
 
 #[cfg(feature = "wayland_frontend")]
@@ -309,7 +310,10 @@ pub trait TabletSeatTrait<D: TabletSeatHandler> {
 impl<D: SeatHandler + TabletSeatHandler + 'static> TabletSeatTrait<D> for Seat<D> {
     fn tablet_seat(&self) -> TabletSeat<D> {
         let user_data = self.user_data();
-        user_data.get_or_insert(TabletSeat::default).clone()
+        let tablet_seat = user_data.get_or_insert(TabletSeat::default).clone();
+        // tools only exist once the tablet seat was created, so the seat can't miss any grabs
+        self.set_tablet_tool_grab_hook(|seat, serial| seat.tablet_seat().tool_with_grab(serial).is_some());
+        tablet_seat
     }
 }
 
@@ -555,6 +559,17 @@ impl<D: TabletSeatHandler + 'static> TabletSeat<D> {
         self.arc.lock().unwrap().tools.get(tool_desc).cloned()
     }
 
+    /// Get a handle to the tablet tool currently grabbed with this serial, if any.
+    pub fn tool_with_grab(&self, serial: Serial) -> Option<TabletToolHandle<D>> {
+        self.arc
+            .lock()
+            .unwrap()
+            .tools
+            .values()
+            .find(|tool| tool.has_grab(serial))
+            .cloned()
+    }
+
     /// Count all tablet tool devices
     pub fn count_tools(&self) -> usize {
         self.arc.lock().unwrap().tools.len()
@@ -584,3 +599,67 @@ impl<D: TabletSeatHandler + 'static> TabletSeat<D> {
         self.arc.lock().unwrap().tools.clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::input::{TabletToolCapabilities, TabletToolType};
+    use crate::input::tablet::tool::{DownEvent, ProximityInEvent, UpEvent};
+    use crate::input::testing::{TestEvent, TestFocus, TestState};
+    use crate::utils::SERIAL_COUNTER;
+
+    // `wl_data_device.start_drag` validates its serial with `Seat::has_tablet_tool_grab`
+    #[test]
+    fn tool_grab_serial() {
+        let (mut state, seat) = TestState::new();
+        let other = SERIAL_COUNTER.next_serial();
+        assert!(!seat.has_tablet_tool_grab(other));
+
+        let tablet_seat = seat.tablet_seat();
+        let tablet = tablet_seat.add_tablet(&TabletDescriptor {
+            name: String::from("tablet"),
+            usb_id: None,
+            syspath: None,
+        });
+        let tool = tablet_seat.add_tool(&TabletToolDescriptor {
+            tool_type: TabletToolType::Pen,
+            hardware_serial: 1,
+            hardware_id_wacom: 0,
+            capabilities: TabletToolCapabilities::empty(),
+        });
+        tool.proximity_in(
+            &mut state,
+            Some((TestFocus, (0.0, 0.0).into())),
+            tablet,
+            &ProximityInEvent {
+                location: (10.0, 10.0).into(),
+                axis: None,
+                serial: SERIAL_COUNTER.next_serial(),
+                time: 0,
+            },
+        );
+        assert!(!seat.has_tablet_tool_grab(other));
+
+        let down = SERIAL_COUNTER.next_serial();
+        tool.down(
+            &mut state,
+            &DownEvent {
+                serial: down,
+                time: 1,
+            },
+        );
+        assert!(seat.has_tablet_tool_grab(down));
+        assert!(!seat.has_tablet_tool_grab(other));
+        assert!(tablet_seat.tool_with_grab(down) == Some(tool.clone()));
+
+        tool.up(
+            &mut state,
+            &UpEvent {
+                serial: SERIAL_COUNTER.next_serial(),
+                time: 2,
+            },
+        );
+        assert!(!seat.has_tablet_tool_grab(down));
+        assert_eq!(state.take_events(), [TestEvent::ToolDown, TestEvent::ToolUp]);
+    }
+}
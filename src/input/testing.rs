@@ -0,0 +1,259 @@
+//! Seat handler recording the events received by its focus, shared by the unit tests of this module
+
+use crate::{
+    backend::input::{ButtonState, KeyState, TabletToolDescriptor, TouchSlot},
+    input::{
+        Seat, SeatHandler, SeatState,
+        keyboard::{KeyboardTarget, Keycode, Keysym, KeysymHandle, ModifiersState},
+        pointer::{
+            AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent, GesturePinchBeginEvent,
+            GesturePinchEndEvent, GesturePinchUpdateEvent, GestureSwipeBeginEvent, GestureSwipeEndEvent,
+            GestureSwipeUpdateEvent, MotionEvent, PointerTarget, RelativeMotionEvent,
+        },
+        tablet::{
+            Tablet, TabletSeatHandler,
+            tool::{self, TabletToolTarget},
+        },
+        touch::{
+            DownEvent, FrameMarker, GrabStartData as TouchGrabStartData, MotionEvent as TouchMotionEvent,
+            OrientationEvent, PointerEmulationConfig, PointerEmulationGrab, ShapeEvent, TouchGrab,
+            TouchTarget, UpEvent,
+        },
+    },
+    utils::{IsAlive, Logical, Point, Serial},
+};
+
+/// Event received by a [`TestFocus`]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TestEvent {
+    Key {
+        keycode: Keycode,
+        sym: Keysym,
+        state: KeyState,
+    },
+    Modifiers(ModifiersState),
+    PointerMotion(Point<f64, Logical>),
+    PointerButton(u32, ButtonState),
+    PointerAxis,
+    PointerFrame,
+    TouchDown(TouchSlot),
+    TouchUp(TouchSlot),
+    TouchMotion(TouchSlot),
+    TouchCancel,
+    ToolDown,
+    ToolUp,
+}
+
+/// Focus target recording all events into the [`TestState`]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TestFocus;
+
+pub(crate) struct TestState {
+    pub seat_state: SeatState<TestState>,
+    pub events: Vec<TestEvent>,
+    /// Emulate pointer input for new touch sequences using the given config
+    pub pointer_emulation: Option<PointerEmulationConfig>,
+    /// Timestamp of the last touch down event, passed to the pointer emulation
+    pub touch_down_time: u32,
+}
+
+impl TestState {
+    pub fn new() -> (TestState, Seat<TestState>) {
+        let mut seat_state = SeatState::new();
+        let seat = seat_state.new_seat("test");
+        let state = TestState {
+            seat_state,
+            events: Vec::new(),
+            pointer_emulation: None,
+            touch_down_time: 0,
+        };
+        (state, seat)
+    }
+
+    /// Returns and clears the recorded events
+    pub fn take_events(&mut self) -> Vec<TestEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl SeatHandler for TestState {
+    type KeyboardFocus = TestFocus;
+    type PointerFocus = TestFocus;
+    type TouchFocus = TestFocus;
+
+    fn seat_state(&mut self) -> &mut SeatState<Self> {
+        &mut self.seat_state
+    }
+
+    fn touch_down_grab(&mut self, start_data: TouchGrabStartData<Self>) -> impl TouchGrab<Self> {
+        let pointer = self.seat_state.seats[0].get_pointer();
+        match (self.pointer_emulation.clone(), pointer) {
+            (Some(config), Some(pointer)) => PointerEmulationGrab::new(
+                start_data,
+                self.touch_down_time,
+                pointer,
+                Some((TestFocus, Point::default())),
+                config,
+            ),
+            _ => PointerEmulationGrab::passthrough(start_data),
+        }
+    }
+}
+
+impl IsAlive for TestFocus {
+    fn alive(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "wayland_frontend")]
+impl crate::wayland::seat::WaylandFocus for TestFocus {
+    fn wl_surface(&self) -> Option<std::borrow::Cow<'_, wayland_server::protocol::wl_surface::WlSurface>> {
+        None
+    }
+}
+
+impl KeyboardTarget<TestState> for TestFocus {
+    fn enter(&self, _: &Seat<TestState>, _: &mut TestState, _: Vec<KeysymHandle<'_>>, _: Serial) {}
+
+    fn leave(&self, _: &Seat<TestState>, _: &mut TestState, _: Serial) {}
+
+    fn key(
+        &self,
+        _: &Seat<TestState>,
+        data: &mut TestState,
+        key: KeysymHandle<'_>,
+        state: KeyState,
+        _: Serial,
+        _: u32,
+    ) {
+        data.events.push(TestEvent::Key {
+            keycode: key.raw_code(),
+            sym: key.modified_sym(),
+            state,
+        });
+    }
+
+    fn modifiers(&self, _: &Seat<TestState>, data: &mut TestState, modifiers: ModifiersState, _: Serial) {
+        data.events.push(TestEvent::Modifiers(modifiers));
+    }
+}
+
+impl PointerTarget<TestState> for TestFocus {
+    fn enter(&self, _: &Seat<TestState>, data: &mut TestState, event: &MotionEvent) {
+        data.events.push(TestEvent::PointerMotion(event.location));
+    }
+
+    fn motion(&self, _: &Seat<TestState>, data: &mut TestState, event: &MotionEvent) {
+        data.events.push(TestEvent::PointerMotion(event.location));
+    }
+
+    fn relative_motion(&self, _: &Seat<TestState>, _: &mut TestState, _: &RelativeMotionEvent) {}
+
+    fn button(&self, _: &Seat<TestState>, data: &mut TestState, event: &ButtonEvent) {
+        data.events
+            .push(TestEvent::PointerButton(event.button, event.state));
+    }
+
+    fn axis(&self, _: &Seat<TestState>, data: &mut TestState, _: AxisFrame) {
+        data.events.push(TestEvent::PointerAxis);
+    }
+
+    fn frame(&self, _: &Seat<TestState>, data: &mut TestState) {
+        data.events.push(TestEvent::PointerFrame);
+    }
+
+    fn gesture_swipe_begin(&self, _: &Seat<TestState>, _: &mut TestState, _: &GestureSwipeBeginEvent) {}
+
+    fn gesture_swipe_update(&self, _: &Seat<TestState>, _: &mut TestState, _: &GestureSwipeUpdateEvent) {}
+
+    fn gesture_swipe_end(&self, _: &Seat<TestState>, _: &mut TestState, _: &GestureSwipeEndEvent) {}
+
+    fn gesture_pinch_begin(&self, _: &Seat<TestState>, _: &mut TestState, _: &GesturePinchBeginEvent) {}
+
+    fn gesture_pinch_update(&self, _: &Seat<TestState>, _: &mut TestState, _: &GesturePinchUpdateEvent) {}
+
+    fn gesture_pinch_end(&self, _: &Seat<TestState>, _: &mut TestState, _: &GesturePinchEndEvent) {}
+
+    fn gesture_hold_begin(&self, _: &Seat<TestState>, _: &mut TestState, _: &GestureHoldBeginEvent) {}
+
+    fn gesture_hold_end(&self, _: &Seat<TestState>, _: &mut TestState, _: &GestureHoldEndEvent) {}
+
+    fn leave(&self, _: &Seat<TestState>, _: &mut TestState, _: Serial, _: u32) {}
+}
+
+impl TouchTarget<TestState> for TestFocus {
+    fn down(&self, _: &Seat<TestState>, data: &mut TestState, event: &DownEvent) {
+        data.events.push(TestEvent::TouchDown(event.slot));
+    }
+
+    fn up(&self, _: &Seat<TestState>, data: &mut TestState, event: &UpEvent) {
+        data.events.push(TestEvent::TouchUp(event.slot));
+    }
+
+    fn motion(&self, _: &Seat<TestState>, data: &mut TestState, event: &TouchMotionEvent) {
+        data.events.push(TestEvent::TouchMotion(event.slot));
+    }
+
+    fn frame(&self, _: &Seat<TestState>, _: &mut TestState, _: FrameMarker) {}
+
+    fn cancel(&self, _: &Seat<TestState>, data: &mut TestState, _: FrameMarker) {
+        data.events.push(TestEvent::TouchCancel);
+    }
+
+    fn shape(&self, _: &Seat<TestState>, _: &mut TestState, _: &ShapeEvent) {}
+
+    fn orientation(&self, _: &Seat<TestState>, _: &mut TestState, _: &OrientationEvent) {}
+
+    fn last_frame(&self, _: &Seat<TestState>, _: &mut TestState) -> Option<FrameMarker> {
+        None
+    }
+}
+
+impl TabletSeatHandler for TestState {
+    type ToolFocus = TestFocus;
+}
+
+impl TabletToolTarget<TestState> for TestFocus {
+    fn proximity_in(
+        &self,
+        _: &Seat<TestState>,
+        _: &mut TestState,
+        _: &TabletToolDescriptor,
+        _: &Tablet,
+        _: Serial,
+    ) {
+    }
+
+    fn proximity_out(&self, _: &Seat<TestState>, _: &mut TestState, _: &TabletToolDescriptor) {}
+
+    fn down(&self, _: &Seat<TestState>, data: &mut TestState, _: &TabletToolDescriptor, _: &tool::DownEvent) {
+        data.events.push(TestEvent::ToolDown);
+    }
+
+    fn up(&self, _: &Seat<TestState>, data: &mut TestState, _: &TabletToolDescriptor, _: &tool::UpEvent) {
+        data.events.push(TestEvent::ToolUp);
+    }
+
+    fn motion(
+        &self,
+        _: &Seat<TestState>,
+        _: &mut TestState,
+        _: &TabletToolDescriptor,
+        _: &tool::MotionEvent,
+    ) {
+    }
+
+    fn axis(&self, _: &Seat<TestState>, _: &mut TestState, _: &TabletToolDescriptor, _: tool::AxisFrame) {}
+
+    fn button(
+        &self,
+        _: &Seat<TestState>,
+        _: &mut TestState,
+        _: &TabletToolDescriptor,
+        _: &tool::ButtonEvent,
+    ) {
+    }
+
+    fn frame(&self, _: &Seat<TestState>, _: &mut TestState, _: &TabletToolDescriptor, _: u32) {}
+}
@@ -0,0 +1,596 @@
+//! Pointer emulation for touch input
+//!
+//! Many X11 and some wayland clients don't handle `wl_touch` at all. The [`PointerEmulationGrab`]
+//! translates touch sequences into pointer events for those clients, similar to the emulation
+//! done by X servers: taps become clicks, long presses right clicks, moving a finger drags and
+//! two fingers scroll.
+//!
+//! The grab relies on the timestamps of the touch events instead of a timer: a long press is
+//! reported with the first touch event past [`PointerEmulationConfig::long_press`], at the latest
+//! when the finger is lifted.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::{
+    backend::input::{Axis, AxisSource, ButtonState, TouchSlot},
+    input::{
+        SeatHandler,
+        pointer::{AxisFrame, ButtonEvent, MotionEvent as PointerMotionEvent, PointerHandle},
+    },
+    utils::{Logical, Point, SERIAL_COUNTER},
+};
+
+use super::{
+    DownEvent, GrabStartData, MotionEvent, OrientationEvent, ShapeEvent, TouchGrab, TouchInnerHandle, UpEvent,
+};
+
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+
+/// Thresholds used by a [`PointerEmulationGrab`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointerEmulationConfig {
+    /// Distance in logical pixels the primary touch point has to travel before a drag starts
+    pub drag_threshold: f64,
+    /// Duration of a touch without motion to be emulated as a right click
+    pub long_press: Duration,
+    /// Factor applied to the motion of two fingers to produce scroll events
+    pub scroll_factor: f64,
+}
+
+impl Default for PointerEmulationConfig {
+    fn default() -> Self {
+        PointerEmulationConfig {
+            drag_threshold: 8.0,
+            long_press: Duration::from_millis(600),
+            scroll_factor: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EmulationState {
+    /// The primary touch point is down, but didn't move yet
+    Pending,
+    /// The left button is pressed and the pointer follows the primary touch point
+    Dragging,
+    /// Two touch points are down, their motion is converted to scroll events
+    Scrolling { center: Point<f64, Logical> },
+    /// The emulated interaction ended, waiting for all touch points to be lifted
+    Finished,
+}
+
+struct Emulation<D: SeatHandler> {
+    pointer: PointerHandle<D>,
+    focus: Option<(<D as SeatHandler>::PointerFocus, Point<f64, Logical>)>,
+    config: PointerEmulationConfig,
+    state: EmulationState,
+    started: u32,
+    cancelled_touch: bool,
+    last_time: u32,
+    points: Vec<(TouchSlot, Point<f64, Logical>)>,
+}
+
+/// Implicit touch grab emulating pointer input for clients not supporting touch input
+///
+/// Meant to be returned by [`SeatHandler::touch_down_grab`] in place of the default
+/// [`TouchDownGrab`](super::TouchDownGrab). The compositor decides per touch sequence, usually
+/// depending on the client owning the focused surface, whether pointer events should be emulated
+/// using [`PointerEmulationGrab::new`], or touch events should be forwarded unchanged using
+/// [`PointerEmulationGrab::passthrough`].
+///
+/// When emulating, the touch sequence is cancelled for the touch focus and the primary touch
+/// point is converted into events on the provided [`PointerHandle`]:
+///
+/// - a tap generates a left click, a touch held in place for
+///   [`PointerEmulationConfig::long_press`] generates a right click
+/// - moving the touch point drags with the left button pressed
+/// - moving two touch points generates finger scroll events
+pub struct PointerEmulationGrab<D: SeatHandler> {
+    start_data: GrabStartData<D>,
+    touch_points: usize,
+    emulation: Option<Emulation<D>>,
+}
+
+impl<D: SeatHandler + 'static> PointerEmulationGrab<D> {
+    /// Create a grab emulating pointer input on the provided pointer
+    ///
+    /// The `focus` is the pointer focus used for the whole touch sequence, usually the surface
+    /// under the location of the touch point that initiated the grab. `time` is the timestamp of
+    /// the down event that initiated the grab, used to detect long presses.
+    pub fn new(
+        start_data: GrabStartData<D>,
+        time: u32,
+        pointer: PointerHandle<D>,
+        focus: Option<(<D as SeatHandler>::PointerFocus, Point<f64, Logical>)>,
+        config: PointerEmulationConfig,
+    ) -> Self {
+        let points = vec![(start_data.slot, start_data.location)];
+        PointerEmulationGrab {
+            start_data,
+            touch_points: 1,
+            emulation: Some(Emulation {
+                pointer,
+                focus,
+                config,
+                state: EmulationState::Pending,
+                started: time,
+                cancelled_touch: false,
+                last_time: time,
+                points,
+            }),
+        }
+    }
+
+    /// Create a grab forwarding touch events unchanged, behaving like the default touch down grab
+    pub fn passthrough(start_data: GrabStartData<D>) -> Self {
+        PointerEmulationGrab {
+            start_data,
+            touch_points: 1,
+            emulation: None,
+        }
+    }
+
+    /// Returns if this grab emulates pointer input
+    pub fn is_emulating(&self) -> bool {
+        self.emulation.is_some()
+    }
+}
+
+impl<D: SeatHandler + 'static> fmt::Debug for PointerEmulationGrab<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PointerEmulationGrab")
+            .field("start_data", &self.start_data)
+            .field("touch_points", &self.touch_points)
+            .field("emulating", &self.emulation.is_some())
+            .finish()
+    }
+}
+
+impl<D: SeatHandler + 'static> Emulation<D> {
+    /// Withdraw the touch sequence from the touch focus, it was already notified of the first
+    /// touch point before the grab was installed
+    fn cancel_touch(&mut self, data: &mut D, handle: &mut TouchInnerHandle<'_, D>) {
+        if !self.cancelled_touch {
+            self.cancelled_touch = true;
+            handle.cancel(data);
+        }
+    }
+
+    fn motion(&self, data: &mut D, location: Point<f64, Logical>, time: u32) {
+        self.pointer.motion(
+            data,
+            self.focus.clone(),
+            &PointerMotionEvent {
+                location,
+                serial: SERIAL_COUNTER.next_serial(),
+                time,
+            },
+        );
+    }
+
+    fn button(&self, data: &mut D, button: u32, state: ButtonState, time: u32) {
+        self.pointer.button(
+            data,
+            &ButtonEvent {
+                serial: SERIAL_COUNTER.next_serial(),
+                time,
+                button,
+                state,
+            },
+        );
+    }
+
+    fn center(&self) -> Point<f64, Logical> {
+        let count = self.points.len().max(1) as f64;
+        let sum = self
+            .points
+            .iter()
+            .fold(Point::<f64, Logical>::default(), |sum, (_, point)| sum + *point);
+        (sum.x / count, sum.y / count).into()
+    }
+
+    fn stop_scroll(&self, data: &mut D, time: u32) {
+        let frame = AxisFrame::new(time)
+            .source(AxisSource::Finger)
+            .stop(Axis::Horizontal)
+            .stop(Axis::Vertical);
+        self.pointer.axis(data, frame);
+        self.pointer.frame(data);
+    }
+
+    /// Emits a right click, if the primary touch point was held in place long enough
+    fn check_long_press(&mut self, data: &mut D, primary: TouchSlot, time: u32) {
+        if self.state != EmulationState::Pending {
+            return;
+        }
+        let Some(location) = self
+            .points
+            .iter()
+            .find(|(slot, _)| *slot == primary)
+            .map(|(_, location)| *location)
+        else {
+            return;
+        };
+
+        // compare using wrapping arithmetic, the timestamps may overflow
+        let elapsed = time.wrapping_sub(self.started);
+        let long_press = self.config.long_press.as_millis().min(u32::MAX as u128 / 2) as u32;
+        if elapsed > u32::MAX / 2 || elapsed < long_press {
+            return;
+        }
+
+        self.motion(data, location, time);
+        self.button(data, BTN_RIGHT, ButtonState::Pressed, time);
+        self.button(data, BTN_RIGHT, ButtonState::Released, time);
+        self.pointer.frame(data);
+        self.state = EmulationState::Finished;
+    }
+
+    fn down(&mut self, data: &mut D, primary: TouchSlot, event: &DownEvent) {
+        self.last_time = event.time;
+        self.check_long_press(data, primary, event.time);
+        self.points.push((event.slot, event.location));
+        match self.state {
+            EmulationState::Pending if self.points.len() == 2 => {
+                // move the pointer first, so scroll events reach the focus
+                let center = self.center();
+                self.motion(data, center, event.time);
+                self.pointer.frame(data);
+                self.state = EmulationState::Scrolling { center };
+            }
+            EmulationState::Scrolling { .. } => {
+                self.stop_scroll(data, event.time);
+                self.state = EmulationState::Finished;
+            }
+            _ => {}
+        }
+    }
+
+    fn up(&mut self, data: &mut D, primary: TouchSlot, event: &UpEvent) {
+        self.last_time = event.time;
+        self.check_long_press(data, primary, event.time);
+        let Some(idx) = self.points.iter().position(|(slot, _)| *slot == event.slot) else {
+            return;
+        };
+        let (_, location) = self.points.remove(idx);
+
+        match self.state {
+            EmulationState::Pending if event.slot == primary => {
+                self.motion(data, location, event.time);
+                self.button(data, BTN_LEFT, ButtonState::Pressed, event.time);
+                self.button(data, BTN_LEFT, ButtonState::Released, event.time);
+                self.pointer.frame(data);
+                self.state = EmulationState::Finished;
+            }
+            EmulationState::Dragging if event.slot == primary => {
+                self.button(data, BTN_LEFT, ButtonState::Released, event.time);
+                self.pointer.frame(data);
+                self.state = EmulationState::Finished;
+            }
+            EmulationState::Scrolling { .. } => {
+                self.stop_scroll(data, event.time);
+                self.state = EmulationState::Finished;
+            }
+            _ => {}
+        }
+    }
+
+    fn touch_motion(
+        &mut self,
+        data: &mut D,
+        primary: TouchSlot,
+        start: Point<f64, Logical>,
+        event: &MotionEvent,
+    ) {
+        self.last_time = event.time;
+        self.check_long_press(data, primary, event.time);
+        let Some(point) = self.points.iter_mut().find(|(slot, _)| *slot == event.slot) else {
+            return;
+        };
+        point.1 = event.location;
+
+        match self.state {
+            EmulationState::Pending if event.slot == primary => {
+                let offset = event.location - start;
+                if offset.x.hypot(offset.y) < self.config.drag_threshold {
+                    return;
+                }
+                self.motion(data, start, event.time);
+                self.button(data, BTN_LEFT, ButtonState::Pressed, event.time);
+                self.motion(data, event.location, event.time);
+                self.pointer.frame(data);
+                self.state = EmulationState::Dragging;
+            }
+            EmulationState::Dragging if event.slot == primary => {
+                self.motion(data, event.location, event.time);
+                self.pointer.frame(data);
+            }
+            EmulationState::Scrolling { center } => {
+                let new_center = self.center();
+                // content follows the fingers, like natural scrolling on touchpads
+                let delta = (center - new_center).upscale(self.config.scroll_factor);
+                self.state = EmulationState::Scrolling { center: new_center };
+                let mut frame = AxisFrame::new(event.time).source(AxisSource::Finger);
+                if delta.x != 0.0 {
+                    frame = frame.value(Axis::Horizontal, delta.x);
+                }
+                if delta.y != 0.0 {
+                    frame = frame.value(Axis::Vertical, delta.y);
+                }
+                self.pointer.axis(data, frame);
+                self.pointer.frame(data);
+            }
+            _ => {}
+        }
+    }
+
+    fn cancel(&mut self, data: &mut D) {
+        // there is no timestamp for cancel events, reuse the last known one
+        let time = self.last_time;
+        match self.state {
+            EmulationState::Dragging => {
+                self.button(data, BTN_LEFT, ButtonState::Released, time);
+                self.pointer.frame(data);
+            }
+            EmulationState::Scrolling { .. } => self.stop_scroll(data, time),
+            _ => {}
+        }
+        self.state = EmulationState::Finished;
+    }
+}
+
+impl<D: SeatHandler + 'static> TouchGrab<D> for PointerEmulationGrab<D> {
+    fn down(
+        &mut self,
+        data: &mut D,
+        handle: &mut TouchInnerHandle<'_, D>,
+        _focus: Option<(<D as SeatHandler>::TouchFocus, Point<f64, Logical>)>,
+        event: &DownEvent,
+    ) {
+        self.touch_points += 1;
+        match self.emulation.as_mut() {
+            Some(emulation) => {
+                emulation.cancel_touch(data, handle);
+                emulation.down(data, self.start_data.slot, event);
+            }
+            None => handle.down(data, self.start_data.focus.clone(), event),
+        }
+    }
+
+    fn up(&mut self, data: &mut D, handle: &mut TouchInnerHandle<'_, D>, event: &UpEvent) {
+        match self.emulation.as_mut() {
+            Some(emulation) => {
+                emulation.cancel_touch(data, handle);
+                emulation.up(data, self.start_data.slot, event);
+            }
+            None => handle.up(data, event),
+        }
+        self.touch_points = self.touch_points.saturating_sub(1);
+        if self.touch_points == 0 {
+            handle.unset_grab(self, data);
+        }
+    }
+
+    fn motion(
+        &mut self,
+        data: &mut D,
+        handle: &mut TouchInnerHandle<'_, D>,
+        _focus: Option<(<D as SeatHandler>::TouchFocus, Point<f64, Logical>)>,
+        event: &MotionEvent,
+    ) {
+        match self.emulation.as_mut() {
+            Some(emulation) => {
+                emulation.cancel_touch(data, handle);
+                emulation.touch_motion(data, self.start_data.slot, self.start_data.location, event);
+            }
+            None => handle.motion(data, self.start_data.focus.clone(), event),
+        }
+    }
+
+    fn frame(&mut self, data: &mut D, handle: &mut TouchInnerHandle<'_, D>) {
+        match self.emulation.as_mut() {
+            Some(emulation) => emulation.cancel_touch(data, handle),
+            None => handle.frame(data),
+        }
+    }
+
+    fn cancel(&mut self, data: &mut D, handle: &mut TouchInnerHandle<'_, D>) {
+        match self.emulation.as_mut() {
+            Some(emulation) => {
+                emulation.cancel_touch(data, handle);
+                emulation.cancel(data);
+            }
+            None => handle.cancel(data),
+        }
+        handle.unset_grab(self, data);
+    }
+
+    fn shape(&mut self, data: &mut D, handle: &mut TouchInnerHandle<'_, D>, event: &ShapeEvent) {
+        if self.emulation.is_none() {
+            handle.shape(data, event)
+        }
+    }
+
+    fn orientation(&mut self, data: &mut D, handle: &mut TouchInnerHandle<'_, D>, event: &OrientationEvent) {
+        if self.emulation.is_none() {
+            handle.orientation(data, event)
+        }
+    }
+
+    fn start_data(&self) -> &GrabStartData<D> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, data: &mut D) {
+        if let Some(emulation) = self.emulation.as_mut() {
+            if emulation.state != EmulationState::Finished {
+                emulation.cancel(data);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::testing::{TestEvent, TestFocus, TestState};
+    use crate::input::touch::TouchHandle;
+
+    fn primary() -> TouchSlot {
+        Some(0).into()
+    }
+
+    fn secondary() -> TouchSlot {
+        Some(1).into()
+    }
+
+    fn setup() -> (TestState, TouchHandle<TestState>) {
+        let (mut state, mut seat) = TestState::new();
+        seat.add_pointer();
+        let touch = seat.add_touch();
+        state.pointer_emulation = Some(PointerEmulationConfig::default());
+        (state, touch)
+    }
+
+    fn down(state: &mut TestState, touch: &TouchHandle<TestState>, slot: TouchSlot, x: f64, time: u32) {
+        state.touch_down_time = time;
+        let event = DownEvent {
+            slot,
+            location: (x, 0.0).into(),
+            serial: SERIAL_COUNTER.next_serial(),
+            time,
+        };
+        touch.down(state, Some((TestFocus, Point::default())), &event);
+        touch.frame(state);
+    }
+
+    fn motion(state: &mut TestState, touch: &TouchHandle<TestState>, slot: TouchSlot, x: f64, time: u32) {
+        let event = MotionEvent {
+            slot,
+            location: (x, 0.0).into(),
+            time,
+        };
+        touch.motion(state, Some((TestFocus, Point::default())), &event);
+        touch.frame(state);
+    }
+
+    fn up(state: &mut TestState, touch: &TouchHandle<TestState>, slot: TouchSlot, time: u32) {
+        let event = UpEvent {
+            slot,
+            serial: SERIAL_COUNTER.next_serial(),
+            time,
+        };
+        touch.up(state, &event);
+        touch.frame(state);
+    }
+
+    fn buttons(events: &[TestEvent]) -> Vec<(u32, ButtonState)> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                TestEvent::PointerButton(button, state) => Some((*button, *state)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tap_clicks() {
+        let (mut state, touch) = setup();
+        down(&mut state, &touch, primary(), 10.0, 1000);
+        up(&mut state, &touch, primary(), 1100);
+
+        let events = state.take_events();
+        assert!(events.contains(&TestEvent::TouchCancel));
+        assert_eq!(
+            buttons(&events),
+            [
+                (BTN_LEFT, ButtonState::Pressed),
+                (BTN_LEFT, ButtonState::Released)
+            ]
+        );
+    }
+
+    #[test]
+    fn long_press_uses_event_time() {
+        let (mut state, touch) = setup();
+        down(&mut state, &touch, primary(), 10.0, 1000);
+        // small jitter below the drag threshold before the long press
+        motion(&mut state, &touch, primary(), 11.0, 1500);
+        assert!(buttons(&state.take_events()).is_empty());
+
+        // the right click is emitted with the first event past the threshold, not on release
+        motion(&mut state, &touch, primary(), 11.0, 1600);
+        assert_eq!(
+            buttons(&state.take_events()),
+            [
+                (BTN_RIGHT, ButtonState::Pressed),
+                (BTN_RIGHT, ButtonState::Released)
+            ]
+        );
+
+        up(&mut state, &touch, primary(), 2000);
+        assert!(buttons(&state.take_events()).is_empty());
+    }
+
+    #[test]
+    fn long_press_on_release() {
+        let (mut state, touch) = setup();
+        // timestamps overflowing during the press
+        down(&mut state, &touch, primary(), 10.0, u32::MAX - 100);
+        up(&mut state, &touch, primary(), 600);
+        assert_eq!(
+            buttons(&state.take_events()),
+            [
+                (BTN_RIGHT, ButtonState::Pressed),
+                (BTN_RIGHT, ButtonState::Released)
+            ]
+        );
+    }
+
+    #[test]
+    fn drag() {
+        let (mut state, touch) = setup();
+        down(&mut state, &touch, primary(), 10.0, 1000);
+        motion(&mut state, &touch, primary(), 50.0, 1100);
+        assert_eq!(buttons(&state.take_events()), [(BTN_LEFT, ButtonState::Pressed)]);
+
+        // dragging past the long press threshold doesn't click
+        motion(&mut state, &touch, primary(), 80.0, 2000);
+        let events = state.take_events();
+        assert!(buttons(&events).is_empty());
+        assert!(events.contains(&TestEvent::PointerMotion((80.0, 0.0).into())));
+
+        up(&mut state, &touch, primary(), 2100);
+        assert_eq!(buttons(&state.take_events()), [(BTN_LEFT, ButtonState::Released)]);
+    }
+
+    #[test]
+    fn two_finger_scroll() {
+        let (mut state, touch) = setup();
+        down(&mut state, &touch, primary(), 10.0, 1000);
+        down(&mut state, &touch, secondary(), 30.0, 1010);
+        motion(&mut state, &touch, secondary(), 60.0, 1050);
+        up(&mut state, &touch, secondary(), 1100);
+        up(&mut state, &touch, primary(), 1100);
+
+        let events = state.take_events();
+        assert!(buttons(&events).is_empty());
+        assert!(events.contains(&TestEvent::PointerAxis));
+    }
+
+    #[test]
+    fn passthrough() {
+        let (mut state, touch) = setup();
+        state.pointer_emulation = None;
+        down(&mut state, &touch, primary(), 10.0, 1000);
+        up(&mut state, &touch, primary(), 2000);
+
+        assert_eq!(
+            state.take_events(),
+            [TestEvent::TouchDown(primary()), TestEvent::TouchUp(primary())]
+        );
+    }
+}
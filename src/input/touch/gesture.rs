@@ -0,0 +1,624 @@
+//! Recognition of multi-finger touchscreen gestures
+//!
+//! Touchscreens report individual touch points, unlike touchpads for which libinput already
+//! recognizes gestures. The [`TouchGestureRecognizer`] tracks the touch points of a sequence and
+//! classifies them into swipe, pinch and hold gestures, which compositors can use for global
+//! bindings like edge swipes or forward as pointer gestures.
+//!
+//! The recognizer only works on the event timestamps and has no timer of its own, hold gestures
+//! are detected by calling [`TouchGestureRecognizer::timeout`] once the
+//! [`TouchGestureRecognizer::hold_deadline`] passed.
+
+use crate::backend::input::TouchSlot;
+use crate::utils::{Logical, Point, Rectangle};
+
+use super::{DownEvent, MotionEvent, UpEvent};
+
+/// Thresholds used by a [`TouchGestureRecognizer`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TouchGestureConfig {
+    /// Minimum number of fingers for a gesture
+    ///
+    /// Single finger swipes are only recognized when starting in the edge zone,
+    /// see [`TouchGestureConfig::edge_area`].
+    pub min_fingers: u32,
+    /// Distance in logical pixels the center of the touch points has to travel to begin a swipe
+    pub swipe_distance: f64,
+    /// Relative change of the distance between the touch points to begin a pinch
+    pub pinch_scale: f64,
+    /// Rotation in degrees of the touch points to begin a pinch
+    pub pinch_rotation: f64,
+    /// Time in milliseconds the touch points have to stay in place to begin a hold
+    pub hold_duration: u32,
+    /// Area used to detect edge swipes, usually the geometry of the touchscreen's output
+    pub edge_area: Option<Rectangle<f64, Logical>>,
+    /// Width in logical pixels of the zone along the borders of the [`TouchGestureConfig::edge_area`]
+    pub edge_size: f64,
+}
+
+impl Default for TouchGestureConfig {
+    fn default() -> Self {
+        TouchGestureConfig {
+            min_fingers: 3,
+            swipe_distance: 16.0,
+            pinch_scale: 0.15,
+            pinch_rotation: 15.0,
+            hold_duration: 500,
+            edge_area: None,
+            edge_size: 16.0,
+        }
+    }
+}
+
+/// Edge of the [`TouchGestureConfig::edge_area`] a swipe started from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TouchEdge {
+    /// Top edge
+    Top,
+    /// Bottom edge
+    Bottom,
+    /// Left edge
+    Left,
+    /// Right edge
+    Right,
+}
+
+/// Gesture recognized from a touch sequence
+#[derive(Debug, Clone, PartialEq)]
+pub enum TouchGestureEvent {
+    /// A swipe gesture started
+    SwipeBegin {
+        /// Timestamp of the event, with millisecond granularity
+        time: u32,
+        /// Number of fingers of the gesture
+        fingers: u32,
+        /// Edge the first finger was put down on, if any
+        edge: Option<TouchEdge>,
+    },
+    /// The fingers of a swipe gesture moved
+    SwipeUpdate {
+        /// Timestamp of the event, with millisecond granularity
+        time: u32,
+        /// Offset of the logical center of the gesture relative to the previous event
+        delta: Point<f64, Logical>,
+    },
+    /// A swipe gesture ended
+    SwipeEnd {
+        /// Timestamp of the event, with millisecond granularity
+        time: u32,
+        /// Whether the gesture was cancelled
+        cancelled: bool,
+    },
+    /// A pinch gesture started
+    PinchBegin {
+        /// Timestamp of the event, with millisecond granularity
+        time: u32,
+        /// Number of fingers of the gesture
+        fingers: u32,
+    },
+    /// The fingers of a pinch gesture moved
+    PinchUpdate {
+        /// Timestamp of the event, with millisecond granularity
+        time: u32,
+        /// Offset of the logical center of the gesture relative to the previous event
+        delta: Point<f64, Logical>,
+        /// Absolute scale compared to the begin event
+        scale: f64,
+        /// Relative angle in degrees clockwise compared to the previous event
+        rotation: f64,
+    },
+    /// A pinch gesture ended
+    PinchEnd {
+        /// Timestamp of the event, with millisecond granularity
+        time: u32,
+        /// Whether the gesture was cancelled
+        cancelled: bool,
+    },
+    /// A hold gesture started
+    HoldBegin {
+        /// Timestamp of the event, with millisecond granularity
+        time: u32,
+        /// Number of fingers of the gesture
+        fingers: u32,
+    },
+    /// A hold gesture ended
+    HoldEnd {
+        /// Timestamp of the event, with millisecond granularity
+        time: u32,
+        /// Whether the gesture was cancelled
+        cancelled: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    /// No finger is down
+    Idle,
+    /// Fingers are down, but no gesture was recognized yet
+    Pending,
+    Swipe,
+    Pinch {
+        initial_spread: f64,
+    },
+    Hold,
+    /// A gesture ended or was cancelled, waiting for all fingers to be lifted
+    Finished,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Baseline {
+    time: u32,
+    center: Point<f64, Logical>,
+    spread: f64,
+    angle: Option<f64>,
+}
+
+/// Recognizer for swipe, pinch and hold gestures on touchscreens
+///
+/// The recognizer is fed with the same events passed to the [`TouchHandle`](super::TouchHandle)
+/// and classifies multi-finger touch sequences, similar to the gestures libinput reports for
+/// touchpads. Each touch sequence produces at most one gesture, a hold may however turn into
+/// a swipe or a pinch once the fingers start moving. Changing the number of fingers ends the
+/// current gesture.
+///
+/// Hold gestures require time to pass without any motion, compositors should call
+/// [`TouchGestureRecognizer::timeout`] when the time returned by
+/// [`TouchGestureRecognizer::hold_deadline`] is reached.
+///
+/// The recognizer doesn't intercept any events, compositors decide whether to keep
+/// forwarding the touch points to clients once a gesture was recognized,
+/// e.g. by calling [`TouchHandle::cancel`](super::TouchHandle::cancel).
+#[derive(Debug)]
+pub struct TouchGestureRecognizer {
+    config: TouchGestureConfig,
+    points: Vec<(TouchSlot, Point<f64, Logical>)>,
+    state: State,
+    edge: Option<TouchEdge>,
+    baseline: Option<Baseline>,
+    last_center: Point<f64, Logical>,
+    last_angle: Option<f64>,
+}
+
+impl TouchGestureRecognizer {
+    /// Create a new recognizer using the given thresholds
+    pub fn new(config: TouchGestureConfig) -> Self {
+        TouchGestureRecognizer {
+            config,
+            points: Vec::new(),
+            state: State::Idle,
+            edge: None,
+            baseline: None,
+            last_center: Point::default(),
+            last_angle: None,
+        }
+    }
+
+    /// Access the thresholds of this recognizer
+    pub fn config(&self) -> &TouchGestureConfig {
+        &self.config
+    }
+
+    /// Change the thresholds of this recognizer
+    ///
+    /// The new thresholds apply to the next recognized gesture.
+    pub fn set_config(&mut self, config: TouchGestureConfig) {
+        self.config = config;
+    }
+
+    /// Number of fingers currently down
+    pub fn fingers(&self) -> u32 {
+        self.points.len() as u32
+    }
+
+    /// Returns if a gesture is currently in progress
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, State::Swipe | State::Pinch { .. } | State::Hold)
+    }
+
+    /// Time at which a hold gesture would begin, if the fingers stay in place
+    pub fn hold_deadline(&self) -> Option<u32> {
+        if self.state != State::Pending || self.fingers() < self.config.min_fingers {
+            return None;
+        }
+        self.baseline
+            .map(|baseline| baseline.time.wrapping_add(self.config.hold_duration))
+    }
+
+    /// Notify the recognizer about a new touch point
+    pub fn down(&mut self, event: &DownEvent) -> Vec<TouchGestureEvent> {
+        let mut events = Vec::new();
+
+        if self.state == State::Idle {
+            self.edge = self.edge_at(event.location);
+            self.state = State::Pending;
+        } else if self.is_active() {
+            events.extend(self.end(event.time, true));
+        }
+
+        self.points.retain(|(slot, _)| *slot != event.slot);
+        self.points.push((event.slot, event.location));
+        if self.state == State::Pending {
+            self.reset_baseline(event.time);
+        }
+
+        events
+    }
+
+    /// Notify the recognizer about a touch point being lifted
+    pub fn up(&mut self, event: &UpEvent) -> Vec<TouchGestureEvent> {
+        let mut events = Vec::new();
+
+        if !self.points.iter().any(|(slot, _)| *slot == event.slot) {
+            return events;
+        }
+        self.points.retain(|(slot, _)| *slot != event.slot);
+
+        if self.is_active() {
+            events.extend(self.end(event.time, false));
+        } else if self.state == State::Pending {
+            self.state = State::Finished;
+        }
+
+        if self.points.is_empty() {
+            self.reset();
+        }
+
+        events
+    }
+
+    /// Notify the recognizer about a touch point moving
+    pub fn motion(&mut self, event: &MotionEvent) -> Vec<TouchGestureEvent> {
+        let mut events = Vec::new();
+
+        let Some(point) = self.points.iter_mut().find(|(slot, _)| *slot == event.slot) else {
+            return events;
+        };
+        point.1 = event.location;
+
+        let Some(baseline) = self.baseline else {
+            return events;
+        };
+        let center = self.center();
+        let angle = self.angle();
+
+        if self.state == State::Pending {
+            events.extend(self.timeout(event.time));
+        }
+
+        if self.state == State::Hold {
+            let offset = center - baseline.center;
+            if offset.x.hypot(offset.y) < self.config.swipe_distance && !self.is_pinch(&baseline) {
+                return events;
+            }
+            events.push(TouchGestureEvent::HoldEnd {
+                time: event.time,
+                cancelled: true,
+            });
+            self.state = State::Pending;
+        }
+
+        match self.state {
+            State::Pending => {
+                let fingers = self.fingers();
+                let edge_swipe = fingers == 1 && self.edge.is_some();
+                if fingers < self.config.min_fingers && !edge_swipe {
+                    return events;
+                }
+
+                if fingers >= 2 && self.is_pinch(&baseline) {
+                    self.state = State::Pinch {
+                        initial_spread: baseline.spread,
+                    };
+                    self.last_center = baseline.center;
+                    self.last_angle = baseline.angle;
+                    events.push(TouchGestureEvent::PinchBegin {
+                        time: event.time,
+                        fingers,
+                    });
+                } else {
+                    let offset = center - baseline.center;
+                    if offset.x.hypot(offset.y) < self.config.swipe_distance {
+                        return events;
+                    }
+                    self.state = State::Swipe;
+                    self.last_center = baseline.center;
+                    events.push(TouchGestureEvent::SwipeBegin {
+                        time: event.time,
+                        fingers,
+                        edge: self.edge,
+                    });
+                }
+            }
+            State::Swipe | State::Pinch { .. } => {}
+            _ => return events,
+        }
+
+        let delta = center - self.last_center;
+        self.last_center = center;
+        match self.state {
+            State::Swipe => events.push(TouchGestureEvent::SwipeUpdate {
+                time: event.time,
+                delta,
+            }),
+            State::Pinch { initial_spread } => {
+                let rotation = match (self.last_angle, angle) {
+                    (Some(last), Some(angle)) => normalize_angle(angle - last),
+                    _ => 0.0,
+                };
+                self.last_angle = angle;
+                let scale = if initial_spread > 0.0 {
+                    self.spread(center) / initial_spread
+                } else {
+                    1.0
+                };
+                events.push(TouchGestureEvent::PinchUpdate {
+                    time: event.time,
+                    delta,
+                    scale,
+                    rotation,
+                });
+            }
+            _ => unreachable!(),
+        }
+
+        events
+    }
+
+    /// Notify the recognizer that the touch sequence was cancelled
+    pub fn cancel(&mut self, time: u32) -> Vec<TouchGestureEvent> {
+        let events = self.end(time, true).into_iter().collect();
+        self.points.clear();
+        self.reset();
+        events
+    }
+
+    /// Check if a hold gesture should begin at the given time
+    ///
+    /// See [`TouchGestureRecognizer::hold_deadline`].
+    pub fn timeout(&mut self, time: u32) -> Option<TouchGestureEvent> {
+        let deadline = self.hold_deadline()?;
+        // compare using wrapping arithmetic, the timestamps may overflow
+        if time.wrapping_sub(deadline) > u32::MAX / 2 {
+            return None;
+        }
+        self.state = State::Hold;
+        Some(TouchGestureEvent::HoldBegin {
+            time,
+            fingers: self.fingers(),
+        })
+    }
+
+    fn end(&mut self, time: u32, cancelled: bool) -> Option<TouchGestureEvent> {
+        let event = match self.state {
+            State::Swipe => TouchGestureEvent::SwipeEnd { time, cancelled },
+            State::Pinch { .. } => TouchGestureEvent::PinchEnd { time, cancelled },
+            State::Hold => TouchGestureEvent::HoldEnd { time, cancelled },
+            _ => return None,
+        };
+        self.state = State::Finished;
+        Some(event)
+    }
+
+    fn reset(&mut self) {
+        self.state = State::Idle;
+        self.edge = None;
+        self.baseline = None;
+        self.last_angle = None;
+    }
+
+    fn reset_baseline(&mut self, time: u32) {
+        let center = self.center();
+        self.baseline = Some(Baseline {
+            time,
+            center,
+            spread: self.spread(center),
+            angle: self.angle(),
+        });
+    }
+
+    fn is_pinch(&self, baseline: &Baseline) -> bool {
+        if self.points.len() < 2 {
+            return false;
+        }
+        let center = self.center();
+        let scaled = baseline.spread > 0.0
+            && (self.spread(center) / baseline.spread - 1.0).abs() >= self.config.pinch_scale;
+        let rotated = match (baseline.angle, self.angle()) {
+            (Some(initial), Some(angle)) => {
+                normalize_angle(angle - initial).abs() >= self.config.pinch_rotation
+            }
+            _ => false,
+        };
+        scaled || rotated
+    }
+
+    fn edge_at(&self, location: Point<f64, Logical>) -> Option<TouchEdge> {
+        let area = self.config.edge_area?;
+        let size = self.config.edge_size;
+        if !area.contains(location) {
+            return None;
+        }
+
+        let distances = [
+            (TouchEdge::Top, location.y - area.loc.y),
+            (TouchEdge::Bottom, area.loc.y + area.size.h - location.y),
+            (TouchEdge::Left, location.x - area.loc.x),
+            (TouchEdge::Right, area.loc.x + area.size.w - location.x),
+        ];
+        distances
+            .into_iter()
+            .filter(|(_, distance)| *distance < size)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(edge, _)| edge)
+    }
+
+    fn center(&self) -> Point<f64, Logical> {
+        let count = self.points.len().max(1) as f64;
+        let sum = self
+            .points
+            .iter()
+            .fold(Point::<f64, Logical>::default(), |sum, (_, point)| sum + *point);
+        (sum.x / count, sum.y / count).into()
+    }
+
+    /// Average distance of the touch points from their center
+    fn spread(&self, center: Point<f64, Logical>) -> f64 {
+        let count = self.points.len().max(1) as f64;
+        self.points
+            .iter()
+            .map(|(_, point)| {
+                let offset = *point - center;
+                offset.x.hypot(offset.y)
+            })
+            .sum::<f64>()
+            / count
+    }
+
+    /// Angle in degrees of the line between the first two touch points
+    fn angle(&self) -> Option<f64> {
+        let [(_, first), (_, second), ..] = self.points[..] else {
+            return None;
+        };
+        let offset = second - first;
+        Some(offset.y.atan2(offset.x).to_degrees())
+    }
+}
+
+fn normalize_angle(angle: f64) -> f64 {
+    let angle = angle.rem_euclid(360.0);
+    if angle > 180.0 { angle - 360.0 } else { angle }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{Serial, Size};
+
+    fn down(slot: u32, x: f64, y: f64, time: u32) -> DownEvent {
+        DownEvent {
+            slot: Some(slot).into(),
+            location: (x, y).into(),
+            serial: Serial::from(0),
+            time,
+        }
+    }
+
+    fn motion(slot: u32, x: f64, y: f64, time: u32) -> MotionEvent {
+        MotionEvent {
+            slot: Some(slot).into(),
+            location: (x, y).into(),
+            time,
+        }
+    }
+
+    fn up(slot: u32, time: u32) -> UpEvent {
+        UpEvent {
+            slot: Some(slot).into(),
+            serial: Serial::from(0),
+            time,
+        }
+    }
+
+    #[test]
+    fn three_finger_swipe() {
+        let mut recognizer = TouchGestureRecognizer::new(TouchGestureConfig::default());
+        for slot in 0..3 {
+            assert!(
+                recognizer
+                    .down(&down(slot, 100.0 + slot as f64 * 50.0, 100.0, 0))
+                    .is_empty()
+            );
+        }
+        assert!(recognizer.motion(&motion(0, 100.0, 110.0, 10)).is_empty());
+        recognizer.motion(&motion(1, 150.0, 120.0, 20));
+        let events = recognizer.motion(&motion(2, 200.0, 130.0, 30));
+        assert!(matches!(
+            events[..],
+            [
+                TouchGestureEvent::SwipeBegin {
+                    fingers: 3,
+                    edge: None,
+                    ..
+                },
+                TouchGestureEvent::SwipeUpdate { .. }
+            ]
+        ));
+
+        assert!(recognizer.is_active());
+        assert_eq!(
+            recognizer.up(&up(0, 40)),
+            vec![TouchGestureEvent::SwipeEnd {
+                time: 40,
+                cancelled: false
+            }]
+        );
+        assert!(!recognizer.is_active());
+        recognizer.up(&up(1, 40));
+        recognizer.up(&up(2, 40));
+        assert_eq!(recognizer.fingers(), 0);
+    }
+
+    #[test]
+    fn pinch_and_hold() {
+        let config = TouchGestureConfig {
+            min_fingers: 2,
+            ..Default::default()
+        };
+        let mut recognizer = TouchGestureRecognizer::new(config);
+        recognizer.down(&down(0, 100.0, 100.0, 0));
+        recognizer.down(&down(1, 200.0, 100.0, 0));
+        assert_eq!(recognizer.hold_deadline(), Some(500));
+        assert_eq!(recognizer.timeout(100), None);
+        assert!(matches!(
+            recognizer.timeout(500),
+            Some(TouchGestureEvent::HoldBegin { fingers: 2, .. })
+        ));
+
+        let events = recognizer.motion(&motion(1, 300.0, 100.0, 600));
+        let [
+            TouchGestureEvent::HoldEnd { cancelled: true, .. },
+            TouchGestureEvent::PinchBegin { fingers: 2, .. },
+            TouchGestureEvent::PinchUpdate { scale, rotation, .. },
+        ] = events[..]
+        else {
+            panic!("unexpected events: {events:?}");
+        };
+        assert!((scale - 2.0).abs() < f64::EPSILON);
+        assert!(rotation.abs() < f64::EPSILON);
+
+        assert_eq!(
+            recognizer.cancel(700),
+            vec![TouchGestureEvent::PinchEnd {
+                time: 700,
+                cancelled: true
+            }]
+        );
+    }
+
+    #[test]
+    fn edge_swipe() {
+        let config = TouchGestureConfig {
+            edge_area: Some(Rectangle::new((0.0, 0.0).into(), Size::from((800.0, 600.0)))),
+            ..Default::default()
+        };
+        let mut recognizer = TouchGestureRecognizer::new(config);
+        recognizer.down(&down(0, 400.0, 595.0, 0));
+        let events = recognizer.motion(&motion(0, 400.0, 550.0, 10));
+        assert!(matches!(
+            events[..],
+            [
+                TouchGestureEvent::SwipeBegin {
+                    fingers: 1,
+                    edge: Some(TouchEdge::Bottom),
+                    ..
+                },
+                TouchGestureEvent::SwipeUpdate { .. }
+            ]
+        ));
+
+        // single finger away from the edges doesn't produce a gesture
+        recognizer.up(&up(0, 20));
+        recognizer.down(&down(0, 400.0, 300.0, 30));
+        assert!(recognizer.motion(&motion(0, 400.0, 200.0, 40)).is_empty());
+    }
+}
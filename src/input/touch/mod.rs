@@ -11,10 +11,14 @@ use wayland_server::Weak;
 use crate::backend::input::TouchSlot;
 use crate::utils::{IsAlive, Logical, Point, Serial};
 
+pub use emulation::{PointerEmulationConfig, PointerEmulationGrab};
+pub use gesture::{TouchEdge, TouchGestureConfig, TouchGestureEvent, TouchGestureRecognizer};
 pub use grab::{DefaultGrab, GrabStartData, TouchDownGrab, TouchGrab};
 
 use super::{GrabStatus, Seat, SeatHandler};
 
+mod emulation;
+mod gesture;
 mod grab;
 
 crate::utils::ids::id_gen!(frame_marker);
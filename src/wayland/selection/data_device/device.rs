@@ -77,72 +77,36 @@ where
                 }
 
                 let serial = Serial::from(serial);
-                if let Some(pointer) = seat.get_pointer() {
-                    if pointer.has_grab(serial) {
-                        if let Some(ref icon) = icon {
-                            if compositor::give_role(icon, DND_ICON_ROLE).is_err() {
-                                resource.post_error(
-                                    wl_data_device::Error::Role,
-                                    "Given surface already has an other role",
-                                );
-                                return;
-                            }
-                        }
-                        // The StartDrag is in response to a pointer implicit grab, all is good
-                        if let Some(source) = source {
-                            handler.dnd_requested(
-                                source,
-                                icon.clone(),
-                                seat.clone(),
-                                serial,
-                                GrabType::Pointer,
-                            );
-                        } else {
-                            handler.dnd_requested(
-                                origin,
-                                icon.clone(),
-                                seat.clone(),
-                                serial,
-                                GrabType::Pointer,
-                            );
-                        }
+                let type_ = if seat.get_pointer().is_some_and(|pointer| pointer.has_grab(serial)) {
+                    GrabType::Pointer
+                } else if seat.get_touch().is_some_and(|touch| touch.has_grab(serial)) {
+                    GrabType::Touch
+                } else if seat.has_tablet_tool_grab(serial) {
+                    GrabType::TabletTool
+                } else {
+                    debug!(
+                        serial = ?serial,
+                        client = ?client,
+                        "denying drag from client without implicit grab"
+                    );
+                    return;
+                };
 
+                if let Some(ref icon) = icon {
+                    if compositor::give_role(icon, DND_ICON_ROLE).is_err() {
+                        resource.post_error(
+                            wl_data_device::Error::Role,
+                            "Given surface already has an other role",
+                        );
                         return;
                     }
                 }
-                if let Some(touch) = seat.get_touch() {
-                    if touch.has_grab(serial) {
-                        if let Some(ref icon) = icon {
-                            if compositor::give_role(icon, DND_ICON_ROLE).is_err() {
-                                resource.post_error(
-                                    wl_data_device::Error::Role,
-                                    "Given surface already has an other role",
-                                );
-                                return;
-                            }
-                        }
-                        // The StartDrag is in response to a touch implicit grab, all is good
-                        if let Some(source) = source {
-                            handler.dnd_requested(
-                                source,
-                                icon.clone(),
-                                seat.clone(),
-                                serial,
-                                GrabType::Touch,
-                            );
-                        } else {
-                            handler.dnd_requested(
-                                origin,
-                                icon.clone(),
-                                seat.clone(),
-                                serial,
-                                GrabType::Touch,
-                            );
-                        }
-                        return;
-                    }
+                // The StartDrag is in response to an implicit grab, all is good
+                if let Some(source) = source {
+                    handler.dnd_requested(source, icon, seat.clone(), serial, type_);
+                } else {
+                    handler.dnd_requested(origin, icon, seat.clone(), serial, type_);
                 }
-                debug!(serial = ?serial, client = ?client, "denying drag from client without implicit grab");
             }
             wl_data_device::Request::SetSelection { source, .. } => {
                 let seat_data = match seat.get_keyboard() {
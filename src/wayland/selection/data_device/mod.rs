@@ -138,7 +138,7 @@ pub trait WaylandDndGrabHandler: SeatHandler + Sized {
     ///   during the drag'n'drop.
     /// * `seat` - The seat on which the DnD operation was started
     /// * `serial` - Serial passed and validated for the grab
-    /// * `type_` - If the grab was validated for pointer or touch input
+    /// * `type_` - If the grab was validated for pointer, touch or tablet tool input
     fn dnd_requested<S: Source>(
         &mut self,
         source: S,
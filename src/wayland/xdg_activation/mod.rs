@@ -206,6 +206,12 @@ impl XdgActivationState {
         self.known_tokens.get_key_value(&token).unwrap()
     }
 
+    // Tracks a token not generated by us, e.g. a startup id announced by an X11 client
+    #[cfg(feature = "xwayland")]
+    pub(crate) fn insert_token(&mut self, token: XdgActivationToken, data: XdgActivationTokenData) {
+        self.known_tokens.entry(token).or_insert(data);
+    }
+
     /// Iterate over all known tokens and their associated data
     pub fn tokens(&self) -> impl Iterator<Item = (&XdgActivationToken, &XdgActivationTokenData)> {
         self.known_tokens.iter()
@@ -0,0 +1,414 @@
+use std::{
+    ffi::{OsStr, OsString},
+    fmt,
+    os::unix::{
+        io::{AsRawFd, RawFd},
+        net::UnixStream,
+    },
+    process::Stdio,
+    sync::Arc,
+    time::Duration,
+};
+
+use calloop::{
+    generic::{FdWrapper, Generic},
+    ping::{Ping, PingSource, make_ping},
+    timer::{TimeoutAction, Timer},
+};
+use tracing::{info, warn};
+use wayland_server::{Client, DisplayHandle};
+
+use crate::utils::user_data::UserDataMap;
+
+use super::{
+    XWayland, XWaylandEvent,
+    x11_sockets::{X11Lock, prepare_x11_sockets},
+    xserver::{Mode, Terminate},
+};
+
+// Delay after which a failed spawn is retried, if an X11 client is still waiting
+const SPAWN_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// An XWayland server, that is only started once the first X11 client connects
+///
+/// Smithay binds the X11 display sockets itself and watches them for incoming connections.
+/// Once the first X11 client tries to connect, Xwayland is spawned and the sockets are handed
+/// over to it. Optionally Xwayland is terminated again after no X11 clients were connected for
+/// a configurable idle period, after which the sockets are watched for new connections again.
+///
+/// The display number is thus available right away, so the `DISPLAY` environment variable can
+/// be set before Xwayland is ever started.
+///
+/// This struct is an event source, that needs to be inserted into your event loop. It will
+/// generate a [`LazyXWaylandEvent::Ready`] event every time Xwayland was spawned and is ready,
+/// at which point the X11 window manager should be started. Once Xwayland terminates, a
+/// [`LazyXWaylandEvent::Exited`] event is generated and the window manager should be dropped.
+///
+/// Terminating Xwayland after an idle period requires Xwayland 23.1 or newer.
+pub struct LazyXWayland {
+    dh: DisplayHandle,
+    display_lock: Arc<X11Lock>,
+    sockets: Vec<UnixStream>,
+    listeners: Vec<Generic<FdWrapper<RawFd>>>,
+    exited: PingSource,
+    exited_ping: Ping,
+    instance: Option<(XWayland, Client)>,
+    instance_registered: bool,
+    instance_done: bool,
+    // exited instance, that still needs to be unregistered
+    exited_instance: Option<(XWayland, Client)>,
+    spawn_retry: Option<Timer>,
+    envs: Vec<(OsString, OsString)>,
+    extra_args: Vec<OsString>,
+    idle_timeout: Option<Duration>,
+    stdout: Box<dyn FnMut() -> Stdio>,
+    stderr: Box<dyn FnMut() -> Stdio>,
+    user_data: Box<dyn FnMut(&UserDataMap)>,
+}
+
+impl fmt::Debug for LazyXWayland {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyXWayland")
+            .field("dh", &self.dh)
+            .field("display_lock", &self.display_lock)
+            .field("sockets", &self.sockets)
+            .field("listeners", &self.listeners)
+            .field("instance", &self.instance)
+            .field("envs", &self.envs)
+            .field("extra_args", &self.extra_args)
+            .field("idle_timeout", &self.idle_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Events generated by a [`LazyXWayland`] instance.
+#[derive(Debug)]
+pub enum LazyXWaylandEvent {
+    /// An X11 client connected, XWayland was started and is ready
+    Ready {
+        /// A privileged X11 connection to XWayland.
+        x11_socket: UnixStream,
+
+        /// The display number the XWayland server is using.
+        display_number: u32,
+
+        /// The [Client](wayland_server::Client) representing the XWayland server.
+        client: Client,
+    },
+
+    /// The XWayland server exited unexpectedly during startup or could not be spawned.
+    ///
+    /// Spawning is retried periodically as long as the X11 client is still waiting.
+    Error,
+
+    /// The XWayland server terminated
+    ///
+    /// Either because it was idle for the configured period or because it crashed. The X11 window
+    /// manager should be dropped. XWayland will be started again once the next X11 client connects.
+    Exited,
+}
+
+impl LazyXWayland {
+    /// Binds the X11 display sockets, without spawning XWayland yet.
+    ///
+    /// ## Arguments
+    ///
+    /// The arguments match those of [`XWayland::spawn`], except for:
+    ///
+    /// - `idle_timeout` - if provided, XWayland terminates once no X11 clients were connected
+    ///   for the given duration. It has a granularity of seconds.
+    /// - `stdout, stderr` - are called every time XWayland is spawned to redirect its output.
+    /// - `user_data` - is called every time XWayland is spawned, with the user data map of the
+    ///   new client.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<K, V, I, A, AI, O, E, F>(
+        dh: &DisplayHandle,
+        display: impl Into<Option<u32>>,
+        envs: I,
+        extra_args: AI,
+        open_abstract_socket: bool,
+        idle_timeout: Option<Duration>,
+        stdout: O,
+        stderr: E,
+        user_data: F,
+    ) -> std::io::Result<Self>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+        AI: IntoIterator<Item = A>,
+        A: AsRef<OsStr>,
+        O: FnMut() -> Stdio + 'static,
+        E: FnMut() -> Stdio + 'static,
+        F: FnMut(&UserDataMap) + 'static,
+    {
+        let (lock, sockets) = prepare_x11_sockets(display.into(), open_abstract_socket)?;
+        let listeners = sockets
+            .iter()
+            .map(|socket| {
+                // SAFETY: the sockets are owned by us and outlive the sources.
+                let wrapper = unsafe { FdWrapper::new(socket.as_raw_fd()) };
+                // We never accept connections ourselves, so only notify about new ones
+                Generic::new(wrapper, calloop::Interest::READ, calloop::Mode::Edge)
+            })
+            .collect();
+        let (exited_ping, exited) = make_ping()?;
+
+        Ok(LazyXWayland {
+            dh: dh.clone(),
+            display_lock: Arc::new(lock),
+            sockets,
+            listeners,
+            exited,
+            exited_ping,
+            instance: None,
+            instance_registered: false,
+            instance_done: false,
+            exited_instance: None,
+            spawn_retry: None,
+            envs: envs
+                .into_iter()
+                .map(|(k, v)| (k.as_ref().to_owned(), v.as_ref().to_owned()))
+                .collect(),
+            extra_args: extra_args.into_iter().map(|a| a.as_ref().to_owned()).collect(),
+            idle_timeout,
+            stdout: Box::new(stdout),
+            stderr: Box::new(stderr),
+            user_data: Box::new(user_data),
+        })
+    }
+
+    /// Returns the X11 display used by the instance, suitable for setting the
+    /// `DISPLAY` environment variable.
+    pub fn display_number(&self) -> u32 {
+        self.display_lock.display_number()
+    }
+
+    /// Returns the [Client](wayland_server::Client) representing the XWayland server, if it is
+    /// currently running.
+    pub fn client(&self) -> Option<&Client> {
+        self.instance.as_ref().map(|(_, client)| client)
+    }
+
+    /// Returns `true` if XWayland is currently running
+    pub fn is_running(&self) -> bool {
+        self.instance.is_some()
+    }
+
+    fn spawn(&mut self) -> std::io::Result<()> {
+        info!(
+            display = self.display_number(),
+            "X11 client connected, spawning XWayland"
+        );
+
+        let data_map = UserDataMap::new();
+        (self.user_data)(&data_map);
+
+        let terminate = match self.idle_timeout {
+            Some(timeout) => Terminate::After(timeout),
+            None => Terminate::Never,
+        };
+        let instance = XWayland::spawn_with_sockets(
+            &self.dh,
+            self.display_lock.clone(),
+            &self.sockets,
+            self.envs.iter().map(|(k, v)| (k, v)),
+            &self.extra_args,
+            Mode::Rootless,
+            terminate,
+            (self.stdout)(),
+            (self.stderr)(),
+            data_map,
+            Some(self.exited_ping.clone()),
+        )?;
+        self.instance = Some(instance);
+        self.instance_registered = false;
+        self.instance_done = false;
+
+        Ok(())
+    }
+
+    // Checks for connections that came in while XWayland was shutting down
+    fn has_pending_connection(&self) -> bool {
+        self.sockets.iter().any(|socket| {
+            matches!(
+                rustix::event::poll(
+                    &mut [rustix::event::PollFd::new(socket, rustix::event::PollFlags::IN)],
+                    Some(&rustix::time::Timespec {
+                        tv_sec: 0,
+                        tv_nsec: 0
+                    })
+                ),
+                Ok(1)
+            )
+        })
+    }
+}
+
+impl calloop::EventSource for LazyXWayland {
+    type Event = LazyXWaylandEvent;
+    type Metadata = ();
+    type Ret = ();
+    type Error = std::io::Error;
+
+    #[profiling::function]
+    fn process_events<F>(
+        &mut self,
+        readiness: calloop::Readiness,
+        token: calloop::Token,
+        mut callback: F,
+    ) -> std::io::Result<calloop::PostAction>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata) -> Self::Ret,
+    {
+        let mut connection = false;
+        for listener in &mut self.listeners {
+            listener.process_events(readiness, token, |_, _| {
+                connection = true;
+                Ok(calloop::PostAction::Continue)
+            })?;
+        }
+
+        let mut exited = false;
+        self.exited
+            .process_events(readiness, token, |_, _| exited = true)
+            .map_err(std::io::Error::other)?;
+        let mut reregister = false;
+        if let Some(instance) = self.instance.take_if(|_| exited) {
+            if self.instance_registered {
+                // unregister the instance before dropping it
+                self.exited_instance = Some(instance);
+                self.instance_registered = false;
+                reregister = true;
+            }
+            info!(display = self.display_number(), "XWayland terminated");
+            callback(LazyXWaylandEvent::Exited, &mut ());
+            connection |= self.has_pending_connection();
+        }
+
+        if let Some(retry) = self.spawn_retry.as_mut() {
+            let mut retry_due = false;
+            retry.process_events(readiness, token, |_, _| {
+                retry_due = true;
+                TimeoutAction::Drop
+            })?;
+            if retry_due {
+                self.spawn_retry = None;
+                connection |= self.has_pending_connection();
+            }
+        }
+
+        if let Some((instance, client)) = self.instance.as_mut() {
+            // connections are handled by the running XWayland
+            let action = instance.process_events(readiness, token, |event, _| match event {
+                XWaylandEvent::Ready {
+                    x11_socket,
+                    display_number,
+                } => callback(
+                    LazyXWaylandEvent::Ready {
+                        x11_socket,
+                        display_number,
+                        client: client.clone(),
+                    },
+                    &mut (),
+                ),
+                XWaylandEvent::Error => callback(LazyXWaylandEvent::Error, &mut ()),
+            })?;
+            if action == calloop::PostAction::Disable {
+                // the startup is done, stop polling the displayfd
+                self.instance_done = true;
+                return Ok(calloop::PostAction::Reregister);
+            }
+            return Ok(calloop::PostAction::Continue);
+        }
+
+        if connection {
+            if let Err(err) = self.spawn() {
+                warn!(?err, "Failed to spawn XWayland");
+                callback(LazyXWaylandEvent::Error, &mut ());
+                // the listeners are edge-triggered, so the waiting client would not be noticed again
+                self.spawn_retry = Some(Timer::from_duration(SPAWN_RETRY_DELAY));
+            }
+            // register the new instance or the retry timer
+            return Ok(calloop::PostAction::Reregister);
+        }
+
+        if reregister {
+            return Ok(calloop::PostAction::Reregister);
+        }
+        Ok(calloop::PostAction::Continue)
+    }
+
+    fn register(
+        &mut self,
+        poll: &mut calloop::Poll,
+        factory: &mut calloop::TokenFactory,
+    ) -> calloop::Result<()> {
+        for listener in &mut self.listeners {
+            listener.register(poll, factory)?;
+        }
+        self.exited.register(poll, factory)?;
+        if let Some(retry) = self.spawn_retry.as_mut() {
+            retry.register(poll, factory)?;
+        }
+        if let Some((instance, _)) = self.instance.as_mut() {
+            if !self.instance_done {
+                instance.register(poll, factory)?;
+                self.instance_registered = true;
+            }
+        }
+        Ok(())
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &mut calloop::Poll,
+        factory: &mut calloop::TokenFactory,
+    ) -> calloop::Result<()> {
+        for listener in &mut self.listeners {
+            listener.reregister(poll, factory)?;
+        }
+        self.exited.reregister(poll, factory)?;
+        if let Some(retry) = self.spawn_retry.as_mut() {
+            retry.reregister(poll, factory)?;
+        }
+        if let Some((mut instance, _)) = self.exited_instance.take() {
+            instance.unregister(poll)?;
+        }
+        if let Some((instance, _)) = self.instance.as_mut() {
+            match (self.instance_registered, self.instance_done) {
+                (true, true) => {
+                    instance.unregister(poll)?;
+                    self.instance_registered = false;
+                }
+                (true, false) => instance.reregister(poll, factory)?,
+                (false, false) => {
+                    instance.register(poll, factory)?;
+                    self.instance_registered = true;
+                }
+                (false, true) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn unregister(&mut self, poll: &mut calloop::Poll) -> calloop::Result<()> {
+        for listener in &mut self.listeners {
+            listener.unregister(poll)?;
+        }
+        self.exited.unregister(poll)?;
+        if let Some(retry) = self.spawn_retry.as_mut() {
+            retry.unregister(poll)?;
+        }
+        if let Some((mut instance, _)) = self.exited_instance.take() {
+            instance.unregister(poll)?;
+        }
+        if let Some((instance, _)) = self.instance.as_mut() {
+            if self.instance_registered {
+                instance.unregister(poll)?;
+                self.instance_registered = false;
+            }
+        }
+        Ok(())
+    }
+}
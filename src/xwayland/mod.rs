@@ -13,9 +13,13 @@
 //! special client, and play the role of an X11 Window Manager.
 //!
 //! Smithay does not provide any helper for doing that yet, but it is planned.
+mod lazy;
+mod rootful;
 mod x11_sockets;
 mod xserver;
 pub mod xwm;
 
+pub use self::lazy::{LazyXWayland, LazyXWaylandEvent};
+pub use self::rootful::RootfulWindow;
 pub use self::xserver::{XWayland, XWaylandClientData, XWaylandEvent};
 pub use self::xwm::{X11Surface, X11Wm, XwmHandler};
@@ -0,0 +1,153 @@
+use std::borrow::Cow;
+
+use wayland_protocols::xdg::shell::server::xdg_toplevel;
+use wayland_server::{Resource, protocol::wl_surface::WlSurface};
+
+use crate::{
+    backend::input::KeyState,
+    input::{
+        Seat, SeatHandler,
+        keyboard::{KeyboardTarget, KeysymHandle, ModifiersState},
+    },
+    utils::{IsAlive, Logical, Serial, Size},
+    wayland::{seat::WaylandFocus, shell::xdg::ToplevelSurface},
+};
+
+use super::XWaylandClientData;
+
+/// The root window of a rootful XWayland instance
+///
+/// A rootful XWayland instance (see [`XWayland::spawn_rootful`](super::XWayland::spawn_rootful))
+/// presents its whole X11 display as a single xdg toplevel. It arrives through the regular
+/// [`XdgShellHandler::new_toplevel`](crate::wayland::shell::xdg::XdgShellHandler::new_toplevel)
+/// callback and can be identified using [`RootfulWindow::from_toplevel`]. It can be mapped like any
+/// other toplevel, e.g. as a `desktop::Window`, while this type offers helpers to
+/// manage it as a single X11 desktop.
+///
+/// Pointer grabs of X11 clients are implemented by XWayland using the pointer constraints
+/// protocol, see [`pointer_constraints`](crate::wayland::pointer_constraints), keyboard grabs using
+/// the [`xwayland_keyboard_grab`](crate::wayland::xwayland_keyboard_grab) protocol. As there is no
+/// [`X11Wm`](super::X11Wm) for a rootful instance, `keyboard_focus_for_xsurface` should resolve
+/// every surface recognized by [`RootfulWindow::is_rootful_surface`] to the rootful window itself.
+///
+/// `RootfulWindow` implements [`KeyboardTarget`], so it can be used as (or wrapped by) the keyboard
+/// focus of the compositor. Keyboard events are forwarded to its surface and the window is activated
+/// while it holds the keyboard focus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RootfulWindow {
+    toplevel: ToplevelSurface,
+}
+
+impl RootfulWindow {
+    /// Returns the rootful window, if the given toplevel was created by a rootful XWayland instance
+    pub fn from_toplevel(toplevel: &ToplevelSurface) -> Option<Self> {
+        Self::is_rootful_surface(toplevel.wl_surface()).then(|| RootfulWindow {
+            toplevel: toplevel.clone(),
+        })
+    }
+
+    /// Checks if the given surface belongs to a rootful XWayland instance
+    pub fn is_rootful_surface(surface: &WlSurface) -> bool {
+        surface.client().is_some_and(|client| {
+            client
+                .get_data::<XWaylandClientData>()
+                .is_some_and(|data| data.is_rootful())
+        })
+    }
+
+    /// Access the underlying toplevel
+    pub fn toplevel(&self) -> &ToplevelSurface {
+        &self.toplevel
+    }
+
+    /// Access the underlying `wl_surface`
+    pub fn wl_surface(&self) -> &WlSurface {
+        self.toplevel.wl_surface()
+    }
+
+    /// Is the rootful window still alive?
+    pub fn alive(&self) -> bool {
+        self.toplevel.alive()
+    }
+
+    /// Resize the root window to the given size
+    ///
+    /// XWayland resizes its root window to match the size configured by the compositor, so this
+    /// should be called whenever the space available to the window changes.
+    ///
+    /// Returns the serial of the configure, if one was sent.
+    pub fn resize(&self, size: Size<i32, Logical>) -> Option<Serial> {
+        self.toplevel.with_pending_state(|state| {
+            state.size = Some(size);
+        });
+        self.toplevel
+            .is_initial_configure_sent()
+            .then(|| self.toplevel.send_configure())
+    }
+
+    /// Activate/Deactivate the rootful window
+    ///
+    /// This should follow the keyboard focus of the window. Returns `true` if the state changed.
+    pub fn set_activated(&self, active: bool) -> bool {
+        let changed = self.toplevel.with_pending_state(|state| {
+            if active {
+                state.states.set(xdg_toplevel::State::Activated)
+            } else {
+                state.states.unset(xdg_toplevel::State::Activated)
+            }
+        });
+        if changed && self.toplevel.is_initial_configure_sent() {
+            self.toplevel.send_configure();
+        }
+        changed
+    }
+
+    /// Ask XWayland to close the root window
+    ///
+    /// XWayland terminates in response, ending the X11 session.
+    pub fn close(&self) {
+        self.toplevel.send_close();
+    }
+}
+
+impl IsAlive for RootfulWindow {
+    #[inline]
+    fn alive(&self) -> bool {
+        self.toplevel.alive()
+    }
+}
+
+impl WaylandFocus for RootfulWindow {
+    #[inline]
+    fn wl_surface(&self) -> Option<Cow<'_, WlSurface>> {
+        Some(Cow::Borrowed(self.toplevel.wl_surface()))
+    }
+}
+
+impl<D: SeatHandler + 'static> KeyboardTarget<D> for RootfulWindow {
+    fn enter(&self, seat: &Seat<D>, data: &mut D, keys: Vec<KeysymHandle<'_>>, serial: Serial) {
+        self.set_activated(true);
+        KeyboardTarget::enter(self.toplevel.wl_surface(), seat, data, keys, serial);
+    }
+
+    fn leave(&self, seat: &Seat<D>, data: &mut D, serial: Serial) {
+        self.set_activated(false);
+        KeyboardTarget::leave(self.toplevel.wl_surface(), seat, data, serial);
+    }
+
+    fn key(
+        &self,
+        seat: &Seat<D>,
+        data: &mut D,
+        key: KeysymHandle<'_>,
+        state: KeyState,
+        serial: Serial,
+        time: u32,
+    ) {
+        KeyboardTarget::key(self.toplevel.wl_surface(), seat, data, key, state, serial, time);
+    }
+
+    fn modifiers(&self, seat: &Seat<D>, data: &mut D, modifiers: ModifiersState, serial: Serial) {
+        KeyboardTarget::modifiers(self.toplevel.wl_surface(), seat, data, modifiers, serial);
+    }
+}
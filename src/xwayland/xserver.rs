@@ -10,13 +10,19 @@ use std::{
     process::{Child, Command},
     sync::{Arc, Mutex},
     thread,
+    time::Duration,
 };
 
+use calloop::ping::Ping;
+
 use tracing::{error, info, trace};
 use wayland_server::backend::{ClientData, ClientId, DisconnectReason};
 use wayland_server::{Client, DisplayHandle};
 
-use crate::{utils::user_data::UserDataMap, wayland::compositor::CompositorClientState};
+use crate::{
+    utils::{Logical, Size, user_data::UserDataMap},
+    wayland::compositor::CompositorClientState,
+};
 
 use super::x11_sockets::{X11Lock, prepare_x11_sockets};
 
@@ -72,6 +78,10 @@ pub enum XWaylandEvent {
     /// The XWayland server is ready
     Ready {
         /// A privileged X11 connection to XWayland.
+        ///
+        /// A rootful instance (see [`XWayland::spawn_rootful`]) is not given a window manager
+        /// connection, as no [`X11Wm`](super::X11Wm) runs for it. Its socket is not connected
+        /// to XWayland and should be dropped.
         x11_socket: UnixStream,
 
         /// The display number the XWayland server is using.
@@ -129,11 +139,108 @@ impl XWayland {
         AI: IntoIterator<Item = A>,
         A: AsRef<OsStr>,
         F: FnOnce(&UserDataMap),
+    {
+        let (lock, listen_sockets) = prepare_x11_sockets(display.into(), open_abstract_socket)?;
+
+        let data_map = UserDataMap::new();
+        user_data(&data_map);
+
+        Self::spawn_with_sockets(
+            dh,
+            Arc::new(lock),
+            &listen_sockets,
+            envs,
+            extra_args,
+            Mode::Rootless,
+            Terminate::LastClient,
+            stdout,
+            stderr,
+            data_map,
+            None,
+        )
+    }
+
+    /// Spawns a rootful XWayland server instance.
+    ///
+    /// In rootful mode XWayland presents its whole root window as a single xdg toplevel, which
+    /// allows running a full X11 desktop session (e.g. a legacy X11 window manager or a remote
+    /// X session) inside the compositor. No [`X11Wm`](super::X11Wm) can be started for a
+    /// rootful instance, so the `x11_socket` of [`XWaylandEvent::Ready`] is not connected.
+    /// Instead its toplevel can be handled using [`RootfulWindow`](super::RootfulWindow).
+    ///
+    /// ## Arguments
+    ///
+    /// The arguments match those of [`XWayland::spawn`], except for:
+    ///
+    /// - `size` - The initial size of the root window. If `None` XWayland picks
+    ///   a size based on the outputs.
+    ///
+    /// Unlike a rootless instance, a rootful one keeps running after its last
+    /// X11 client disconnected.
+    #[allow(clippy::too_many_arguments)]
+    pub fn spawn_rootful<K, V, I, A, AI, F>(
+        dh: &DisplayHandle,
+        display: impl Into<Option<u32>>,
+        envs: I,
+        extra_args: AI,
+        open_abstract_socket: bool,
+        size: Option<Size<i32, Logical>>,
+        stdout: impl Into<std::process::Stdio>,
+        stderr: impl Into<std::process::Stdio>,
+        user_data: F,
+    ) -> std::io::Result<(Self, Client)>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+        AI: IntoIterator<Item = A>,
+        A: AsRef<OsStr>,
+        F: FnOnce(&UserDataMap),
+    {
+        let (lock, listen_sockets) = prepare_x11_sockets(display.into(), open_abstract_socket)?;
+
+        let data_map = UserDataMap::new();
+        user_data(&data_map);
+
+        Self::spawn_with_sockets(
+            dh,
+            Arc::new(lock),
+            &listen_sockets,
+            envs,
+            extra_args,
+            Mode::Rootful { size },
+            Terminate::Never,
+            stdout,
+            stderr,
+            data_map,
+            None,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn spawn_with_sockets<K, V, I, A, AI>(
+        dh: &DisplayHandle,
+        lock: Arc<X11Lock>,
+        listen_sockets: &[UnixStream],
+        envs: I,
+        extra_args: AI,
+        mode: Mode,
+        terminate: Terminate,
+        stdout: impl Into<std::process::Stdio>,
+        stderr: impl Into<std::process::Stdio>,
+        data_map: UserDataMap,
+        exited: Option<Ping>,
+    ) -> std::io::Result<(Self, Client)>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+        AI: IntoIterator<Item = A>,
+        A: AsRef<OsStr>,
     {
         let (x_wm_x11, x_wm_me) = UnixStream::pair()?;
         let (wl_x11, wl_me) = UnixStream::pair()?;
 
-        let (lock, listen_sockets) = prepare_x11_sockets(display.into(), open_abstract_socket)?;
         let display_number = lock.display_number();
 
         // XWayland writes the the display number and a newline to this pipe when it's ready.
@@ -147,17 +254,24 @@ impl XWayland {
             .stderr(stderr)
             .arg(format!(":{display_number}"))
             .arg("-verbose")
-            .arg("-rootless")
-            .arg("-terminate")
-            .arg("-wm")
-            .arg(x_wm_x11.as_raw_fd().to_string())
+            .args(mode.args(x_wm_x11.as_raw_fd()))
             .arg("-displayfd")
             .arg(displayfd_send.as_raw_fd().to_string());
 
-        for socket in &listen_sockets {
+        for socket in listen_sockets {
             command.arg("-listenfd").arg(socket.as_raw_fd().to_string());
         }
 
+        match terminate {
+            Terminate::Never => {}
+            Terminate::LastClient => {
+                command.arg("-terminate");
+            }
+            Terminate::After(delay) => {
+                command.arg("-terminate").arg(delay.as_secs().max(1).to_string());
+            }
+        }
+
         for arg in extra_args {
             command.arg(arg);
         }
@@ -176,7 +290,8 @@ impl XWayland {
 
         unsafe {
             let wayland_socket_fd = wl_x11.as_raw_fd();
-            let wm_socket_fd = x_wm_x11.as_raw_fd();
+            // only passed to xwayland in rootless mode
+            let wm_socket_fd = matches!(mode, Mode::Rootless).then(|| x_wm_x11.as_raw_fd());
             let pipe_fd = displayfd_send.as_raw_fd();
             let socket_fds: Vec<_> = listen_sockets.iter().map(|socket| socket.as_raw_fd()).collect();
 
@@ -184,7 +299,9 @@ impl XWayland {
                 // unset the CLOEXEC flag from the sockets we need to pass
                 // to xwayland.
                 unset_cloexec(wayland_socket_fd)?;
-                unset_cloexec(wm_socket_fd)?;
+                if let Some(wm_socket_fd) = wm_socket_fd {
+                    unset_cloexec(wm_socket_fd)?;
+                }
                 unset_cloexec(pipe_fd)?;
                 for &socket in socket_fds.iter() {
                     unset_cloexec(socket)?;
@@ -207,9 +324,6 @@ impl XWayland {
             x11_socket: Some(x_wm_me),
         };
 
-        let data_map = UserDataMap::new();
-        user_data(&data_map);
-
         // Insert the client into the display handle. The order is important
         // here; XWayland never starts up at all unless it can roundtrip with
         // wayland.
@@ -222,6 +336,8 @@ impl XWayland {
                 compositor_state: CompositorClientState::default(),
                 data_map,
                 child: Mutex::new(Some(child)),
+                exited,
+                rootful: matches!(mode, Mode::Rootful { .. }),
             }),
         )?;
 
@@ -269,9 +385,40 @@ impl XWayland {
     }
 }
 
+/// How Xwayland presents its X11 windows
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Mode {
+    /// Every X11 window is a separate surface, managed by the `X11Wm`
+    Rootless,
+    /// The root window is presented as a single toplevel
+    Rootful { size: Option<Size<i32, Logical>> },
+}
+
+impl Mode {
+    // Arguments selecting the mode, `wm_fd` is the privileged connection for the `X11Wm`
+    fn args(&self, wm_fd: RawFd) -> Vec<String> {
+        match self {
+            Mode::Rootless => vec!["-rootless".into(), "-wm".into(), wm_fd.to_string()],
+            Mode::Rootful { size: Some(size) } => vec!["-geometry".into(), format!("{}x{}", size.w, size.h)],
+            Mode::Rootful { size: None } => Vec::new(),
+        }
+    }
+}
+
+/// When Xwayland should terminate on its own
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Terminate {
+    /// Keep running until the wayland connection is closed
+    Never,
+    /// Terminate once the last X11 client disconnected
+    LastClient,
+    /// Terminate after no X11 clients were connected for the given duration
+    After(Duration),
+}
+
 #[derive(Debug)]
 struct Instance {
-    display_lock: X11Lock,
+    display_lock: Arc<X11Lock>,
     x11_socket: Option<UnixStream>,
     display_fd: OwnedFd,
 }
@@ -377,6 +524,8 @@ pub struct XWaylandClientData {
     pub compositor_state: CompositorClientState,
     data_map: UserDataMap,
     child: Mutex<Option<Child>>,
+    exited: Option<Ping>,
+    rootful: bool,
 }
 
 impl ClientData for XWaylandClientData {
@@ -385,6 +534,10 @@ impl ClientData for XWaylandClientData {
             error!("Xwayland disconnected: {}", err);
         }
 
+        if let Some(exited) = self.exited.as_ref() {
+            exited.ping();
+        }
+
         let mut child = self.child.lock().unwrap().take().unwrap();
         thread::spawn(move || {
             if let Ok(status) = child.wait() {
@@ -401,6 +554,13 @@ impl XWaylandClientData {
     pub fn user_data(&self) -> &UserDataMap {
         &self.data_map
     }
+
+    /// Returns `true` if this client is a rootful XWayland instance
+    ///
+    /// See [`XWayland::spawn_rootful`].
+    pub fn is_rootful(&self) -> bool {
+        self.rootful
+    }
 }
 
 /// Removes the `O_CLOEXEC` flag from a `RawFd`, causing it to leak when we
@@ -410,3 +570,20 @@ unsafe fn unset_cloexec(fd: RawFd) -> std::io::Result<()> {
     rustix::io::fcntl_setfd(fd, rustix::io::FdFlags::empty())?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mode_args() {
+        assert_eq!(Mode::Rootless.args(7), ["-rootless", "-wm", "7"]);
+
+        // a rootful instance has no window manager
+        let rootful = Mode::Rootful {
+            size: Some((1280, 720).into()),
+        };
+        assert_eq!(rootful.args(7), ["-geometry", "1280x720"]);
+        assert!(Mode::Rootful { size: None }.args(7).is_empty());
+    }
+}
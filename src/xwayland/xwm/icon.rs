@@ -0,0 +1,107 @@
+use crate::utils::{Buffer, Size};
+
+/// A single icon image of an X11 window
+///
+/// X11 clients may provide their icon in multiple sizes through the `_NET_WM_ICON` property,
+/// so the compositor can choose the one fitting best.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct X11Icon {
+    /// Size of the icon in pixels
+    pub size: Size<i32, Buffer>,
+    /// Pixel data of the icon
+    ///
+    /// The pixels are stored row by row without any padding in the `Argb8888` format
+    /// (see [`Fourcc::Argb8888`](crate::backend::allocator::Fourcc::Argb8888)), i.e. each pixel is a
+    /// little-endian `u32`. The alpha channel is not premultiplied.
+    pub data: Vec<u8>,
+}
+
+impl X11Icon {
+    /// Parses all icons contained in the data of a `_NET_WM_ICON` property
+    ///
+    /// Each icon is stored as its width and height followed by `width * height` pixels.
+    /// Parsing stops at the first icon with invalid dimensions.
+    pub(super) fn parse_all(mut data: &[u32]) -> Vec<X11Icon> {
+        let mut icons = Vec::new();
+        while let [width, height, rest @ ..] = data {
+            let Some(len) = (*width as usize).checked_mul(*height as usize) else {
+                break;
+            };
+            if len == 0 || len > rest.len() || *width > i32::MAX as u32 || *height > i32::MAX as u32 {
+                break;
+            }
+
+            let (pixels, rest) = rest.split_at(len);
+            icons.push(X11Icon {
+                size: (*width as i32, *height as i32).into(),
+                data: pixels.iter().flat_map(|pixel| pixel.to_le_bytes()).collect(),
+            });
+            data = rest;
+        }
+        icons
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_single() {
+        let icons = X11Icon::parse_all(&[2, 1, 0xff00_00ff, 0x8012_3456]);
+        assert_eq!(
+            icons,
+            vec![X11Icon {
+                size: (2, 1).into(),
+                data: vec![0xff, 0x00, 0x00, 0xff, 0x56, 0x34, 0x12, 0x80],
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_multiple_sizes() {
+        let mut data = vec![1, 1, 0xffff_ffff, 2, 2];
+        data.extend([0x1111_1111; 4]);
+        data.extend([3, 1, 1, 2, 3]);
+
+        let icons = X11Icon::parse_all(&data);
+        assert_eq!(
+            icons.iter().map(|icon| icon.size).collect::<Vec<_>>(),
+            vec![(1, 1).into(), (2, 2).into(), (3, 1).into()]
+        );
+        assert!(
+            icons
+                .iter()
+                .all(|icon| icon.data.len() == (icon.size.w * icon.size.h * 4) as usize)
+        );
+        assert_eq!(icons[1].data, vec![0x11; 16]);
+    }
+
+    #[test]
+    fn parse_truncated() {
+        // the second icon announces more pixels than there are
+        let icons = X11Icon::parse_all(&[1, 1, 0, 2, 2, 0, 0, 0]);
+        assert_eq!(icons.len(), 1);
+        assert_eq!(icons[0].size, (1, 1).into());
+
+        // a trailing width without height is ignored
+        assert_eq!(X11Icon::parse_all(&[1, 1, 0, 16]).len(), 1);
+        assert!(X11Icon::parse_all(&[]).is_empty());
+    }
+
+    #[test]
+    fn parse_zero_size() {
+        assert!(X11Icon::parse_all(&[0, 0]).is_empty());
+        assert!(X11Icon::parse_all(&[0, 16, 1, 1, 0]).is_empty());
+        // parsing stops at the invalid icon
+        assert_eq!(X11Icon::parse_all(&[1, 1, 0, 16, 0, 1, 1, 0]).len(), 1);
+    }
+
+    #[test]
+    fn parse_overflowing_size() {
+        assert!(X11Icon::parse_all(&[u32::MAX, u32::MAX, 0, 0]).is_empty());
+        assert!(X11Icon::parse_all(&[0x8000_0000, 1, 0, 0]).is_empty());
+        assert!(X11Icon::parse_all(&[1, 0x8000_0000, 0, 0]).is_empty());
+        assert_eq!(X11Icon::parse_all(&[1, 1, 0, u32::MAX, 2, 0, 0]).len(), 1);
+    }
+}
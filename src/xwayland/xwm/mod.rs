@@ -142,6 +142,7 @@ use crate::{
     utils::{Client, Logical, Point, Rectangle, Size, x11rb::X11Source},
     wayland::{
         selection::SelectionTarget,
+        xdg_activation::{XdgActivationState, XdgActivationToken, XdgActivationTokenData},
         xwayland_shell::{self, XWaylandShellHandler},
     },
 };
@@ -163,7 +164,7 @@ use std::{
     },
 };
 use tracing::{debug, debug_span, info, trace, warn};
-use wayland_server::{DisplayHandle, Resource};
+use wayland_server::{DisplayHandle, Resource, backend::ClientId};
 
 pub use x11rb::protocol::xproto::Window as X11Window;
 use x11rb::{
@@ -190,11 +191,18 @@ use x11rb::{
 };
 
 mod dnd;
+mod icon;
+pub use self::icon::*;
 mod mwm;
 pub use self::mwm::*;
 pub mod settings;
 use settings::{NameError, Value, XSettings};
 mod selection;
+mod startup;
+pub use self::startup::X11StartupToken;
+use self::startup::{MAX_STARTUP_TOKENS, STARTUP_TOKEN_TIMEOUT, StartupInfoBuffer, StartupMessageKind};
+mod strut;
+pub use self::strut::*;
 mod surface;
 use self::dnd::XWmDnd;
 pub use self::dnd::XwmOfferData;
@@ -248,7 +256,14 @@ mod atoms {
             _NET_WM_OPAQUE_REGION,
             _MOTIF_WM_HINTS,
             _NET_STARTUP_ID,
+            _NET_STARTUP_INFO_BEGIN,
+            _NET_STARTUP_INFO,
             _GTK_FRAME_EXTENTS,
+            _NET_WM_STRUT,
+            _NET_WM_STRUT_PARTIAL,
+            _NET_WM_ICON,
+            _XWAYLAND_RANDR_EMU_MONITOR_RECTS,
+            _NET_WM_BYPASS_COMPOSITOR,
 
             // server -> client
             WM_S0,
@@ -522,6 +537,48 @@ pub trait XwmHandler {
         let _ = (xwm, window, timestamp, currently_active_window);
     }
 
+    /// Returns the [`XdgActivationState`] used to resolve startup ids of X11 clients
+    ///
+    /// If provided, launches announced by X11 clients through `_NET_STARTUP_INFO` messages are
+    /// tracked as xdg activation tokens, so wayland clients launched by X11 clients can use them
+    /// to request activation. The tokens are marked with [`X11StartupToken`], as they were not
+    /// validated by the compositor, and are removed again once the launch is completed or expired.
+    ///
+    /// **Note:** Any X11 client can announce launches, so these tokens can't be trusted. Compositors
+    /// providing the state **must** check [`X11StartupToken::is_set`] in
+    /// [`XdgActivationHandler::request_activation`] and reject or down-rank such tokens, e.g. by not
+    /// moving the keyboard focus, otherwise X11 clients can hand out activation to wayland clients.
+    ///
+    /// Mapped windows and `_NET_ACTIVE_WINDOW` requests of windows with a `_NET_STARTUP_ID`
+    /// matching a token created by the compositor are reported through
+    /// [`XwmHandler::activation_request`].
+    ///
+    /// The default implementation returns `None`, disabling this behavior.
+    ///
+    /// [`XdgActivationHandler::request_activation`]: crate::wayland::xdg_activation::XdgActivationHandler::request_activation
+    fn xdg_activation_state(&mut self) -> Option<&mut XdgActivationState> {
+        None
+    }
+
+    /// Window requests activation using an xdg activation token of the compositor as its `_NET_STARTUP_ID`
+    ///
+    /// This is called after [`XwmHandler::map_window_request`] for newly mapped windows or instead of
+    /// [`XwmHandler::active_window_request`]. Compositors should apply the same policy as for
+    /// [`XdgActivationHandler::request_activation`].
+    ///
+    /// Like for wayland clients the token remains in the pool until the compositor removes it.
+    ///
+    /// [`XdgActivationHandler::request_activation`]: crate::wayland::xdg_activation::XdgActivationHandler::request_activation
+    fn activation_request(
+        &mut self,
+        xwm: XwmId,
+        window: X11Surface,
+        token: XdgActivationToken,
+        token_data: XdgActivationTokenData,
+    ) {
+        let _ = (xwm, window, token, token_data);
+    }
+
     /// Window has responded to a `_NET_WM_PING` request.
     fn ping_acked(&mut self, xwm: XwmId, window: X11Surface, timestamp: u32) {
         let _ = (xwm, window, timestamp);
@@ -598,6 +655,8 @@ pub struct X11Wm {
     servertime_counter: Option<Counter>,
     xsettings: XSettings,
     randr_primary: u32,
+    client_id: ClientId,
+    startup_info: StartupInfoBuffer,
 
     pub(crate) unpaired_surfaces: HashMap<u64, X11Window>,
     sequences_to_ignore: BinaryHeap<Reverse<u16>>,
@@ -1032,6 +1091,8 @@ impl X11Wm {
             servertime_counter,
             xsettings,
             randr_primary,
+            client_id: client.id(),
+            startup_info: StartupInfoBuffer::default(),
             wm_window,
             _xfixes_data,
             clipboard,
@@ -1500,6 +1561,19 @@ impl X11Wm {
         self.is_showing_desktop
     }
 
+    /// Returns the current size of the X11 screen
+    ///
+    /// Reservations of [`WmStrut`]s are relative to the edges of the screen.
+    pub fn screen_size(&self) -> Size<i32, Logical> {
+        Size::<i32, Client>::from((
+            self.screen.width_in_pixels as i32,
+            self.screen.height_in_pixels as i32,
+        ))
+        .to_f64()
+        .to_logical(self.client_scale.load(Ordering::Acquire))
+        .to_i32_round()
+    }
+
     fn colormap_for_visual(&self, visual: Visualid) -> Result<Colormap, ReplyOrIdError> {
         if let Some(colormap) = self.colormaps.borrow().get(&visual) {
             Ok(*colormap)
@@ -1514,6 +1588,19 @@ impl X11Wm {
     }
 }
 
+fn activation_token<D: XwmHandler>(
+    state: &mut D,
+    surface: &X11Surface,
+) -> Option<(XdgActivationToken, XdgActivationTokenData)> {
+    let token = XdgActivationToken::from(surface.startup_id()?);
+    let data = state.xdg_activation_state()?.data_for_token(&token)?;
+    // X11 clients must not be able to activate themselves with ids they announced
+    if X11StartupToken::is_set(data) {
+        return None;
+    }
+    Some((token, data.clone()))
+}
+
 fn handle_event<D>(
     loop_handle: &LoopHandle<'_, D>,
     dh: &DisplayHandle,
@@ -1704,7 +1791,10 @@ where
                     }
 
                     drop(_guard);
-                    state.map_window_request(xwm_id, surface);
+                    state.map_window_request(xwm_id, surface.clone());
+                    if let Some((token, token_data)) = activation_token(state, &surface) {
+                        state.activation_request(xwm_id, surface, token, token_data);
+                    }
                 }
             }
         }
@@ -1903,6 +1993,7 @@ where
             }
         }
         Event::DestroyNotify(n) => {
+            xwm.startup_info.window_destroyed(n.window);
             xwm.clipboard.window_destroyed(&n.window, loop_handle);
             xwm.primary.window_destroyed(&n.window, loop_handle);
             xwm.dnd.window_destroyed(&n.window, loop_handle);
@@ -2680,7 +2771,52 @@ where
                         xwm.windows.iter().find(|x| x.window_id() == data[2]).cloned();
                     if let Some(surface) = xwm.windows.iter().find(|x| x.window_id() == msg.window).cloned() {
                         drop(_guard);
-                        state.active_window_request(xwm_id, surface, timestamp, currently_active_window);
+                        if let Some((token, token_data)) = activation_token(state, &surface) {
+                            state.activation_request(xwm_id, surface, token, token_data);
+                        } else {
+                            state.active_window_request(xwm_id, surface, timestamp, currently_active_window);
+                        }
+                    }
+                }
+                x if x == xwm.atoms._NET_STARTUP_INFO_BEGIN || x == xwm.atoms._NET_STARTUP_INFO => {
+                    let begin = x == xwm.atoms._NET_STARTUP_INFO_BEGIN;
+                    if let Some(message) = xwm.startup_info.push(msg.window, begin, &msg.data.as_data8()) {
+                        let client_id = xwm.client_id.clone();
+                        drop(_guard);
+                        if let Some(activation_state) = state.xdg_activation_state() {
+                            let token = XdgActivationToken::from(message.id);
+                            match message.kind {
+                                StartupMessageKind::New => {
+                                    activation_state.retain_tokens(|_, data| {
+                                        !X11StartupToken::is_set(data)
+                                            || data.timestamp.elapsed() < STARTUP_TOKEN_TIMEOUT
+                                    });
+                                    let tracked = activation_state
+                                        .tokens()
+                                        .filter(|(_, data)| X11StartupToken::is_set(data))
+                                        .count();
+                                    if tracked < MAX_STARTUP_TOKENS {
+                                        let data = XdgActivationTokenData {
+                                            client_id: Some(client_id),
+                                            ..Default::default()
+                                        };
+                                        data.user_data.insert_if_missing(|| X11StartupToken);
+                                        activation_state.insert_token(token, data);
+                                    } else {
+                                        debug!("Ignoring launch announced by X11 client, too many pending");
+                                    }
+                                }
+                                StartupMessageKind::Remove => {
+                                    if activation_state
+                                        .data_for_token(&token)
+                                        .is_some_and(X11StartupToken::is_set)
+                                    {
+                                        activation_state.remove_token(&token);
+                                    }
+                                }
+                                StartupMessageKind::Change => {}
+                            }
+                        }
                     }
                 }
                 x if x == xwm.atoms.WM_PROTOCOLS => {
@@ -0,0 +1,281 @@
+use std::{collections::HashMap, time::Duration};
+
+use x11rb::protocol::xproto::Window as X11Window;
+
+use crate::wayland::xdg_activation::XdgActivationTokenData;
+
+// Upper bound for the length of a single startup notification message
+const MAX_MESSAGE_LENGTH: usize = 4096;
+// Launches announced by X11 clients, that are tracked at the same time
+pub(super) const MAX_STARTUP_TOKENS: usize = 64;
+// Duration after which launches announced by X11 clients are forgotten, if they didn't complete
+pub(super) const STARTUP_TOKEN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Marker inserted into the user data of xdg activation tokens, which were
+/// announced by X11 clients through `_NET_STARTUP_INFO`
+///
+/// These tokens were not created by the compositor and are not associated with any input
+/// event, so they should be treated as unvalidated when wayland clients request activation
+/// with them. They expire after 30 seconds and can't be used by X11 windows to activate
+/// themselves.
+#[derive(Debug)]
+pub struct X11StartupToken;
+
+impl X11StartupToken {
+    /// Returns `true` if the token was announced by an X11 client
+    pub fn is_set(data: &XdgActivationTokenData) -> bool {
+        data.user_data.get::<X11StartupToken>().is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum StartupMessageKind {
+    New,
+    Change,
+    Remove,
+}
+
+/// A message of the startup notification protocol
+///
+/// See <https://specifications.freedesktop.org/startup-notification-spec/latest/>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct StartupMessage {
+    pub kind: StartupMessageKind,
+    pub id: String,
+}
+
+impl StartupMessage {
+    /// Parses a message of the form `new: ID="..." NAME=...`, only keeping the `ID` key.
+    pub(super) fn parse(message: &str) -> Option<StartupMessage> {
+        let (kind, mut rest) = message.split_once(':')?;
+        let kind = match kind {
+            "new" => StartupMessageKind::New,
+            "change" => StartupMessageKind::Change,
+            "remove" => StartupMessageKind::Remove,
+            _ => return None,
+        };
+
+        let mut id = None;
+        loop {
+            rest = rest.trim_start_matches(' ');
+            let Some((key, value)) = rest.split_once('=') else {
+                break;
+            };
+
+            // Values are either quoted or end at the next space, backslashes escape the next character
+            let mut chars = value.char_indices();
+            let quoted = value.starts_with('"');
+            if quoted {
+                chars.next();
+            }
+            let mut parsed = String::new();
+            let mut end = value.len();
+            while let Some((idx, c)) = chars.next() {
+                match c {
+                    '\\' => parsed.extend(chars.next().map(|(_, c)| c)),
+                    '"' if quoted => {
+                        end = idx + 1;
+                        break;
+                    }
+                    ' ' if !quoted => {
+                        end = idx;
+                        break;
+                    }
+                    c => parsed.push(c),
+                }
+            }
+
+            if key == "ID" {
+                id = Some(parsed);
+            }
+            rest = &value[end..];
+        }
+
+        Some(StartupMessage { kind, id: id? })
+    }
+}
+
+/// Assembles startup notification messages, which are split across multiple client messages
+#[derive(Debug, Default)]
+pub(super) struct StartupInfoBuffer {
+    pending: HashMap<X11Window, Vec<u8>>,
+}
+
+impl StartupInfoBuffer {
+    /// Adds the data of a `_NET_STARTUP_INFO_BEGIN` or `_NET_STARTUP_INFO` client message
+    /// sent by `window`, returning the message once it is complete.
+    pub(super) fn push(&mut self, window: X11Window, begin: bool, data: &[u8]) -> Option<StartupMessage> {
+        let buffer = self.pending.entry(window).or_default();
+        if begin {
+            buffer.clear();
+        }
+
+        let (data, complete) = match data.iter().position(|b| *b == 0) {
+            Some(pos) => (&data[..pos], true),
+            None => (data, false),
+        };
+        buffer.extend_from_slice(data);
+
+        if buffer.len() > MAX_MESSAGE_LENGTH {
+            self.pending.remove(&window);
+            return None;
+        }
+        if !complete {
+            return None;
+        }
+
+        let buffer = self.pending.remove(&window)?;
+        std::str::from_utf8(&buffer).ok().and_then(StartupMessage::parse)
+    }
+
+    /// Drops partial messages of a destroyed window
+    pub(super) fn window_destroyed(&mut self, window: X11Window) {
+        self.pending.remove(&window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(message: &str) -> Option<(StartupMessageKind, String)> {
+        StartupMessage::parse(message).map(|message| (message.kind, message.id))
+    }
+
+    #[test]
+    fn parse_kinds() {
+        assert_eq!(parse("new: ID=a"), Some((StartupMessageKind::New, "a".into())));
+        assert_eq!(
+            parse("change: ID=a"),
+            Some((StartupMessageKind::Change, "a".into()))
+        );
+        assert_eq!(
+            parse("remove: ID=a"),
+            Some((StartupMessageKind::Remove, "a".into()))
+        );
+        assert_eq!(parse("other: ID=a"), None);
+        assert_eq!(parse("new ID=a"), None);
+        // messages without an id are ignored
+        assert_eq!(parse("new: NAME=a"), None);
+    }
+
+    #[test]
+    fn parse_keys() {
+        assert_eq!(
+            parse("new: NAME=Terminal ID=term-1_TIME42 SCREEN=0"),
+            Some((StartupMessageKind::New, "term-1_TIME42".into()))
+        );
+        assert_eq!(
+            parse("new:   ID=a   NAME=b"),
+            Some((StartupMessageKind::New, "a".into()))
+        );
+        // the id key has to match exactly
+        assert_eq!(parse("new: XID=a"), None);
+    }
+
+    #[test]
+    fn parse_quoting() {
+        assert_eq!(
+            parse(r#"new: NAME="My Terminal" ID="id with spaces" SCREEN=0"#),
+            Some((StartupMessageKind::New, "id with spaces".into()))
+        );
+        assert_eq!(
+            parse(r#"new: ID="a=b" NAME=c"#),
+            Some((StartupMessageKind::New, "a=b".into()))
+        );
+        // an unterminated quote extends to the end of the message
+        assert_eq!(
+            parse(r#"new: ID="a b"#),
+            Some((StartupMessageKind::New, "a b".into()))
+        );
+    }
+
+    #[test]
+    fn parse_escaping() {
+        assert_eq!(
+            parse(r#"new: ID=a\ b NAME=c"#),
+            Some((StartupMessageKind::New, "a b".into()))
+        );
+        assert_eq!(
+            parse(r#"new: ID="a\"b\\c""#),
+            Some((StartupMessageKind::New, r#"a"b\c"#.into()))
+        );
+        assert_eq!(
+            parse(r#"new: NAME="x\" ID=y" ID=z"#),
+            Some((StartupMessageKind::New, "z".into()))
+        );
+        // a trailing backslash is dropped
+        assert_eq!(parse(r"new: ID=a\"), Some((StartupMessageKind::New, "a".into())));
+    }
+
+    #[test]
+    fn push_chunks() {
+        let mut buffer = StartupInfoBuffer::default();
+        // client messages carry 20 bytes each, the last one is padded with zeros
+        assert_eq!(buffer.push(1, true, b"new: NAME=Terminal I"), None);
+        assert_eq!(buffer.push(1, false, b"D=term-1 SCREEN=0 TI"), None);
+        assert_eq!(
+            buffer.push(1, false, b"MESTAMP=42\0\0\0\0\0\0\0\0\0\0"),
+            Some(StartupMessage {
+                kind: StartupMessageKind::New,
+                id: "term-1".into(),
+            })
+        );
+        assert!(buffer.pending.is_empty());
+    }
+
+    #[test]
+    fn push_interleaved_windows() {
+        let mut buffer = StartupInfoBuffer::default();
+        assert_eq!(buffer.push(1, true, b"new: ID=a NAME=xxxxx"), None);
+        assert_eq!(
+            buffer.push(2, true, b"remove: ID=b\0\0\0\0\0\0\0\0"),
+            Some(StartupMessage {
+                kind: StartupMessageKind::Remove,
+                id: "b".into(),
+            })
+        );
+        assert_eq!(
+            buffer.push(1, false, b"\0"),
+            Some(StartupMessage {
+                kind: StartupMessageKind::New,
+                id: "a".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn push_begin_restarts_message() {
+        let mut buffer = StartupInfoBuffer::default();
+        assert_eq!(buffer.push(1, true, b"new: ID=stale NAME="), None);
+        assert_eq!(
+            buffer.push(1, true, b"new: ID=fresh\0"),
+            Some(StartupMessage {
+                kind: StartupMessageKind::New,
+                id: "fresh".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn push_drops_invalid_messages() {
+        let mut buffer = StartupInfoBuffer::default();
+
+        // too long
+        assert_eq!(buffer.push(1, true, b"new: ID=a NAME="), None);
+        for _ in 0..=MAX_MESSAGE_LENGTH / 20 {
+            buffer.push(1, false, &[b'x'; 20]);
+        }
+        assert!(buffer.pending.is_empty());
+        assert_eq!(buffer.push(1, false, b"\0"), None);
+
+        // not utf-8
+        assert_eq!(buffer.push(1, true, b"new: ID=\xff\0"), None);
+        assert!(buffer.pending.is_empty());
+
+        // partial messages of destroyed windows
+        assert_eq!(buffer.push(1, true, b"new: ID=a"), None);
+        buffer.window_destroyed(1);
+        assert!(buffer.pending.is_empty());
+    }
+}
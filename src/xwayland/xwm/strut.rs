@@ -0,0 +1,297 @@
+use crate::utils::{Logical, Rectangle, Size};
+
+/// Space reserved at one edge of the X11 screen
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StrutEdge {
+    /// Width of the reserved space, measured from the edge of the screen
+    pub size: i32,
+    /// Start of the range along the edge, which the reservation applies to
+    pub start: i32,
+    /// End of the range along the edge, which the reservation applies to (inclusive)
+    pub end: i32,
+}
+
+impl StrutEdge {
+    fn new(size: u32, start: u32, end: u32) -> StrutEdge {
+        StrutEdge {
+            size: size.min(i32::MAX as u32) as i32,
+            start: start.min(i32::MAX as u32) as i32,
+            end: end.min(i32::MAX as u32) as i32,
+        }
+    }
+
+    /// Returns `true` if this edge doesn't reserve any space
+    pub fn is_empty(&self) -> bool {
+        self.size <= 0 || self.end < self.start
+    }
+
+    // Length of the range along the edge
+    fn length(&self) -> i32 {
+        self.end.saturating_sub(self.start).saturating_add(1)
+    }
+
+    fn to_logical(self, scale: f64) -> StrutEdge {
+        let to_logical = |value: i32| (value as f64 / scale).round() as i32;
+        StrutEdge {
+            size: to_logical(self.size),
+            start: to_logical(self.start),
+            end: to_logical(self.end),
+        }
+    }
+}
+
+/// Space reserved by a dock or panel window at the edges of the X11 screen
+///
+/// Corresponds to the `_NET_WM_STRUT_PARTIAL` property or the older `_NET_WM_STRUT` property,
+/// in which case each reservation spans the whole edge of the screen.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WmStrut {
+    /// Space reserved at the left edge of the screen
+    pub left: StrutEdge,
+    /// Space reserved at the right edge of the screen
+    pub right: StrutEdge,
+    /// Space reserved at the top edge of the screen
+    pub top: StrutEdge,
+    /// Space reserved at the bottom edge of the screen
+    pub bottom: StrutEdge,
+}
+
+impl WmStrut {
+    pub(super) fn parse(data: &[u32]) -> Option<WmStrut> {
+        match *data {
+            [
+                left,
+                right,
+                top,
+                bottom,
+                left_start_y,
+                left_end_y,
+                right_start_y,
+                right_end_y,
+                top_start_x,
+                top_end_x,
+                bottom_start_x,
+                bottom_end_x,
+            ] => Some(WmStrut {
+                left: StrutEdge::new(left, left_start_y, left_end_y),
+                right: StrutEdge::new(right, right_start_y, right_end_y),
+                top: StrutEdge::new(top, top_start_x, top_end_x),
+                bottom: StrutEdge::new(bottom, bottom_start_x, bottom_end_x),
+            }),
+            [left, right, top, bottom] => Some(WmStrut {
+                left: StrutEdge::new(left, 0, u32::MAX),
+                right: StrutEdge::new(right, 0, u32::MAX),
+                top: StrutEdge::new(top, 0, u32::MAX),
+                bottom: StrutEdge::new(bottom, 0, u32::MAX),
+            }),
+            _ => None,
+        }
+    }
+
+    pub(super) fn to_logical(self, scale: f64) -> WmStrut {
+        WmStrut {
+            left: self.left.to_logical(scale),
+            right: self.right.to_logical(scale),
+            top: self.top.to_logical(scale),
+            bottom: self.bottom.to_logical(scale),
+        }
+    }
+
+    /// Returns `true` if the strut doesn't reserve any space
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty() && self.right.is_empty() && self.top.is_empty() && self.bottom.is_empty()
+    }
+
+    /// Shrinks the given area of an output by the space reserved by this strut
+    ///
+    /// `output` is the geometry of the output the area belongs to, `screen_size` the size of the
+    /// X11 screen (see [`X11Wm::screen_size`](super::X11Wm::screen_size)). Both as well as
+    /// `area` are expected in X11 coordinates. A reservation only shrinks the area, if it
+    /// overlaps the output, so panels on one output don't affect the others.
+    pub fn shrink_area(
+        &self,
+        area: Rectangle<i32, Logical>,
+        output: Rectangle<i32, Logical>,
+        screen_size: Size<i32, Logical>,
+    ) -> Rectangle<i32, Logical> {
+        let reserved = |edge: &StrutEdge, loc: (i32, i32), size: (i32, i32)| {
+            !edge.is_empty() && Rectangle::new(loc.into(), size.into()).overlaps(output)
+        };
+
+        let mut left = area.loc.x;
+        let mut top = area.loc.y;
+        let mut right = area.loc.x.saturating_add(area.size.w);
+        let mut bottom = area.loc.y.saturating_add(area.size.h);
+
+        let edge = &self.left;
+        if reserved(edge, (0, edge.start), (edge.size, edge.length())) {
+            left = left.max(edge.size);
+        }
+        let edge = &self.right;
+        let right_x = screen_size.w.saturating_sub(edge.size);
+        if reserved(edge, (right_x, edge.start), (edge.size, edge.length())) {
+            right = right.min(right_x);
+        }
+        let edge = &self.top;
+        if reserved(edge, (edge.start, 0), (edge.length(), edge.size)) {
+            top = top.max(edge.size);
+        }
+        let edge = &self.bottom;
+        let bottom_y = screen_size.h.saturating_sub(edge.size);
+        if reserved(edge, (edge.start, bottom_y), (edge.length(), edge.size)) {
+            bottom = bottom.min(bottom_y);
+        }
+
+        Rectangle::new(
+            (left, top).into(),
+            (
+                right.saturating_sub(left).max(0),
+                bottom.saturating_sub(top).max(0),
+            )
+                .into(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: (i32, i32) = (3840, 1080);
+
+    fn left_output() -> Rectangle<i32, Logical> {
+        Rectangle::new((0, 0).into(), (1920, 1080).into())
+    }
+
+    fn right_output() -> Rectangle<i32, Logical> {
+        Rectangle::new((1920, 0).into(), (1920, 1080).into())
+    }
+
+    fn shrink(strut: &WmStrut, output: Rectangle<i32, Logical>) -> Rectangle<i32, Logical> {
+        strut.shrink_area(output, output, SCREEN.into())
+    }
+
+    #[test]
+    fn parse_partial() {
+        let strut = WmStrut::parse(&[10, 20, 30, 40, 1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        assert_eq!(
+            strut,
+            WmStrut {
+                left: StrutEdge {
+                    size: 10,
+                    start: 1,
+                    end: 2
+                },
+                right: StrutEdge {
+                    size: 20,
+                    start: 3,
+                    end: 4
+                },
+                top: StrutEdge {
+                    size: 30,
+                    start: 5,
+                    end: 6
+                },
+                bottom: StrutEdge {
+                    size: 40,
+                    start: 7,
+                    end: 8
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn parse_legacy() {
+        let strut = WmStrut::parse(&[0, 0, 30, 0]).unwrap();
+        assert_eq!(
+            strut.top,
+            StrutEdge {
+                size: 30,
+                start: 0,
+                end: i32::MAX
+            }
+        );
+        assert!(strut.left.is_empty() && strut.right.is_empty() && strut.bottom.is_empty());
+        assert!(!strut.is_empty());
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!(WmStrut::parse(&[]), None);
+        assert_eq!(WmStrut::parse(&[1, 2, 3]), None);
+        assert_eq!(WmStrut::parse(&[0; 8]), None);
+        assert_eq!(WmStrut::parse(&[0; 13]), None);
+        assert!(WmStrut::parse(&[0; 12]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_clamps_values() {
+        let strut = WmStrut::parse(&[u32::MAX, 0, 0, 0]).unwrap();
+        assert_eq!(strut.left.size, i32::MAX);
+        assert_eq!(strut.left.end, i32::MAX);
+    }
+
+    #[test]
+    fn shrink_legacy_strut() {
+        // a bottom panel spanning the whole screen applies to all outputs
+        let strut = WmStrut::parse(&[0, 0, 0, 40]).unwrap();
+        assert_eq!(
+            shrink(&strut, left_output()),
+            Rectangle::new((0, 0).into(), (1920, 1040).into())
+        );
+        assert_eq!(
+            shrink(&strut, right_output()),
+            Rectangle::new((1920, 0).into(), (1920, 1040).into())
+        );
+    }
+
+    #[test]
+    fn shrink_partial_strut_on_other_output() {
+        // a top panel on the left output
+        let strut = WmStrut::parse(&[0, 0, 30, 0, 0, 0, 0, 0, 0, 1919, 0, 0]).unwrap();
+        assert_eq!(
+            shrink(&strut, left_output()),
+            Rectangle::new((0, 30).into(), (1920, 1050).into())
+        );
+        assert_eq!(shrink(&strut, right_output()), right_output());
+
+        // a dock at the right edge of the screen, on the right output
+        let strut = WmStrut::parse(&[0, 50, 0, 0, 0, 0, 0, 1079, 0, 0, 0, 0]).unwrap();
+        assert_eq!(shrink(&strut, left_output()), left_output());
+        assert_eq!(
+            shrink(&strut, right_output()),
+            Rectangle::new((1920, 0).into(), (1870, 1080).into())
+        );
+    }
+
+    #[test]
+    fn shrink_already_reduced_area() {
+        let strut = WmStrut::parse(&[20, 0, 0, 0]).unwrap();
+        let area = Rectangle::new((40, 0).into(), (1880, 1080).into());
+        assert_eq!(strut.shrink_area(area, left_output(), SCREEN.into()), area);
+    }
+
+    #[test]
+    fn shrink_inverted_range() {
+        let strut = WmStrut::parse(&[30, 0, 0, 0, 100, 50, 0, 0, 0, 0, 0, 0]).unwrap();
+        assert!(strut.is_empty());
+        assert_eq!(shrink(&strut, left_output()), left_output());
+    }
+
+    #[test]
+    fn shrink_saturates() {
+        // reservations covering the whole screen leave an empty area
+        let strut = WmStrut::parse(&[u32::MAX, u32::MAX, u32::MAX, u32::MAX]).unwrap();
+        let area = shrink(&strut, left_output());
+        assert!(area.is_empty());
+
+        // extreme areas don't overflow
+        let strut = WmStrut::default();
+        let area = Rectangle::new((10, 10).into(), (i32::MAX, i32::MAX).into());
+        assert_eq!(
+            strut.shrink_area(area, left_output(), SCREEN.into()),
+            Rectangle::new((10, 10).into(), (i32::MAX - 10, i32::MAX - 10).into())
+        );
+    }
+}
@@ -1,5 +1,13 @@
 use crate::{
-    backend::{input::KeyState, renderer::utils::RendererSurfaceStateUserData},
+    backend::{
+        input::KeyState,
+        renderer::{
+            element::Kind,
+            utils::{
+                RendererSurfaceStateUserData, RetainedSurfaceState, retain_surface_state, take_surface_state,
+            },
+        },
+    },
     input::{
         Seat, SeatHandler,
         keyboard::{KeyboardTarget, KeysymHandle, ModifiersState},
@@ -17,10 +25,11 @@ use crate::{
     },
     wayland::{
         compositor::{self, CompositorHandler, RectangleKind, RegionAttributes, SurfaceAttributes},
+        idle_inhibit::IdleInhibitHandler,
         pointer_constraints::PointerConstraintsHandler,
         seat::{WaylandFocus, keyboard::enter_internal},
     },
-    xwayland::xwm::MwmHints,
+    xwayland::xwm::{MwmHints, WmStrut, X11Icon},
 };
 #[cfg(feature = "desktop")]
 use crate::{
@@ -40,7 +49,7 @@ use std::{
     time::Duration,
 };
 use tracing::warn;
-use wayland_server::protocol::wl_surface::WlSurface;
+use wayland_server::{Resource, protocol::wl_surface::WlSurface};
 use xkbcommon::xkb::Keycode;
 
 use x11rb::{
@@ -135,6 +144,8 @@ pub(crate) struct SharedSurfaceState {
     wl_surface: Option<WlSurface>,
     opaque_regions_hook_id: Option<HookId>,
     deferred_sync_hook_id: Option<HookId>,
+    // The buffer of the last wl_surface, kept once the window got unmapped.
+    retained_state: RetainedSurfaceState,
 
     // State for _NET_WM_SYNC_REQUEST.
     sync_counter: Option<SyncRequestCounter>,
@@ -167,6 +178,11 @@ pub(crate) struct SharedSurfaceState {
     opaque_region: Option<RegionAttributes>,
     opaque_region_dirty: bool,
     frame_extents: FrameExtents<i32, Physical>,
+    strut: Option<WmStrut>,
+    icons: Arc<[X11Icon]>,
+    randr_emulation_rects: Vec<Rectangle<i32, Client>>,
+    bypass_compositor: BypassCompositor,
+    idle_inhibitor: Option<WlSurface>,
     pending_enter: Option<(
         Box<dyn std::any::Any + Send + 'static>,
         Vec<Keycode>,
@@ -176,6 +192,9 @@ pub(crate) struct SharedSurfaceState {
     pub(super) pending_ping_timestamp: Option<u32>,
 }
 
+// Upper bound of the `_NET_WM_ICON` data in 32-bit units (16 MiB)
+const MAX_ICON_PROPERTY_LENGTH: u32 = 4 * 1024 * 1024;
+
 pub(super) type Protocols = Vec<WMProtocol>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -290,6 +309,24 @@ pub enum WmWindowProperty {
     Pid,
     Opacity,
     FrameExtents,
+    Strut,
+    Icon,
+    RandrEmulation,
+    BypassCompositor,
+}
+
+/// Compositing preference of an X11 window
+///
+/// Corresponds to the `_NET_WM_BYPASS_COMPOSITOR` property, usually set by games and video players.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BypassCompositor {
+    /// The window has no preference
+    #[default]
+    NoPreference,
+    /// The window requests to bypass the compositor, e.g. through direct scanout
+    Bypass,
+    /// The window requests to always be composited
+    Composite,
 }
 
 /// https://x.org/releases/X11R7.6/doc/xorg-docs/specs/ICCCM/icccm.html#input_focus
@@ -344,6 +381,7 @@ impl X11Surface {
                 wl_surface: None,
                 opaque_regions_hook_id: None,
                 deferred_sync_hook_id: None,
+                retained_state: RetainedSurfaceState::default(),
                 sync_counter: None,
                 sync_alarm: None,
                 last_set_sync_timeout: DEFAULT_SYNC_REQUEST_TIMEOUT,
@@ -372,6 +410,11 @@ impl X11Surface {
                 opaque_region: None,
                 opaque_region_dirty: true,
                 frame_extents: Default::default(),
+                strut: None,
+                icons: Arc::new([]),
+                randr_emulation_rects: Vec::new(),
+                bypass_compositor: BypassCompositor::NoPreference,
+                idle_inhibitor: None,
                 pending_enter: None,
                 pending_ping_timestamp: None,
             })),
@@ -892,6 +935,11 @@ impl X11Surface {
         self.state.lock().unwrap().wl_surface_serial
     }
 
+    // The buffer and textures of the last wl_surface, if the window got unmapped since
+    pub(crate) fn retained_state(&self) -> RetainedSurfaceState {
+        self.state.lock().unwrap().retained_state.clone()
+    }
+
     pub(crate) fn set_wl_surface<D: SeatHandler + 'static>(&self, data: &mut D, surface: Option<WlSurface>) {
         let mut state = self.state.lock().unwrap();
 
@@ -917,6 +965,15 @@ impl X11Surface {
                 }
             }
         }
+        match (surface.as_ref(), state.wl_surface.as_ref()) {
+            // XWayland destroys the surface of an unmapped window, keep its last buffer
+            (None, Some(old)) if old.is_alive() => take_surface_state(old, &state.retained_state),
+            (Some(new), _) => {
+                state.retained_state.lock().unwrap().take();
+                retain_surface_state(new, &state.retained_state);
+            }
+            (None, _) => {}
+        }
         state.wl_surface = surface;
 
         if let Some(wl_surface) = state.wl_surface.clone() {
@@ -1549,6 +1606,10 @@ impl X11Surface {
             state.opaque_region_dirty = false;
         }
         self.update_toolkit_frame_extents()?;
+        self.update_strut()?;
+        self.update_icons()?;
+        self.update_randr_emulation()?;
+        self.update_bypass_compositor()?;
         Ok(())
     }
 
@@ -1608,6 +1669,22 @@ impl X11Surface {
                 self.update_toolkit_frame_extents()?;
                 Ok(Some(WmWindowProperty::FrameExtents))
             }
+            atom if atom == self.atoms._NET_WM_STRUT_PARTIAL || atom == self.atoms._NET_WM_STRUT => {
+                self.update_strut()?;
+                Ok(Some(WmWindowProperty::Strut))
+            }
+            atom if atom == self.atoms._NET_WM_ICON => {
+                self.update_icons()?;
+                Ok(Some(WmWindowProperty::Icon))
+            }
+            atom if atom == self.atoms._XWAYLAND_RANDR_EMU_MONITOR_RECTS => {
+                self.update_randr_emulation()?;
+                Ok(Some(WmWindowProperty::RandrEmulation))
+            }
+            atom if atom == self.atoms._NET_WM_BYPASS_COMPOSITOR => {
+                self.update_bypass_compositor()?;
+                Ok(Some(WmWindowProperty::BypassCompositor))
+            }
 
             _ => Ok(None), // unknown
         }
@@ -1753,6 +1830,232 @@ impl X11Surface {
             .to_i32_round()
     }
 
+    fn fetch_strut(&self, atom: Atom, len: u32) -> Result<Option<WmStrut>, ConnectionError> {
+        let conn = self.conn.upgrade().ok_or(ConnectionError::UnknownError)?;
+        match conn
+            .get_property(false, self.window, atom, AtomEnum::CARDINAL, 0, len)?
+            .reply_unchecked()
+        {
+            Ok(Some(reply)) => Ok(reply
+                .value32()
+                .and_then(|values| WmStrut::parse(&values.collect::<Vec<_>>()))),
+            Ok(None) | Err(ConnectionError::ParseError(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn update_strut(&self) -> Result<(), ConnectionError> {
+        // _NET_WM_STRUT_PARTIAL takes precedence, _NET_WM_STRUT is the older variant
+        let strut = match self.fetch_strut(self.atoms._NET_WM_STRUT_PARTIAL, 12)? {
+            Some(strut) => Some(strut),
+            None => self.fetch_strut(self.atoms._NET_WM_STRUT, 4)?,
+        };
+        self.state.lock().unwrap().strut = strut;
+        Ok(())
+    }
+
+    /// Returns the space reserved by the window at the edges of the X11 screen
+    ///
+    /// This is usually set by dock windows, like panels or taskbars, to prevent other windows
+    /// from overlapping them. Changes are signaled through
+    /// [`XwmHandler::property_notify`](super::XwmHandler::property_notify) with
+    /// [`WmWindowProperty::Strut`]. See [`WmStrut::shrink_area`] to compute the usable area of an
+    /// output.
+    pub fn strut(&self) -> Option<WmStrut> {
+        let scale = self
+            .client_scale
+            .as_ref()
+            .map(|scale| scale.load(Ordering::Acquire))
+            .unwrap_or(1.);
+        self.state
+            .lock()
+            .unwrap()
+            .strut
+            .map(|strut| strut.to_logical(scale))
+    }
+
+    fn update_icons(&self) -> Result<(), ConnectionError> {
+        let conn = self.conn.upgrade().ok_or(ConnectionError::UnknownError)?;
+        let icons = match conn
+            .get_property(
+                false,
+                self.window,
+                self.atoms._NET_WM_ICON,
+                AtomEnum::CARDINAL,
+                0,
+                MAX_ICON_PROPERTY_LENGTH,
+            )?
+            .reply_unchecked()
+        {
+            Ok(Some(reply)) => reply
+                .value32()
+                .map(|values| X11Icon::parse_all(&values.collect::<Vec<_>>()))
+                .unwrap_or_default(),
+            Ok(None) | Err(ConnectionError::ParseError(_)) => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        self.state.lock().unwrap().icons = icons.into();
+        Ok(())
+    }
+
+    /// Returns the icons of the underlying X11 window in all sizes provided by the client
+    ///
+    /// Corresponds to the `_NET_WM_ICON` property, changes are signaled through
+    /// [`XwmHandler::property_notify`](super::XwmHandler::property_notify) with
+    /// [`WmWindowProperty::Icon`].
+    pub fn icons(&self) -> Arc<[X11Icon]> {
+        self.state.lock().unwrap().icons.clone()
+    }
+
+    fn update_randr_emulation(&self) -> Result<(), ConnectionError> {
+        let conn = self.conn.upgrade().ok_or(ConnectionError::UnknownError)?;
+        let rects = match conn
+            .get_property(
+                false,
+                self.window,
+                self.atoms._XWAYLAND_RANDR_EMU_MONITOR_RECTS,
+                AtomEnum::CARDINAL,
+                0,
+                1024,
+            )?
+            .reply_unchecked()
+        {
+            Ok(Some(reply)) => reply
+                .value32()
+                .map(|values| {
+                    values
+                        .collect::<Vec<_>>()
+                        .chunks_exact(4)
+                        .map(|rect| {
+                            Rectangle::new(
+                                (rect[0] as i32, rect[1] as i32).into(),
+                                (rect[2] as i32, rect[3] as i32).into(),
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            Ok(None) | Err(ConnectionError::ParseError(_)) => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        self.state.lock().unwrap().randr_emulation_rects = rects;
+        Ok(())
+    }
+
+    /// Returns the geometries of the outputs, whose resolution the client changed through RandR
+    ///
+    /// XWayland emulates RandR mode changes of X11 clients, e.g. games switching to a lower
+    /// resolution, as long as the compositor exposes the
+    /// [`viewporter`](crate::wayland::viewporter) global. For each output it advertises the
+    /// output's current mode and a set of common lower resolutions as virtual modes. Once a client
+    /// switches to one of them, the output appears at the emulated size to that client only.
+    ///
+    /// The returned rectangles are positioned at the outputs' locations, but sized to the emulated
+    /// modes. Changes are signaled through
+    /// [`XwmHandler::property_notify`](super::XwmHandler::property_notify) with
+    /// [`WmWindowProperty::RandrEmulation`].
+    pub fn randr_emulation_rects(&self) -> Vec<Rectangle<i32, Logical>> {
+        let scale = self
+            .client_scale
+            .as_ref()
+            .map(|scale| scale.load(Ordering::Acquire))
+            .unwrap_or(1.);
+        self.state
+            .lock()
+            .unwrap()
+            .randr_emulation_rects
+            .iter()
+            .map(|rect| rect.to_f64().to_logical(scale).to_i32_round())
+            .collect()
+    }
+
+    /// Returns the emulated geometry of the output with the given geometry, if the client changed
+    /// its resolution through RandR
+    ///
+    /// When fullscreening the window on that output it should be configured to this geometry
+    /// instead of the output geometry. XWayland then scales the surface of the window to cover
+    /// the whole output using a viewport, see [`X11Surface::randr_emulation_rects`].
+    pub fn randr_emulated_geometry(
+        &self,
+        output_geometry: Rectangle<i32, Logical>,
+    ) -> Option<Rectangle<i32, Logical>> {
+        self.randr_emulation_rects()
+            .into_iter()
+            .find(|rect| rect.loc == output_geometry.loc && rect.size != output_geometry.size)
+    }
+
+    fn update_bypass_compositor(&self) -> Result<(), ConnectionError> {
+        let hint = match self.read_window_property_u32(self.atoms._NET_WM_BYPASS_COMPOSITOR)? {
+            Some(1) => BypassCompositor::Bypass,
+            Some(2) => BypassCompositor::Composite,
+            _ => BypassCompositor::NoPreference,
+        };
+        self.state.lock().unwrap().bypass_compositor = hint;
+        Ok(())
+    }
+
+    /// Returns the compositing preference of the window
+    ///
+    /// Changes are signaled through
+    /// [`XwmHandler::property_notify`](super::XwmHandler::property_notify) with
+    /// [`WmWindowProperty::BypassCompositor`].
+    pub fn bypass_compositor(&self) -> BypassCompositor {
+        self.state.lock().unwrap().bypass_compositor
+    }
+
+    /// Returns the [`Kind`] render elements of this window should use
+    ///
+    /// Windows requesting to bypass the compositor are considered [`Kind::ScanoutCandidate`]s,
+    /// which allows the [`DrmCompositor`](crate::backend::drm::compositor::DrmCompositor) to scan
+    /// them out directly, like wayland clients with a `video` or `game` content type.
+    pub fn scanout_kind(&self) -> Kind {
+        match self.bypass_compositor() {
+            BypassCompositor::Bypass => Kind::ScanoutCandidate,
+            _ => Kind::Unspecified,
+        }
+    }
+
+    /// Returns `true` if the window hints that the session should not go idle
+    ///
+    /// Visible windows requesting to bypass the compositor through `_NET_WM_BYPASS_COMPOSITOR`,
+    /// like games and video players, are considered to inhibit idling. X11 clients suspending the
+    /// screensaver through the `XScreenSaverSuspend` call are handled by the X server itself, which
+    /// can't be observed by the window manager.
+    ///
+    /// Being fullscreen alone is not considered a hint, compositors wanting to keep the session
+    /// awake for fullscreen windows have to check [`X11Surface::is_fullscreen`] themselves.
+    pub fn inhibits_idle(&self) -> bool {
+        self.alive()
+            && self.is_mapped()
+            && !self.is_hidden()
+            && self.bypass_compositor() == BypassCompositor::Bypass
+    }
+
+    /// Forwards the idle inhibition hint of the window to an [`IdleInhibitHandler`]
+    ///
+    /// This calls [`IdleInhibitHandler::inhibit`] and [`IdleInhibitHandler::uninhibit`] with the
+    /// `wl_surface` of the window, whenever [`X11Surface::inhibits_idle`] changed, so compositors
+    /// can track X11 and wayland clients the same way. It should be called whenever the window is
+    /// mapped, unmapped, destroyed, its hidden state changes and on
+    /// [`WmWindowProperty::BypassCompositor`] notifications.
+    pub fn update_idle_inhibit<D: IdleInhibitHandler>(&self, state: &mut D) {
+        let surface = self.inhibits_idle().then(|| self.wl_surface()).flatten();
+        let previous = {
+            let mut guard = self.state.lock().unwrap();
+            if guard.idle_inhibitor == surface {
+                return;
+            }
+            std::mem::replace(&mut guard.idle_inhibitor, surface.clone())
+        };
+
+        if let Some(previous) = previous {
+            state.uninhibit(previous);
+        }
+        if let Some(surface) = surface {
+            state.inhibit(surface);
+        }
+    }
+
     fn update_protocols(&self) -> Result<(), ConnectionError> {
         let conn = self.conn.upgrade().ok_or(ConnectionError::UnknownError)?;
         let Some(protocols) = (match conn